@@ -0,0 +1,435 @@
+use std::time::{Duration, Instant};
+use rust_nist_drbg::drbg::buffered::BufferedDrbg;
+use rust_nist_drbg::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use rust_nist_drbg::mechs::gen_mech::DRBG_Mechanism_Functions;
+use rust_nist_drbg::mechs::hash_mech::HashDrbgMech;
+use rust_nist_drbg::mechs::hmac_mech::HmacDrbgMech;
+use rust_nist_drbg::mechs::ctr_mech::CtrDrbgMech;
+use rust_nist_drbg::mechs::ctr_mech_with_df::CtrDrbgMech_DF;
+use rust_nist_drbg::mechs::derivation;
+use aes::{Aes128, Aes256};
+use aes::cipher::{BlockEncrypt, KeyInit, generic_array::GenericArray};
+use digest::{BlockInput, FixedOutput, Reset, Update};
+use hmac::{Hmac, Mac, NewMac};
+use sha2::{Sha256, Sha512};
+use typenum::Unsigned;
+
+/*  Throughput benchmark of the generate function of the DRBG mechanisms.
+
+    Each mechanism is instantiated with fixed entropy and asked to produce req_bytes bytes per generate call for
+    at least BENCH_TIME. The instance is reseeded whenever its seed life is over.
+    The mechanisms are also compared with a baseline, a copy of their generate path before it was optimized, whose output
+    is checked to be the same as the one of the mechanism:
+        - BaselineHash: Hash-DRBG before the generate path was made allocation-free (V cloned for every hash, the prefix
+          byte inserted at index 0 and Vec based modular additions)
+        - BaselineHmac: HMAC-DRBG before the keyed HMAC state was cached (the padded key hashed again on every HMAC
+          invocation and the output pushed byte by byte)
+        - BaselineCtr: CTR-DRBG (no DF) before the key schedule was cached and the counter blocks were batched (one
+          block at a time, a new key schedule on every update and Vec conversions on every counter increment)
+    The latency of small requests (e.g. nonces) through the DRBG envelope is then compared with the one of a
+    BufferedDrbg serving them from pre-generated blocks.
+    Run with: cargo bench --bench generate
+    The throughput (MiB/s) and latency of every line are printed by the benchmark itself, the numbers depend on the CPU
+    (e.g. on whether AES-NI is available to the 'aes' crate). */
+
+/*  Minimum duration of each measurement. */
+const BENCH_TIME: Duration = Duration::from_secs(2);
+
+/*  Fixed inputs used for instantiation and reseeding (long enough for every mechanism). */
+const ENTROPY: [u8; 48] = [0x5a; 48];
+const NONCE: [u8; 16] = [0xa5; 16];
+
+/*  Measures the generate throughput of a mechanism for the given request size. */
+fn bench_mech<T: DRBG_Mechanism_Functions>(name: &str, mut strength: usize, req_bytes: usize) {
+    let mut drbg = T::new(&ENTROPY, &NONCE, &[], &mut strength).expect("instantiation failed");
+    let mut result = Vec::<u8>::with_capacity(req_bytes);
+
+    let mut total: usize = 0;
+    let mut calls: usize = 0;
+    let start = Instant::now();
+    while start.elapsed() < BENCH_TIME {
+        if drbg.reseed_needed() {
+            drbg.reseed(&ENTROPY, None);
+        }
+        drbg.generate(&mut result, req_bytes, None);
+        total += result.len();
+        calls += 1;
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+
+    println!("{:<28} {:>8} B/req  {:>10.2} MiB/s  {:>10.0} ns/req",
+        name, req_bytes, total as f64 / elapsed / (1024.0 * 1024.0), elapsed * 1e9 / calls as f64);
+}
+
+/*  A copy of the generate path of a mechanism before it was optimized, instantiated with ENTROPY and NONCE and without
+    personalization string. Only generate without additional input is implemented. */
+trait Baseline {
+    fn new() -> Self;
+    fn generate(&mut self, result: &mut Vec<u8>, req_bytes: usize);
+}
+
+/*  The Hash-DRBG generate path before it was made allocation-free (SP 800-90A, section 10.1.1). The instance is
+    instantiated again once its seed life is over. */
+struct BaselineHash<D> {
+    v: Vec<u8>,
+    c: Vec<u8>,
+    count: usize,
+    hash_fun: D,
+}
+
+impl<D: Update + FixedOutput + Reset + Default> BaselineHash<D> {
+    /*  The seed life of the Hash-DRBG before the reseed interval became configurable. */
+    const SEED_LIFE: usize = 255;
+
+    fn hashgen(&mut self, result: &mut Vec<u8>, num_bytes: usize) {
+        let mut data = self.v.clone();
+
+        let mut i: usize = 0;
+        while i < num_bytes {
+            self.hash_fun.update(&data);
+            let w = self.hash_fun.finalize_fixed_reset().to_vec();
+            let hash_len = w.len();
+
+            for (j, byte) in w.iter().enumerate() {
+                if j+i >= num_bytes {
+                    return;
+                }
+                result.push(*byte);
+            }
+
+            modular_add(&mut data, 1);
+            i += hash_len;
+        }
+    }
+}
+
+impl<D: Update + FixedOutput + Reset + Default> Baseline for BaselineHash<D> {
+    fn new() -> Self {
+        let seedlen = if D::OutputSize::USIZE == 32 { 55 } else { 111 };
+        let mut seed_material = ENTROPY.to_vec();
+        seed_material.extend_from_slice(&NONCE);
+        let v = derivation::hash_df::<D>(&seed_material, seedlen).expect("Hash_df failed");
+        let mut seed_material = v.clone();
+        seed_material.insert(0, 0x00);
+        let c = derivation::hash_df::<D>(&seed_material, seedlen).expect("Hash_df failed");
+
+        Self { v, c, count: 1, hash_fun: D::default() }
+    }
+
+    fn generate(&mut self, result: &mut Vec<u8>, req_bytes: usize) {
+        if self.count >= Self::SEED_LIFE {
+            *self = Self::new();
+        }
+        result.clear();
+
+        self.hashgen(result, req_bytes);
+
+        let mut seed_material = self.v.clone();
+        seed_material.insert(0, 0x03);
+        self.hash_fun.update(seed_material);
+        let w = self.hash_fun.finalize_fixed_reset().to_vec();
+
+        let mut v_clone = self.v.clone();
+        modular_add_vec(&mut v_clone, w);
+        self.v.clear();
+        self.v.append(&mut v_clone);
+
+        let mut v_clone = self.v.clone();
+        modular_add_vec(&mut v_clone, self.c.clone());
+        self.v.clear();
+        self.v.append(&mut v_clone);
+
+        let mut v_clone = self.v.clone();
+        modular_add(&mut v_clone, self.count as u8);
+        self.v.clear();
+        self.v.append(&mut v_clone);
+
+        self.count += 1;
+    }
+}
+
+/*  The in-place addition of a byte to a big-endian number used by BaselineHash. */
+fn modular_add(num: &mut [u8], rhs: u8) {
+    if num.is_empty() {
+        return;
+    }
+
+    let mut j = num.len()-1;
+    let (mut res, mut carry) = num[j].overflowing_add(rhs);
+    num[j] = res;
+
+    if j>=1 {
+        j -= 1;
+        while carry && j>0 {
+            (res, carry) = num[j].overflowing_add(1);
+            num[j] = res;
+            j -= 1;
+        }
+
+        if carry {
+            num[0] = num[0].wrapping_add(1);
+        }
+    }
+}
+
+/*  The addition of two big-endian numbers used by BaselineHash, which copies num1 on every carry. */
+fn modular_add_vec(num1: &mut Vec<u8>, num2: Vec<u8>) {
+    if num1.is_empty() || num2.is_empty() || num2.len() > num1.len() {
+        return;
+    }
+
+    let mut i = num2.len();
+    let mut j = num1.len();
+    while i > 0 {
+        let (res, carry) = num1[j-1].overflowing_add(num2[i-1]);
+
+        num1[j-1] = res;
+        if carry {
+            let mut num1_copy = num1[..j-1].to_vec();
+            let mut num1_rem = num1[j-1..].to_vec();
+
+            modular_add(&mut num1_copy, 1);
+
+            num1.clear();
+            num1.append(&mut num1_copy);
+            num1.append(&mut num1_rem);
+        }
+
+        i -= 1;
+        j -= 1;
+    }
+}
+
+/*  The HMAC-DRBG generate path before the keyed HMAC state was cached (SP 800-90A, section 10.1.2). The instance is
+    instantiated again once its seed life is over. */
+struct BaselineHmac<D: Update + BlockInput + FixedOutput + Default> {
+    k: GenericArray<u8, D::OutputSize>,
+    v: GenericArray<u8, D::OutputSize>,
+    count: usize,
+}
+
+impl<D: Update + BlockInput + FixedOutput + Reset + Clone + Default> BaselineHmac<D> {
+    /*  The seed life of the HMAC-DRBG before the reseed interval became configurable. */
+    const SEED_LIFE: usize = 1000;
+
+    fn update(&mut self, seeds: Option<&[&[u8]]>) {
+        let mut kmac = self.hmac();
+        kmac.update(&self.v);
+        kmac.update(&[0x00]);
+        if let Some(seeds) = seeds {
+            for seed in seeds {
+                kmac.update(seed);
+            }
+        }
+        self.k = kmac.finalize().into_bytes();
+
+        let mut vmac = self.hmac();
+        vmac.update(&self.v);
+        self.v = vmac.finalize().into_bytes();
+
+        let seeds = match seeds {
+            None => return,
+            Some(seeds) => seeds,
+        };
+
+        let mut kmac = self.hmac();
+        kmac.update(&self.v);
+        kmac.update(&[0x01]);
+        for seed in seeds {
+            kmac.update(seed);
+        }
+        self.k = kmac.finalize().into_bytes();
+
+        let mut vmac = self.hmac();
+        vmac.update(&self.v);
+        self.v = vmac.finalize().into_bytes();
+    }
+
+    /*  Keys a new HMAC with K, hashing the padded key again. */
+    fn hmac(&self) -> Hmac<D> {
+        Hmac::new_varkey(&self.k).expect("Smaller and larger key size are handled by default")
+    }
+}
+
+impl<D: Update + BlockInput + FixedOutput + Reset + Clone + Default> Baseline for BaselineHmac<D> {
+    fn new() -> Self {
+        let mut this = Self { k: GenericArray::default(), v: GenericArray::default(), count: 0 };
+        this.v.iter_mut().for_each(|byte| *byte = 0x01);
+        this.update(Some(&[&ENTROPY, &NONCE, &[]]));
+        this.count = 1;
+        this
+    }
+
+    fn generate(&mut self, result: &mut Vec<u8>, req_bytes: usize) {
+        if self.count >= Self::SEED_LIFE {
+            *self = Self::new();
+        }
+        result.clear();
+
+        let mut i = 0;
+        while i < req_bytes {
+            let mut vmac = self.hmac();
+            vmac.update(&self.v);
+            self.v = vmac.finalize().into_bytes();
+
+            for j in 0..self.v.len() {
+                if i+j >= req_bytes {
+                    break;
+                }
+                result.push(self.v[j]);
+            }
+            i += self.v.len();
+        }
+
+        self.update(None);
+        self.count += 1;
+    }
+}
+
+/*  The CTR-DRBG (no DF) generate path before the key schedule was cached and the counter blocks were batched
+    (SP 800-90A, section 10.2.1). */
+struct BaselineCtr<D: BlockEncrypt + KeyInit> {
+    k: GenericArray<u8, D::KeySize>,
+    v: GenericArray<u8, D::BlockSize>,
+    seedlen: usize,
+}
+
+impl<D: BlockEncrypt + KeyInit> BaselineCtr<D> {
+    /*  The length of the counter used by the block cipher in bytes. */
+    const CTR_LEN: usize = 4;
+
+    /*  Encrypts successive values of V, one block at a time. */
+    fn blocks(&mut self, cipher: &D, out: &mut Vec<u8>, num_bytes: usize) {
+        let mut i: usize = 0;
+        while i < num_bytes {
+            let mid_point = self.v.len() - Self::CTR_LEN;
+            let mut right_v = self.v[mid_point..].to_vec();
+            for byte in right_v.iter_mut().rev() {
+                *byte = byte.wrapping_add(1);
+                if *byte != 0 {
+                    break;
+                }
+            }
+            let mut v_clone = GenericArray::<u8, D::BlockSize>::default();
+            let (left, right) = v_clone.split_at_mut(mid_point);
+            left.clone_from_slice(&self.v[..mid_point]);
+            right.clone_from_slice(right_v.as_slice());
+            self.v.clone_from(&v_clone);
+
+            let mut block = self.v.clone();
+            cipher.encrypt_block(&mut block);
+            out.append(&mut block.to_vec());
+            i += self.v.len();
+        }
+        out.resize(num_bytes, 0x00);
+    }
+
+    fn update(&mut self, provided_data: &[u8]) {
+        let cipher = D::new(&self.k);
+        let mut temp = Vec::<u8>::new();
+        self.blocks(&cipher, &mut temp, self.seedlen);
+
+        for (byte, data) in temp.iter_mut().zip(provided_data.iter()) {
+            *byte ^= *data;
+        }
+        let keylen = self.k.len();
+        self.k.clone_from_slice(&temp[..keylen]);
+        self.v.clone_from_slice(&temp[keylen..]);
+    }
+}
+
+impl<D: BlockEncrypt + KeyInit> Baseline for BaselineCtr<D> {
+    fn new() -> Self {
+        let mut this = Self { k: GenericArray::default(), v: GenericArray::default(), seedlen: 0 };
+        this.seedlen = this.k.len() + this.v.len();
+        let seedlen = this.seedlen;
+        this.update(&ENTROPY[..seedlen]);
+        this
+    }
+
+    fn generate(&mut self, result: &mut Vec<u8>, req_bytes: usize) {
+        result.clear();
+        let new_add_in = vec![0x00; self.seedlen];
+
+        let cipher = D::new(&self.k);
+        self.blocks(&cipher, result, req_bytes);
+        self.update(&new_add_in);
+    }
+}
+
+/*  Measures the generate throughput of the baseline B of the mechanism T, after checking that both produce the same
+    output. */
+fn bench_baseline<T: DRBG_Mechanism_Functions, B: Baseline>(name: &str, mut strength: usize, req_bytes: usize) {
+    let mut drbg = T::new(&ENTROPY, &NONCE, &[], &mut strength).expect("instantiation failed");
+    let mut baseline = B::new();
+    let (mut expected, mut result) = (Vec::<u8>::new(), Vec::<u8>::with_capacity(req_bytes));
+    drbg.generate(&mut expected, req_bytes, None);
+    baseline.generate(&mut result, req_bytes);
+    assert_eq!(result, expected, "the baseline output differs from the one of the mechanism");
+
+    let mut total: usize = 0;
+    let mut calls: usize = 0;
+    let start = Instant::now();
+    while start.elapsed() < BENCH_TIME {
+        baseline.generate(&mut result, req_bytes);
+        total += result.len();
+        calls += 1;
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+
+    println!("{:<28} {:>8} B/req  {:>10.2} MiB/s  {:>10.0} ns/req",
+        name, req_bytes, total as f64 / elapsed / (1024.0 * 1024.0), elapsed * 1e9 / calls as f64);
+}
+
+/*  Measures the latency of requests of req_bytes bytes through the DRBG envelope and through a BufferedDrbg pre-generating
+    blocks of block_size bytes. */
+fn bench_buffered<T: DRBG_Mechanism_Functions + 'static>(name: &str, strength: usize, req_bytes: usize, block_size: usize) {
+    let mut drbg = DRBG::<T>::new(strength, None).expect("instantiation failed");
+    let mut result = Vec::<u8>::with_capacity(req_bytes);
+    let direct = measure(|| drbg.generate(&mut result, req_bytes, strength, false, None));
+
+    let mut buffered = BufferedDrbg::new(DRBG::<T>::new(strength, None).expect("instantiation failed"), block_size);
+    let mut out = vec![0u8; req_bytes];
+    let pre_generated = measure(|| buffered.generate(&mut out, strength, false, None));
+
+    println!("{:<28} {:>8} B/req  {:>10.0} ns/req  {:>10.0} ns/req buffered ({} B blocks)  x{:.1}",
+        name, req_bytes, direct, pre_generated, block_size, direct / pre_generated);
+}
+
+/*  Returns the average duration in ns of the calls of request made during BENCH_TIME, which must all succeed. */
+fn measure<F: FnMut() -> usize>(mut request: F) -> f64 {
+    let mut calls: usize = 0;
+    let start = Instant::now();
+    while start.elapsed() < BENCH_TIME {
+        assert_eq!(request(), 0, "generate failed");
+        calls += 1;
+    }
+
+    start.elapsed().as_secs_f64() * 1e9 / calls as f64
+}
+
+fn main() {
+    for req_bytes in [16, 256, 65536] {
+        bench_baseline::<HashDrbgMech<Sha256>, BaselineHash<Sha256>>("Hash-DRBG SHA-256 baseline", 32, req_bytes);
+        bench_mech::<HashDrbgMech<Sha256>>("Hash-DRBG SHA-256", 32, req_bytes);
+        bench_baseline::<HashDrbgMech<Sha512>, BaselineHash<Sha512>>("Hash-DRBG SHA-512 baseline", 32, req_bytes);
+        bench_mech::<HashDrbgMech<Sha512>>("Hash-DRBG SHA-512", 32, req_bytes);
+        bench_baseline::<HmacDrbgMech<Sha256>, BaselineHmac<Sha256>>("HMAC-DRBG SHA-256 baseline", 32, req_bytes);
+        bench_mech::<HmacDrbgMech<Sha256>>("HMAC-DRBG SHA-256", 32, req_bytes);
+        bench_baseline::<HmacDrbgMech<Sha512>, BaselineHmac<Sha512>>("HMAC-DRBG SHA-512 baseline", 32, req_bytes);
+        bench_mech::<HmacDrbgMech<Sha512>>("HMAC-DRBG SHA-512", 32, req_bytes);
+        bench_baseline::<CtrDrbgMech<Aes128>, BaselineCtr<Aes128>>("CTR-DRBG AES-128 baseline", 16, req_bytes);
+        bench_mech::<CtrDrbgMech<Aes128>>("CTR-DRBG AES-128 (no DF)", 16, req_bytes);
+        bench_baseline::<CtrDrbgMech<Aes256>, BaselineCtr<Aes256>>("CTR-DRBG AES-256 baseline", 32, req_bytes);
+        bench_mech::<CtrDrbgMech<Aes256>>("CTR-DRBG AES-256 (no DF)", 32, req_bytes);
+        bench_mech::<CtrDrbgMech_DF<Aes128>>("CTR-DRBG AES-128 (DF)", 16, req_bytes);
+        bench_mech::<CtrDrbgMech_DF<Aes256>>("CTR-DRBG AES-256 (DF)", 32, req_bytes);
+    }
+
+    for req_bytes in [12, 16] {
+        bench_buffered::<HashDrbgMech<Sha256>>("Hash-DRBG SHA-256", 32, req_bytes, 4096);
+        bench_buffered::<HmacDrbgMech<Sha256>>("HMAC-DRBG SHA-256", 32, req_bytes, 4096);
+        bench_buffered::<CtrDrbgMech<Aes128>>("CTR-DRBG AES-128 (no DF)", 16, req_bytes, 4096);
+        bench_buffered::<CtrDrbgMech_DF<Aes256>>("CTR-DRBG AES-256 (DF)", 32, req_bytes, 4096);
+    }
+}
@@ -0,0 +1,22 @@
+/*  Generates the C header of the ffi module with cbindgen into OUT_DIR, so that a build never writes into the source tree
+    (e.g. the read-only sources verified by cargo package). The committed header (include/drbg.h) is only rewritten
+    when DRBG_GEN_HEADER=1 is set, tests/ffi.rs checks that it matches the generated one. */
+fn main() {
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=DRBG_GEN_HEADER");
+
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("cargo sets CARGO_MANIFEST_DIR");
+    let out_dir = std::env::var("OUT_DIR").expect("cargo sets OUT_DIR");
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir)).expect("invalid cbindgen.toml");
+
+    match cbindgen::Builder::new().with_config(config).with_src(format!("{}/src/ffi.rs", crate_dir)).generate() {
+        Ok(bindings) => {
+            bindings.write_to_file(format!("{}/drbg.h", out_dir));
+            if std::env::var("DRBG_GEN_HEADER").as_deref() == Ok("1") {
+                bindings.write_to_file(format!("{}/include/drbg.h", crate_dir));
+            }
+        }
+        Err(err) => panic!("cannot generate drbg.h: {}", err),
+    }
+}
@@ -0,0 +1,92 @@
+/* C interface of the NIST SP 800-90A DRBGs of the rust_nist_drbg crate (see src/ffi.rs). */
+
+#ifndef DRBG_H
+#define DRBG_H
+
+/* This file is generated by cbindgen from src/ffi.rs, do not edit it. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define DRBG_OK 0
+
+#define DRBG_ERR_INVALID_ARGUMENT 1
+
+#define DRBG_ERR_PANIC 2
+
+#define DRBG_ERR_SELFTEST 3
+
+#define DRBG_ERR_INSTANTIATE 10
+
+#define DRBG_ERR_GENERATE 20
+
+#define DRBG_ERR_RESEED 30
+
+#define DRBG_ERR_UNINSTANTIATE 40
+
+#define DRBG_MECH_ALL 0
+
+#define DRBG_MECH_HASH_SHA256 1
+
+#define DRBG_MECH_HASH_SHA512 2
+
+#define DRBG_MECH_HMAC_SHA256 3
+
+#define DRBG_MECH_HMAC_SHA512 4
+
+#define DRBG_MECH_CTR_AES128 5
+
+#define DRBG_MECH_CTR_AES192 6
+
+#define DRBG_MECH_CTR_AES256 7
+
+#define DRBG_MECH_CTR_DF_AES128 8
+
+#define DRBG_MECH_CTR_DF_AES192 9
+
+#define DRBG_MECH_CTR_DF_AES256 10
+
+typedef struct drbg_t drbg_t;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+int drbg_new(uint32_t mech_id,
+             size_t strength,
+             const uint8_t *pers,
+             size_t pers_len,
+             struct drbg_t **handle);
+
+#if defined(DRBG_TEST_ENTROPY)
+int drbg_new_with_entropy(uint32_t mech_id,
+                          size_t strength,
+                          const uint8_t *entropy,
+                          size_t entropy_len,
+                          size_t chunk_len,
+                          const uint8_t *pers,
+                          size_t pers_len,
+                          struct drbg_t **handle);
+#endif
+
+int drbg_generate(struct drbg_t *handle,
+                  uint8_t *out,
+                  size_t out_len,
+                  size_t strength,
+                  int prediction_resistance,
+                  const uint8_t *add,
+                  size_t add_len);
+
+int drbg_reseed(struct drbg_t *handle, const uint8_t *add, size_t add_len);
+
+int drbg_uninstantiate(struct drbg_t *handle);
+
+void drbg_free(struct drbg_t *handle);
+
+int drbg_selftest(uint32_t mech_id);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* DRBG_H */
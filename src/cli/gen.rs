@@ -0,0 +1,178 @@
+use std::fs::File;
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::path::PathBuf;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::{Args, ValueEnum};
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use super::*;
+
+/*  The 'gen' command: instantiates a DRBG with the requested mechanism and strength and writes the requested number of
+    bytes to the standard output or to a file.
+
+    Requests larger than the maximum number of bytes per generate call (MAX_PRB, see 'drbg_conf') are served by
+    consecutive generate calls on the same instance, each with the same prediction resistance request and additional
+    input. The instance is reseeded by the DRBG itself whenever its reseed interval is reached. Output is written as the
+    bytes are generated, so large requests use constant memory. A closed pipe on the output (e.g. 'drbg gen ... | head')
+    is not an error. */
+
+/*  The encodings of the generated bytes. */
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Raw,
+    Hex,
+    Base64,
+}
+
+/*  Bytes given in hex on the command line. */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexBytes(pub Vec<u8>);
+
+/*  The arguments of the 'gen' command. */
+#[derive(Args, Debug)]
+pub struct GenArgs {
+    #[arg(long, value_enum, default_value = "hmac-sha512", help = "DRBG mechanism")]
+    pub mech: Mechanism,
+    #[arg(long, value_parser = parse_strength, help = "Security strength in bits [default: the highest supported by the mechanism]")]
+    pub strength: Option<usize>,
+    #[arg(long, value_parser = parse_size, help = "Number of bytes to generate, K, M and G suffixes are multiples of 1024")]
+    pub bytes: usize,
+    #[arg(long, value_enum, default_value = "hex", help = "Output format")]
+    pub format: OutputFormat,
+    #[arg(long, help = "Request prediction resistance on every generate call")]
+    pub pr: bool,
+    #[arg(long, value_parser = parse_hex, help = "Personalization string in hex")]
+    pub pers: Option<HexBytes>,
+    #[arg(long, value_parser = parse_hex, help = "Additional input in hex, used on every generate call")]
+    pub add: Option<HexBytes>,
+    #[arg(short, long, help = "Output file [default: standard output]")]
+    pub output: Option<PathBuf>,
+}
+
+/*  Parses a number of bytes with an optional K, M or G suffix (case insensitive). */
+pub fn parse_size(value: &str) -> Result<usize, String> {
+    let value = value.trim();
+    let (digits, multiplier) = match value.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&value[..value.len() - 1], 1usize << 10),
+        Some('M') => (&value[..value.len() - 1], 1 << 20),
+        Some('G') => (&value[..value.len() - 1], 1 << 30),
+        _ => (value, 1),
+    };
+
+    digits.parse::<usize>().ok()
+        .and_then(|num| num.checked_mul(multiplier))
+        .ok_or_else(|| format!("invalid size '{}', expected e.g. 4096, 64K, 1M or 2G", value))
+}
+
+/*  Parses a security strength in bits, which must be a multiple of 8. */
+pub fn parse_strength(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(bits) if bits > 0 && bits % 8 == 0 => Ok(bits / 8),
+        _ => Err(format!("invalid strength '{}', expected a number of bits multiple of 8 (e.g. 128, 192, 256)", value)),
+    }
+}
+
+/*  Parses bytes given in hex. */
+pub fn parse_hex(value: &str) -> Result<HexBytes, String> {
+    hex::decode(value).map(HexBytes).map_err(|err| format!("invalid hex string: {}", err))
+}
+
+/*  Runs the 'gen' command.
+
+    Return values:
+        - the exit code of the binary (see 'cli') */
+pub fn run(args: &GenArgs) -> u8 {
+    let res = match &args.output {
+        None => run_to(args, &mut BufWriter::new(io::stdout().lock())),
+        Some(path) => match File::create(path) {
+            Ok(file) => run_to(args, &mut BufWriter::new(file)),
+            Err(err) => Err(CliError::new(EXIT_FAILURE, format!("cannot create '{}': {}", path.display(), err))),
+        },
+    };
+
+    exit_code(res)
+}
+
+/*  Same as run, but the bytes are written to out instead of the output selected by the arguments.
+
+    Return values:
+        - Ok(()): SUCCESS, the bytes have been written (or the output has been closed by the reader)
+        - Err(err): ERROR, err.code is the exit code of the binary */
+pub fn run_to(args: &GenArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    with_mechanism!(args.mech, generate(args, out))
+}
+
+/*  Instantiates the DRBG and writes the generated bytes to out. */
+fn generate<T: DRBG_Mechanism_Functions + 'static>(args: &GenArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    let mut drbg = instantiate::<T>(args.mech, args.strength, args.pers.as_ref())?;
+
+    let res = write_bytes(&mut drbg, out, args);
+    drbg.uninstantiate();
+
+    match res {
+        Ok(()) => Ok(()),
+        Err(GenError::Drbg(err)) => Err(CliError::new(EXIT_GENERATE + err as u8, format!("generation failed with error {}", err))),
+        Err(GenError::Io(err)) if err.kind() == ErrorKind::BrokenPipe => Ok(()),
+        Err(GenError::Io(err)) => Err(CliError::new(EXIT_FAILURE, format!("cannot write the output: {}", err))),
+    }
+}
+
+/*  Instantiates a DRBG with the requested strength (default: the highest supported by mech) and personalization
+    string, the errors are mapped to the exit codes of the binary. */
+pub(crate) fn instantiate<T: DRBG_Mechanism_Functions + 'static>(mech: Mechanism, strength: Option<usize>, pers: Option<&HexBytes>) -> Result<DRBG<T>, CliError> {
+    DRBG::<T>::new(strength.unwrap_or(mech.max_strength()), pers.map(|pers| pers.0.as_slice())).map_err(|err|
+        CliError::new(EXIT_INSTANTIATE + err as u8, format!("instantiation of {} failed with error {}", T::drbg_name(), err)))
+}
+
+/*  The errors of write_bytes. */
+enum GenError {
+    Drbg(usize),
+    Io(io::Error),
+}
+
+impl From<io::Error> for GenError {
+    fn from(err: io::Error) -> Self {
+        GenError::Io(err)
+    }
+}
+
+/*  Generates args.bytes bytes in chunks of at most MAX_PRB bytes and writes them to out with the requested encoding. */
+fn write_bytes<T: DRBG_Mechanism_Functions + 'static>(drbg: &mut DRBG<T>, out: &mut dyn Write, args: &GenArgs) -> Result<(), GenError> {
+    let add = args.add.as_ref().map(|add| add.0.as_slice());
+    let mut bytes = Vec::<u8>::new();
+    let mut left = args.bytes;
+
+    // Base64 is encoded in groups of 3 bytes, the bytes of an incomplete group are kept for the next chunk.
+    let mut pending = Vec::<u8>::new();
+
+    while left > 0 {
+        let chunk = left.min(drbg.get_max_pbr());
+        let res = drbg.generate(&mut bytes, chunk, drbg.get_sec_str(), args.pr, add);
+        if res != 0 {
+            return Err(GenError::Drbg(res));
+        }
+
+        match args.format {
+            OutputFormat::Raw => out.write_all(&bytes)?,
+            OutputFormat::Hex => out.write_all(hex::encode(&bytes).as_bytes())?,
+            OutputFormat::Base64 => {
+                pending.extend_from_slice(&bytes);
+                let ready = pending.len() - pending.len() % 3;
+                out.write_all(STANDARD.encode(&pending[..ready]).as_bytes())?;
+                pending.drain(..ready);
+            }
+        }
+        left -= chunk;
+    }
+
+    if !pending.is_empty() {
+        out.write_all(STANDARD.encode(&pending).as_bytes())?;
+        pending.fill(0);
+    }
+    if args.format != OutputFormat::Raw {
+        out.write_all(b"\n")?;
+    }
+    bytes.fill(0);
+
+    Ok(out.flush()?)
+}
@@ -0,0 +1,221 @@
+pub mod gen;
+pub mod selftest;
+pub mod stream;
+pub mod vectors;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use std::io::{self, Write};
+use crate::self_tests::report::SelfTestReport;
+use crate::self_tests::run_tests;
+
+/*  Command line interface of the crate (binary 'drbg').
+
+    COMMAND         DESCRIPTION
+    gen             generates random bytes with the requested mechanism and writes them as raw bytes, hex or base64
+                    (see 'cli::gen')
+    stream          writes an unbounded stream of random bytes to the standard output, e.g. for statistical test
+                    batteries such as dieharder, PractRand or TestU01 (see 'cli::stream')
+    selftest        runs all the self-tests, or those of a single mechanism, and prints a summary or a JSON report
+                    (see 'cli::selftest')
+    vectors         runs a CAVP response file or an ACVP vector set and prints a summary or a JSON report
+                    (see 'cli::vectors')
+    demo            runs all the self-tests and then the interactive demo (see 'demos'), this is the default command
+
+    The exit codes are mapped to the errors of the DRBG functions (see 'gen_drbg') so that scripts can tell them apart:
+        - 0: SUCCESS
+        - 1: ERROR, a file could not be read or written
+        - 2: ERROR, invalid arguments (including malformed or unsupported vector files)
+        - 3: ERROR, some self-test or test vector failed
+        - 10 + e: ERROR, the instantiation failed with error e (see DRBG_Functions::new)
+        - 20 + e: ERROR, the generation failed with error e (see DRBG_Functions::generate) */
+
+pub const EXIT_SUCCESS: u8 = 0;
+pub const EXIT_FAILURE: u8 = 1;
+pub const EXIT_USAGE: u8 = 2;
+pub const EXIT_TESTS_FAILED: u8 = 3;
+pub const EXIT_INSTANTIATE: u8 = 10;
+pub const EXIT_GENERATE: u8 = 20;
+
+/*  An error of a command: the exit code of the binary and the message printed on the standard error. */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliError {
+    pub code: u8,
+    pub message: String,
+}
+
+impl CliError {
+    /*  Creates an error with the given exit code and message. */
+    pub fn new(code: u8, message: String) -> Self {
+        Self { code, message }
+    }
+}
+
+/*  Prints the error of a command, if any, and returns the exit code of the binary. */
+pub fn exit_code(res: Result<(), CliError>) -> u8 {
+    match res {
+        Ok(()) => EXIT_SUCCESS,
+        Err(err) => {
+            eprintln!("drbg: {}", err.message);
+            err.code
+        }
+    }
+}
+
+/*  The exit codes printed by --help. */
+const EXIT_CODES_HELP: &str = "Exit codes:
+  0       success
+  1       a file could not be read or written
+  2       invalid arguments or vector files
+  3       some self-test or test vector failed
+  10 + e  the instantiation failed with DRBG error e
+  20 + e  the generation failed with DRBG error e";
+
+/*  The arguments of the binary. */
+#[derive(Parser, Debug)]
+#[command(name = "drbg", version, about = "NIST SP 800-90A deterministic random bit generators", after_help = EXIT_CODES_HELP)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/*  The commands of the binary. */
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    #[command(about = "Generate random bytes")]
+    Gen(gen::GenArgs),
+    #[command(about = "Write an unbounded stream of random bytes to the standard output")]
+    Stream(stream::StreamArgs),
+    #[command(about = "Run the self-tests")]
+    Selftest(selftest::SelftestArgs),
+    #[command(about = "Run a CAVP response file (.rsp) or an ACVP vector set (.json)")]
+    Vectors(vectors::VectorsArgs),
+    #[command(about = "Run the self-tests and the interactive demo")]
+    Demo,
+}
+
+/*  The mechanisms that can be selected on the command line. */
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mechanism {
+    #[value(name = "hash-sha256")]
+    HashSha256,
+    #[value(name = "hash-sha512")]
+    HashSha512,
+    #[value(name = "hmac-sha256")]
+    HmacSha256,
+    #[value(name = "hmac-sha512")]
+    HmacSha512,
+    #[value(name = "ctr-aes128")]
+    CtrAes128,
+    #[value(name = "ctr-aes192")]
+    CtrAes192,
+    #[value(name = "ctr-aes256")]
+    CtrAes256,
+    #[value(name = "ctr-df-aes128")]
+    CtrDfAes128,
+    #[value(name = "ctr-df-aes192")]
+    CtrDfAes192,
+    #[value(name = "ctr-df-aes256")]
+    CtrDfAes256,
+}
+
+impl Mechanism {
+    /*  Returns the name of the mechanism as in the sections of the self-tests log (e.g. "Hash-DRBG Sha-256"). */
+    pub fn title(&self) -> &'static str {
+        match self {
+            Mechanism::HashSha256 => "Hash-DRBG Sha-256",
+            Mechanism::HashSha512 => "Hash-DRBG Sha-512",
+            Mechanism::HmacSha256 => "HMAC-DRBG Sha-256",
+            Mechanism::HmacSha512 => "HMAC-DRBG Sha-512",
+            Mechanism::CtrAes128 => "CTR-DRBG AES-128 (no DF)",
+            Mechanism::CtrAes192 => "CTR-DRBG AES-192 (no DF)",
+            Mechanism::CtrAes256 => "CTR-DRBG AES-256 (no DF)",
+            Mechanism::CtrDfAes128 => "CTR-DRBG AES-128 (DF)",
+            Mechanism::CtrDfAes192 => "CTR-DRBG AES-192 (DF)",
+            Mechanism::CtrDfAes256 => "CTR-DRBG AES-256 (DF)",
+        }
+    }
+
+    /*  Returns the highest security strength (in bytes) supported by the mechanism. */
+    pub fn max_strength(&self) -> usize {
+        match self {
+            Mechanism::CtrAes128 | Mechanism::CtrDfAes128 => 16,
+            Mechanism::CtrAes192 | Mechanism::CtrDfAes192 => 24,
+            _ => 32,
+        }
+    }
+}
+
+/*  Calls a generic function with the mechanism type selected by a Mechanism value, e.g.
+    with_mechanism!(mech, generate(&args)) calls generate::<HmacDrbgMech<Sha512>>(&args) for Mechanism::HmacSha512. */
+macro_rules! with_mechanism {
+    ($mech:expr, $func:ident ( $($arg:expr),* )) => {{
+        use $crate::mechs::{hash_mech::HashDrbgMech, hmac_mech::HmacDrbgMech, ctr_mech::CtrDrbgMech,
+                            ctr_mech_with_df::CtrDrbgMech_DF};
+        use $crate::cli::Mechanism;
+        match $mech {
+            Mechanism::HashSha256 => $func::<HashDrbgMech<sha2::Sha256>>($($arg),*),
+            Mechanism::HashSha512 => $func::<HashDrbgMech<sha2::Sha512>>($($arg),*),
+            Mechanism::HmacSha256 => $func::<HmacDrbgMech<sha2::Sha256>>($($arg),*),
+            Mechanism::HmacSha512 => $func::<HmacDrbgMech<sha2::Sha512>>($($arg),*),
+            Mechanism::CtrAes128 => $func::<CtrDrbgMech<aes::Aes128>>($($arg),*),
+            Mechanism::CtrAes192 => $func::<CtrDrbgMech<aes::Aes192>>($($arg),*),
+            Mechanism::CtrAes256 => $func::<CtrDrbgMech<aes::Aes256>>($($arg),*),
+            Mechanism::CtrDfAes128 => $func::<CtrDrbgMech_DF<aes::Aes128>>($($arg),*),
+            Mechanism::CtrDfAes192 => $func::<CtrDrbgMech_DF<aes::Aes192>>($($arg),*),
+            Mechanism::CtrDfAes256 => $func::<CtrDrbgMech_DF<aes::Aes256>>($($arg),*),
+        }
+    }};
+}
+pub(crate) use with_mechanism;
+
+/*  Writes a report to out, either as JSON or as a human readable summary, and checks that it has no failures.
+
+    Return values:
+        - Ok(()): SUCCESS, the report has been written and all its tests passed
+        - Err(err): ERROR, the report could not be written or some test failed (EXIT_TESTS_FAILED) */
+pub fn write_report(report: &SelfTestReport, json: bool, out: &mut dyn Write) -> Result<(), CliError> {
+    let summary = report.summary();
+    let res = if json {
+        writeln!(out, "{}", report.to_json())
+    }
+    else {
+        writeln!(out, "{}: {} tests, {} passed, {} failed ({:.3} s)", summary.suite, summary.total, summary.passed,
+                 summary.failed, summary.duration_us as f64 / 1e6)
+            .and_then(|_| summary.failed_tests.iter().try_for_each(|test| writeln!(out, "FAILED {}", test)))
+    };
+    res.and_then(|_| out.flush())
+        .map_err(|err| CliError::new(EXIT_FAILURE, format!("cannot write the output: {}", err)))?;
+
+    match (summary.total, summary.failed) {
+        (0, _) => Err(CliError::new(EXIT_TESTS_FAILED, format!("{}: no test has been run", summary.suite))),
+        (_, 0) => Ok(()),
+        (_, failed) => Err(CliError::new(EXIT_TESTS_FAILED, format!("{}: {} tests have failed", summary.suite, failed))),
+    }
+}
+
+/*  Runs the selected command.
+
+    Return values:
+        - the exit code of the binary (see the module description) */
+pub fn run(cli: Cli) -> u8 {
+    match cli.command {
+        Some(Command::Gen(args)) => gen::run(&args),
+        Some(Command::Stream(args)) => exit_code(stream::run_to(&args, &mut io::stdout().lock())),
+        Some(Command::Selftest(args)) => exit_code(selftest::run_to(&args, &mut io::stdout().lock())),
+        Some(Command::Vectors(args)) => exit_code(vectors::run_to(&args, &mut io::stdout().lock())),
+        Some(Command::Demo) | None => demo(),
+    }
+}
+
+/*  Runs all the self-tests and then the interactive demo. */
+fn demo() -> u8 {
+    let res = run_tests::run_all().failures();
+    if res > 0 {
+        return exit_code(Err(CliError::new(EXIT_TESTS_FAILED,
+            format!("FATAL ERROR - {res} self-tests have failed, please check the testing log for more information."))));
+    }
+
+    crate::demos::run_demo();
+
+    EXIT_SUCCESS
+}
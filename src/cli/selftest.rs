@@ -0,0 +1,39 @@
+use std::io::Write;
+use clap::Args;
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use crate::self_tests::report::SelfTestReport;
+use crate::self_tests::run_tests;
+use super::*;
+
+/*  The 'selftest' command: runs all the self-tests of the crate (see 'self_tests::run_tests::run_all') or only the
+    mechanism and DRBG self-tests of the selected mechanism, and prints a summary or the JSON report of the tests (see
+    'self_tests::report'). The details of every test are also written to the self-tests log, if one is selected (see
+    'formats'). */
+
+/*  The arguments of the 'selftest' command. */
+#[derive(Args, Debug)]
+pub struct SelftestArgs {
+    #[arg(long, value_enum, help = "Only run the self-tests of this mechanism")]
+    pub mech: Option<Mechanism>,
+    #[arg(long, help = "Print the JSON report instead of a summary")]
+    pub json: bool,
+}
+
+/*  Runs the 'selftest' command, writing its output to out.
+
+    Return values:
+        - Ok(()): SUCCESS, all the self-tests passed
+        - Err(err): ERROR, err.code is the exit code of the binary (see 'cli') */
+pub fn run_to(args: &SelftestArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    let report = match args.mech {
+        None => run_tests::run_all(),
+        Some(mech) => with_mechanism!(mech, run_mechanism(mech)),
+    };
+
+    write_report(&report, args.json, out)
+}
+
+/*  Runs the self-tests of a single mechanism. */
+fn run_mechanism<T: DRBG_Mechanism_Functions + 'static>(mech: Mechanism) -> SelfTestReport {
+    run_tests::run_mechanism::<T>(&format!("{} self-tests", mech.title()), mech.max_strength())
+}
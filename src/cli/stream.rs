@@ -0,0 +1,79 @@
+use std::io::{self, ErrorKind, Read, Write};
+use clap::Args;
+use crate::drbg::gen_drbg::DRBG_Functions;
+use crate::drbg::reader::DrbgReader;
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use super::*;
+use super::gen::{HexBytes, instantiate, parse_hex, parse_size, parse_strength};
+
+/*  The 'stream' command: writes the output of a DRBG to the standard output until the reader closes the pipe (or
+    --bytes bytes have been written), e.g.
+
+        drbg stream --mech ctr-df-aes256 | RNG_test stdin8
+        drbg stream --mech hash-sha512 --pr-interval 1024 | dieharder -a -g 200
+
+    The bytes are read from a DrbgReader (see 'drbg::reader') into a buffer of --buffer bytes, which is written with a
+    single call to the locked standard output so that the stream keeps a high throughput. A closed pipe ends the
+    stream successfully. */
+
+/*  The arguments of the 'stream' command. */
+#[derive(Args, Debug)]
+pub struct StreamArgs {
+    #[arg(long, value_enum, default_value = "hmac-sha512", help = "DRBG mechanism")]
+    pub mech: Mechanism,
+    #[arg(long, value_parser = parse_strength, help = "Security strength in bits [default: the highest supported by the mechanism]")]
+    pub strength: Option<usize>,
+    #[arg(long, value_parser = parse_hex, help = "Personalization string in hex")]
+    pub pers: Option<HexBytes>,
+    #[arg(long, help = "Request prediction resistance every N generate calls")]
+    pub pr_interval: Option<usize>,
+    #[arg(long, value_parser = parse_size, default_value = "1M", help = "Size of the output buffer, K, M and G suffixes are multiples of 1024")]
+    pub buffer: usize,
+    #[arg(long, value_parser = parse_size, help = "Stop after this number of bytes [default: unbounded]")]
+    pub bytes: Option<usize>,
+}
+
+/*  Runs the 'stream' command, writing the stream to out.
+
+    Return values:
+        - Ok(()): SUCCESS, the stream has been closed by the reader or --bytes bytes have been written
+        - Err(err): ERROR, err.code is the exit code of the binary (see 'cli') */
+pub fn run_to(args: &StreamArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    if args.buffer == 0 {
+        return Err(CliError::new(EXIT_USAGE, "the buffer size must be greater than 0".to_string()));
+    }
+
+    with_mechanism!(args.mech, stream(args, out))
+}
+
+/*  Instantiates the DRBG and writes its stream to out. */
+fn stream<T: DRBG_Mechanism_Functions + 'static>(args: &StreamArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    let mut reader = DrbgReader::new(instantiate::<T>(args.mech, args.strength, args.pers.as_ref())?, args.pr_interval);
+    let mut buffer = vec![0u8; args.buffer];
+    let mut left = args.bytes;
+
+    let write_error = |err: io::Error| match err.kind() {
+        ErrorKind::BrokenPipe => Ok(()),
+        _ => Err(CliError::new(EXIT_FAILURE, format!("cannot write the output: {}", err))),
+    };
+
+    let res = loop {
+        let len = left.map_or(buffer.len(), |left| left.min(buffer.len()));
+        if len == 0 {
+            break out.flush().or_else(write_error);
+        }
+        if reader.read_exact(&mut buffer[..len]).is_err() {
+            let err = reader.last_error().unwrap_or(0);
+            break Err(CliError::new(EXIT_GENERATE + err as u8, format!("generation failed with error {}", err)));
+        }
+        if let Err(err) = out.write_all(&buffer[..len]) {
+            break write_error(err);
+        }
+        left = left.map(|left| left - len);
+    };
+
+    buffer.fill(0);
+    reader.get_mut().uninstantiate();
+
+    res
+}
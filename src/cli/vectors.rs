@@ -0,0 +1,128 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use clap::Args;
+use crate::self_tests::acvp::acvp_drbg::*;
+use crate::self_tests::cavp::rsp_runner::run_rsp_file;
+use crate::self_tests::report::{SelfTestReport, TestCaseReport, TestStatus};
+use super::*;
+
+/*  The 'vectors' command: runs a file of test vectors against the mechanisms of this crate.
+
+    FILE                    PROCESSING
+    *.rsp                   CAVP response file named Hash_DRBG.rsp, HMAC_DRBG.rsp or CTR_DRBG.rsp (see 'cavp::rsp_runner'),
+                            every section is a test of the report, sections of unsupported functions are skipped
+    *.json                  ACVP vector set (see 'acvp::acvp_drbg'), the response can be written with --response; with
+                            --expected every test case is a test of the report, otherwise the response is printed
+
+    The report (suite "vectors") is printed as a summary or as JSON and the command fails if any test failed. */
+
+/*  The arguments of the 'vectors' command. */
+#[derive(Args, Debug)]
+pub struct VectorsArgs {
+    #[arg(help = "CAVP response file (.rsp) or ACVP vector set (.json)")]
+    pub file: PathBuf,
+    #[arg(long, help = "ACVP expected results to compare the response with")]
+    pub expected: Option<PathBuf>,
+    #[arg(short = 'o', long, help = "Write the ACVP response to this file")]
+    pub response: Option<PathBuf>,
+    #[arg(long, help = "Print the JSON report (or the ACVP response) instead of a summary")]
+    pub json: bool,
+}
+
+/*  Runs the 'vectors' command, writing its output to out.
+
+    Return values:
+        - Ok(()): SUCCESS, all the test vectors passed
+        - Err(err): ERROR, err.code is the exit code of the binary (see 'cli') */
+pub fn run_to(args: &VectorsArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    match args.file.extension().and_then(|ext| ext.to_str()) {
+        Some("rsp") if args.expected.is_none() && args.response.is_none() => run_rsp(&args.file, args.json, out),
+        Some("rsp") => Err(CliError::new(EXIT_USAGE, "--expected and --response only apply to ACVP vector sets".to_string())),
+        Some("json") => run_acvp(args, out),
+        _ => Err(CliError::new(EXIT_USAGE, format!("'{}' is neither a .rsp nor a .json file", args.file.display()))),
+    }
+}
+
+/*  Runs a CAVP response file. */
+fn run_rsp(path: &Path, json: bool, out: &mut dyn Write) -> Result<(), CliError> {
+    let started = Instant::now();
+    let results = run_rsp_file(path).map_err(|err| match err {
+        1 => CliError::new(EXIT_FAILURE, format!("cannot read '{}'", path.display())),
+        2 => CliError::new(EXIT_USAGE, "the response file must be named Hash_DRBG.rsp, HMAC_DRBG.rsp or CTR_DRBG.rsp".to_string()),
+        _ => CliError::new(EXIT_USAGE, format!("'{}' is malformed", path.display())),
+    })?;
+
+    let mut report = SelfTestReport::new("vectors");
+    for result in results.iter() {
+        if let Some(mechanism) = &result.mechanism {
+            report.add_test(mechanism, TestCaseReport {
+                module: path.display().to_string(),
+                name: result.section.clone(),
+                status: if result.failed() == 0 { TestStatus::Passed } else { TestStatus::Failed },
+                message: format!("{} test cases passed, failed COUNTs: {:?}", result.passed, result.failed_counts),
+                duration_us: 0,
+            });
+        }
+    }
+
+    report.duration_us = started.elapsed().as_micros() as u64;
+
+    let skipped = results.iter().filter(|result| result.skipped()).count();
+    if !json && skipped > 0 {
+        let _ = writeln!(out, "{} sections of unsupported functions have been skipped", skipped);
+    }
+    write_report(&report, json, out)
+}
+
+/*  Runs an ACVP vector set. */
+fn run_acvp(args: &VectorsArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    let acvp_error = |err: usize, path: &Path| match err {
+        1 => CliError::new(EXIT_FAILURE, format!("cannot read or write '{}'", path.display())),
+        5 => CliError::new(EXIT_TESTS_FAILED, format!("a DRBG function failed while running '{}'", path.display())),
+        _ => CliError::new(EXIT_USAGE, format!("'{}' is not a supported ACVP DRBG file (error {})", path.display(), err)),
+    };
+    let read = |path: &Path| fs::read_to_string(path).map_err(|_| acvp_error(1, path));
+    let started = Instant::now();
+
+    let vector_set = parse_vector_set(&read(&args.file)?).map_err(|err| acvp_error(err, &args.file))?;
+    let response = run_vector_set(&vector_set).map_err(|err| acvp_error(err, &args.file))?;
+    if let Some(path) = &args.response {
+        fs::write(path, response_to_json(&response)).map_err(|_| acvp_error(1, path))?;
+    }
+
+    let expected = match &args.expected {
+        Some(path) => parse_response(&read(path)?).map_err(|err| acvp_error(err, path))?,
+        None => {
+            let tests: usize = response.test_groups.iter().map(|group| group.tests.len()).sum();
+            let res = if args.json {
+                writeln!(out, "{}", response_to_json(&response))
+            }
+            else {
+                writeln!(out, "{} vsId {}: {} test cases run, no expected results to compare with", response.algorithm,
+                         response.vs_id, tests)
+            };
+            return res.map_err(|err| CliError::new(EXIT_FAILURE, format!("cannot write the output: {}", err)));
+        }
+    };
+
+    let mismatches = compare_response(&response, &expected);
+    let mut report = SelfTestReport::new("vectors");
+    for exp_group in expected.test_groups.iter() {
+        let mode = vector_set.test_groups.iter().find(|group| group.tg_id == exp_group.tg_id).map_or("", |group| group.mode.as_str());
+        for test in exp_group.tests.iter() {
+            let failed = mismatches.contains(&test.tc_id);
+            report.add_test(&format!("{} {}", vector_set.algorithm, mode), TestCaseReport {
+                module: args.file.display().to_string(),
+                name: format!("tgId {} tcId {}", exp_group.tg_id, test.tc_id),
+                status: if failed { TestStatus::Failed } else { TestStatus::Passed },
+                message: if failed { "wrong or missing returnedBits".to_string() } else { "returnedBits match".to_string() },
+                duration_us: 0,
+            });
+        }
+    }
+    report.duration_us = started.elapsed().as_micros() as u64;
+
+    write_report(&report, args.json, out)
+}
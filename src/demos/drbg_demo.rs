@@ -8,11 +8,12 @@ pub fn drbg_demo<T: DRBG_Mechanism_Functions + 'static>(drbg: &mut DRBG<T>) -> u
 
     println!("Great! Your {} has been instantiated.", T::drbg_name());
     println!("The supported security strength is: {} bytes.", drbg.get_sec_str());
+    println!("The reseed interval is: {} generate requests.", drbg.get_seed_life());
 
     while user_choice != 0 {
         println!("-------------------------------------------------------------------------------------");
         println!("Your reseed counter is of {}", drbg.get_count());
-        println!("You still have {} generations before a forced reseed occurs.\n", drbg.get_seed_life() - drbg.get_count());
+        println!("You still have {} generations before a forced reseed occurs.\n", drbg.get_seed_life() + 1 - drbg.get_count());
         println!("What do you want to try?");
         println!("\t1- Generate bytes");
         println!("\t2- Reseed the DRBG");
@@ -11,8 +11,8 @@ use aes::{Aes128, Aes192, Aes256};
 use sha2::*;
 
 /*  Tries to instantiate the requested DRBG and run the related demo. */
-fn try_run_demo<T: DRBG_Mechanism_Functions + 'static>(str: usize, need_ps: usize) -> usize {
-    let res = inst_drbg::<T>(str, need_ps);
+fn try_run_demo<T: DRBG_Mechanism_Functions + 'static>(str: usize, need_ps: usize, reseed_interval: usize) -> usize {
+    let res = inst_drbg::<T>(str, need_ps, reseed_interval);
 
                         let mut drbg;
                         match res {
@@ -21,6 +21,7 @@ fn try_run_demo<T: DRBG_Mechanism_Functions + 'static>(str: usize, need_ps: usiz
                                     1 => {println!("\nInstantiation failed with error {}: inappropriate security strength.", err);}
                                     2 => {println!("\nInstantiation failed with error {}: personalization string is too long (max sec_str bytes).", err);}
                                     4 => {println!("\nInstantiation failed with error {}: self-tests on first time run failed.", err);}
+                                    7 => {println!("\nInstantiation failed with error {}: the reseed interval is not supported by the mechanism.", err);}
                                     _ => {println!("\nInstantiation failed with error {}: instantiation of the internal mechanism failed.", err);}
                                 }
 
@@ -79,6 +80,10 @@ pub fn run_demo() {
 
         let need_ps = get_input();
 
+        print!("> How many generate requests should be served between reseedings? (DEFAULT=the seed life of the mechanism): ");
+
+        let reseed_interval = get_input();
+
         match scelta_drbg {
             1 => {
                 println!("-------------------------------------------------------------------------------------");
@@ -92,10 +97,10 @@ pub fn run_demo() {
 
                 match mech {
                     1 => {
-                        user_choice = try_run_demo::<HmacDrbgMech<Sha256>>(strength, need_ps);
+                        user_choice = try_run_demo::<HmacDrbgMech<Sha256>>(strength, need_ps, reseed_interval);
                     }
                     2 => {
-                        user_choice = try_run_demo::<HmacDrbgMech<Sha512>>(strength, need_ps);
+                        user_choice = try_run_demo::<HmacDrbgMech<Sha512>>(strength, need_ps, reseed_interval);
                     }
                     _ => {
                         println!("\n\nThanks for testing my drbg!");
@@ -116,10 +121,10 @@ pub fn run_demo() {
 
                 match mech {
                     1 => {
-                        user_choice = try_run_demo::<HashDrbgMech<Sha256>>(strength, need_ps);
+                        user_choice = try_run_demo::<HashDrbgMech<Sha256>>(strength, need_ps, reseed_interval);
                     }
                     2 => {
-                        user_choice = try_run_demo::<HashDrbgMech<Sha512>>(strength, need_ps);
+                        user_choice = try_run_demo::<HashDrbgMech<Sha512>>(strength, need_ps, reseed_interval);
                     }
                     _ => {
                         println!("\n\nThanks for testing my drbg!");
@@ -139,13 +144,13 @@ pub fn run_demo() {
 
                 match mech {
                     1 => {
-                        user_choice = try_run_demo::<CtrDrbgMech<Aes128>>(strength, need_ps);
+                        user_choice = try_run_demo::<CtrDrbgMech<Aes128>>(strength, need_ps, reseed_interval);
                     }
                     2 => {
-                        user_choice = try_run_demo::<CtrDrbgMech<Aes192>>(strength, need_ps);
+                        user_choice = try_run_demo::<CtrDrbgMech<Aes192>>(strength, need_ps, reseed_interval);
                     }
                     3 => {
-                        user_choice = try_run_demo::<CtrDrbgMech<Aes256>>(strength, need_ps);
+                        user_choice = try_run_demo::<CtrDrbgMech<Aes256>>(strength, need_ps, reseed_interval);
                     }
                     _ => {
                         println!("\n\nThanks for testing my drbg!");
@@ -165,13 +170,13 @@ pub fn run_demo() {
 
                 match mech {
                     1 => {
-                        user_choice = try_run_demo::<CtrDrbgMech_DF<Aes128>>(strength, need_ps);
+                        user_choice = try_run_demo::<CtrDrbgMech_DF<Aes128>>(strength, need_ps, reseed_interval);
                     }
                     2 => {
-                        user_choice = try_run_demo::<CtrDrbgMech_DF<Aes192>>(strength, need_ps);
+                        user_choice = try_run_demo::<CtrDrbgMech_DF<Aes192>>(strength, need_ps, reseed_interval);
                     }
                     3 => {
-                        user_choice = try_run_demo::<CtrDrbgMech_DF<Aes256>>(strength, need_ps);
+                        user_choice = try_run_demo::<CtrDrbgMech_DF<Aes256>>(strength, need_ps, reseed_interval);
                     }
                     _ => {
                         println!("\n\nThanks for testing my drbg!");
@@ -25,28 +25,29 @@ pub fn get_input() -> usize {
     }
 }
 
-/*  Utility function that instantiates the desired DRBG with the desired strength and ps */
-pub fn inst_drbg<T: DRBG_Mechanism_Functions + 'static>(sec_str: usize, need_ps: usize) -> Result<DRBG<T>, usize> {
+/*  Utility function that instantiates the desired DRBG with the desired strength, ps and reseed interval (0 for the
+    default one of the mechanism) */
+pub fn inst_drbg<T: DRBG_Mechanism_Functions + 'static>(sec_str: usize, need_ps: usize, reseed_interval: usize) -> Result<DRBG<T>, usize> {
+    let ps: [u8; 32] = rand::thread_rng().gen();
+    let mut actual_pers = None;
+
     if need_ps == 1 {
-        let ps: [u8; 32];
-        ps = rand::thread_rng().gen();
-        let actual_pers;
-        
         if sec_str > 32 {
-            actual_pers = ps.as_slice();
+            actual_pers = Some(ps.as_slice());
         }
         else{
-            actual_pers = &ps[0..sec_str];
+            actual_pers = Some(&ps[0..sec_str]);
         }
         
         println!("-------------------------------------------------------------------------------------");
-        println!("Used pers: {}, len: {}\n", hex::encode(&actual_pers), actual_pers.len());
-
-        return DRBG::<T>::new(sec_str, Some(&actual_pers));
+        println!("Used pers: {}, len: {}\n", hex::encode(actual_pers.unwrap()), actual_pers.unwrap().len());
     }
-    else {
-        return DRBG::<T>::new(sec_str, None);
+
+    if reseed_interval != 0 {
+        return DRBG::<T>::new_with_reseed_interval(sec_str, actual_pers, reseed_interval);
     }
+    
+    DRBG::<T>::new(sec_str, actual_pers)
 }
 
 /*  Utility function that generates bytes using the passed DRBG */
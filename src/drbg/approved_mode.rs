@@ -0,0 +1,119 @@
+use std::any::TypeId;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::mechs::{gen_mech::DRBG_Mechanism_Functions, hash_mech::HashDrbgMech, hmac_mech::HmacDrbgMech,
+                   ctr_mech::CtrDrbgMech, ctr_mech_with_df::CtrDrbgMech_DF};
+use sha2::*;
+use aes::*;
+
+/*  Approved mode policy and service indicator.
+
+    Every instantiate, reseed and generate call sets a service indicator that states whether the service that has been
+    delivered is approved. A service is approved if it succeeded and the instance that delivered it is approved:
+        - the mechanism is one of the approved mechanisms of this crate (see approved_mechanism)
+        - the entropy source is approved (see 'entropy', test sources like FixedEntropySource are not)
+        - the entropy inputs are at least security_strength bytes long and the nonce at least security_strength/2 bytes
+        - the security strength of the instance is at least MIN_APPROVED_SEC_STR bytes
+    An instance stops being approved if one of its reseeds gets a short entropy input.
+
+    The indicator of an instance is returned by DRBG_Functions::get_service_indicator, the indicator of the last call
+    made on the current thread (including failed instantiations) by last_service_indicator.
+
+    When the approved mode is enabled:
+        - non-approved instances are rejected by DRBG::new (error 6)
+        - non-approved instances created before the approved mode was enabled can no longer be reseeded (error 4) or
+          used to generate bytes (error 6)
+        - the self-tests of each mechanism must be run before its first use, even after the overall self-tests
+
+    The self-tests that are run inside the DRBG (e.g. on first use) are not subject to the approved mode, as they
+    purposely use test entropy sources and non-approved parameters. */
+
+/*  The minimum security strength (in bytes) of an approved instance (112 bits). */
+pub const MIN_APPROVED_SEC_STR: usize = 14;
+
+/*  The indicator of the service delivered by a DRBG function. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceIndicator {
+    Approved,
+    NonApproved,
+}
+
+impl ServiceIndicator {
+    /*  Returns the indicator matching the given approval. */
+    pub fn from_approved(approved: bool) -> Self {
+        if approved { ServiceIndicator::Approved } else { ServiceIndicator::NonApproved }
+    }
+
+    /*  Returns true if the service was approved. */
+    pub fn is_approved(&self) -> bool {
+        *self == ServiceIndicator::Approved
+    }
+}
+
+static APPROVED_MODE: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static LAST_INDICATOR: Cell<ServiceIndicator> = const { Cell::new(ServiceIndicator::NonApproved) };
+    static SELF_TEST_SCOPE: Cell<usize> = const { Cell::new(0) };
+}
+
+/*  Enables or disables the approved mode, returning the previous setting. */
+pub fn set_approved_mode(enabled: bool) -> bool {
+    APPROVED_MODE.swap(enabled, Ordering::SeqCst)
+}
+
+/*  Returns true if the approved mode is enforced on the current thread (it is not enforced on the self-tests). */
+pub fn approved_mode() -> bool {
+    APPROVED_MODE.load(Ordering::SeqCst) && SELF_TEST_SCOPE.with(|scope| scope.get()) == 0
+}
+
+/*  Returns true if the mechanism T is approved. */
+pub fn approved_mechanism<T: DRBG_Mechanism_Functions + 'static>() -> bool {
+    let approved = [
+        TypeId::of::<HashDrbgMech<Sha256>>(),
+        TypeId::of::<HashDrbgMech<Sha512>>(),
+        TypeId::of::<HmacDrbgMech<Sha256>>(),
+        TypeId::of::<HmacDrbgMech<Sha512>>(),
+        TypeId::of::<CtrDrbgMech<Aes128>>(),
+        TypeId::of::<CtrDrbgMech<Aes192>>(),
+        TypeId::of::<CtrDrbgMech<Aes256>>(),
+        TypeId::of::<CtrDrbgMech_DF<Aes128>>(),
+        TypeId::of::<CtrDrbgMech_DF<Aes192>>(),
+        TypeId::of::<CtrDrbgMech_DF<Aes256>>(),
+    ];
+
+    approved.contains(&TypeId::of::<T>())
+}
+
+/*  Returns the service indicator of the last instantiate, reseed or generate call made on the current thread. */
+pub fn last_service_indicator() -> ServiceIndicator {
+    LAST_INDICATOR.with(|last| last.get())
+}
+
+/*  Records the service indicator of a call made on the current thread. */
+pub(crate) fn set_last_service_indicator(indicator: ServiceIndicator) {
+    LAST_INDICATOR.with(|last| last.set(indicator));
+}
+
+/*  Marks the current thread as running self-tests while it is alive. It is also dropped while unwinding, so that a panic
+    caught outside of the self-tests (e.g. by the C interface) does not leave the approved mode disabled on the thread. */
+struct SelfTestScope;
+
+impl SelfTestScope {
+    fn enter() -> Self {
+        SELF_TEST_SCOPE.with(|scope| scope.set(scope.get() + 1));
+        SelfTestScope
+    }
+}
+
+impl Drop for SelfTestScope {
+    fn drop(&mut self) {
+        SELF_TEST_SCOPE.with(|scope| scope.set(scope.get() - 1));
+    }
+}
+
+/*  Runs self-tests on the current thread without enforcing the approved mode. */
+pub(crate) fn self_test_scope<R>(f: impl FnOnce() -> R) -> R {
+    let _scope = SelfTestScope::enter();
+    f()
+}
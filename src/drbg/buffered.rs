@@ -0,0 +1,218 @@
+use std::time::Instant;
+use crate::drbg::fork;
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+
+/*  DRBG wrapper that serves small requests (e.g. 12-16 byte nonces) from a block of pre-generated bytes, so that most
+    requests do not go through the checks, allocations and state update of a generate call.
+
+    The block is block_size bytes long and is generated on demand (or ahead of time, see prefetch) by as many generate
+    calls of at most MAX_PRB bytes as needed, at the full security strength of the instance. Each served byte is wiped
+    from the block, and so is the whole block when it is discarded or the wrapper is dropped. The block is discarded:
+        - on reseed and uninstantiate (see reseed and uninstantiate of this wrapper)
+        - when the process has been forked since the block was generated (see 'fork'), so that parent and child never
+          serve the same bytes
+        - on requests with additional input or prediction resistance, which are never served from old bytes. By default
+          such requests bypass the block and are served by a generate call of their own; with bypass disabled, a new
+          block is generated with the additional input and prediction resistance and the request is served from it.
+        - once the max_duration of the reseed policy of the instance (see 'reseed_policy') has elapsed since the seed
+          the block was generated from, so that no byte is served after the time limit of its seed
+
+    The reseed policy is otherwise enforced on the generate calls that fill the blocks: a block is never longer than the
+    max_bytes of the policy, and max_requests counts these generate calls, not the requests served from the blocks.
+
+    Requests larger than the block are served directly by the instance. Every request, also when served from the block,
+    goes through the state checks of generate: no byte is served by an instance or a module in error state (see
+    'module_state') nor, in approved mode, by a non-approved instance (see 'approved_mode'), in which case the block is
+    discarded. The service indicator of the instance is set on every request. */
+pub struct BufferedDrbg<T: DRBG_Mechanism_Functions + 'static> {
+    drbg: DRBG<T>,
+    block: Vec<u8>,
+    scratch: Vec<u8>,
+    pos: usize,
+    block_size: usize,
+    bypass: bool,
+    fork_generation: u64,
+    seeded: Instant,
+}
+
+impl<T: DRBG_Mechanism_Functions + 'static> BufferedDrbg<T> {
+    /*  Creates a wrapper over the given instance which pre-generates blocks of block_size bytes (at least 1). */
+    pub fn new(drbg: DRBG<T>, block_size: usize) -> Self {
+        Self { drbg, block: Vec::new(), scratch: Vec::new(), pos: 0, block_size: block_size.max(1), bypass: true,
+               fork_generation: fork::generation(), seeded: Instant::now() }
+    }
+
+    /*  Returns the underlying instance. */
+    pub fn get_ref(&self) -> &DRBG<T> {
+        &self.drbg
+    }
+
+    /*  Returns the size of the pre-generated blocks, bounded by the max_bytes of the reseed policy of the instance. */
+    pub fn block_size(&self) -> usize {
+        match self.drbg.get_reseed_policy().max_bytes {
+            Some(max_bytes) => self.block_size.min(usize::try_from(max_bytes).unwrap_or(usize::MAX)),
+            None => self.block_size,
+        }
+    }
+
+    /*  Returns the number of pre-generated bytes that have not been served yet. */
+    pub fn buffered(&self) -> usize {
+        self.block.len() - self.pos
+    }
+
+    /*  Sets whether requests with additional input or prediction resistance bypass the block (the default) or are
+        served from a new block generated with them. */
+    pub fn set_bypass(&mut self, bypass: bool) {
+        self.bypass = bypass;
+    }
+
+    /*  Returns whether requests with additional input or prediction resistance bypass the block. */
+    pub fn get_bypass(&self) -> bool {
+        self.bypass
+    }
+
+    /*  Fills out with pseudo-random bytes, see DRBG_Functions::generate for the parameters and the error codes.
+        Nothing is written into out on failure. */
+    pub fn generate(&mut self, out: &mut [u8], req_str: usize, pred_res_req: bool, add: Option<&[u8]>) -> usize {
+        let res = self.drbg.serve_check();
+        if res != 0 {
+            self.discard();
+            return res;
+        }
+        if self.stale() {
+            self.discard();
+        }
+
+        // Requests that cannot be served from the current block.
+        let block_size = self.block_size();
+        if pred_res_req || add.is_some() {
+            self.discard();
+            if self.bypass || out.len() > block_size {
+                return self.generate_direct(out, req_str, pred_res_req, add);
+            }
+            if req_str > self.drbg.get_sec_str() {
+                return self.generate_direct(out, req_str, false, None);
+            }
+
+            let res = self.refill(pred_res_req, add);
+            if res != 0 {
+                return res;
+            }
+        }
+        else if out.len() > block_size || req_str > self.drbg.get_sec_str() {
+            return self.generate_direct(out, req_str, false, None);
+        }
+
+        // Serving the request from the block, eventually refilling it.
+        let mut served = 0;
+        while served < out.len() {
+            if self.pos == self.block.len() {
+                let res = self.refill(false, None);
+                if res != 0 {
+                    out[..served].fill(0);
+                    return res;
+                }
+            }
+
+            let len = (out.len() - served).min(self.block.len() - self.pos);
+            out[served..served + len].copy_from_slice(&self.block[self.pos..self.pos + len]);
+            self.block[self.pos..self.pos + len].fill(0);
+            self.pos += len;
+            served += len;
+        }
+
+        0
+    }
+
+    /*  Fills the block if it has been consumed, so that the next requests are served without a generate call.
+        Return values are those of DRBG_Functions::generate. */
+    pub fn prefetch(&mut self) -> usize {
+        let res = self.drbg.serve_check();
+        if res != 0 {
+            self.discard();
+            return res;
+        }
+        if self.stale() {
+            self.discard();
+        }
+        if self.buffered() > 0 {
+            return 0;
+        }
+
+        self.refill(false, None)
+    }
+
+    /*  Discards the block and reseeds the instance, see DRBG_Functions::reseed. */
+    pub fn reseed(&mut self, add: Option<&[u8]>) -> usize {
+        self.discard();
+        self.drbg.reseed(add)
+    }
+
+    /*  Discards the block and uninstantiates the instance, see DRBG_Functions::uninstantiate. */
+    pub fn uninstantiate(&mut self) -> usize {
+        self.discard();
+        self.drbg.uninstantiate()
+    }
+
+    /*  Returns true if the block must not be served anymore: the process has been forked since it was generated, or the
+        max_duration of the reseed policy has elapsed since its seed. */
+    fn stale(&self) -> bool {
+        self.fork_generation != fork::generation() ||
+            self.drbg.get_reseed_policy().max_duration.is_some_and(|max| self.seeded.elapsed() >= max)
+    }
+
+    /*  Wipes and empties the block. */
+    fn discard(&mut self) {
+        self.block.fill(0);
+        self.block.clear();
+        self.pos = 0;
+    }
+
+    /*  Replaces the block with a new one, the additional input and prediction resistance are used on its first
+        generate call. */
+    fn refill(&mut self, pred_res_req: bool, add: Option<&[u8]>) -> usize {
+        self.discard();
+        self.fork_generation = fork::generation();
+
+        let block_size = self.block_size();
+        let (mut pred_res_req, mut add) = (pred_res_req, add);
+        while self.block.len() < block_size {
+            let len = (block_size - self.block.len()).min(self.drbg.get_max_pbr());
+            let res = self.drbg.generate(&mut self.scratch, len, self.drbg.get_sec_str(), pred_res_req, add);
+            if res != 0 {
+                self.discard();
+                return res;
+            }
+
+            // The block expires with the seed of its first bytes, the later ones can only come from a newer seed.
+            if self.block.is_empty() {
+                self.seeded = self.drbg.last_reseed();
+            }
+
+            self.block.extend_from_slice(&self.scratch);
+            self.scratch.fill(0);
+            (pred_res_req, add) = (false, None);
+        }
+
+        0
+    }
+
+    /*  Serves a request with a generate call of its own. */
+    fn generate_direct(&mut self, out: &mut [u8], req_str: usize, pred_res_req: bool, add: Option<&[u8]>) -> usize {
+        let res = self.drbg.generate(&mut self.scratch, out.len(), req_str, pred_res_req, add);
+        if res == 0 {
+            out.copy_from_slice(&self.scratch);
+        }
+        self.scratch.fill(0);
+
+        res
+    }
+}
+
+impl<T: DRBG_Mechanism_Functions + 'static> Drop for BufferedDrbg<T> {
+    fn drop(&mut self) {
+        self.block.fill(0);
+        self.scratch.fill(0);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::drbg::gen_drbg::DRBG_Functions;
+
+/*  Unbiased random values drawn from the output of a DRBG instance, so that applications do not reduce generated bytes
+    modulo a bound (which favours the smallest values whenever the bound is not a power of 2).
+
+    Every value is obtained by rejection sampling, as in the simple discard method of appendix A of FIPS 186-5: a
+    candidate of the bit length of the bound is generated and discarded as long as it is not below the bound. Less than
+    two candidates are needed on average. The bytes are generated at the full security strength of the instance,
+    without prediction resistance nor additional input, by as many generate calls of at most get_max_pbr bytes as the
+    length of the bound needs (e.g. two calls for the 512-byte modulus of an RSA-4096 key).
+
+    The functions are available on every DRBG (see DRBG_Functions) and return:
+        - Ok(value): SUCCESS
+        - Err(1..7): ERROR, a generate call failed (see DRBG_Functions::generate)
+        - Err(INVALID_ARGUMENT): ERROR, empty range, bound or slice, or more samples than items */
+
+/*  The error returned for invalid arguments (above the error codes of DRBG_Functions::generate). */
+pub const INVALID_ARGUMENT: usize = 8;
+
+#[allow(non_camel_case_types)]
+pub trait Distribution_Functions {
+    /*  Returns a uniform integer below the given bound, both as big-endian bytes of the same length as the bound (which
+        can have leading zeros and be of any length). */
+    fn gen_below(&mut self, bound: &[u8]) -> Result<Vec<u8>, usize>;
+
+    /*  Returns a uniform integer in [lo, hi). */
+    fn gen_range(&mut self, lo: u64, hi: u64) -> Result<u64, usize>;
+
+    /*  Shuffles the items in place with the Fisher-Yates algorithm, every permutation being equally likely. */
+    fn shuffle<E>(&mut self, items: &mut [E]) -> Result<(), usize>;
+
+    /*  Returns k distinct items chosen uniformly (sampling without replacement), in random order. */
+    fn sample<'a, E>(&mut self, items: &'a [E], k: usize) -> Result<Vec<&'a E>, usize>;
+
+    /*  Returns an item chosen uniformly. */
+    fn choose<'a, E>(&mut self, items: &'a [E]) -> Result<&'a E, usize>;
+}
+
+impl<D: DRBG_Functions + ?Sized> Distribution_Functions for D {
+    fn gen_below(&mut self, bound: &[u8]) -> Result<Vec<u8>, usize> {
+        // The candidates have the bit length of the bound, the leading zero bytes of the bound are kept as they are.
+        let start = match bound.iter().position(|byte| *byte != 0) {
+            None => return Err(INVALID_ARGUMENT),
+            Some(start) => start,
+        };
+        let mask = 0xffu8 >> bound[start].leading_zeros();
+        let len = bound.len() - start;
+        // An uninstantiated instance has no maximum, its generate calls fail anyway.
+        let max_pbr = self.get_max_pbr().max(1);
+
+        let mut candidate = Vec::<u8>::with_capacity(len);
+        let mut chunk = Vec::<u8>::new();
+        loop {
+            // Filling the candidate with requests of at most max_pbr bytes.
+            candidate.clear();
+            while candidate.len() < len {
+                let res = self.generate(&mut chunk, (len - candidate.len()).min(max_pbr), self.get_sec_str(), false, None);
+                if res != 0 {
+                    candidate.fill(0);
+                    return Err(res);
+                }
+                candidate.extend_from_slice(&chunk);
+                chunk.fill(0);
+            }
+            candidate[0] &= mask;
+
+            // Big-endian values of the same length are compared as byte strings.
+            if candidate[..] < bound[start..] {
+                let mut value = vec![0u8; start];
+                value.append(&mut candidate);
+                return Ok(value);
+            }
+        }
+    }
+
+    fn gen_range(&mut self, lo: u64, hi: u64) -> Result<u64, usize> {
+        if lo >= hi {
+            return Err(INVALID_ARGUMENT);
+        }
+
+        let value = self.gen_below(&(hi - lo).to_be_bytes())?;
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&value);
+
+        Ok(lo + u64::from_be_bytes(bytes))
+    }
+
+    fn shuffle<E>(&mut self, items: &mut [E]) -> Result<(), usize> {
+        for i in (1..items.len()).rev() {
+            let j = self.gen_range(0, i as u64 + 1)? as usize;
+            items.swap(i, j);
+        }
+
+        Ok(())
+    }
+
+    fn sample<'a, E>(&mut self, items: &'a [E], k: usize) -> Result<Vec<&'a E>, usize> {
+        if k > items.len() {
+            return Err(INVALID_ARGUMENT);
+        }
+
+        // The first k steps of a Fisher-Yates shuffle of the indexes.
+        let mut indexes: Vec<usize> = (0..items.len()).collect();
+        for i in 0..k {
+            let j = self.gen_range(i as u64, items.len() as u64)? as usize;
+            indexes.swap(i, j);
+        }
+
+        Ok(indexes[..k].iter().map(|index| &items[*index]).collect())
+    }
+
+    fn choose<'a, E>(&mut self, items: &'a [E]) -> Result<&'a E, usize> {
+        if items.is_empty() {
+            return Err(INVALID_ARGUMENT);
+        }
+
+        Ok(&items[self.gen_range(0, items.len() as u64)? as usize])
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::atomic::AtomicBool;
+
 /*  Configuration of the DRBG.
     
     This DRBG can be instantiated using anyone of the mechanisms defined in the 'mechs' module. These mechanisms support 
@@ -21,18 +23,25 @@
 pub const MAX_SEC_STR: usize = 32;      // Maximum security strength supported by any of the available mechanisms
 pub const MAX_PRB: usize = 256;         // Maximum number of bytes that can be requested at each generate call
 
-/*  These constants are used to determine whether each mechanism has been already used or not. If not, self-tests for that
-    mechanism must be run and passed before instantiating and operating the DRGB and with that specific mechanism. */
-pub static mut FIRST_USE_HASH_SHA_256: bool = true;         // true => first time the HASH-DRBG with Sha-256 has been instantiated
-pub static mut FIRST_USE_HASH_SHA_512: bool = true;         // true => first time the HASH-DRBG with Sha-512 has been instantiated
-pub static mut FIRST_USE_HMAC_SHA_256: bool = true;         // true => first time the HMAC-DRBG with Sha-256 has been instantiated
-pub static mut FIRST_USE_HMAC_SHA_512: bool = true;         // true => first time the HMAC-DRBG with Sha-512 has been instantiated
-pub static mut FIRST_USE_CTR_NO_DF_AES_128: bool = true;    // true => first time the CTR-DRBG (no DF) with AES-128 has been instantiated
-pub static mut FIRST_USE_CTR_NO_DF_AES_192: bool = true;    // true => first time the CTR-DRBG (no DF) with AES-192 has been instantiated
-pub static mut FIRST_USE_CTR_NO_DF_AES_256: bool = true;    // true => first time the CTR-DRBG (no DF) with AES-256 has been instantiated
-pub static mut FIRST_USE_CTR_DF_AES_128: bool = true;       // true => first time the CTR-DRBG (DF) with AES-128 has been instantiated
-pub static mut FIRST_USE_CTR_DF_AES_192: bool = true;       // true => first time the CTR-DRBG (DF) with AES-192 has been instantiated
-pub static mut FIRST_USE_CTR_DF_AES_256: bool = true;       // true => first time the CTR-DRBG (DF) with AES-256 has been instantiated
+/*  Default number of generate calls of a mechanism between two runs of its health tests (see 'health'). */
+pub const HEALTH_TEST_INTERVAL: usize = 1 << 16;
+
+/*  Default maximum number of internal states that a DrbgModule can hold at the same time (see 'drbg_module'). */
+pub const MAX_INSTANCES: usize = 16;
+
+/*  These flags are used to determine whether each mechanism has been already used or not. If not, self-tests for that
+    mechanism must be run and passed before instantiating and operating the DRGB and with that specific mechanism.
+    They are atomics since instances can be created from several threads at once. */
+pub static FIRST_USE_HASH_SHA_256: AtomicBool = AtomicBool::new(true);         // true => first time the HASH-DRBG with Sha-256 has been instantiated
+pub static FIRST_USE_HASH_SHA_512: AtomicBool = AtomicBool::new(true);         // true => first time the HASH-DRBG with Sha-512 has been instantiated
+pub static FIRST_USE_HMAC_SHA_256: AtomicBool = AtomicBool::new(true);         // true => first time the HMAC-DRBG with Sha-256 has been instantiated
+pub static FIRST_USE_HMAC_SHA_512: AtomicBool = AtomicBool::new(true);         // true => first time the HMAC-DRBG with Sha-512 has been instantiated
+pub static FIRST_USE_CTR_NO_DF_AES_128: AtomicBool = AtomicBool::new(true);    // true => first time the CTR-DRBG (no DF) with AES-128 has been instantiated
+pub static FIRST_USE_CTR_NO_DF_AES_192: AtomicBool = AtomicBool::new(true);    // true => first time the CTR-DRBG (no DF) with AES-192 has been instantiated
+pub static FIRST_USE_CTR_NO_DF_AES_256: AtomicBool = AtomicBool::new(true);    // true => first time the CTR-DRBG (no DF) with AES-256 has been instantiated
+pub static FIRST_USE_CTR_DF_AES_128: AtomicBool = AtomicBool::new(true);       // true => first time the CTR-DRBG (DF) with AES-128 has been instantiated
+pub static FIRST_USE_CTR_DF_AES_192: AtomicBool = AtomicBool::new(true);       // true => first time the CTR-DRBG (DF) with AES-192 has been instantiated
+pub static FIRST_USE_CTR_DF_AES_256: AtomicBool = AtomicBool::new(true);       // true => first time the CTR-DRBG (DF) with AES-256 has been instantiated
 
-/*  This constant is set when overall self-tests are run over all available algorithms to avoid running tests twice. */
-pub static mut OVERALL_TEST_RUN: bool = false;
\ No newline at end of file
+/*  This flag is set when overall self-tests are run over all available algorithms to avoid running tests twice. */
+pub static OVERALL_TEST_RUN: AtomicBool = AtomicBool::new(false);
\ No newline at end of file
@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::drbg::drbg_conf::MAX_INSTANCES;
+use crate::drbg::entropy::Entropy_Source_Functions;
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::drbg::approved_mode::ServiceIndicator;
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+
+/*  Registry of DRBG instances addressed by state handles, as described in section 9 of SP 800-90A.
+
+    A DrbgModule owns the internal states of all its instances, which can be of different mechanisms, and holds at most
+    max_instances of them at the same time. Instantiate returns an opaque StateHandle, which is then passed to
+    generate, reseed and uninstantiate. A handle is only valid in the module that returned it and until its instance is
+    uninstantiated: it is never reused, so a stale handle can never refer to another instance.
+
+    Every call has the same behaviour and error flags of the matching DRBG function (see 'gen_drbg'), in addition:
+        - instantiate returns Err(8) when max_instances internal states are already in use (step 9 of section 9.1), a
+          flag that no DRBG function returns (7 is the fork refusal of generate, see 'fork')
+        - generate, reseed and uninstantiate return 1 for an invalid handle (as for an invalid internal state)
+
+    The instances can be enumerated (see instances) and uninstantiated all at once (see uninstantiate_all), which is
+    also done when the module is dropped. */
+
+/*  The source of the identifiers of the handles, shared by all modules so that handles are unique in the process. */
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+/*  Opaque handle to an internal state held by a DrbgModule. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StateHandle(u64);
+
+/*  The description of an instance held by a module.
+
+    - handle: the handle of the instance
+    - mechanism: the identifier of its mechanism (see DRBG_Mechanism_Functions::mech_id)
+    - security_strength, count, seed_life: see the utility functions of DRBG_Functions
+    - approved: whether the last call on the instance was an approved service (see 'approved_mode') */
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstanceInfo {
+    pub handle: StateHandle,
+    pub mechanism: String,
+    pub security_strength: usize,
+    pub count: usize,
+    pub seed_life: usize,
+    pub approved: bool,
+}
+
+/*  An instance held by a module. */
+struct Instance {
+    mechanism: String,
+    drbg: Box<dyn DRBG_Functions + Send>,
+}
+
+/*  Module owning the internal states of DRBG instances (see the description of this file). */
+pub struct DrbgModule {
+    instances: BTreeMap<StateHandle, Instance>,
+    max_instances: usize,
+}
+
+impl Default for DrbgModule {
+    fn default() -> Self {
+        Self::new(MAX_INSTANCES)
+    }
+}
+
+impl DrbgModule {
+    /*  Creates an empty module that holds at most max_instances internal states. */
+    pub fn new(max_instances: usize) -> Self {
+        Self { instances: BTreeMap::new(), max_instances }
+    }
+
+    /*  Returns the maximum number of internal states of this module. */
+    pub fn max_instances(&self) -> usize {
+        self.max_instances
+    }
+
+    /*  Returns the number of internal states in use. */
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    /*  Returns true if no internal state is in use. */
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    /*  Instantiates a new DRBG using the mechanism T (see DRBG_Functions::new).
+
+        Return values:
+            - Ok(handle): SUCCESS, the handle of the new instance
+            - Err(1..6): ERROR, see DRBG_Functions::new
+            - Err(8): ERROR, all the internal states of the module are in use */
+    pub fn instantiate<T: DRBG_Mechanism_Functions + Send + 'static>(&mut self, req_sec_str: usize, ps: Option<&[u8]>) -> Result<StateHandle, usize> {
+        self.add::<T>(|| DRBG::<T>::new(req_sec_str, ps))
+    }
+
+    /*  Same as instantiate, using the given entropy source (see DRBG_Functions::new_with_entropy). */
+    pub fn instantiate_with_entropy<T: DRBG_Mechanism_Functions + Send + 'static>(&mut self, req_sec_str: usize, ps: Option<&[u8]>,
+                                                                                 entropy_source: Box<dyn Entropy_Source_Functions>) -> Result<StateHandle, usize> {
+        self.add::<T>(|| DRBG::<T>::new_with_entropy(req_sec_str, ps, entropy_source))
+    }
+
+    /*  Takes ownership of an instance that has been created (or restored, see DRBG::import_state) outside the module.
+
+        Return values:
+            - Ok(handle): SUCCESS, the handle of the instance
+            - Err(drbg): ERROR, all the internal states of the module are in use, the instance is given back */
+    pub fn insert<T: DRBG_Mechanism_Functions + Send + 'static>(&mut self, drbg: DRBG<T>) -> Result<StateHandle, DRBG<T>> {
+        if self.instances.len() >= self.max_instances {
+            return Err(drbg);
+        }
+
+        Ok(self.store::<T>(drbg))
+    }
+
+    /*  Generates pseudo-random bytes with the instance of the given handle (see DRBG_Functions::generate).
+
+        Return values:
+            - 0: SUCCESS
+            - 1: ERROR, invalid handle, see also DRBG_Functions::generate
+            - 2..7: ERROR, see DRBG_Functions::generate */
+    pub fn generate(&mut self, handle: StateHandle, bytes: &mut Vec<u8>, req_bytes: usize, req_str: usize, pred_res_req: bool,
+                    add: Option<&[u8]>) -> usize {
+        match self.instances.get_mut(&handle) {
+            None => {
+                bytes.clear();
+                1
+            }
+            Some(instance) => instance.drbg.generate(bytes, req_bytes, req_str, pred_res_req, add),
+        }
+    }
+
+    /*  Reseeds the instance of the given handle (see DRBG_Functions::reseed).
+
+        Return values:
+            - 0: SUCCESS
+            - 1: ERROR, invalid handle, see also DRBG_Functions::reseed
+            - 2..4: ERROR, see DRBG_Functions::reseed */
+    pub fn reseed(&mut self, handle: StateHandle, add: Option<&[u8]>) -> usize {
+        match self.instances.get_mut(&handle) {
+            None => 1,
+            Some(instance) => instance.drbg.reseed(add),
+        }
+    }
+
+    /*  Zeroizes the instance of the given handle and releases its internal state, the handle is no longer valid.
+
+        Return values:
+            - 0: SUCCESS
+            - 1: ERROR, invalid handle */
+    pub fn uninstantiate(&mut self, handle: StateHandle) -> usize {
+        match self.instances.remove(&handle) {
+            None => 1,
+            Some(mut instance) => {
+                // An instance in error state has already been zeroized.
+                instance.drbg.uninstantiate();
+                0
+            }
+        }
+    }
+
+    /*  Zeroizes all the instances of the module and releases their internal states.
+
+        Return values:
+            - the number of instances that have been uninstantiated */
+    pub fn uninstantiate_all(&mut self) -> usize {
+        let handles = self.handles();
+        for handle in handles.iter() {
+            self.uninstantiate(*handle);
+        }
+
+        handles.len()
+    }
+
+    /*  Returns the handles of the instances of the module, in order of instantiation. */
+    pub fn handles(&self) -> Vec<StateHandle> {
+        self.instances.keys().copied().collect()
+    }
+
+    /*  Returns the description of the instance of the given handle, if valid. */
+    pub fn info(&self, handle: StateHandle) -> Option<InstanceInfo> {
+        self.instances.get(&handle).map(|instance| InstanceInfo {
+            handle,
+            mechanism: instance.mechanism.clone(),
+            security_strength: instance.drbg.get_sec_str(),
+            count: instance.drbg.get_count(),
+            seed_life: instance.drbg.get_seed_life(),
+            approved: instance.drbg.get_service_indicator() == ServiceIndicator::Approved,
+        })
+    }
+
+    /*  Returns the description of all the instances of the module, in order of instantiation. */
+    pub fn instances(&self) -> Vec<InstanceInfo> {
+        self.handles().into_iter().filter_map(|handle| self.info(handle)).collect()
+    }
+
+    /*  Creates an instance with the given function if an internal state is available. */
+    fn add<T: DRBG_Mechanism_Functions + Send + 'static>(&mut self, new: impl FnOnce() -> Result<DRBG<T>, usize>) -> Result<StateHandle, usize> {
+        // Finding an unused internal state (step 9 of section 9.1).
+        if self.instances.len() >= self.max_instances {
+            return Err(8);
+        }
+
+        Ok(self.store::<T>(new()?))
+    }
+
+    /*  Stores an instance under a new handle. */
+    fn store<T: DRBG_Mechanism_Functions + Send + 'static>(&mut self, drbg: DRBG<T>) -> StateHandle {
+        let handle = StateHandle(NEXT_HANDLE.fetch_add(1, Ordering::Relaxed));
+        self.instances.insert(handle, Instance { mechanism: T::mech_id(), drbg: Box::new(drbg) });
+
+        handle
+    }
+}
+
+impl Drop for DrbgModule {
+    fn drop(&mut self) {
+        self.uninstantiate_all();
+    }
+}
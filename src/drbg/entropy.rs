@@ -0,0 +1,87 @@
+use std::collections::VecDeque;
+use rand::Rng;
+
+/*  Sources of entropy that can feed a DRBG (see 'gen_drbg').
+
+    Every entropy input, nonce and random padding used by a DRBG instance is taken from its entropy source. By default
+    instances use SystemEntropySource, which assumes that fresh entropy is always available. Other sources can be
+    injected when the DRBG is instantiated, e.g. FixedEntropySource makes the output of the DRBG deterministic so that
+    known-answer tests can be run on the whole DRBG envelope. Only SystemEntropySource is approved (see 'approved_mode'). */
+#[allow(non_camel_case_types)]
+pub trait Entropy_Source_Functions: Send {
+    /*  Retrieves entropy bytes from the source.
+
+        Parameters:
+            - result: target vector for entropy bytes (cleared before use)
+            - bytes: number of entropy bytes that are requested
+
+        Return values:
+            - 0: SUCCESS, result contains the entropy bytes
+            - 1: ERROR, the source could not provide entropy */
+    fn get_entropy(&mut self, result: &mut Vec<u8>, bytes: usize) -> usize;
+
+    /*  Returns true if the source is approved for use in approved mode (see 'approved_mode'). Sources are not approved
+        unless they state otherwise. */
+    fn is_approved(&self) -> bool {
+        false
+    }
+}
+
+/*  Entropy source backed by the random number generator of the operating system (through 'rand::thread_rng'). */
+pub struct SystemEntropySource;
+
+impl Entropy_Source_Functions for SystemEntropySource {
+    fn get_entropy(&mut self, result: &mut Vec<u8>, bytes: usize) -> usize {
+        // Eventually deleting result contents.
+        if !result.is_empty() {
+            result.clear();
+        }
+
+        result.resize(bytes, 0x00);
+        rand::thread_rng().fill(result.as_mut_slice());
+
+        0
+    }
+
+    fn is_approved(&self) -> bool {
+        true
+    }
+}
+
+/*  Deterministic entropy source that replays a predefined sequence of byte strings, one per request.
+
+    The requested length is ignored: each request receives the next string of the sequence as it is. This allows to
+    replay test vectors whose entropy inputs are shorter than what the DRBG asks for (e.g. the CTR-DRBG without DF,
+    which requests 48 bytes and only uses seedlen of them). An error is returned once the sequence is over. */
+pub struct FixedEntropySource {
+    chunks: VecDeque<Vec<u8>>,
+}
+
+impl FixedEntropySource {
+    /*  Creates a new source that will return the given strings in order. */
+    pub fn new(chunks: Vec<Vec<u8>>) -> Self {
+        Self { chunks: chunks.into() }
+    }
+
+    /*  Returns the number of strings that have not been requested yet. */
+    pub fn remaining(&self) -> usize {
+        self.chunks.len()
+    }
+}
+
+impl Entropy_Source_Functions for FixedEntropySource {
+    fn get_entropy(&mut self, result: &mut Vec<u8>, _bytes: usize) -> usize {
+        // Eventually deleting result contents.
+        if !result.is_empty() {
+            result.clear();
+        }
+
+        match self.chunks.pop_front() {
+            None => 1,
+            Some(chunk) => {
+                result.extend_from_slice(&chunk);
+                0
+            }
+        }
+    }
+}
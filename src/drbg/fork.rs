@@ -0,0 +1,142 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Once;
+
+/*  Detection of the forks of the process, so that parent and child never share the output of a DRBG instance.
+
+    Each process is identified by a generation, which changes in a forked child. Every DRBG instance records the
+    generation at its instantiation and reseed: if the generation has changed on the next generate call, the instance
+    has been copied into a child and its internal state is also known to the parent. The instance then either forces
+    a prediction-resistance reseed from its entropy source, or refuses to generate until it is explicitly reseeded
+    (see ForkPolicy and DRBG_Functions::set_fork_policy).
+
+    The internal states are NOT placed in MADV_WIPEONFORK memory: they contain types that are not valid when zeroed
+    (digest and cipher states, vectors), so a forked child inherits an intact copy of the V, Key and C values of its
+    parent. Only a generation marker is kept in wipe-on-fork memory, and fork safety relies on every path that uses the
+    internal state of an instance comparing its generation with the one returned by generation() first:
+        - DRBG::generate, which reseeds or refuses to generate (see ForkPolicy)
+        - DRBG::export_state, which refuses to export the state until the instance is reseeded
+        - BufferedDrbg, which discards the bytes generated before the fork
+    A new path that reads or copies the internal state must perform the same check.
+
+    A fork is detected by:
+        - a page marked with MADV_WIPEONFORK (on Linux 4.14 and above), which the kernel zeroes in the child even when
+          the fork is done by a raw system call that skips the pthread_atfork handlers. The page only holds the marker.
+        - a change of the PID of the process, which is checked when the page is not available (the check costs a
+          system call on every generate call, the page only a memory read)
+        - a pthread_atfork child handler (on unix, see register_atfork), which also catches a child that gets the PID of
+          its parent back (e.g. after a double fork in another PID namespace) */
+
+/*  The action taken by an instance on its first generate call in a forked child. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ForkPolicy {
+    /*  Reseeding from the entropy source before generating (as if prediction resistance was requested). */
+    #[default]
+    Reseed,
+    /*  Failing the generate calls until the instance is reseeded (see DRBG_Functions::reseed). */
+    Refuse,
+}
+
+/*  The current generation, increased at each detected fork. */
+static GENERATION: AtomicU64 = AtomicU64::new(1);
+
+/*  The PID the current generation belongs to, when the MADV_WIPEONFORK page is not available (0 before the first check). */
+static PID: AtomicU32 = AtomicU32::new(0);
+
+/*  Whether the pthread_atfork handler has been registered. */
+static ATFORK: AtomicBool = AtomicBool::new(false);
+static ATFORK_ONCE: Once = Once::new();
+
+/*  Returns the current generation of the process, which differs from the one returned before a fork in the child. */
+pub fn generation() -> u64 {
+    let forked = match sys::wipe_page() {
+        Some(marker) => marker.load(Ordering::SeqCst) == 0 && marker.swap(1, Ordering::SeqCst) == 0,
+        None => {
+            let pid = std::process::id();
+            PID.load(Ordering::SeqCst) != pid && PID.swap(pid, Ordering::SeqCst) != pid
+        }
+    };
+    if forked {
+        GENERATION.fetch_add(1, Ordering::SeqCst);
+    }
+
+    GENERATION.load(Ordering::SeqCst)
+}
+
+/*  Registers a pthread_atfork child handler that increases the generation, so that forks are also detected when the
+    child gets the PID of the parent. The registration is done once per process.
+
+    Return values:
+        - 0: SUCCESS, the handler is registered
+        - 1: ERROR, the handler could not be registered (or pthread_atfork is not available) */
+pub fn register_atfork() -> usize {
+    ATFORK_ONCE.call_once(|| {
+        ATFORK.store(sys::register_atfork(), Ordering::SeqCst);
+    });
+
+    if ATFORK.load(Ordering::SeqCst) { 0 } else { 1 }
+}
+
+/*  Returns whether the MADV_WIPEONFORK page is in use on this system. */
+pub fn wipe_on_fork() -> bool {
+    sys::wipe_page().is_some()
+}
+
+/*  The handler run in the child after a fork (only async-signal-safe operations are allowed here). */
+#[cfg(unix)]
+extern "C" fn atfork_child() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::sync::atomic::AtomicU64;
+
+    pub fn register_atfork() -> bool {
+        unsafe { libc::pthread_atfork(None, None, Some(super::atfork_child)) == 0 }
+    }
+
+    /*  Returns the marker stored in the MADV_WIPEONFORK page, which is mapped on the first call. The marker is set to 1
+        when the page is mapped and is 0 in a forked child. */
+    #[cfg(target_os = "linux")]
+    pub fn wipe_page() -> Option<&'static AtomicU64> {
+        use std::sync::OnceLock;
+
+        static PAGE: OnceLock<usize> = OnceLock::new();
+        let page = *PAGE.get_or_init(|| unsafe {
+            let len = libc::sysconf(libc::_SC_PAGESIZE).max(4096) as usize;
+            let addr = libc::mmap(std::ptr::null_mut(), len, libc::PROT_READ | libc::PROT_WRITE,
+                                  libc::MAP_PRIVATE | libc::MAP_ANONYMOUS, -1, 0);
+            if addr == libc::MAP_FAILED {
+                return 0;
+            }
+            // Kernels older than 4.14 do not support the advice, the page is then of no use.
+            if libc::madvise(addr, len, libc::MADV_WIPEONFORK) != 0 {
+                libc::munmap(addr, len);
+                return 0;
+            }
+            (*(addr as *const AtomicU64)).store(1, std::sync::atomic::Ordering::SeqCst);
+            addr as usize
+        });
+
+        // The page is never unmapped, so the reference is valid for the whole process.
+        if page == 0 { None } else { Some(unsafe { &*(page as *const AtomicU64) }) }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn wipe_page() -> Option<&'static AtomicU64> {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+mod sys {
+    use std::sync::atomic::AtomicU64;
+
+    pub fn register_atfork() -> bool {
+        false
+    }
+
+    pub fn wipe_page() -> Option<&'static AtomicU64> {
+        None
+    }
+}
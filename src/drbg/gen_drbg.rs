@@ -1,15 +1,24 @@
 use std::any::TypeId;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
 
 use crate::mechs::ctr_mech::CtrDrbgMech;
 use crate::mechs::ctr_mech_with_df::CtrDrbgMech_DF;
 use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
 use crate::mechs::hash_mech::HashDrbgMech;
 use crate::mechs::hmac_mech::HmacDrbgMech;
-use crate::self_tests::{self, formats};
+use crate::self_tests::{self, formats, report};
+use crate::self_tests::report::{SelfTestReport, TestCaseReport, TestStatus};
 use crate::self_tests::drbg_tests;
 use crate::drbg::drbg_conf::*;
+use crate::drbg::entropy::*;
+use crate::drbg::health;
+use crate::drbg::module_state::{self, ModuleErrorCause};
+use crate::drbg::approved_mode::{self, ServiceIndicator};
+use crate::drbg::reseed_policy::ReseedPolicy;
+use crate::drbg::fork::{self, ForkPolicy};
+use crate::drbg::persist::{self, Monotonic_Counter_Functions, StateSnapshot, WrappingKey, FORMAT_VERSION};
 use aes::{Aes128, Aes192};
-use rand::Rng;
 use sha2::{Sha512, Sha256};
 
 /*  This is the general structure of a DRBG and is independent of the specific mechanism that will be requested at runtime.
@@ -19,17 +28,38 @@ use sha2::{Sha512, Sha256};
                              by the application using the DRBG but is always kept <= MAX_SEC_STR by this crate.
         - error_state: indicates whether the DRBG entered an error state following a failure during normal operation and/or a failure
                        of on-demand self-tests. If set, this instance has to be deleted and recreated by the user.
+        - entropy_source: the source of the entropy inputs, nonces and paddings used by this instance (see 'entropy').
+        - approved: whether this instance is approved (see 'approved_mode').
+        - indicator: the service indicator of the last instantiate, reseed or generate call on this instance.
+        - health_test: set on the separate instances used by the health tests (see 'health'), which are not subject to the
+                       module error state and do not trigger further health tests.
+        - reseed_policy: the further limits that trigger a reseed of this instance (see 'reseed_policy').
+        - last_reseed, bytes_since_reseed: the time of the last instantiation or reseed and the bytes generated since then.
+        - fork_policy: the action taken on the first generate call after the instance has been copied into a forked child.
+        - fork_generation: the generation of the process at the last instantiation or reseed (see 'fork').
     
     In this design, the prediction_resistance_flag is not used. This has been done because we are assuming that the DRBG is accessing
     an entropy source that always provides fresh entropy bytes. This means that is always possible for the DRBG to provide prediction
     resistance when needed. 
     For the same reason, the reseed function defined below does not use the prediction_resistance_request parameter, as fresh entropy is
-    provided on every reseed request by default. */
+    provided on every reseed request by default.
+
+    The fields are private, the internal state can only be accessed through the functions below. Applications that manage several
+    instances can let a DrbgModule own them and refer to them by state handles (see 'drbg_module'). */
 pub struct DRBG<T>
 {
-    pub internal_state: Option<T>,
-    pub security_strength: usize,
-    pub error_state: bool,
+    internal_state: Option<T>,
+    security_strength: usize,
+    error_state: bool,
+    entropy_source: Box<dyn Entropy_Source_Functions>,
+    approved: bool,
+    indicator: ServiceIndicator,
+    health_test: bool,
+    reseed_policy: ReseedPolicy,
+    last_reseed: Instant,
+    bytes_since_reseed: u64,
+    fork_policy: ForkPolicy,
+    fork_generation: u64,
 }
 
 #[allow(non_camel_case_types)]
@@ -48,9 +78,42 @@ pub trait DRBG_Functions{
             1 - ERROR, inappropriate security strength
             2 - ERROR, personalization string is too long (max security_strength bytes)
             3 - ERROR, the instantiation of the underlying mechanism failed
+            4 - ERROR, the self-tests of the mechanism failed on its first use
+            5 - ERROR, the module is in error state (see 'module_state')
+            6 - ERROR, the approved mode is enabled and the instance would not be approved (see 'approved_mode')
     */
     fn new(req_sec_str: usize, ps: Option<&[u8]>) -> Result<Self, usize> where Self: Sized;
 
+    /*  Same as 'new', but the entropy inputs, nonces and paddings of the new instance are taken from the given entropy source
+        instead of the default one (see 'entropy'). This is mainly useful to run known-answer tests on the whole DRBG.
+
+        Parameters:
+            - req_sec_str: the security strength needed by the calling application (expressed in bytes).
+            - ps: optional personalization string (see 'new').
+            - entropy_source: the entropy source to be used by the new instance.
+
+        Return values:
+            Self - SUCCESS, a pointer to the newly created DRBG instance
+            1, 2, 3, 4, 5, 6 - ERROR, see 'new' (3 is also returned if the entropy source fails)
+    */
+    fn new_with_entropy(req_sec_str: usize, ps: Option<&[u8]>, entropy_source: Box<dyn Entropy_Source_Functions>) -> Result<Self, usize> where Self: Sized;
+
+    /*  Same as 'new', but the new instance is reseeded every reseed_interval generate requests instead of the default
+        seed life of the mechanism (see get_seed_life). The reseed interval is bounded by the maximum of the mechanism in
+        table 2 (or 3) of section 10 of the SP.
+
+        Parameters:
+            - req_sec_str: the security strength needed by the calling application (expressed in bytes).
+            - ps: optional personalization string (see 'new').
+            - reseed_interval: the number of generate requests that can be served between reseedings.
+
+        Return values:
+            Self - SUCCESS, a pointer to the newly created DRBG instance
+            1, 2, 3, 4, 5, 6 - ERROR, see 'new'
+            7 - ERROR, the reseed interval is 0 or above the maximum of the mechanism
+    */
+    fn new_with_reseed_interval(req_sec_str: usize, ps: Option<&[u8]>, reseed_interval: usize) -> Result<Self, usize> where Self: Sized;
+
     /*  This function serves as an envelope to the reseed algorithm of the underlying DRBG mechanism and is defined in section 9.2 of the SP.
         It reseeds the internal state of the DRBG by acquiring fresh entropy from the entropy source.
         If the reseeding fails, an error state >0 is returned to the application that is using the DRBG.
@@ -61,9 +124,10 @@ pub trait DRBG_Functions{
 
         Return Values:
             0 - SUCCESS, internal state has been succesfully reseeded
-            1 - ERROR, internal state is not valid (uninstantiated or in error state)
+            1 - ERROR, internal state is not valid (uninstantiated or in error state) or the module is in error state
             2 - ERROR, additional input is too long (max MAX_SEC_STR bytes)
             3 - ERROR, internal state reseeding failed unexpectedly
+            4 - ERROR, the approved mode is enabled and this instance is not approved (see 'approved_mode')
     */
     fn reseed(&mut self, add: Option<&[u8]>) -> usize;
 
@@ -82,11 +146,14 @@ pub trait DRBG_Functions{
         
         Return values:
             0 - SUCCESS, bytes have been generated succesfully and can be used for the desired purpose
-            1 - ERROR, internal state is not valid (uninstantiated or in error state)
+            1 - ERROR, internal state is not valid (uninstantiated or in error state), the module is in error state or
+                a periodic health test failed (see 'module_state' and 'health')
             2 - ERROR, requested too many pseudo-random bytes
             3 - ERROR, security strenght not supported
             4 - ERROR, additional input is too long (max security_strength bytes)
-            5 - ERROR, bit generation failed unexpectedly
+            5 - ERROR, bit generation or the reseed it required failed unexpectedly
+            6 - ERROR, the approved mode is enabled and this instance is not approved (see 'approved_mode')
+            7 - ERROR, the process has been forked and the fork policy requires a reseed first (see 'fork')
     */
     fn generate(&mut self, bytes: &mut Vec<u8>, req_bytes: usize, req_str: usize, pred_res_req: bool, add: Option<&[u8]>) -> usize;
 
@@ -100,17 +167,18 @@ pub trait DRBG_Functions{
     */
     fn uninstantiate(&mut self) -> usize;
 
-    /*  This function is used to retrieve entropy bytes directly from the underlying entropy source that is available. 
+    /*  This function is used to retrieve entropy bytes directly from the default entropy source (see 'entropy::SystemEntropySource').
         For the moment we are assuming that the underlying source of entropy is always available and always provides fresh entropy to be used.
 
         Parameters:
             - vec: target vector for entropy bytes
             - bytes: number of entropy bytes to be generated
     */
-    fn get_entropy_input(vec: &mut Vec<u8>, bytes: usize);
+    fn get_entropy_input(vec: &mut Vec<u8>, bytes: usize) where Self: Sized;
 
     /*  This function runs on-demand self-tests through a particular instance that is already in use. If self-tests fail an
-        error state is set and that particular instance is zeorized and can no longer be used.
+        error state is set and that particular instance is zeorized and can no longer be used. The failure also puts the
+        whole module in error state (see 'module_state').
         
         Return values:
             - 0: all tests passed, no error state set
@@ -118,6 +186,14 @@ pub trait DRBG_Functions{
     */
     fn run_self_tests(&mut self) -> usize;
 
+    /*  This function runs the same on-demand self-tests of run_self_tests and returns the details of every test.
+
+        Return values:
+            - the report of the self-tests (see 'self_tests::report'), if the DRBG is already in error state no test is
+              run and the report contains a single failed test
+    */
+    fn run_self_tests_report(&mut self) -> SelfTestReport;
+
     /*  FROM HERE WE HAVE UTILITY FUNCTIONS THAT ARE NOT SPECIFICALLY TIED TO THE SP REQUIREMENTS. */
 
     /*  Utility function that returns the supported security strength of the DRBG.
@@ -133,7 +209,8 @@ pub trait DRBG_Functions{
             - the security strength supported by the DRBG instance. */
     fn get_count(&self) -> usize;
 
-    /*  Utility function that returns the value seed life of the DRBG.
+    /*  Utility function that returns the value seed life of the DRBG, i.e. its reseed interval (see
+        new_with_reseed_interval).
         Eventually returns 0 if the DRBG is zeroized or in error state.
     
         Return values:
@@ -146,6 +223,32 @@ pub trait DRBG_Functions{
         Return values:
             - the seed life used by the DRBG instance. */
     fn get_max_pbr(&self) -> usize;
+
+    /*  Utility function that returns the service indicator of the last instantiate, reseed or generate call on this
+        instance (see 'approved_mode').
+
+        Return values:
+            - Approved: the last call succeeded and was an approved service
+            - NonApproved: otherwise */
+    fn get_service_indicator(&self) -> ServiceIndicator;
+
+    /*  Utility function that sets the reseed policy of the DRBG (see 'reseed_policy'), which is checked before every
+        generate call. The time and the bytes of the new policy are counted from the last reseed.
+
+        Return values:
+            - 0: SUCCESS, the policy is in use
+            - 1, 2, 3: ERROR, the policy is not valid and the previous one is kept (see ReseedPolicy::validate) */
+    fn set_reseed_policy(&mut self, policy: ReseedPolicy) -> usize;
+
+    /*  Utility function that returns the reseed policy of the DRBG. */
+    fn get_reseed_policy(&self) -> ReseedPolicy;
+
+    /*  Utility function that sets the action taken by the DRBG on its first generate call in a forked child: a forced
+        reseed from the entropy source (the default) or the failure of the generate calls until the next reseed. */
+    fn set_fork_policy(&mut self, policy: ForkPolicy);
+
+    /*  Utility function that returns the fork policy of the DRBG. */
+    fn get_fork_policy(&self) -> ForkPolicy;
 }
 
 /*  This is the implementation of the generic DRBG_Functions trait for a DRBG using one of the mechanisms defined in the 'mechs' module. */
@@ -154,80 +257,230 @@ where
     T: DRBG_Mechanism_Functions + 'static
 {
     /*  Step 4 of this process (as specified in the SP) is handled directly by the mechanisms by allowing then to modify the security strength accordingly. */
-    fn new(mut req_sec_str: usize, ps: Option<&[u8]>) -> Result<Self, usize>{
-        // Checking the validity of the security strength (step 1).
-        if req_sec_str > MAX_SEC_STR{
-            return Err(1);
+    fn new(req_sec_str: usize, ps: Option<&[u8]>) -> Result<Self, usize>{
+        Self::new_with_entropy(req_sec_str, ps, Box::new(SystemEntropySource))
+    }
+
+    fn new_with_entropy(req_sec_str: usize, ps: Option<&[u8]>, entropy_source: Box<dyn Entropy_Source_Functions>) -> Result<Self, usize>{
+        let res = Self::checked_instantiate(req_sec_str, ps, entropy_source);
+        approved_mode::set_last_service_indicator(res.as_ref().map_or(ServiceIndicator::NonApproved, |drbg| drbg.indicator));
+
+        res
+    }
+
+    fn new_with_reseed_interval(req_sec_str: usize, ps: Option<&[u8]>, reseed_interval: usize) -> Result<Self, usize> {
+        let res = if reseed_interval == 0 || reseed_interval > T::max_seed_life() {
+            Err(7)
         }
+        else {
+            Self::checked_instantiate(req_sec_str, ps, Box::new(SystemEntropySource)).map(|mut drbg| {
+                drbg.internal_state.as_mut().unwrap().set_seed_life(reseed_interval);
+                drbg
+            })
+        };
+        approved_mode::set_last_service_indicator(res.as_ref().map_or(ServiceIndicator::NonApproved, |drbg| drbg.indicator));
+
+        res
+    }
 
-        // Eventually running self-tests the requested mechanism has never been instantiated
-        if Self::first_time_testing() != 0{
-            Self::reset_first_time();
-            return Err(4);
+    fn reseed(&mut self, add: Option<&[u8]>) -> usize{
+        let res = self.reseed_instance(add);
+        self.set_indicator(res)
+    }
+
+    fn generate(&mut self, bytes: &mut Vec<u8>, req_bytes: usize, req_str: usize, pred_res_req: bool, add: Option<&[u8]>) -> usize {
+        let res = self.generate_bytes(bytes, req_bytes, req_str, pred_res_req, add);
+        self.set_indicator(res)
+    }
+
+    fn uninstantiate(&mut self) -> usize{
+        // Internal state already gone.
+        if self.internal_state.is_none() || self.error_state{
+            return 1;
         }
+        
+        // Zeroizing the internal state of the DRBG.
+        self.internal_state.as_mut().unwrap().zeroize();
+        self.internal_state = None;
+        
+        0
+    }
 
-        // Extracting the eventual personalization string.
-        let mut actual_pers = Vec::<u8>::new();
-        if ps.is_some() {
-            actual_pers.append(&mut ps.unwrap().to_vec());
+    fn get_entropy_input(result: &mut Vec<u8>, bytes: usize){
+        SystemEntropySource.get_entropy(result, bytes);
+    }
 
-            // Checking the validity of the personalization string (step 3).
-            if actual_pers.len() > req_sec_str {
-                return Err(2);
-            }
+    fn get_sec_str(&self) -> usize{
+        if self.error_state || self.internal_state.is_none() {
+            return 0;
+        }
 
-            // Eventually padding the personalization string with random bytes in case of CTR mechanism with no DF.
-            if T::drbg_name() == "CTR-DRBG" {
-                let mut padding = Vec::<u8>::new();
-                DRBG::<T>::get_entropy_input(&mut padding, 48 - actual_pers.len());
-                actual_pers.append(&mut padding);
-            }
+        self.security_strength
+    }
+
+    fn get_count(&self) -> usize{
+        // Internal state already gone.
+        if self.error_state || self.internal_state.is_none(){
+            return 0;
         }
 
-        // Acquiring the entropy input according to mechanisms' specifics (step 6).
-        let mut entropy= Vec::<u8>::new();
-        if T::drbg_name() != "CTR-DRBG" {
-            DRBG::<T>::get_entropy_input(&mut entropy, req_sec_str);
+        self.internal_state.as_ref().unwrap().count()
+    }
+
+    fn get_seed_life(&self) -> usize {
+        if self.error_state || self.internal_state.is_none() {
+            return 0;
         }
-        else {
-            DRBG::<T>::get_entropy_input(&mut entropy, 48);
+
+        self.internal_state.as_ref().unwrap().seed_life()
+    }
+
+    fn get_service_indicator(&self) -> ServiceIndicator {
+        self.indicator
+    }
+
+    fn set_reseed_policy(&mut self, policy: ReseedPolicy) -> usize {
+        let res = policy.validate(self.get_seed_life());
+        if res == 0 {
+            self.reseed_policy = policy;
         }
 
-        // Acquiring the nonce for mechanisms that are different from CTR-DRBG withouth derivation function (step 8).
-        let mut nonce= Vec::<u8>::new();
-        if T::drbg_name() != "CTR-DRBG" {      
-            DRBG::<T>::get_entropy_input(&mut nonce, req_sec_str/2);
+        res
+    }
+
+    fn get_reseed_policy(&self) -> ReseedPolicy {
+        self.reseed_policy
+    }
+
+    fn set_fork_policy(&mut self, policy: ForkPolicy) {
+        self.fork_policy = policy;
+    }
+
+    fn get_fork_policy(&self) -> ForkPolicy {
+        self.fork_policy
+    }
+
+    fn get_max_pbr(&self) -> usize {
+        if self.error_state || self.internal_state.is_none() {
+            return 0;
         }
 
-        // Trying to allocate the DRBG's internal state (step 9).
-        let drbg_mech = T::new(&entropy.as_slice(), &nonce.as_slice(), &actual_pers.as_slice(), &mut req_sec_str);
+        MAX_PRB
+    }
 
-        // Checking the validity of the allocated state (step 10,11,12).
-        match drbg_mech{
-            None => {
-                return Err(3);
+    fn run_self_tests(&mut self) -> usize {
+        if self.run_self_tests_report().passed() { 0 } else { 1 }
+    }
+
+    fn run_self_tests_report(&mut self) -> SelfTestReport {
+        // DRBG or module is in error state
+        if self.error_state || module_state::module_error() {
+            let mut report = SelfTestReport::new("on-demand self-tests");
+            report.add_test(&T::drbg_name(), TestCaseReport {
+                module: "DRBG".to_string(),
+                name: "error_state".to_string(),
+                status: TestStatus::Failed,
+                message: "the DRBG or the module is in error state, no test has been run.".to_string(),
+                duration_us: 0,
+            });
+            return report;
+        }
+        
+        let this_id = TypeId::of::<T>();
+        
+        // Building the log message based on the mechanism that is being tested.
+        let mut log_message = T::drbg_name();
+
+        if T::drbg_name() == "CTR-DRBG" {
+            log_message.push_str(" AES-");
+            log_message.push_str(&(self.security_strength*8).to_string());
+            log_message.push_str(" (no DF)");
+        }
+        else if T::drbg_name() == "CTR-DRBG-DF" {
+            log_message.push_str(" AES-");
+            log_message.push_str(&(self.security_strength*8).to_string());
+            log_message.push_str(" (DF)");
+        }
+        else {
+            log_message.push_str(" Sha ");
+
+            if this_id == TypeId::of::<HashDrbgMech<Sha512>>() 
+                || this_id == TypeId::of::<HmacDrbgMech<Sha512>>() {
+                log_message.push_str("512");
             }
-            Some(_) => {
-                Ok(Self{security_strength: req_sec_str, internal_state: drbg_mech, error_state: false})
+            else {
+                log_message.push_str(&(self.security_strength*8).to_string());
             }
         }
+        
+        log_message.push_str(" on-demand self-tests");
+        formats::start_section(&log_message);
+
+        // Running tests
+        let strength = self.security_strength;
+        let report = approved_mode::self_test_scope(|| report::capture("on-demand self-tests", || {
+            drbg_tests::run_all::run_tests::<T>(strength).failures() +
+                self_tests::mech_tests::run_all::run_tests::<T>(strength).failures()
+        }));
+
+        // If tests have failed we uninstantiate the DRBG and set the error state of the instance and of the module.
+        if !report.passed() {
+            self.uninstantiate();
+            self.error_state = true;
+            module_state::enter_error_state(ModuleErrorCause::OnDemandSelfTest { mechanism: log_message });
+        }
+
+        report
     }
+}
 
-    fn reseed(&mut self, add: Option<&[u8]>) -> usize{
-        // DRBG is in error state
-        if self.error_state {
+impl<T> DRBG<T>
+where
+    T: DRBG_Mechanism_Functions + 'static
+{
+    /*  Instantiation requested by the application: the module state and the self-tests of the mechanism are checked
+        before instantiating the DRBG (see 'new'). */
+    fn checked_instantiate(req_sec_str: usize, ps: Option<&[u8]>, entropy_source: Box<dyn Entropy_Source_Functions>) -> Result<Self, usize>{
+        // Checking the validity of the security strength (step 1).
+        if req_sec_str > MAX_SEC_STR{
+            return Err(1);
+        }
+
+        // No instance can be created while the module is in error state.
+        if module_state::module_error() {
+            return Err(5);
+        }
+
+        // Eventually running self-tests the requested mechanism has never been instantiated, a failure puts the module
+        // in error state and the tests will be run again on the first use after recovery.
+        if Self::first_time_testing() != 0{
+            Self::reset_first_time();
+            return Err(4);
+        }
+
+        // Eventually running the health tests of the requested mechanism.
+        if health::on_instantiate::<T>() != 0 {
+            return Err(5);
+        }
+
+        Self::instantiate(req_sec_str, ps, entropy_source, false)
+    }
+
+    /*  Reseed of the instance (see 'reseed'). */
+    fn reseed_instance(&mut self, add: Option<&[u8]>) -> usize{
+        // DRBG or module is in error state
+        if self.error_state || (!self.health_test && module_state::module_error()) {
             return 1;
         }
 
-        // Retrieving the actual internal state if available and valid (step 1).
-        let working_state;
-        match self.internal_state.as_mut(){
-            None => {
-                return 1;
-            }
-            Some(value) => {
-                working_state = value;
-            }
+        // Checking that the internal state is available and valid (step 1).
+        if self.internal_state.is_none() {
+            return 1;
+        }
+
+        // Non-approved instances cannot be used in approved mode.
+        if !self.health_test && !self.approved && approved_mode::approved_mode() {
+            return 4;
         }
 
         // Retrieving the additional input if present.
@@ -245,7 +498,9 @@ where
                 // Eventually padding the additional input with random bytes if the mechanism is CTR with no DF.
                 if T::drbg_name() == "CTR-DRBG" {
                     let mut padding = Vec::<u8>::new();
-                    DRBG::<T>::get_entropy_input(&mut padding, 48 - actual_add_in.len());
+                    if self.entropy_source.get_entropy(&mut padding, 48 - actual_add_in.len()) != 0 {
+                        return 3;
+                    }
                     actual_add_in.append(&mut padding);
                 }
             }
@@ -253,14 +508,17 @@ where
 
         // Acquiring the entropy input according to mechanisms' specifics (step 4).
         let mut entropy_input= Vec::<u8>::new();
-        if T::drbg_name() != "CTR-DRBG" {
-            DRBG::<T>::get_entropy_input(&mut entropy_input, self.security_strength);
+        let entropy_len = if T::drbg_name() != "CTR-DRBG" { self.security_strength } else { 48 };
+        if self.entropy_source.get_entropy(&mut entropy_input, entropy_len) != 0 {
+            return 3;
         }
-        else {
-            DRBG::<T>::get_entropy_input(&mut entropy_input, 48);
+        // A short entropy input makes the instance non-approved.
+        if entropy_input.len() < self.security_strength {
+            self.approved = false;
         }
 
         // Reseeding the internal state (step 6).
+        let working_state = self.internal_state.as_mut().unwrap();
         let res;
         if actual_add_in.len() != 0 {
             res = working_state.reseed(&entropy_input, Some(&actual_add_in));
@@ -273,11 +531,13 @@ where
         if res > 0 {
             return 3;
         }
+        self.reseeded();
 
         return 0;
     }
 
-    fn generate(&mut self, bytes: &mut Vec<u8>, req_bytes: usize, req_str: usize, pred_res_req: bool, add: Option<&[u8]>) -> usize {
+    /*  Generation of pseudo-random bytes (see 'generate'). */
+    fn generate_bytes(&mut self, bytes: &mut Vec<u8>, req_bytes: usize, req_str: usize, pred_res_req: bool, add: Option<&[u8]>) -> usize {
         // Eventually clearing existing data from the return vector.
         if !bytes.is_empty(){
             bytes.clear();
@@ -288,6 +548,16 @@ where
                 return 1;
         }
 
+        // Checking the module error state and eventually running the periodic health tests on a separate instance.
+        if !self.health_test && (module_state::module_error() || health::on_generate::<T>() != 0) {
+            return 1;
+        }
+
+        // Non-approved instances cannot be used in approved mode.
+        if !self.health_test && !self.approved && approved_mode::approved_mode() {
+            return 6;
+        }
+
         // Checking the validity of the requested number of bytes (step 2).
         if req_bytes > MAX_PRB {
             return 2;
@@ -313,37 +583,58 @@ where
                 // Eventually padding the additional input if the CTR mechanism with no DF is used.
                 if T::drbg_name() == "CTR-DRBG" {
                     let mut padding = Vec::<u8>::new();
-                    DRBG::<T>::get_entropy_input(&mut padding, 48 - actual_add_in.len());
+                    if self.entropy_source.get_entropy(&mut padding, 48 - actual_add_in.len()) != 0 {
+                        return 5;
+                    }
                     actual_add_in.append(&mut padding);
                 }
             }
         }
         
-        // Eventually reseeding the internal state if needed (step 7).
-        let working_state = self.internal_state.as_mut().unwrap();
+        // Checking whether this instance has been copied into a forked child, which shares its internal state with the
+        // parent (see 'fork').
+        let forked = self.fork_generation != fork::generation();
+        if forked && self.fork_policy == ForkPolicy::Refuse {
+            bytes.clear();
+            return 7;
+        }
+
+        // Eventually reseeding the internal state if needed, also according to the reseed and fork policies (step 7).
+        // The reseed counter starts at 1, so count - 1 requests have been served since the last (re)seeding.
+        let policy_reseed = self.reseed_policy.reseed_needed(self.internal_state.as_ref().unwrap().count().saturating_sub(1),
+                                                             self.last_reseed.elapsed(), self.bytes_since_reseed, req_bytes);
         let gen_res;
-        if pred_res_req || working_state.reseed_needed() {
+        if pred_res_req || forked || self.internal_state.as_ref().unwrap().reseed_needed() || policy_reseed {
             let mut entropy_input= Vec::<u8>::new();
             // Retreiving entropy for the reseed.
-            if T::drbg_name() != "CTR-DRBG" {
-                DRBG::<T>::get_entropy_input(&mut entropy_input, self.security_strength);
+            let entropy_len = if T::drbg_name() != "CTR-DRBG" { self.security_strength } else { 48 };
+            if self.entropy_source.get_entropy(&mut entropy_input, entropy_len) != 0 {
+                return 5;
             }
-            else {
-                DRBG::<T>::get_entropy_input(&mut entropy_input, 48);
+            // A short entropy input makes the instance non-approved.
+            if entropy_input.len() < self.security_strength {
+                self.approved = false;
             }
 
-            // Reseeding the internal state (step 7.1).
-            if actual_add_in.len() != 0 {
-                working_state.reseed(&entropy_input, Some(&actual_add_in));
+            // Reseeding the internal state (step 7.1). On failure no output is served: the instance is still marked as
+            // forked and the reseed is attempted again by the next request.
+            let reseed_res = if !actual_add_in.is_empty() {
+                self.internal_state.as_mut().unwrap().reseed(&entropy_input, Some(&actual_add_in))
             }
             else {
-                working_state.reseed(&entropy_input, None);
+                self.internal_state.as_mut().unwrap().reseed(&entropy_input, None)
+            };
+            if reseed_res != 0 {
+                bytes.clear();
+                return 5;
             }
+            self.reseeded();
 
             // Generating the requested bytes (step 8, prr).
-            gen_res = working_state.generate(bytes, req_bytes, None);
+            gen_res = self.internal_state.as_mut().unwrap().generate(bytes, req_bytes, None);
         }
         else {
+            let working_state = self.internal_state.as_mut().unwrap();
 
             // Generating the requested bytes (step 8, no prr).
             if actual_add_in.len() != 0 {
@@ -356,6 +647,7 @@ where
 
         // Checking the result of the generation (step 10,11).
         if gen_res == 0 {
+            self.bytes_since_reseed += req_bytes as u64;
             return 0;
         }
         else {
@@ -364,138 +656,267 @@ where
         }
     }
 
-    fn uninstantiate(&mut self) -> usize{
-        // Internal state already gone.
-        if self.internal_state.is_none() || self.error_state{
-            return 1;
+    /*  Exports the state of this instance as a blob sealed under the given wrapping key (see 'persist'), so that the
+        instance can be restored by import_state after a restart. On success the instance is uninstantiated: its state
+        continues only through the blob, so that no output can be generated twice.
+        If a monotonic counter is given, the snapshot is bound to its next value and the counter is only advanced once
+        the blob has been sealed (replay protection). On failure the instance is left instantiated.
+        WARNING: a snapshot exported without a counter has no freshness binding and can be restored any number of times,
+        each restored instance then generating the same output. Such snapshots must be destroyed after their import.
+
+        Parameters:
+            - wrapping_key: the key that wraps the snapshot (AES-GCM or AES-KW)
+            - counter: optional monotonic counter the snapshot is bound to
+
+        Return values:
+            - Ok(blob): SUCCESS, the sealed snapshot
+            - Err(1): ERROR, internal state is not valid (uninstantiated, in error state or copied into a forked child that
+              has not reseeded it, see 'fork') or the module is in error state
+            - Err(2): ERROR, the monotonic counter could not be advanced
+            - Err(3): ERROR, the snapshot could not be sealed */
+    pub fn export_state(&mut self, wrapping_key: &WrappingKey, counter: Option<&mut dyn Monotonic_Counter_Functions>) -> Result<Vec<u8>, usize> {
+        // DRBG or module is in error state
+        if self.error_state || self.internal_state.is_none() || module_state::module_error() {
+            return Err(1);
         }
-        
-        // Zeroizing the internal state of the DRBG.
-        self.internal_state.as_mut().unwrap().zeroize();
-        self.internal_state = None;
-        
-        return 0;
-    }
 
-    fn get_entropy_input(result: &mut Vec<u8>, bytes: usize){
-        // Eventually deleting result contents.
-        if !result.is_empty() {
-            result.clear();
-        }
-
-        // Bytes are generated at a CHUNK_DIM-wide chunk ratio (CHUNK_DIM bytes at a time).
-        const CHUNK_DIM: usize = 16;
-        let mut chunk: [u8; CHUNK_DIM] = [0; CHUNK_DIM];
-
-        // Generate CHUNK_DIM bytes at a time and copy the generated chunk into result.
-        let mut count = 0;
-        let mut end = false;
-        while result.len() < bytes && !end {
-            rand::thread_rng().fill(&mut chunk);
-            for j in 0..chunk.len() {
-
-                // The requested number of bytes has been reached, stop generation
-                if count+j >= bytes{
-                    end = true;
-                    break;
-                }
-                result.push(chunk[j]);
+        // The internal state of an instance copied into a forked child is also known to the parent.
+        if self.fork_generation != fork::generation() {
+            return Err(1);
+        }
+
+        let state = match self.internal_state.as_ref().unwrap().export_state() {
+            Some(state) => state,
+            None => return Err(1),
+        };
+
+        // The snapshot is bound to the next value of the counter.
+        let (counter, value) = match counter {
+            None => (None, None),
+            Some(counter) => match counter.current().and_then(|value| value.checked_add(1)) {
+                Some(value) => (Some(counter), Some(value)),
+                None => return Err(2),
+            },
+        };
+
+        let snapshot = StateSnapshot{
+            version: FORMAT_VERSION,
+            algorithm: wrapping_key.algorithm(),
+            mechanism: T::mech_id(),
+            security_strength: self.security_strength,
+            approved: self.approved,
+            counter: value,
+            state,
+        };
+        let blob = match persist::seal(&snapshot, wrapping_key) {
+            Some(blob) => blob,
+            None => return Err(3),
+        };
+
+        // Every export advances the counter once the blob exists, which invalidates the snapshots exported before.
+        if let Some(counter) = counter {
+            if counter.advance() != value {
+                return Err(2);
             }
-            // Next chunk.
-            count += CHUNK_DIM;
         }
+
+        self.uninstantiate();
+        Ok(blob)
     }
 
-    fn get_sec_str(&self) -> usize{
-        if self.error_state || self.internal_state.is_none() {
-            return 0;
-        }
+    /*  Restores an instance from a blob returned by export_state. The restored instance uses the default entropy source
+        (see 'entropy') and is subject to the same module checks of 'new'.
+        If the snapshot is bound to a monotonic counter, the same counter must be given: the snapshot is only accepted if
+        it carries the current value of the counter, which is then advanced so that the snapshot cannot be restored again.
+        A snapshot exported without a counter is not protected against replays: it is accepted every time it is imported.
 
-        self.security_strength
+        Parameters:
+            - blob: the sealed snapshot
+            - wrapping_key: the key that wrapped the snapshot
+            - counter: the monotonic counter the snapshot is bound to, if any
+
+        Return values:
+            - Self: SUCCESS, the restored instance
+            - Err(1): ERROR, the blob is malformed, of an unsupported version or wrapped with another algorithm
+            - Err(2): ERROR, the blob could not be authenticated (wrong wrapping key or modified blob)
+            - Err(3): ERROR, the snapshot was exported by another mechanism
+            - Err(4): ERROR, the snapshot has already been restored or is not the last one (replay), or the counter failed
+            - Err(5): ERROR, the working state or the security strength of the snapshot is not valid
+            - Err(6): ERROR, the module is in error state or the self-tests of the mechanism failed on its first use
+            - Err(7): ERROR, the approved mode is enabled and the instance would not be approved (see 'approved_mode') */
+    pub fn import_state(blob: &[u8], wrapping_key: &WrappingKey, counter: Option<&mut dyn Monotonic_Counter_Functions>) -> Result<Self, usize> {
+        let res = Self::restore(blob, wrapping_key, counter);
+        approved_mode::set_last_service_indicator(res.as_ref().map_or(ServiceIndicator::NonApproved, |drbg| drbg.indicator));
+
+        res
     }
 
-    fn get_count(&self) -> usize{
-        // Internal state already gone.
-        if self.error_state || self.internal_state.is_none(){
-            return 0;
+    /*  Restoring of an instance (see 'import_state'). */
+    fn restore(blob: &[u8], wrapping_key: &WrappingKey, counter: Option<&mut dyn Monotonic_Counter_Functions>) -> Result<Self, usize> {
+        // No instance can be restored while the module is in error state.
+        if module_state::module_error() {
+            return Err(6);
         }
 
-        self.internal_state.as_ref().unwrap().count()
-    }
+        // Eventually running self-tests if the mechanism has never been instantiated.
+        if Self::first_time_testing() != 0 {
+            Self::reset_first_time();
+            return Err(6);
+        }
 
-    fn get_seed_life(&self) -> usize {
-        if self.error_state || self.internal_state.is_none() {
-            return 0;
+        let snapshot = persist::unseal(blob, wrapping_key)?;
+
+        // The snapshot must have been exported by the same mechanism.
+        if snapshot.mechanism != T::mech_id() {
+            return Err(3);
         }
 
-        return T::seed_life();
-    }
+        if snapshot.security_strength == 0 || snapshot.security_strength > MAX_SEC_STR {
+            return Err(5);
+        }
+        let internal_state = match T::import_state(&snapshot.state) {
+            Some(state) => state,
+            None => return Err(5),
+        };
+
+        let approved = snapshot.approved && approved_mode::approved_mechanism::<T>();
+        if !approved && approved_mode::approved_mode() {
+            return Err(7);
+        }
 
-    fn get_max_pbr(&self) -> usize {
-        if self.error_state || self.internal_state.is_none() {
-            return 0;
+        // The snapshot must carry the current value of the counter, which is advanced as the last step.
+        match (snapshot.counter, counter) {
+            (None, None) => {}
+            (Some(value), Some(counter)) => {
+                if counter.current() != Some(value) || counter.advance().is_none() {
+                    return Err(4);
+                }
+            }
+            _ => return Err(4),
         }
 
-        return MAX_PRB;
+        Ok(Self{security_strength: snapshot.security_strength, internal_state: Some(internal_state), error_state: false,
+                entropy_source: Box::new(SystemEntropySource), approved, indicator: ServiceIndicator::from_approved(approved),
+                health_test: false, reseed_policy: ReseedPolicy::default(), last_reseed: Instant::now(), bytes_since_reseed: 0,
+                        fork_policy: ForkPolicy::default(), fork_generation: fork::generation()})
     }
 
-    fn run_self_tests(&mut self) -> usize {
-        // DRBG is in error state
-        if self.error_state {
-            return 1;
-        }
-        
-        let this_id = TypeId::of::<T>();
-        
-        // Building the log message based on the mechanism that is being tested.
-        let mut log_message = "\n*** STARTING ".to_string();
-        log_message.push_str(T::drbg_name().as_str());
+    /*  Restarts the limits of the reseed policy and the fork detection after a reseed. */
+    fn reseeded(&mut self) {
+        self.last_reseed = Instant::now();
+        self.bytes_since_reseed = 0;
+        self.fork_generation = fork::generation();
+    }
 
-        if T::drbg_name() == "CTR-DRBG" {
-            log_message.push_str(" AES-");
-            log_message.push_str(&(self.security_strength*8).to_string());
-            log_message.push_str(" (no DF)");
+    /*  Sets the service indicator of a reseed or generate call given its result. */
+    fn set_indicator(&mut self, res: usize) -> usize {
+        self.indicator = ServiceIndicator::from_approved(res == 0 && self.approved);
+        approved_mode::set_last_service_indicator(self.indicator);
+
+        res
+    }
+
+    /*  Runs the checks of generate that do not depend on the request before pre-generated bytes of this instance are
+        served (see 'buffered'), and sets the service indicator of the request accordingly.
+
+        Return values:
+            - 0: SUCCESS, the bytes can be served
+            - 1: ERROR, the instance is uninstantiated or the instance or the module is in error state
+            - 6: ERROR, the approved mode is enabled and this instance is not approved (see 'approved_mode') */
+    pub(crate) fn serve_check(&mut self) -> usize {
+        let res = if self.internal_state.is_none() || self.error_state || module_state::module_error() {
+            1
         }
-        else if T::drbg_name() == "CTR-DRBG-DF" {
-            log_message.push_str(" AES-");
-            log_message.push_str(&(self.security_strength*8).to_string());
-            log_message.push_str(" (DF)");
+        else if !self.approved && approved_mode::approved_mode() {
+            6
         }
         else {
-            log_message.push_str(" Sha ");
+            0
+        };
 
-            if this_id == TypeId::of::<HashDrbgMech<Sha512>>() 
-                || this_id == TypeId::of::<HmacDrbgMech<Sha512>>() {
-                log_message.push_str("512");
+        self.set_indicator(res)
+    }
+
+    /*  Returns the time of the last instantiation or reseed, from which the max_duration of the reseed policy is counted
+        (see 'buffered'). */
+    pub(crate) fn last_reseed(&self) -> Instant {
+        self.last_reseed
+    }
+
+    /*  Creates a separate instance for the health tests (see 'health'), which skips the first use self-tests and is not
+        subject to the module error state. */
+    pub(crate) fn new_health_test(req_sec_str: usize, ps: Option<&[u8]>, entropy_source: FixedEntropySource) -> Result<Self, usize> {
+        Self::instantiate(req_sec_str, ps, Box::new(entropy_source), true)
+    }
+
+    /*  Instantiation of the DRBG once the self-tests and the module state have been checked (steps 1-12 of section 9.1 of
+        the SP, see 'new'). */
+    fn instantiate(mut req_sec_str: usize, ps: Option<&[u8]>, mut entropy_source: Box<dyn Entropy_Source_Functions>, health_test: bool) -> Result<Self, usize> {
+        // Checking the validity of the security strength (step 1).
+        if req_sec_str > MAX_SEC_STR{
+            return Err(1);
+        }
+
+        // Extracting the eventual personalization string.
+        let mut actual_pers = Vec::<u8>::new();
+        if let Some(ps) = ps {
+            actual_pers.extend_from_slice(ps);
+
+            // Checking the validity of the personalization string (step 3).
+            if actual_pers.len() > req_sec_str {
+                return Err(2);
             }
-            else {
-                log_message.push_str(&(self.security_strength*8).to_string());
+
+            // Eventually padding the personalization string with random bytes in case of CTR mechanism with no DF.
+            if T::drbg_name() == "CTR-DRBG" {
+                let mut padding = Vec::<u8>::new();
+                if entropy_source.get_entropy(&mut padding, 48 - actual_pers.len()) != 0 {
+                    return Err(3);
+                }
+                actual_pers.append(&mut padding);
             }
         }
-        
-        log_message.push_str(" on-demand self-tests ***\n");
-        formats::write_to_log(log_message);
 
-        // Running tests
-        let res = drbg_tests::run_all::run_tests::<T>(self.security_strength) +
-                self_tests::mech_tests::run_all::run_tests::<T>(self.security_strength);
+        // Acquiring the entropy input according to mechanisms' specifics (step 6).
+        let mut entropy= Vec::<u8>::new();
+        let entropy_len = if T::drbg_name() != "CTR-DRBG" { req_sec_str } else { 48 };
+        if entropy_source.get_entropy(&mut entropy, entropy_len) != 0 {
+            return Err(3);
+        }
 
-        // If tests have failed we set the error state and uninstantiate the DRBG.
-        if res != 0 {
-            self.error_state = true;
-            self.uninstantiate();
-            return 1;
+        // Acquiring the nonce for mechanisms that are different from CTR-DRBG withouth derivation function (step 8).
+        let mut nonce= Vec::<u8>::new();
+        if T::drbg_name() != "CTR-DRBG" && entropy_source.get_entropy(&mut nonce, req_sec_str/2) != 0 {
+            return Err(3);
         }
 
-        0
+        // Trying to allocate the DRBG's internal state (step 9).
+        let drbg_mech = T::new(entropy.as_slice(), nonce.as_slice(), actual_pers.as_slice(), &mut req_sec_str);
+
+        // Checking the validity of the allocated state (step 10,11,12).
+        match drbg_mech{
+            None => {
+                Err(3)
+            }
+            Some(_) => {
+                // Checking whether the new instance is approved.
+                let approved = approved_mode::approved_mechanism::<T>() && entropy_source.is_approved() &&
+                    req_sec_str >= approved_mode::MIN_APPROVED_SEC_STR && entropy.len() >= req_sec_str &&
+                    (T::drbg_name() == "CTR-DRBG" || nonce.len() >= req_sec_str/2);
+                if !health_test && !approved && approved_mode::approved_mode() {
+                    return Err(6);
+                }
+
+                Ok(Self{security_strength: req_sec_str, internal_state: drbg_mech, error_state: false, entropy_source, approved,
+                        indicator: ServiceIndicator::from_approved(approved), health_test, reseed_policy: ReseedPolicy::default(),
+                        last_reseed: Instant::now(), bytes_since_reseed: 0,
+                        fork_policy: ForkPolicy::default(), fork_generation: fork::generation()})
+            }
+        }
     }
-}
 
-impl<T> DRBG<T>
-where
-    T: DRBG_Mechanism_Functions + 'static
-{
     /*  Function used to run self-tests on a specific DRBG mechanism if this is the first time it is instantiated.
+        In approved mode the tests are run even if the overall self-tests have already been run (see 'approved_mode').
         
         Return values:
             - 0: test passed (or not needed)
@@ -503,109 +924,101 @@ where
     fn first_time_testing() -> usize {
         let this_id = TypeId::of::<T>();
         let drbg_name = T::drbg_name();
-        let mut log_message = "\n*** STARTING ".to_string();
-        log_message.push_str(&drbg_name);
+        let mut log_message = drbg_name.clone();
         let mut tests_needed = false;
         let mut req_str: usize = 32;
 
-        unsafe {
-            if OVERALL_TEST_RUN != true {
-                if drbg_name == "Hash-DRBG" {
-                    if this_id == TypeId::of::<HashDrbgMech<Sha256>>() {
-                        if FIRST_USE_HASH_SHA_256 {
-                            FIRST_USE_HASH_SHA_256 = false;
-                            log_message.push_str(" Sha 256");
-                            tests_needed = true;
-                        }
-                    }
-                    else {
-                        if FIRST_USE_HASH_SHA_512 {
-                            FIRST_USE_HASH_SHA_512 = false;
-                            log_message.push_str(" Sha 512");
-                            tests_needed = true;
-                        }
+        if !OVERALL_TEST_RUN.load(Ordering::SeqCst) || approved_mode::approved_mode() {
+            if drbg_name == "Hash-DRBG" {
+                if this_id == TypeId::of::<HashDrbgMech<Sha256>>() {
+                    if FIRST_USE_HASH_SHA_256.swap(false, Ordering::SeqCst) {
+                        log_message.push_str(" Sha 256");
+                        tests_needed = true;
                     }
                 }
-                else if drbg_name == "HMAC-DRBG" {
-                    if this_id == TypeId::of::<HmacDrbgMech<Sha256>>() {
-                        if FIRST_USE_HMAC_SHA_256 {
-                            FIRST_USE_HMAC_SHA_256 = false;
-                            log_message.push_str(" Sha 256");
-                            tests_needed = true;
-                        }
+                else {
+                    if FIRST_USE_HASH_SHA_512.swap(false, Ordering::SeqCst) {
+                        log_message.push_str(" Sha 512");
+                        tests_needed = true;
                     }
-                    else {
-                        if FIRST_USE_HMAC_SHA_512 {
-                            FIRST_USE_HMAC_SHA_512 = false;
-                            log_message.push_str(" Sha 512");
-                            tests_needed = true;
-                        }
+                }
+            }
+            else if drbg_name == "HMAC-DRBG" {
+                if this_id == TypeId::of::<HmacDrbgMech<Sha256>>() {
+                    if FIRST_USE_HMAC_SHA_256.swap(false, Ordering::SeqCst) {
+                        log_message.push_str(" Sha 256");
+                        tests_needed = true;
                     }
                 }
-                else if drbg_name == "CTR-DRBG" {
-                    if this_id == TypeId::of::<CtrDrbgMech<Aes128>>() {
-                        if FIRST_USE_CTR_NO_DF_AES_128 {
-                            FIRST_USE_CTR_NO_DF_AES_128 = false;
-                            log_message.push_str(" AES 128 (no DF)");
-                            tests_needed = true;
-                            req_str = 16;
-                        }
+                else {
+                    if FIRST_USE_HMAC_SHA_512.swap(false, Ordering::SeqCst) {
+                        log_message.push_str(" Sha 512");
+                        tests_needed = true;
                     }
-                    else if this_id == TypeId::of::<CtrDrbgMech<Aes192>>() {
-                        if FIRST_USE_CTR_NO_DF_AES_192 {
-                            FIRST_USE_CTR_NO_DF_AES_192 = false;
-                            log_message.push_str(" AES 192 (no DF)");
-                            tests_needed = true;
-                            req_str = 24;
-                        }
+                }
+            }
+            else if drbg_name == "CTR-DRBG" {
+                if this_id == TypeId::of::<CtrDrbgMech<Aes128>>() {
+                    if FIRST_USE_CTR_NO_DF_AES_128.swap(false, Ordering::SeqCst) {
+                        log_message.push_str(" AES 128 (no DF)");
+                        tests_needed = true;
+                        req_str = 16;
                     }
-                    else {
-                        if FIRST_USE_CTR_NO_DF_AES_256 {
-                            FIRST_USE_CTR_NO_DF_AES_256 = false;
-                            log_message.push_str(" AES 256 (no DF)");
-                            tests_needed = true;
-                        }
+                }
+                else if this_id == TypeId::of::<CtrDrbgMech<Aes192>>() {
+                    if FIRST_USE_CTR_NO_DF_AES_192.swap(false, Ordering::SeqCst) {
+                        log_message.push_str(" AES 192 (no DF)");
+                        tests_needed = true;
+                        req_str = 24;
                     }
                 }
                 else {
-                    if this_id == TypeId::of::<CtrDrbgMech_DF<Aes128>>() {
-                        if FIRST_USE_CTR_DF_AES_128 {
-                            FIRST_USE_CTR_DF_AES_128 = false;
-                            log_message.push_str(" AES 128 (DF)");
-                            tests_needed = true;
-                            req_str = 16;
-                        }
+                    if FIRST_USE_CTR_NO_DF_AES_256.swap(false, Ordering::SeqCst) {
+                        log_message.push_str(" AES 256 (no DF)");
+                        tests_needed = true;
                     }
-                    else if this_id == TypeId::of::<CtrDrbgMech_DF<Aes192>>() {
-                        if FIRST_USE_CTR_DF_AES_192 {
-                            FIRST_USE_CTR_DF_AES_192 = false;
-                            log_message.push_str(" AES 192 (DF)");
-                            tests_needed = true;
-                            req_str = 24;
-                        }
+                }
+            }
+            else {
+                if this_id == TypeId::of::<CtrDrbgMech_DF<Aes128>>() {
+                    if FIRST_USE_CTR_DF_AES_128.swap(false, Ordering::SeqCst) {
+                        log_message.push_str(" AES 128 (DF)");
+                        tests_needed = true;
+                        req_str = 16;
                     }
-                    else {
-                        if FIRST_USE_CTR_DF_AES_256 {
-                            FIRST_USE_CTR_DF_AES_256 = false;
-                            log_message.push_str(" AES 256 (DF)");
-                            tests_needed = true;
-                        }
+                }
+                else if this_id == TypeId::of::<CtrDrbgMech_DF<Aes192>>() {
+                    if FIRST_USE_CTR_DF_AES_192.swap(false, Ordering::SeqCst) {
+                        log_message.push_str(" AES 192 (DF)");
+                        tests_needed = true;
+                        req_str = 24;
+                    }
+                }
+                else {
+                    if FIRST_USE_CTR_DF_AES_256.swap(false, Ordering::SeqCst) {
+                        log_message.push_str(" AES 256 (DF)");
+                        tests_needed = true;
                     }
                 }
             }
         }
 
-        log_message.push_str(" self-tests for first time use ***\n");
+        log_message.push_str(" self-tests for first time use");
 
         if tests_needed {
-            formats::write_to_log(log_message);
+            let section = formats::current_section();
+            formats::start_section(&log_message);
 
             // Running tests
-            let res = drbg_tests::run_all::run_tests::<T>(req_str) +
-            self_tests::mech_tests::run_all::run_tests::<T>(req_str);
+            let res = approved_mode::self_test_scope(|| {
+                drbg_tests::run_all::run_tests::<T>(req_str).failures() +
+                    self_tests::mech_tests::run_all::run_tests::<T>(req_str).failures()
+            });
+            formats::set_section(&section);
 
-            // If tests have failed we set the error state and uninstantiate the DRBG.
+            // If tests have failed we set the error state of the module.
             if res != 0 {
+                module_state::enter_error_state(ModuleErrorCause::FirstUseSelfTest { mechanism: log_message });
                 return 1;
             }
         }
@@ -613,49 +1026,47 @@ where
         0
     }
 
-    fn reset_first_time() {
+    pub(crate) fn reset_first_time() {
         let this_id = TypeId::of::<T>();
         let drbg_name = T::drbg_name();
 
-        unsafe {
-            if OVERALL_TEST_RUN != true {
-                if drbg_name == "Hash-DRBG" {
-                    if this_id == TypeId::of::<HashDrbgMech<Sha256>>() {
-                        FIRST_USE_HASH_SHA_256 = true;
-                    }
-                    else {
-                        FIRST_USE_HASH_SHA_512 = true;
-                    }
+        if !OVERALL_TEST_RUN.load(Ordering::SeqCst) || approved_mode::approved_mode() {
+            if drbg_name == "Hash-DRBG" {
+                if this_id == TypeId::of::<HashDrbgMech<Sha256>>() {
+                    FIRST_USE_HASH_SHA_256.store(true, Ordering::SeqCst);
                 }
-                else if drbg_name == "HMAC-DRBG" {
-                    if this_id == TypeId::of::<HmacDrbgMech<Sha256>>() {
-                        FIRST_USE_HMAC_SHA_256 = true;
-                    }
-                    else {
-                        FIRST_USE_HMAC_SHA_512 = true;
-                    }
+                else {
+                    FIRST_USE_HASH_SHA_512.store(true, Ordering::SeqCst);
                 }
-                else if drbg_name == "CTR-DRBG" {
-                    if this_id == TypeId::of::<CtrDrbgMech<Aes128>>() {
-                        FIRST_USE_CTR_NO_DF_AES_128 = true;
-                    }
-                    else if this_id == TypeId::of::<CtrDrbgMech<Aes192>>() {
-                        FIRST_USE_CTR_NO_DF_AES_192 = true;
-                    }
-                    else {
-                        FIRST_USE_CTR_NO_DF_AES_256 = true;
-                    }
+            }
+            else if drbg_name == "HMAC-DRBG" {
+                if this_id == TypeId::of::<HmacDrbgMech<Sha256>>() {
+                    FIRST_USE_HMAC_SHA_256.store(true, Ordering::SeqCst);
                 }
                 else {
-                    if this_id == TypeId::of::<CtrDrbgMech_DF<Aes128>>() {
-                        FIRST_USE_CTR_DF_AES_128 = true;
-                    }
-                    else if this_id == TypeId::of::<CtrDrbgMech_DF<Aes192>>() {
-                        FIRST_USE_CTR_DF_AES_192 = true;
-                    }
-                    else {
-                        FIRST_USE_CTR_DF_AES_256 = true;
-                    }
+                    FIRST_USE_HMAC_SHA_512.store(true, Ordering::SeqCst);
+                }
+            }
+            else if drbg_name == "CTR-DRBG" {
+                if this_id == TypeId::of::<CtrDrbgMech<Aes128>>() {
+                    FIRST_USE_CTR_NO_DF_AES_128.store(true, Ordering::SeqCst);
+                }
+                else if this_id == TypeId::of::<CtrDrbgMech<Aes192>>() {
+                    FIRST_USE_CTR_NO_DF_AES_192.store(true, Ordering::SeqCst);
+                }
+                else {
+                    FIRST_USE_CTR_NO_DF_AES_256.store(true, Ordering::SeqCst);
+                }
+            }
+            else {
+                if this_id == TypeId::of::<CtrDrbgMech_DF<Aes128>>() {
+                    FIRST_USE_CTR_DF_AES_128.store(true, Ordering::SeqCst);
+                }
+                else if this_id == TypeId::of::<CtrDrbgMech_DF<Aes192>>() {
+                    FIRST_USE_CTR_DF_AES_192.store(true, Ordering::SeqCst);
+                }
+                else {
+                    FIRST_USE_CTR_DF_AES_256.store(true, Ordering::SeqCst);
                 }
             }
         }
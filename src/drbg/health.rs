@@ -0,0 +1,345 @@
+use std::any::TypeId;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use crate::drbg::drbg_conf::*;
+use crate::drbg::entropy::FixedEntropySource;
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::drbg::module_state::{self, ModuleErrorCause};
+use crate::mechs::{gen_mech::DRBG_Mechanism_Functions, hash_mech::HashDrbgMech, hmac_mech::HmacDrbgMech,
+                   ctr_mech::CtrDrbgMech, ctr_mech_with_df::CtrDrbgMech_DF};
+use sha2::*;
+use aes::*;
+
+/*  Health testing of the DRBG functions, as required by section 11.3 of the SP.
+
+    Each function of the DRBG envelope (instantiate, reseed, generate and uninstantiate) is tested with a lightweight
+    known-answer test on a separate test instance, never on the instances used by the application:
+
+        FUNCTION            TEST
+        instantiate         error handling (security strength and personalization string too long), instantiation with
+                            a known entropy input and nonce
+        reseed              error handling (additional input too long), reseed with a known entropy input
+        generate            error handling (too many bytes requested), two generate calls whose output is compared
+                            against the expected value of a NIST vector
+        uninstantiate       the test instance is zeroized and can no longer be used
+
+    The tests of a mechanism are run the first time it is instantiated and then periodically, every generate_interval
+    generate calls and/or every time_interval (see HealthTestConfig). A failure latches the module error state, which
+    halts every DRBG instance until the module is recovered (see 'module_state').
+
+    The configuration and the interval counters are atomics, so that the generate calls of concurrent instances are not
+    serialized by the health testing. */
+
+/*  The periodicity of the health tests, a value of 0 (or None) disables the related trigger. */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HealthTestConfig {
+    pub generate_interval: usize,
+    pub time_interval: Option<Duration>,
+}
+
+impl Default for HealthTestConfig {
+    fn default() -> Self {
+        Self { generate_interval: HEALTH_TEST_INTERVAL, time_interval: None }
+    }
+}
+
+/*  A known-answer vector (hex encoded) taken from the no_prr NIST vectors of a mechanism: instantiate with entropy and
+    nonce, reseed with entropy_reseed, two generate calls, expected is the output of the second call. */
+struct HealthVector {
+    strength: usize,
+    entropy: &'static str,
+    nonce: &'static str,
+    entropy_reseed: &'static str,
+    expected: &'static str,
+}
+
+/*  The health vectors of every mechanism of this crate. */
+static HEALTH_VECTORS: [(fn() -> TypeId, HealthVector); 10] = [
+    (TypeId::of::<HashDrbgMech<Sha256>>, HealthVector {
+        strength: 32,
+        entropy: "63363377e41e86468deb0ab4a8ed683f6a134e47e014c700454e81e95358a569",
+        nonce: "808aa38f2a72a62359915a9f8a04ca68",
+        entropy_reseed: "e62b8a8ee8f141b6980566e3bfe3c04903dad4ac2cdf9f2280010a6739bc83d3",
+        expected: "04eec63bb231df2c630a1afbe724949d005a587851e1aa795e477347c8b056621c18bddcdd8d99fc5fc2b92053d8cfacfb0bb8831205fad1ddd6c071318a6018f03b73f5ede4d4d071f9de03fd7aea105d9299b8af99aa075bdb4db9aa28c18d174b56ee2a014d098896ff2282c955a81969e069fa8ce007a180183a07dfae17",
+    }),
+    (TypeId::of::<HashDrbgMech<Sha512>>, HealthVector {
+        strength: 32,
+        entropy: "3144e17a10c856129764f58fd8e4231020546996c0bf6cff8e91c24ee09be333",
+        nonce: "b16fcb1cf0c010f31feab733588b8e04",
+        entropy_reseed: "a0b3584c2c8412f618406834404d1eb0ce999ba28966054d7e497e0db608b967",
+        expected: "efa35dd0362adb7626456b36fac74d3c28d01d926420275a28bea9c9dd7547c15e7931852ac1277076567535239c1f429c7f75cf74c2267deb6a3e596cf326156c796941283b8d583f171c2f6e3323f7555e1b181ffda30507210cb1f589b23cd71880fd44370cacf43375b0db7e336f12b309bfd4f610bb8f20e1a15e253a4fe511a027968df0b105a1d73aff7c7a826d39f640dfb8f522259ed402282e2c2e9d3a498f51725fe4141b06da5598a42ac1e0494e997d566a1a39b676b96a6003a4c5db84f246584ee65af70ff2160278166da16d91c9b8f2deb02751a1088ad6be4e80ef966eb73e66bc87cad87c77c0b34a21ba1da0ba6d16ca5046dc4abda0",
+    }),
+    (TypeId::of::<HmacDrbgMech<Sha256>>, HealthVector {
+        strength: 32,
+        entropy: "06032cd5eed33f39265f49ecb142c511da9aff2af71203bffaf34a9ca5bd9c0d",
+        nonce: "0e66f71edc43e42a45ad3c6fc6cdc4df",
+        entropy_reseed: "01920a4e669ed3a85ae8a33b35a74ad7fb2a6bb4cf395ce00334a9c9a5a5d552",
+        expected: "76fc79fe9b50beccc991a11b5635783a83536add03c157fb30645e611c2898bb2b1bc215000209208cd506cb28da2a51bdb03826aaf2bd2335d576d519160842e7158ad0949d1a9ec3e66ea1b1a064b005de914eac2e9d4f2d72a8616a80225422918250ff66a41bd2f864a6a38cc5b6499dc43f7f2bd09e1e0f8f5885935124",
+    }),
+    (TypeId::of::<HmacDrbgMech<Sha512>>, HealthVector {
+        strength: 32,
+        entropy: "48c121b18733af15c27e1dd9ba66a9a81a5579cdba0f5b657ec53c2b9e90bbf6",
+        nonce: "bbb7c777428068fad9970891f879b1af",
+        entropy_reseed: "e0ffefdadb9ccf990504d568bdb4d862cbe17ccce6e22dfcab8b4804fd21421a",
+        expected: "05da6aac7d980da038f65f392841476d37fe70fbd3e369d1f80196e66e54b8fadb1d60e1a0f3d4dc173769d75fc3410549d7a843270a54a068b4fe767d7d9a59604510a875ad1e9731c8afd0fd50b825e2c50d062576175106a9981be37e02ec7c5cd0a69aa0ca65bddaee1b0de532e10cfa1f5bf6a026e47379736a099d6750ab121dbe3622b841baf8bdcbe875c85ba4b586b8b5b57b0fecbec08c12ff2a9453c47c6e32a52103d972c62ab9affb8e728a31fcefbbccc556c0f0a35f4b10ace2d96b906e36cbb72233201e536d3e13b045187b417d2449cad1edd192e061f12d22147b0a176ea8d9c4c35404395b6502ef333a813b6586037479e0fa3c6a23",
+    }),
+    (TypeId::of::<CtrDrbgMech<Aes128>>, HealthVector {
+        strength: 16,
+        entropy: "ed1e7f21ef66ea5d8e2a85b9337245445b71d6393a4eecb0e63c193d0f72f9a9",
+        nonce: "",
+        entropy_reseed: "303fb519f0a4e17d6df0b6426aa0ecb2a36079bd48be47ad2a8dbfe48da3efad",
+        expected: "f80111d08e874672f32f42997133a5210f7a9375e22cea70587f9cfafebe0f6a6aa2eb68e7dd9164536d53fa020fcab20f54caddfab7d6d91e5ffec1dfd8deaa",
+    }),
+    (TypeId::of::<CtrDrbgMech<Aes192>>, HealthVector {
+        strength: 24,
+        entropy: "d6e18f4565fdf2826d0d56419647c020413b963299d8de2c6510277f8ce988a7f0b3bc1df85b153f",
+        nonce: "",
+        entropy_reseed: "a823d6311f9f66df329e3d7065e24fe2507e6b9dbcc22838483fa729ca5116d03a91028139d7130a",
+        expected: "4bf806690af13dbcfd448c79a3532e000bcabcef36f2643f3e1c9de607104282f81cd6cdcf8da8429c94108245114d3da17b9f48bb07094c073a94f5d2ef9e30",
+    }),
+    (TypeId::of::<CtrDrbgMech<Aes256>>, HealthVector {
+        strength: 32,
+        entropy: "e4bc23c5089a19d86f4119cb3fa08c0a4991e0a1def17e101e4c14d9c323460a7c2fb58e0b086c6c57b55f56cae25bad",
+        nonce: "",
+        entropy_reseed: "fd85a836bba85019881e8c6bad23c9061adc75477659acaea8e4a01dfe07a1832dad1c136f59d70f8653a5dc118663d6",
+        expected: "b2cb8905c05e5950ca31895096be29ea3d5a3b82b269495554eb80fe07de43e193b9e7c3ece73b80e062b1c1f68202fbb1c52a040ea2478864295282234aaada",
+    }),
+    (TypeId::of::<CtrDrbgMech_DF<Aes128>>, HealthVector {
+        strength: 16,
+        entropy: "0f65da13dca407999d4773c2b4a11d85",
+        nonce: "5209e5b4ed82a234",
+        entropy_reseed: "1dea0a12c52bf64339dd291c80d8ca89",
+        expected: "2859cc468a76b08661ffd23b28547ffd0997ad526a0f51261b99ed3a37bd407bf418dbe6c6c3e26ed0ddefcb7474d899bd99f3655427519fc5b4057bcaf306d4",
+    }),
+    (TypeId::of::<CtrDrbgMech_DF<Aes192>>, HealthVector {
+        strength: 24,
+        entropy: "b11d8b104a7ced9b9f37e5d92ad3dfcbb817552b1ae88f6a",
+        nonce: "017510f270c66586a51313eadc32b07e",
+        entropy_reseed: "6d14cfb36f30c9c1a1ba0e0a32c2f99d1b47f219a3a8ac14",
+        expected: "53fbba563ae014ebc080767aab8452a9f36ce40bbf68f1a12dc0a6388c870c8dfa4250526cbc8c983fee6449903c6bd7c2c02e327680a66b464267edbc4e6797",
+    }),
+    (TypeId::of::<CtrDrbgMech_DF<Aes256>>, HealthVector {
+        strength: 32,
+        entropy: "2d4c9f46b981c6a0b2b5d8c69391e569ff13851437ebc0fc00d616340252fed5",
+        nonce: "0bf814b411f65ec4866be1abb59d3c32",
+        entropy_reseed: "93500fae4fa32b86033b7a7bac9d37e710dcc67ca266bc8607d665937766d207",
+        expected: "322dd28670e75c0ea638f3cb68d6a9d6e50ddfd052b772a7b1d78263a7b8978b6740c2b65a9550c3a76325866fa97e16d74006bc96f26249b9f0a90d076f08e5",
+    }),
+];
+
+/*  The health testing state of a mechanism (the one of the vector at the same index of HEALTH_VECTORS).
+
+    - tested: the mechanism has been tested since the start of the program (or the last run_all_health_tests)
+    - generates: the generate calls since its last health test
+    - last_test: the time of its last health test (in ns since EPOCH) */
+struct HealthState {
+    tested: AtomicBool,
+    generates: AtomicUsize,
+    last_test: AtomicU64,
+}
+
+impl HealthState {
+    const fn new() -> Self {
+        Self { tested: AtomicBool::new(false), generates: AtomicUsize::new(0), last_test: AtomicU64::new(0) }
+    }
+}
+
+/*  The time_interval of the configuration in ns, NO_TIME_INTERVAL for None. */
+const NO_TIME_INTERVAL: u64 = u64::MAX;
+
+static GENERATE_INTERVAL: AtomicUsize = AtomicUsize::new(HEALTH_TEST_INTERVAL);
+static TIME_INTERVAL: AtomicU64 = AtomicU64::new(NO_TIME_INTERVAL);
+static STATES: [HealthState; 10] = [const { HealthState::new() }; 10];
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+static TEST_RUNS: AtomicUsize = AtomicUsize::new(0);
+static FAULT_INJECTION: AtomicBool = AtomicBool::new(false);
+
+/*  Sets the periodicity of the health tests, returning the previous one. */
+pub fn set_health_test_config(config: HealthTestConfig) -> HealthTestConfig {
+    let time_interval = config.time_interval.map_or(NO_TIME_INTERVAL, |interval| (interval.as_nanos() as u64).min(NO_TIME_INTERVAL - 1));
+
+    HealthTestConfig {
+        generate_interval: GENERATE_INTERVAL.swap(config.generate_interval, Ordering::SeqCst),
+        time_interval: from_nanos(TIME_INTERVAL.swap(time_interval, Ordering::SeqCst)),
+    }
+}
+
+/*  Returns the periodicity of the health tests. */
+pub fn health_test_config() -> HealthTestConfig {
+    HealthTestConfig {
+        generate_interval: GENERATE_INTERVAL.load(Ordering::SeqCst),
+        time_interval: from_nanos(TIME_INTERVAL.load(Ordering::SeqCst)),
+    }
+}
+
+/*  Returns the time_interval stored in TIME_INTERVAL. */
+fn from_nanos(nanos: u64) -> Option<Duration> {
+    if nanos == NO_TIME_INTERVAL { None } else { Some(Duration::from_nanos(nanos)) }
+}
+
+/*  Returns the current time in ns since EPOCH. */
+fn now() -> u64 {
+    EPOCH.get_or_init(Instant::now).elapsed().as_nanos() as u64
+}
+
+/*  Returns the health testing state of the mechanism T, None if it has no health vector. */
+fn state<T: 'static>() -> Option<&'static HealthState> {
+    HEALTH_VECTORS.iter().position(|(type_id, _)| type_id() == TypeId::of::<T>()).map(|index| &STATES[index])
+}
+
+/*  Returns the number of times the health tests have been run since the start of the program. */
+pub fn health_test_runs() -> usize {
+    TEST_RUNS.load(Ordering::SeqCst)
+}
+
+/*  Makes the known-answer tests fail, used by the self-tests to verify the error state. */
+pub(crate) fn set_fault_injection(enabled: bool) {
+    FAULT_INJECTION.store(enabled, Ordering::SeqCst);
+}
+
+/*  Runs the health tests of the mechanism T on a separate test instance. On failure the module error state is latched
+    (see 'module_state').
+
+    Return values:
+        - 0: SUCCESS, all the tests passed
+        - 1: ERROR, the test of the instantiate function failed (or T has no health vector)
+        - 2: ERROR, the test of the reseed function failed
+        - 3: ERROR, the test of the generate function failed
+        - 4: ERROR, the test of the uninstantiate function failed */
+pub fn run_health_tests<T: DRBG_Mechanism_Functions + 'static>() -> usize {
+    TEST_RUNS.fetch_add(1, Ordering::SeqCst);
+    let res = known_answer_tests::<T>();
+
+    if res != 0 {
+        let function = ["instantiate", "reseed", "generate", "uninstantiate"][res - 1];
+        module_state::enter_error_state(ModuleErrorCause::HealthTest { mechanism: T::drbg_name(), function: function.to_string() });
+    }
+
+    res
+}
+
+/*  Runs the health tests of every mechanism without latching the module error state, the mechanisms are then tested
+    again on their next instantiation. The recovery of the module does not run them (see 'module_state'): they are used by
+    the self-tests of the module state as a recovery that does not depend on the mechanism self-tests.
+
+    Return values:
+        - the number of mechanisms whose health tests failed */
+pub(crate) fn run_all_health_tests() -> usize {
+    let results = [
+        known_answer_tests::<HashDrbgMech<Sha256>>(),
+        known_answer_tests::<HashDrbgMech<Sha512>>(),
+        known_answer_tests::<HmacDrbgMech<Sha256>>(),
+        known_answer_tests::<HmacDrbgMech<Sha512>>(),
+        known_answer_tests::<CtrDrbgMech<Aes128>>(),
+        known_answer_tests::<CtrDrbgMech<Aes192>>(),
+        known_answer_tests::<CtrDrbgMech<Aes256>>(),
+        known_answer_tests::<CtrDrbgMech_DF<Aes128>>(),
+        known_answer_tests::<CtrDrbgMech_DF<Aes192>>(),
+        known_answer_tests::<CtrDrbgMech_DF<Aes256>>(),
+    ];
+    TEST_RUNS.fetch_add(results.len(), Ordering::SeqCst);
+    STATES.iter().for_each(|state| state.tested.store(false, Ordering::SeqCst));
+
+    results.iter().filter(|res| **res != 0).count()
+}
+
+/*  Called on every instantiation of the mechanism T, runs its health tests if it has never been tested.
+
+    Return values:
+        - 0: SUCCESS, the mechanism can be used
+        - 1: ERROR, a health test failed */
+pub(crate) fn on_instantiate<T: DRBG_Mechanism_Functions + 'static>() -> usize {
+    if state::<T>().is_some_and(|state| state.tested.load(Ordering::SeqCst)) {
+        return 0;
+    }
+
+    run_checked::<T>()
+}
+
+/*  Called on every generate call of the mechanism T, runs its health tests if the configured interval has elapsed.
+
+    Return values:
+        - 0: SUCCESS, the generate call can proceed
+        - 1: ERROR, a health test failed */
+pub(crate) fn on_generate<T: DRBG_Mechanism_Functions + 'static>() -> usize {
+    let state = match state::<T>() {
+        None => return run_checked::<T>(),
+        Some(state) => state,
+    };
+    let generates = state.generates.fetch_add(1, Ordering::Relaxed) + 1;
+    let generate_interval = GENERATE_INTERVAL.load(Ordering::Relaxed);
+    let time_interval = TIME_INTERVAL.load(Ordering::Relaxed);
+
+    let test_needed = (generate_interval > 0 && generates >= generate_interval) ||
+        (time_interval != NO_TIME_INTERVAL && now().saturating_sub(state.last_test.load(Ordering::Relaxed)) >= time_interval);
+    if !test_needed {
+        return 0;
+    }
+
+    run_checked::<T>()
+}
+
+/*  Runs the health tests of T and resets its interval counters. */
+fn run_checked<T: DRBG_Mechanism_Functions + 'static>() -> usize {
+    let res = run_health_tests::<T>();
+    if let Some(state) = state::<T>() {
+        state.generates.store(0, Ordering::SeqCst);
+        state.last_test.store(now(), Ordering::SeqCst);
+        state.tested.store(true, Ordering::SeqCst);
+    }
+
+    if res != 0 { 1 } else { 0 }
+}
+
+/*  The known-answer tests of every DRBG function (see run_health_tests for the return values). */
+fn known_answer_tests<T: DRBG_Mechanism_Functions + 'static>() -> usize {
+    let vector = match HEALTH_VECTORS.iter().find(|(type_id, _)| type_id() == TypeId::of::<T>()) {
+        None => return 1,
+        Some((_, vector)) => vector,
+    };
+    let decode = |value: &str| hex::decode(value).unwrap_or_default();
+    let strength = vector.strength;
+    let mut expected = decode(vector.expected);
+    if FAULT_INJECTION.load(Ordering::SeqCst) {
+        expected[0] ^= 0x01;
+    }
+
+    // Instantiate: error handling and instantiation of the test instance.
+    let long_input = vec![0x00; strength + 1];
+    let mut chunks = vec![decode(vector.entropy)];
+    if T::drbg_name() != "CTR-DRBG" {
+        chunks.push(decode(vector.nonce));
+    }
+    chunks.push(decode(vector.entropy_reseed));
+
+    if DRBG::<T>::new_health_test(MAX_SEC_STR + 1, None, FixedEntropySource::new(Vec::new())).err() != Some(1) ||
+        DRBG::<T>::new_health_test(strength, Some(&long_input), FixedEntropySource::new(Vec::new())).err() != Some(2) {
+        return 1;
+    }
+    let mut drbg = match DRBG::<T>::new_health_test(strength, None, FixedEntropySource::new(chunks)) {
+        Err(_) => return 1,
+        Ok(drbg) => drbg,
+    };
+
+    // Reseed: error handling and reseed with the known entropy input.
+    if drbg.reseed(Some(&long_input)) != 2 || drbg.reseed(None) != 0 {
+        return 2;
+    }
+
+    // Generate: error handling and known-answer test.
+    let mut result = Vec::<u8>::new();
+    if drbg.generate(&mut result, MAX_PRB + 1, strength, false, None) != 2 ||
+        drbg.generate(&mut result, expected.len(), strength, false, None) != 0 ||
+        drbg.generate(&mut result, expected.len(), strength, false, None) != 0 ||
+        result != expected {
+        return 3;
+    }
+
+    // Uninstantiate: the test instance can no longer be used.
+    if drbg.uninstantiate() != 0 || drbg.generate(&mut result, 1, strength, false, None) != 1 || drbg.get_count() != 0 {
+        return 4;
+    }
+
+    0
+}
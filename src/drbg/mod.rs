@@ -1,2 +1,13 @@
 pub mod gen_drbg;
-pub mod drbg_conf;
\ No newline at end of file
+pub mod drbg_conf;
+pub mod entropy;
+pub mod health;
+pub mod approved_mode;
+pub mod module_state;
+pub mod reader;
+pub mod persist;
+pub mod drbg_module;
+pub mod reseed_policy;
+pub mod fork;
+pub mod buffered;
+pub mod distributions;
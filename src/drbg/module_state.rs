@@ -0,0 +1,193 @@
+use std::cell::Cell;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU8, Ordering};
+use serde::{Deserialize, Serialize};
+use crate::self_tests::{formats, run_tests};
+use crate::self_tests::report::{ReportSummary, SelfTestReport};
+
+/*  Process-wide state of the cryptographic module.
+
+    The module starts in the Operational state. Any failure of the self-tests (first use or on-demand, see 'gen_drbg') or
+    of the health tests (see 'health') latches the Error state, which halts every DRBG instance of the process:
+        - DRBG::new returns Err(5)
+        - reseed and generate return 1
+        - uninstantiate is still allowed, so that instances can be zeroized
+    The state changes as follows:
+
+        Operational --(self-test or health test failure)--> Error
+        Error       --(recover)-------------------------->  Recovering
+        Recovering  --(all self-tests passed)------------>  Operational
+        Recovering  --(some self-test failed)------------>  Error
+
+    Recovery is the only way out of the Error state: recover re-runs the first use and on-demand self-tests of every
+    mechanism (see 'self_tests::run_tests::run_recovery') on the calling thread and clears the error only if all of them
+    pass. The development self-tests, which change the global state of the module (approved mode, health test
+    configuration, forks), are not run by a recovery. While the module is Recovering, DRBG instances can only be used by
+    the recovery self-tests. If the self-tests panic, the module is put back in the Error state as for a failed
+    recovery.
+
+    The status of the module (state, cause of the last error, counters and summary of the last recovery) is reported by
+    module_status. */
+
+/*  The states of the module. */
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleState {
+    Operational,
+    Error,
+    Recovering,
+}
+
+/*  The causes of the Error state. */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "cause", rename_all = "snake_case")]
+pub enum ModuleErrorCause {
+    FirstUseSelfTest { mechanism: String },
+    OnDemandSelfTest { mechanism: String },
+    HealthTest { mechanism: String, function: String },
+}
+
+/*  The status of the module.
+
+    - state: the current state
+    - cause, since: the cause and the time of the last error (None if no error ever occurred)
+    - errors: the number of times the Error state has been entered
+    - recoveries, failed_recoveries: the number of recoveries that succeeded and failed
+    - last_recovery: the summary of the self-tests run by the last recovery */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ModuleStatus {
+    pub state: ModuleState,
+    pub cause: Option<ModuleErrorCause>,
+    pub since: Option<String>,
+    pub errors: usize,
+    pub recoveries: usize,
+    pub failed_recoveries: usize,
+    pub last_recovery: Option<ReportSummary>,
+}
+
+const OPERATIONAL: u8 = 0;
+const ERROR: u8 = 1;
+const RECOVERING: u8 = 2;
+
+/*  The current state is kept in an atomic so that it can be checked on every DRBG call, the rest of the status is only
+    accessed on state changes. */
+static STATE: AtomicU8 = AtomicU8::new(OPERATIONAL);
+static STATUS: Mutex<ModuleStatus> = Mutex::new(ModuleStatus {
+    state: ModuleState::Operational,
+    cause: None,
+    since: None,
+    errors: 0,
+    recoveries: 0,
+    failed_recoveries: 0,
+    last_recovery: None,
+});
+
+thread_local! {
+    static RECOVERY_SCOPE: Cell<bool> = const { Cell::new(false) };
+}
+
+/*  Runs f on the status, a poisoned lock is recovered since the status is always consistent. */
+fn with_status<R>(f: impl FnOnce(&mut ModuleStatus) -> R) -> R {
+    let mut status = STATUS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut status)
+}
+
+/*  Returns the current state of the module. */
+pub fn module_state() -> ModuleState {
+    match STATE.load(Ordering::SeqCst) {
+        OPERATIONAL => ModuleState::Operational,
+        ERROR => ModuleState::Error,
+        _ => ModuleState::Recovering,
+    }
+}
+
+/*  Returns the status of the module. */
+pub fn module_status() -> ModuleStatus {
+    with_status(|status| {
+        status.state = module_state();
+        status.clone()
+    })
+}
+
+/*  Returns true if DRBG instances must not operate on the current thread, i.e. the module is not Operational and the
+    thread is not running the self-tests of a recovery. */
+pub fn module_error() -> bool {
+    STATE.load(Ordering::SeqCst) != OPERATIONAL && !recovering()
+}
+
+/*  Returns true if the current thread is running the self-tests of a recovery. */
+pub fn recovering() -> bool {
+    RECOVERY_SCOPE.with(|scope| scope.get())
+}
+
+/*  Latches the Error state, the failure is logged. */
+pub(crate) fn enter_error_state(cause: ModuleErrorCause) {
+    formats::write_to_log(format!("MODULE ERROR STATE: {:?}", cause));
+
+    with_status(|status| {
+        STATE.store(ERROR, Ordering::SeqCst);
+        status.cause = Some(cause);
+        status.since = Some(chrono::offset::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false));
+        status.errors += 1;
+    });
+}
+
+/*  Recovers the module from the Error state by re-running the first use and on-demand self-tests of every mechanism.
+
+    Return values:
+        - 0: SUCCESS, all the self-tests passed and the module is Operational
+        - 1: ERROR, some self-test failed and the module is still in Error state (see module_status)
+        - 2: ERROR, the module is not in Error state */
+pub fn recover() -> usize {
+    recover_with(run_tests::run_recovery)
+}
+
+/*  Marks the current thread as running the self-tests of a recovery while it is alive. If it is dropped while unwinding
+    (the self-tests panicked), the recovery is counted as failed and the Error state is latched again, so that the module
+    never stays Recovering. */
+struct RecoveryScope;
+
+impl RecoveryScope {
+    fn enter() -> Self {
+        RECOVERY_SCOPE.with(|scope| scope.set(true));
+        RecoveryScope
+    }
+}
+
+impl Drop for RecoveryScope {
+    fn drop(&mut self) {
+        RECOVERY_SCOPE.with(|scope| scope.set(false));
+
+        if std::thread::panicking() {
+            with_status(|status| {
+                status.failed_recoveries += 1;
+                STATE.store(ERROR, Ordering::SeqCst);
+            });
+        }
+    }
+}
+
+/*  Same as recover, but the given self-tests are run (used by the self-tests of the state machine). */
+pub(crate) fn recover_with(self_tests: impl FnOnce() -> SelfTestReport) -> usize {
+    if STATE.compare_exchange(ERROR, RECOVERING, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+        return 2;
+    }
+
+    let scope = RecoveryScope::enter();
+    let report = self_tests();
+    drop(scope);
+
+    let passed = report.passed();
+    with_status(|status| {
+        if passed {
+            status.recoveries += 1;
+        }
+        else {
+            status.failed_recoveries += 1;
+        }
+        status.last_recovery = Some(report.summary());
+        STATE.store(if passed { OPERATIONAL } else { ERROR }, Ordering::SeqCst);
+    });
+
+    if passed { 0 } else { 1 }
+}
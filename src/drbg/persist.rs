@@ -0,0 +1,318 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use aes::{Aes128, Aes192, Aes256};
+use aes::cipher::{BlockCipher, BlockDecrypt, BlockEncrypt, BlockSizeUser, KeyInit, consts::{U12, U16}};
+use aes_gcm::AesGcm;
+use aes_gcm::aead::{Aead, Payload};
+use aes_kw::Kek;
+use generic_array::GenericArray;
+use serde::{Deserialize, Serialize};
+use crate::drbg::entropy::{Entropy_Source_Functions, SystemEntropySource};
+use crate::mechs::gen_mech::WorkingState;
+
+/*  Persistence of the state of DRBG instances across restarts.
+
+    DRBG::export_state takes a snapshot of an instance (working state, reseed counter, security strength and mechanism)
+    and seals it under a wrapping key, DRBG::import_state restores the instance from the sealed blob (see 'gen_drbg').
+    The snapshot is serialized with serde and wrapped with one of:
+        - AES-GCM: a random 96-bit nonce is used for each snapshot and the header of the blob is authenticated
+        - AES-KW: the AES key wrap with padding (KWP) of SP 800-38F, the header is repeated inside the snapshot
+    The wrapping key selects AES-128, AES-192 or AES-256 by its length.
+
+    The blob is laid out as:
+        MAGIC (4 bytes) || FORMAT_VERSION (1 byte) || algorithm (1 byte) || nonce (12 bytes, AES-GCM only) || wrapped snapshot
+
+    Replay protection: snapshots can be bound to a monotonic counter that survives restarts (e.g. FileCounter). Every
+    export stores the next value of the counter in the snapshot and advances the counter once the snapshot is sealed,
+    every import requires the snapshot to carry the current value of the counter and advances it again. Therefore only
+    the last exported snapshot can be restored, and only once. Snapshots exported with a counter can only be imported with a counter and vice versa.
+    Snapshots exported without a counter are NOT protected: the same blob can be restored any number of times, and the
+    restored instances generate the same output. They must only be used when the blob is destroyed after its import. */
+
+/*  The version of the format of the sealed snapshots. */
+pub const FORMAT_VERSION: u8 = 1;
+
+/*  The first bytes of every sealed snapshot. */
+const MAGIC: &[u8; 4] = b"DRBG";
+
+/*  The length of the AES-GCM nonces. */
+const GCM_NONCE_LEN: usize = 12;
+
+/*  The algorithms that can wrap a snapshot. */
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyWrapAlgorithm {
+    AesGcm,
+    AesKw,
+}
+
+impl KeyWrapAlgorithm {
+    /*  Returns the identifier of the algorithm in the header of a blob. */
+    fn id(&self) -> u8 {
+        match self {
+            KeyWrapAlgorithm::AesGcm => 1,
+            KeyWrapAlgorithm::AesKw => 2,
+        }
+    }
+
+    /*  Returns the algorithm with the given identifier, if any. */
+    fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(KeyWrapAlgorithm::AesGcm),
+            2 => Some(KeyWrapAlgorithm::AesKw),
+            _ => None,
+        }
+    }
+}
+
+/*  A key that wraps the snapshots with the given algorithm. The key is overwritten when dropped. */
+pub struct WrappingKey {
+    algorithm: KeyWrapAlgorithm,
+    key: Vec<u8>,
+}
+
+impl WrappingKey {
+    /*  Creates a new wrapping key, the key must be 16, 24 or 32 bytes long (AES-128, AES-192 or AES-256).
+
+        Return values:
+            - Some(key): SUCCESS
+            - None: ERROR, the length of the key is not valid */
+    pub fn new(algorithm: KeyWrapAlgorithm, key: &[u8]) -> Option<Self> {
+        if ![16, 24, 32].contains(&key.len()) {
+            return None;
+        }
+
+        Some(Self { algorithm, key: key.to_vec() })
+    }
+
+    /*  Creates a new AES-GCM wrapping key (see new). */
+    pub fn aes_gcm(key: &[u8]) -> Option<Self> {
+        Self::new(KeyWrapAlgorithm::AesGcm, key)
+    }
+
+    /*  Creates a new AES-KW wrapping key (see new). */
+    pub fn aes_kw(key: &[u8]) -> Option<Self> {
+        Self::new(KeyWrapAlgorithm::AesKw, key)
+    }
+
+    /*  Returns the wrapping algorithm of this key. */
+    pub fn algorithm(&self) -> KeyWrapAlgorithm {
+        self.algorithm
+    }
+}
+
+impl Drop for WrappingKey {
+    fn drop(&mut self) {
+        self.key.fill(0x00);
+    }
+}
+
+/*  The content of a sealed snapshot.
+
+    - version, algorithm: the version of the format and the wrapping algorithm, which must match the header of the blob
+    - mechanism: the identifier of the mechanism of the instance (see DRBG_Mechanism_Functions::mech_id)
+    - security_strength, approved: the security strength and the approval of the instance
+    - counter: the value of the monotonic counter the snapshot is bound to, if any
+    - state: the working state of the instance */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StateSnapshot {
+    pub version: u8,
+    pub algorithm: KeyWrapAlgorithm,
+    pub mechanism: String,
+    pub security_strength: usize,
+    pub approved: bool,
+    pub counter: Option<u64>,
+    pub state: WorkingState,
+}
+
+/*  Monotonic counters that protect the snapshots against replays (see the description of this module). The value of
+    the counter must never decrease, also across restarts of the process. */
+#[allow(non_camel_case_types)]
+pub trait Monotonic_Counter_Functions {
+    /*  Returns the current value of the counter.
+
+        Return values:
+            - Some(value): SUCCESS
+            - None: ERROR, the counter could not be read */
+    fn current(&mut self) -> Option<u64>;
+
+    /*  Advances the counter by one, the new value must be durable before this function returns.
+
+        Return values:
+            - Some(value): SUCCESS, the new value of the counter
+            - None: ERROR, the counter could not be advanced */
+    fn advance(&mut self) -> Option<u64>;
+}
+
+/*  Counter kept in memory, which only protects against replays within the same process (mainly useful for tests). */
+pub struct MemoryCounter {
+    value: u64,
+}
+
+impl MemoryCounter {
+    /*  Creates a new counter with the given value. */
+    pub fn new(value: u64) -> Self {
+        Self { value }
+    }
+}
+
+impl Monotonic_Counter_Functions for MemoryCounter {
+    fn current(&mut self) -> Option<u64> {
+        Some(self.value)
+    }
+
+    fn advance(&mut self) -> Option<u64> {
+        self.value = self.value.checked_add(1)?;
+        Some(self.value)
+    }
+}
+
+/*  Counter stored as a decimal number in a file, a missing file stands for 0. The file is replaced atomically (through a
+    temporary file in the same directory) every time the counter is advanced. */
+pub struct FileCounter {
+    path: PathBuf,
+}
+
+impl FileCounter {
+    /*  Creates a counter stored in the given file. */
+    pub fn new(path: &Path) -> Self {
+        Self { path: path.to_path_buf() }
+    }
+}
+
+impl Monotonic_Counter_Functions for FileCounter {
+    fn current(&mut self) -> Option<u64> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => content.trim().parse().ok(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Some(0),
+            Err(_) => None,
+        }
+    }
+
+    fn advance(&mut self) -> Option<u64> {
+        let value = self.current()?.checked_add(1)?;
+
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        let file = fs::File::create(&tmp).ok()?;
+        std::io::Write::write_all(&mut &file, value.to_string().as_bytes()).ok()?;
+        file.sync_all().ok()?;
+        fs::rename(&tmp, &self.path).ok()?;
+
+        Some(value)
+    }
+}
+
+/*  Serializes and wraps a snapshot under the given key.
+
+    Return values:
+        - Some(blob): SUCCESS
+        - None: ERROR, the snapshot could not be wrapped */
+pub(crate) fn seal(snapshot: &StateSnapshot, key: &WrappingKey) -> Option<Vec<u8>> {
+    let mut plaintext = serde_json::to_vec(snapshot).ok()?;
+
+    let mut blob = MAGIC.to_vec();
+    blob.push(FORMAT_VERSION);
+    blob.push(key.algorithm.id());
+
+    let wrapped = match key.algorithm {
+        KeyWrapAlgorithm::AesGcm => {
+            let mut nonce = Vec::<u8>::new();
+            if SystemEntropySource.get_entropy(&mut nonce, GCM_NONCE_LEN) != 0 {
+                return None;
+            }
+            blob.extend_from_slice(&nonce);
+            match key.key.len() {
+                16 => gcm_encrypt::<Aes128>(&key.key, &nonce, &plaintext, &blob),
+                24 => gcm_encrypt::<Aes192>(&key.key, &nonce, &plaintext, &blob),
+                _ => gcm_encrypt::<Aes256>(&key.key, &nonce, &plaintext, &blob),
+            }
+        }
+        KeyWrapAlgorithm::AesKw => match key.key.len() {
+            16 => kw_wrap::<Aes128>(&key.key, &plaintext),
+            24 => kw_wrap::<Aes192>(&key.key, &plaintext),
+            _ => kw_wrap::<Aes256>(&key.key, &plaintext),
+        },
+    };
+    plaintext.fill(0x00);
+
+    blob.extend_from_slice(&wrapped?);
+    Some(blob)
+}
+
+/*  Unwraps and deserializes a snapshot sealed under the given key.
+
+    Return values:
+        - Ok(snapshot): SUCCESS
+        - Err(1): ERROR, the blob is malformed, of an unsupported version or wrapped with another algorithm
+        - Err(2): ERROR, the blob could not be authenticated (wrong key or modified blob) */
+pub(crate) fn unseal(blob: &[u8], key: &WrappingKey) -> Result<StateSnapshot, usize> {
+    let header_len = MAGIC.len() + 2;
+    if blob.len() < header_len || &blob[..MAGIC.len()] != MAGIC || blob[MAGIC.len()] != FORMAT_VERSION ||
+        KeyWrapAlgorithm::from_id(blob[MAGIC.len() + 1]) != Some(key.algorithm) {
+        return Err(1);
+    }
+
+    let plaintext = match key.algorithm {
+        KeyWrapAlgorithm::AesGcm => {
+            if blob.len() < header_len + GCM_NONCE_LEN {
+                return Err(1);
+            }
+            let (aad, wrapped) = blob.split_at(header_len + GCM_NONCE_LEN);
+            let nonce = &aad[header_len..];
+            match key.key.len() {
+                16 => gcm_decrypt::<Aes128>(&key.key, nonce, wrapped, aad),
+                24 => gcm_decrypt::<Aes192>(&key.key, nonce, wrapped, aad),
+                _ => gcm_decrypt::<Aes256>(&key.key, nonce, wrapped, aad),
+            }
+        }
+        KeyWrapAlgorithm::AesKw => match key.key.len() {
+            16 => kw_unwrap::<Aes128>(&key.key, &blob[header_len..]),
+            24 => kw_unwrap::<Aes192>(&key.key, &blob[header_len..]),
+            _ => kw_unwrap::<Aes256>(&key.key, &blob[header_len..]),
+        },
+    };
+    let mut plaintext = plaintext.ok_or(2usize)?;
+
+    let snapshot = serde_json::from_slice::<StateSnapshot>(&plaintext);
+    plaintext.fill(0x00);
+
+    match snapshot {
+        Ok(snapshot) if snapshot.version == FORMAT_VERSION && snapshot.algorithm == key.algorithm => Ok(snapshot),
+        _ => Err(1),
+    }
+}
+
+/*  AES-GCM encryption of msg, authenticating aad. */
+fn gcm_encrypt<C>(key: &[u8], nonce: &[u8], msg: &[u8], aad: &[u8]) -> Option<Vec<u8>>
+where
+    C: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt + KeyInit,
+{
+    let cipher = AesGcm::<C, U12>::new_from_slice(key).ok()?;
+    cipher.encrypt(GenericArray::from_slice(nonce), Payload { msg, aad }).ok()
+}
+
+/*  AES-GCM decryption of msg, authenticating aad. */
+fn gcm_decrypt<C>(key: &[u8], nonce: &[u8], msg: &[u8], aad: &[u8]) -> Option<Vec<u8>>
+where
+    C: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt + KeyInit,
+{
+    let cipher = AesGcm::<C, U12>::new_from_slice(key).ok()?;
+    cipher.decrypt(GenericArray::from_slice(nonce), Payload { msg, aad }).ok()
+}
+
+/*  AES key wrap with padding of data. */
+fn kw_wrap<C>(key: &[u8], data: &[u8]) -> Option<Vec<u8>>
+where
+    C: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt + BlockDecrypt + KeyInit,
+{
+    Kek::<C>::new(GenericArray::from_slice(key)).wrap_with_padding_vec(data).ok()
+}
+
+/*  AES key unwrap with padding of data. */
+fn kw_unwrap<C>(key: &[u8], data: &[u8]) -> Option<Vec<u8>>
+where
+    C: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt + BlockDecrypt + KeyInit,
+{
+    Kek::<C>::new(GenericArray::from_slice(key)).unwrap_with_padding_vec(data).ok()
+}
@@ -0,0 +1,91 @@
+use std::io::{self, Read};
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+
+/*  Unbounded byte stream over a DRBG instance, e.g. to feed statistical test batteries or any std::io consumer.
+
+    The bytes are generated in chunks of at most MAX_PRB bytes (see 'drbg_conf') at the full security strength of the
+    instance and handed out as the reader is consumed. The instance keeps reseeding itself whenever its seed life is
+    over (see DRBG_Functions::get_seed_life); in addition, prediction resistance can be requested every pr_interval
+    generate calls so that fresh entropy is periodically mixed in regardless of the seed life.
+
+    A failure of the DRBG is returned as an io::Error of kind Other, the code of the error is kept (see last_error).
+    The buffered bytes are zeroized when the reader is dropped. */
+pub struct DrbgReader<T: DRBG_Mechanism_Functions + 'static> {
+    drbg: DRBG<T>,
+    buffer: Vec<u8>,
+    pos: usize,
+    pr_interval: Option<usize>,
+    generates: usize,
+    last_error: Option<usize>,
+}
+
+impl<T: DRBG_Mechanism_Functions + 'static> DrbgReader<T> {
+    /*  Creates a reader over the given instance.
+
+        Parameters:
+            - drbg: the instance that generates the stream
+            - pr_interval: if Some(n), prediction resistance is requested on every n-th generate call (n > 0) */
+    pub fn new(drbg: DRBG<T>, pr_interval: Option<usize>) -> Self {
+        Self { drbg, buffer: Vec::new(), pos: 0, pr_interval: pr_interval.filter(|interval| *interval > 0), generates: 0,
+               last_error: None }
+    }
+
+    /*  Returns the underlying instance. */
+    pub fn get_ref(&self) -> &DRBG<T> {
+        &self.drbg
+    }
+
+    /*  Returns the underlying instance, e.g. to reseed it with an additional input. */
+    pub fn get_mut(&mut self) -> &mut DRBG<T> {
+        &mut self.drbg
+    }
+
+    /*  Returns the number of generate calls made so far. */
+    pub fn generates(&self) -> usize {
+        self.generates
+    }
+
+    /*  Returns the error code of the last failed generate call (see DRBG_Functions::generate), if any. */
+    pub fn last_error(&self) -> Option<usize> {
+        self.last_error
+    }
+
+    /*  Refills the buffer with a new chunk of bytes. */
+    fn refill(&mut self) -> io::Result<()> {
+        let pred_res = self.pr_interval.is_some_and(|interval| (self.generates + 1).is_multiple_of(interval));
+        let res = self.drbg.generate(&mut self.buffer, self.drbg.get_max_pbr(), self.drbg.get_sec_str(), pred_res, None);
+        if res != 0 {
+            self.last_error = Some(res);
+            return Err(io::Error::other(format!("DRBG generate failed with error {}", res)));
+        }
+
+        self.generates += 1;
+        self.pos = 0;
+        Ok(())
+    }
+}
+
+impl<T: DRBG_Mechanism_Functions + 'static> Read for DrbgReader<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.pos >= self.buffer.len() {
+            self.refill()?;
+        }
+
+        let len = buf.len().min(self.buffer.len() - self.pos);
+        buf[..len].copy_from_slice(&self.buffer[self.pos..self.pos + len]);
+        self.buffer[self.pos..self.pos + len].fill(0);
+        self.pos += len;
+
+        Ok(len)
+    }
+}
+
+impl<T: DRBG_Mechanism_Functions + 'static> Drop for DrbgReader<T> {
+    fn drop(&mut self) {
+        self.buffer.fill(0);
+    }
+}
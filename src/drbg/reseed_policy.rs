@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+/*  Reseed policies of the DRBG instances.
+
+    Every mechanism reseeds its internal state once its seed life (the reseed_interval of SP 800-90A, see
+    DRBG_Functions::get_seed_life) is over. A ReseedPolicy adds further limits to an instance, checked by the generate
+    envelope before every request (see 'gen_drbg'): the instance is reseeded before serving a request if any limit would
+    be exceeded, whichever comes first:
+        - max_requests: the number of generate requests served since the last reseed
+        - max_duration: the time elapsed since the last reseed, measured with the monotonic clock (std::time::Instant)
+          so that adjustments of the wall clock can neither delay nor anticipate a reseed
+        - max_bytes: the number of bytes generated since the last reseed, including the bytes of the request
+    A limit that is None is not enforced. The limit on the requests is bounded by the seed life of the mechanism, which
+    is always enforced. The default policy enforces no further limit. */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReseedPolicy {
+    pub max_requests: Option<usize>,
+    pub max_duration: Option<Duration>,
+    pub max_bytes: Option<u64>,
+}
+
+impl ReseedPolicy {
+    /*  Checks the limits of the policy against the seed life of the mechanism.
+
+        Return values:
+            - 0: SUCCESS, the policy is valid
+            - 1: ERROR, max_requests is 0 or longer than the seed life
+            - 2: ERROR, max_duration is zero
+            - 3: ERROR, max_bytes is 0 */
+    pub fn validate(&self, seed_life: usize) -> usize {
+        if self.max_requests.is_some_and(|requests| requests == 0 || requests > seed_life) {
+            return 1;
+        }
+        if self.max_duration.is_some_and(|duration| duration.is_zero()) {
+            return 2;
+        }
+        if self.max_bytes == Some(0) {
+            return 3;
+        }
+
+        0
+    }
+
+    /*  Returns true if the instance must be reseeded before serving a request.
+
+        Parameters:
+            - requests: the number of generate requests served since the last reseed
+            - elapsed: the time elapsed since the last reseed
+            - bytes: the number of bytes generated since the last reseed
+            - req_bytes: the number of bytes of the request */
+    pub fn reseed_needed(&self, requests: usize, elapsed: Duration, bytes: u64, req_bytes: usize) -> bool {
+        self.max_requests.is_some_and(|max| requests >= max) ||
+            self.max_duration.is_some_and(|max| elapsed >= max) ||
+            self.max_bytes.is_some_and(|max| bytes.saturating_add(req_bytes as u64) > max)
+    }
+}
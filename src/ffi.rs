@@ -0,0 +1,256 @@
+#![allow(clippy::missing_safety_doc)]
+
+use std::os::raw::c_int;
+use std::panic::{self, AssertUnwindSafe};
+use crate::cli::{with_mechanism, Mechanism};
+use crate::drbg::entropy::FixedEntropySource;
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use crate::self_tests::run_tests;
+
+/*  C interface of the crate, built into the cdylib and declared in include/drbg.h (generated by cbindgen from this file
+    with DRBG_GEN_HEADER=1 cargo build, see build.rs).
+
+    drbg_new_with_entropy, which creates deterministic and non-approved instances for known-answer tests, is only built
+    with the test-entropy feature and is declared in the header only when DRBG_TEST_ENTROPY is defined.
+
+    An instance is created by drbg_new and used through the returned drbg_t pointer, which is owned by the caller until
+    it is passed to drbg_free. drbg_uninstantiate zeroizes the internal state, after which the pointer can only be freed.
+    A pointer must not be used by several threads at the same time, different pointers are independent.
+
+    The pointers passed to the functions must be either NULL (only with a length of 0 for the buffers) or valid for the
+    given length. No panic crosses the boundary: a panic is reported as DRBG_ERR_PANIC.
+
+    The error codes are stable and follow the exit codes of the 'drbg' binary (see 'cli'):
+        - DRBG_OK (0): SUCCESS
+        - DRBG_ERR_INVALID_ARGUMENT (1): unknown mechanism, NULL handle or NULL buffer with a length > 0
+        - DRBG_ERR_PANIC (2): unexpected internal failure
+        - DRBG_ERR_SELFTEST (3): some self-test failed
+        - DRBG_ERR_INSTANTIATE + e: the instantiation failed with error e (see DRBG_Functions::new)
+        - DRBG_ERR_GENERATE + e: the generation failed with error e (see DRBG_Functions::generate)
+        - DRBG_ERR_RESEED + e: the reseed failed with error e (see DRBG_Functions::reseed)
+        - DRBG_ERR_UNINSTANTIATE + e: the uninstantiation failed with error e (see DRBG_Functions::uninstantiate) */
+
+pub const DRBG_OK: c_int = 0;
+pub const DRBG_ERR_INVALID_ARGUMENT: c_int = 1;
+pub const DRBG_ERR_PANIC: c_int = 2;
+pub const DRBG_ERR_SELFTEST: c_int = 3;
+pub const DRBG_ERR_INSTANTIATE: c_int = 10;
+pub const DRBG_ERR_GENERATE: c_int = 20;
+pub const DRBG_ERR_RESEED: c_int = 30;
+pub const DRBG_ERR_UNINSTANTIATE: c_int = 40;
+
+/*  The identifiers of the mechanisms (DRBG_MECH_ALL only selects the self-tests of every mechanism in drbg_selftest). */
+pub const DRBG_MECH_ALL: u32 = 0;
+pub const DRBG_MECH_HASH_SHA256: u32 = 1;
+pub const DRBG_MECH_HASH_SHA512: u32 = 2;
+pub const DRBG_MECH_HMAC_SHA256: u32 = 3;
+pub const DRBG_MECH_HMAC_SHA512: u32 = 4;
+pub const DRBG_MECH_CTR_AES128: u32 = 5;
+pub const DRBG_MECH_CTR_AES192: u32 = 6;
+pub const DRBG_MECH_CTR_AES256: u32 = 7;
+pub const DRBG_MECH_CTR_DF_AES128: u32 = 8;
+pub const DRBG_MECH_CTR_DF_AES192: u32 = 9;
+pub const DRBG_MECH_CTR_DF_AES256: u32 = 10;
+
+/*  An instance created through the C interface (drbg_t in drbg.h). */
+pub struct DrbgHandle {
+    drbg: Box<dyn DRBG_Functions + Send>,
+}
+
+/*  Returns the mechanism of the given identifier. */
+fn mechanism(mech_id: u32) -> Option<Mechanism> {
+    match mech_id {
+        DRBG_MECH_HASH_SHA256 => Some(Mechanism::HashSha256),
+        DRBG_MECH_HASH_SHA512 => Some(Mechanism::HashSha512),
+        DRBG_MECH_HMAC_SHA256 => Some(Mechanism::HmacSha256),
+        DRBG_MECH_HMAC_SHA512 => Some(Mechanism::HmacSha512),
+        DRBG_MECH_CTR_AES128 => Some(Mechanism::CtrAes128),
+        DRBG_MECH_CTR_AES192 => Some(Mechanism::CtrAes192),
+        DRBG_MECH_CTR_AES256 => Some(Mechanism::CtrAes256),
+        DRBG_MECH_CTR_DF_AES128 => Some(Mechanism::CtrDfAes128),
+        DRBG_MECH_CTR_DF_AES192 => Some(Mechanism::CtrDfAes192),
+        DRBG_MECH_CTR_DF_AES256 => Some(Mechanism::CtrDfAes256),
+        _ => None,
+    }
+}
+
+/*  Runs the body of a function of the interface, turning a panic into DRBG_ERR_PANIC. */
+fn guarded<F: FnOnce() -> c_int>(body: F) -> c_int {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or(DRBG_ERR_PANIC)
+}
+
+/*  Returns the buffer of the given pointer and length, None if the pointer is NULL and the length is not 0. */
+unsafe fn buffer<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    match (ptr.is_null(), len) {
+        (_, 0) => Some(&[]),
+        (true, _) => None,
+        (false, _) => Some(std::slice::from_raw_parts(ptr, len)),
+    }
+}
+
+/*  Returns the optional input of the given pointer and length (None for an empty input). */
+unsafe fn optional<'a>(ptr: *const u8, len: usize) -> Result<Option<&'a [u8]>, c_int> {
+    match buffer(ptr, len) {
+        None => Err(DRBG_ERR_INVALID_ARGUMENT),
+        Some([]) => Ok(None),
+        Some(input) => Ok(Some(input)),
+    }
+}
+
+/*  Instantiates a DRBG of the mechanism T, using the given entropy source if any. */
+fn instantiate<T: DRBG_Mechanism_Functions + Send + 'static>(strength: usize, pers: Option<&[u8]>,
+                                                            entropy: Option<FixedEntropySource>) -> Result<Box<dyn DRBG_Functions + Send>, usize> {
+    let drbg = match entropy {
+        None => DRBG::<T>::new(strength, pers)?,
+        Some(entropy) => DRBG::<T>::new_with_entropy(strength, pers, Box::new(entropy))?,
+    };
+
+    Ok(Box::new(drbg))
+}
+
+/*  Creates an instance and stores its pointer in handle (see drbg_new and drbg_new_with_entropy). */
+pub(crate) unsafe fn new_handle(mech_id: u32, strength: usize, pers: *const u8, pers_len: usize, entropy: Option<FixedEntropySource>,
+                     handle: *mut *mut DrbgHandle) -> c_int {
+    if handle.is_null() {
+        return DRBG_ERR_INVALID_ARGUMENT;
+    }
+    *handle = std::ptr::null_mut();
+
+    let (mech, pers) = match (mechanism(mech_id), optional(pers, pers_len)) {
+        (Some(mech), Ok(pers)) => (mech, pers),
+        _ => return DRBG_ERR_INVALID_ARGUMENT,
+    };
+
+    match with_mechanism!(mech, instantiate(strength, pers, entropy)) {
+        Err(err) => DRBG_ERR_INSTANTIATE + err as c_int,
+        Ok(drbg) => {
+            *handle = Box::into_raw(Box::new(DrbgHandle { drbg }));
+            DRBG_OK
+        }
+    }
+}
+
+/*  Instantiates a DRBG of the given mechanism (see DRBG_Functions::new) and stores its pointer in handle, which is set
+    to NULL on failure.
+
+    Parameters:
+        - mech_id: the mechanism (DRBG_MECH_*)
+        - strength: the requested security strength (in bytes)
+        - pers, pers_len: the optional personalization string (NULL and 0 for none)
+        - handle: where the pointer to the new instance is stored */
+#[no_mangle]
+pub unsafe extern "C" fn drbg_new(mech_id: u32, strength: usize, pers: *const u8, pers_len: usize,
+                                  handle: *mut *mut DrbgHandle) -> c_int {
+    guarded(|| new_handle(mech_id, strength, pers, pers_len, None, handle))
+}
+
+/*  Same as drbg_new, but the entropy inputs, nonces and paddings of the instance are the consecutive chunks of
+    chunk_len bytes of the given buffer (see DRBG_Functions::new_with_entropy and FixedEntropySource). This is meant
+    for known-answer tests: the instance is not approved and fails once the chunks are over. */
+#[cfg(feature = "test-entropy")]
+#[no_mangle]
+pub unsafe extern "C" fn drbg_new_with_entropy(mech_id: u32, strength: usize, entropy: *const u8, entropy_len: usize,
+                                               chunk_len: usize, pers: *const u8, pers_len: usize,
+                                               handle: *mut *mut DrbgHandle) -> c_int {
+    guarded(|| {
+        let chunks = match buffer(entropy, entropy_len) {
+            Some(entropy) if chunk_len > 0 => entropy.chunks(chunk_len).map(|chunk| chunk.to_vec()).collect(),
+            _ => return DRBG_ERR_INVALID_ARGUMENT,
+        };
+
+        new_handle(mech_id, strength, pers, pers_len, Some(FixedEntropySource::new(chunks)), handle)
+    })
+}
+
+/*  Generates out_len pseudo-random bytes into out (see DRBG_Functions::generate). Nothing is written on failure.
+
+    Parameters:
+        - handle: the instance
+        - out, out_len: the output buffer
+        - strength: the security strength required for the bytes (in bytes)
+        - prediction_resistance: non-zero to reseed the instance before the generation
+        - add, add_len: the optional additional input (NULL and 0 for none) */
+#[no_mangle]
+pub unsafe extern "C" fn drbg_generate(handle: *mut DrbgHandle, out: *mut u8, out_len: usize, strength: usize,
+                                       prediction_resistance: c_int, add: *const u8, add_len: usize) -> c_int {
+    guarded(|| {
+        let (handle, add) = match (handle.as_mut(), optional(add, add_len)) {
+            (Some(handle), Ok(add)) if !out.is_null() || out_len == 0 => (handle, add),
+            _ => return DRBG_ERR_INVALID_ARGUMENT,
+        };
+
+        let mut bytes = Vec::<u8>::new();
+        let res = handle.drbg.generate(&mut bytes, out_len, strength, prediction_resistance != 0, add);
+        if res != 0 {
+            return DRBG_ERR_GENERATE + res as c_int;
+        }
+
+        if out_len > 0 {
+            std::slice::from_raw_parts_mut(out, out_len).copy_from_slice(&bytes);
+        }
+        bytes.fill(0x00);
+        DRBG_OK
+    })
+}
+
+/*  Reseeds the instance with fresh entropy and the optional additional input (see DRBG_Functions::reseed). */
+#[no_mangle]
+pub unsafe extern "C" fn drbg_reseed(handle: *mut DrbgHandle, add: *const u8, add_len: usize) -> c_int {
+    guarded(|| {
+        match (handle.as_mut(), optional(add, add_len)) {
+            (Some(handle), Ok(add)) => match handle.drbg.reseed(add) {
+                0 => DRBG_OK,
+                err => DRBG_ERR_RESEED + err as c_int,
+            },
+            _ => DRBG_ERR_INVALID_ARGUMENT,
+        }
+    })
+}
+
+/*  Zeroizes the internal state of the instance (see DRBG_Functions::uninstantiate). The pointer must still be freed. */
+#[no_mangle]
+pub unsafe extern "C" fn drbg_uninstantiate(handle: *mut DrbgHandle) -> c_int {
+    guarded(|| {
+        match handle.as_mut() {
+            None => DRBG_ERR_INVALID_ARGUMENT,
+            Some(handle) => match handle.drbg.uninstantiate() {
+                0 => DRBG_OK,
+                err => DRBG_ERR_UNINSTANTIATE + err as c_int,
+            },
+        }
+    })
+}
+
+/*  Uninstantiates the instance, if needed, and releases it. NULL is ignored. */
+#[no_mangle]
+pub unsafe extern "C" fn drbg_free(handle: *mut DrbgHandle) {
+    guarded(|| {
+        if !handle.is_null() {
+            let mut handle = Box::from_raw(handle);
+            handle.drbg.uninstantiate();
+        }
+        DRBG_OK
+    });
+}
+
+/*  Runs the on-demand self-tests (the mechanism and DRBG self-tests) of the given mechanism, or of every mechanism with
+    DRBG_MECH_ALL. The global state of the module is left untouched (see 'self_tests::run_tests::run_recovery'): the
+    development self-tests of the crate are not run by this function. */
+#[no_mangle]
+pub extern "C" fn drbg_selftest(mech_id: u32) -> c_int {
+    guarded(|| {
+        let report = match (mech_id, mechanism(mech_id)) {
+            (DRBG_MECH_ALL, _) => run_tests::run_recovery(),
+            (_, Some(mech)) => with_mechanism!(mech, run_mechanism(mech)),
+            (_, None) => return DRBG_ERR_INVALID_ARGUMENT,
+        };
+
+        if report.passed() { DRBG_OK } else { DRBG_ERR_SELFTEST }
+    })
+}
+
+/*  Runs the self-tests of a single mechanism. */
+fn run_mechanism<T: DRBG_Mechanism_Functions + 'static>(mech: Mechanism) -> crate::self_tests::report::SelfTestReport {
+    run_tests::run_mechanism::<T>(&format!("{} self-tests", mech.title()), mech.max_strength())
+}
@@ -0,0 +1,229 @@
+use super::prf::PRF_Functions;
+
+/*  Implementation of the key-based key derivation functions defined in NIST SP 800-108r1, section 4.
+
+    Every KDF takes the key derivation key KI, the fixed input data (Label || 0x00 || Context || [L]_2 or any other encoding
+    agreed between the parties) and the number of bytes of keying material to be derived. The counter [i]_r can be placed
+    at different locations of the PRF input and can be encoded on r = 8, 16, 24 or 32 bits:
+        MODE                    SUPPORTED COUNTER LOCATIONS                         COUNTER
+        counter                 BeforeFixed, AfterFixed, MiddleFixed                mandatory
+        feedback                BeforeIter, AfterIter, AfterFixed                   optional
+        double-pipeline         BeforeIter, AfterIter, AfterFixed                   optional
+
+    All functions return an error flag >0 on failure:
+        1 - ERROR, the key is not valid for the requested PRF
+        2 - ERROR, the counter width is not supported (r must be 8, 16, 24 or 32 bits)
+        3 - ERROR, the counter location is not supported by the requested mode
+        4 - ERROR, too many bytes requested (n > 2^r - 1 or L > 2^32 - 1 bits) */
+
+/*  Location of the counter inside the input of the PRF.
+
+    - BeforeFixed: [i]_r || FixedInputData
+    - AfterFixed: FixedInputData || [i]_r (K(i-1) or A(i) is prepended in feedback and double-pipeline mode)
+    - MiddleFixed(pos): FixedInputData[..pos] || [i]_r || FixedInputData[pos..]
+    - BeforeIter: [i]_r || K(i-1) || FixedInputData in feedback mode, [i]_r || A(i) || FixedInputData in double-pipeline mode
+    - AfterIter: K(i-1) || [i]_r || FixedInputData in feedback mode, A(i) || [i]_r || FixedInputData in double-pipeline mode */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CtrLocation {
+    BeforeFixed,
+    AfterFixed,
+    MiddleFixed(usize),
+    BeforeIter,
+    AfterIter,
+}
+
+/*  Configuration of the counter used by the KDFs.
+
+    - location: where the counter is placed inside the input of the PRF
+    - rlen: the width of the counter in bits */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CtrConfig {
+    pub location: CtrLocation,
+    pub rlen: usize,
+}
+
+/*  Checks the validity of the counter configuration and of the requested length.
+
+    Parameters:
+        - ctr: the eventual counter configuration
+        - out_bytes: the number of bytes to be derived
+        - h: the output length of the PRF in bytes
+
+    Return values:
+        - Ok(n): the number of iterations of the PRF
+        - Err(flag): see the error flags of this module */
+fn check_params(ctr: Option<&CtrConfig>, out_bytes: usize, h: usize) -> Result<usize, usize> {
+    // L must be representable on 32 bits.
+    if out_bytes as u64 * 8 > u32::MAX as u64 {
+        return Err(4);
+    }
+
+    let n = out_bytes.div_ceil(h);
+    let max_n = match ctr {
+        None => u32::MAX as u64,
+        Some(ctr) => {
+            if ctr.rlen != 8 && ctr.rlen != 16 && ctr.rlen != 24 && ctr.rlen != 32 {
+                return Err(2);
+            }
+            (1u64 << ctr.rlen) - 1
+        }
+    };
+
+    // The counter must not wrap (n <= 2^r - 1).
+    if n as u64 > max_n {
+        return Err(4);
+    }
+
+    Ok(n)
+}
+
+/*  Encodes the counter i on rlen bits in big-endian order. */
+fn encode_ctr(i: usize, rlen: usize) -> Vec<u8> {
+    (i as u32).to_be_bytes()[4 - rlen / 8..].to_vec()
+}
+
+/*  KDF in counter mode (SP 800-108r1, section 4.1).
+
+    Parameters:
+        - key: the key derivation key KI
+        - fixed: the fixed input data
+        - out_bytes: the number of bytes of keying material to be derived
+        - ctr: the configuration of the counter (BeforeFixed, AfterFixed or MiddleFixed)
+
+    Return values:
+        - Ok(ko): SUCCESS, the derived keying material
+        - Err(flag): see the error flags of this module */
+pub fn kdf_counter<P: PRF_Functions>(key: &[u8], fixed: &[u8], out_bytes: usize, ctr: &CtrConfig) -> Result<Vec<u8>, usize> {
+    let h = P::output_len();
+
+    // Checking the parameters (steps 1-2).
+    let n = check_params(Some(ctr), out_bytes, h)?;
+    match ctr.location {
+        CtrLocation::BeforeFixed | CtrLocation::AfterFixed => {}
+        CtrLocation::MiddleFixed(pos) => {
+            if pos > fixed.len() {
+                return Err(3);
+            }
+        }
+        _ => {
+            return Err(3);
+        }
+    }
+
+    let prf = P::new(key).ok_or(1usize)?;
+
+    // K(i) = PRF(KI, [i]_r || FixedInputData) for i = 1..n (steps 3-4).
+    let mut result = Vec::<u8>::with_capacity(n * h);
+    for i in 1..=n {
+        let counter = encode_ctr(i, ctr.rlen);
+        let block = match ctr.location {
+            CtrLocation::BeforeFixed => prf.compute(&[&counter, fixed]),
+            CtrLocation::AfterFixed => prf.compute(&[fixed, &counter]),
+            CtrLocation::MiddleFixed(pos) => prf.compute(&[&fixed[..pos], &counter, &fixed[pos..]]),
+            _ => unreachable!(),
+        };
+        result.extend_from_slice(&block);
+    }
+
+    // Returning the leftmost L bits (step 5).
+    result.truncate(out_bytes);
+    Ok(result)
+}
+
+/*  KDF in feedback mode (SP 800-108r1, section 4.2).
+
+    Parameters:
+        - key: the key derivation key KI
+        - iv: the initial value K(0) (may be empty)
+        - fixed: the fixed input data
+        - out_bytes: the number of bytes of keying material to be derived
+        - ctr: the optional configuration of the counter (BeforeIter, AfterIter or AfterFixed)
+
+    Return values:
+        - Ok(ko): SUCCESS, the derived keying material
+        - Err(flag): see the error flags of this module */
+pub fn kdf_feedback<P: PRF_Functions>(key: &[u8], iv: &[u8], fixed: &[u8], out_bytes: usize, ctr: Option<&CtrConfig>) -> Result<Vec<u8>, usize> {
+    let h = P::output_len();
+
+    // Checking the parameters (steps 1-2).
+    let n = check_params(ctr, out_bytes, h)?;
+    check_iter_location(ctr)?;
+
+    let prf = P::new(key).ok_or(1usize)?;
+
+    // K(i) = PRF(KI, K(i-1) {|| [i]_r} || FixedInputData) with K(0) = IV (steps 3-4).
+    let mut result = Vec::<u8>::with_capacity(n * h);
+    let mut k_prev = iv.to_vec();
+    for i in 1..=n {
+        k_prev = match ctr {
+            None => prf.compute(&[&k_prev, fixed]),
+            Some(ctr) => {
+                let counter = encode_ctr(i, ctr.rlen);
+                match ctr.location {
+                    CtrLocation::BeforeIter => prf.compute(&[&counter, &k_prev, fixed]),
+                    CtrLocation::AfterIter => prf.compute(&[&k_prev, &counter, fixed]),
+                    _ => prf.compute(&[&k_prev, fixed, &counter]),
+                }
+            }
+        };
+        result.extend_from_slice(&k_prev);
+    }
+
+    // Returning the leftmost L bits (step 5).
+    result.truncate(out_bytes);
+    Ok(result)
+}
+
+/*  KDF in double-pipeline iteration mode (SP 800-108r1, section 4.3).
+
+    Parameters:
+        - key: the key derivation key KI
+        - fixed: the fixed input data
+        - out_bytes: the number of bytes of keying material to be derived
+        - ctr: the optional configuration of the counter (BeforeIter, AfterIter or AfterFixed)
+
+    Return values:
+        - Ok(ko): SUCCESS, the derived keying material
+        - Err(flag): see the error flags of this module */
+pub fn kdf_double_pipeline<P: PRF_Functions>(key: &[u8], fixed: &[u8], out_bytes: usize, ctr: Option<&CtrConfig>) -> Result<Vec<u8>, usize> {
+    let h = P::output_len();
+
+    // Checking the parameters (steps 1-2).
+    let n = check_params(ctr, out_bytes, h)?;
+    check_iter_location(ctr)?;
+
+    let prf = P::new(key).ok_or(1usize)?;
+
+    // A(0) = FixedInputData (step 3).
+    let mut a = fixed.to_vec();
+
+    // A(i) = PRF(KI, A(i-1)) and K(i) = PRF(KI, A(i) {|| [i]_r} || FixedInputData) (step 4).
+    let mut result = Vec::<u8>::with_capacity(n * h);
+    for i in 1..=n {
+        a = prf.compute(&[&a]);
+        let block = match ctr {
+            None => prf.compute(&[&a, fixed]),
+            Some(ctr) => {
+                let counter = encode_ctr(i, ctr.rlen);
+                match ctr.location {
+                    CtrLocation::BeforeIter => prf.compute(&[&counter, &a, fixed]),
+                    CtrLocation::AfterIter => prf.compute(&[&a, &counter, fixed]),
+                    _ => prf.compute(&[&a, fixed, &counter]),
+                }
+            }
+        };
+        result.extend_from_slice(&block);
+    }
+
+    // Returning the leftmost L bits (step 5).
+    result.truncate(out_bytes);
+    Ok(result)
+}
+
+/*  Checks that the counter location is supported by the feedback and double-pipeline modes. */
+fn check_iter_location(ctr: Option<&CtrConfig>) -> Result<(), usize> {
+    match ctr.map(|ctr| ctr.location) {
+        None | Some(CtrLocation::BeforeIter) | Some(CtrLocation::AfterIter) | Some(CtrLocation::AfterFixed) => Ok(()),
+        _ => Err(3),
+    }
+}
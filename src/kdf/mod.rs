@@ -0,0 +1,14 @@
+/*  Key derivation functions built on top of the primitives already used by the DRBG mechanisms of this crate.
+
+    The 'kbkdf' module implements the key-based KDFs of NIST SP 800-108r1 in counter, feedback and double-pipeline
+    mode. The pseudo-random functions that can be plugged into those KDFs are defined in the 'prf' module:
+        PRF                                         KEY LENGTH                                      RELATED TYPE
+        HMAC with Sha 256                           any                                             prf::HmacPrf<Sha256>
+        HMAC with Sha 512                           any                                             prf::HmacPrf<Sha512>
+        CMAC with AES 128                           16 bytes                                        prf::CmacPrf<Aes128>
+        CMAC with AES 192                           24 bytes                                        prf::CmacPrf<Aes192>
+        CMAC with AES 256                           32 bytes                                        prf::CmacPrf<Aes256>
+
+    Keys used as input to these KDFs are typically generated with one of the DRBGs of this crate (see 'drbg'). */
+pub mod prf;
+pub mod kbkdf;
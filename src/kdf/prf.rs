@@ -0,0 +1,189 @@
+use digest::{BlockInput, FixedOutput, Reset, Update};
+use generic_array::ArrayLength;
+use generic_array::typenum::Unsigned;
+use hmac::{Hmac, Mac, NewMac};
+use aes::cipher::{
+    BlockCipher, BlockEncrypt, KeyInit,
+    generic_array::GenericArray,
+};
+
+/*  This public trait defines the functions that are common to all pseudo-random functions that can be used by the
+    KDFs of the 'kbkdf' module (see NIST SP 800-108r1, section 4). */
+#[allow(non_camel_case_types)]
+pub trait PRF_Functions {
+    /*  Keys a new instance of the PRF.
+
+        Parameters:
+            - key: the key derivation key
+
+        Return values:
+            - Some(inst): where 'inst' is the keyed PRF
+            - None: the key is not valid for this PRF */
+    fn new(key: &[u8]) -> Option<Self> where Self: Sized;
+
+    /*  Computes the PRF over the concatenation of the passed data strings. The keyed instance is not consumed and can be
+        used for further computations.
+
+        Parameters:
+            - data: the strings to be concatenated and processed
+
+        Return values:
+            - the output block of the PRF (output_len() bytes) */
+    fn compute(&self, data: &[&[u8]]) -> Vec<u8>;
+
+    /*  Function that retrieves the length of the output block of the PRF in bytes. */
+    fn output_len() -> usize;
+
+    /*  Function that retrieves the name of the PRF. */
+    fn prf_name() -> String;
+}
+
+/*  HMAC based PRF. As for the HMAC-DRBG mechanism, this PRF is meant to be used with Sha256 or Sha512.
+
+    - mac: the HMAC primitive keyed with the key derivation key */
+pub struct HmacPrf<D>
+where
+    D: Update + BlockInput + FixedOutput + Reset + Clone + Default,
+    D::BlockSize: ArrayLength<u8>,
+    D::OutputSize: ArrayLength<u8>,
+{
+    mac: Hmac<D>,
+}
+
+impl<D> PRF_Functions for HmacPrf<D>
+where
+    D: Update + BlockInput + FixedOutput + Reset + Clone + Default,
+    D::BlockSize: ArrayLength<u8>,
+    D::OutputSize: ArrayLength<u8>,
+{
+    fn new(key: &[u8]) -> Option<Self> {
+        match Hmac::<D>::new_varkey(key) {
+            Err(_) => None,
+            Ok(mac) => Some(Self{ mac }),
+        }
+    }
+
+    fn compute(&self, data: &[&[u8]]) -> Vec<u8> {
+        let mut mac = self.mac.clone();
+        for string in data {
+            mac.update(string);
+        }
+
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    fn output_len() -> usize {
+        D::OutputSize::to_usize()
+    }
+
+    fn prf_name() -> String {
+        "HMAC".to_string()
+    }
+}
+
+/*  CMAC based PRF as specified in NIST SP 800-38B. This PRF is meant to be used with AES 128/192/256, thus only
+    block ciphers with 16 bytes blocks are supported.
+
+    - cipher: the block cipher keyed with the key derivation key
+    - k1, k2: the subkeys derived from the cipher key (SP 800-38B, section 6.1) */
+pub struct CmacPrf<C>
+where
+    C: BlockCipher + BlockEncrypt + KeyInit,
+{
+    cipher: C,
+    k1: GenericArray<u8, C::BlockSize>,
+    k2: GenericArray<u8, C::BlockSize>,
+}
+
+/*  The constant used for subkey generation with 128 bits blocks (SP 800-38B, section 5.3). */
+const CMAC_RB_128: u8 = 0x87;
+
+impl<C> CmacPrf<C>
+where
+    C: BlockCipher + BlockEncrypt + KeyInit,
+{
+    /*  Multiplies a block by x in GF(2^128), as required by the subkey generation algorithm (SP 800-38B, section 6.1). */
+    fn dbl(block: &GenericArray<u8, C::BlockSize>) -> GenericArray<u8, C::BlockSize> {
+        let mut res = GenericArray::<u8, C::BlockSize>::default();
+        let len = block.len();
+
+        for i in 0..len {
+            let next = if i + 1 < len { block[i + 1] >> 7 } else { 0 };
+            res[i] = (block[i] << 1) | next;
+        }
+
+        if block[0] & 0x80 != 0 {
+            res[len - 1] ^= CMAC_RB_128;
+        }
+
+        res
+    }
+}
+
+impl<C> PRF_Functions for CmacPrf<C>
+where
+    C: BlockCipher + BlockEncrypt + KeyInit,
+{
+    fn new(key: &[u8]) -> Option<Self> {
+        // Only 128 bits block ciphers are supported and the key must be exactly keylen bytes long.
+        if C::block_size() != 16 || key.len() != C::key_size() {
+            return None;
+        }
+
+        let cipher = C::new(GenericArray::from_slice(key));
+
+        // Generating the subkeys (SP 800-38B, section 6.1, steps 1-3).
+        let mut l = GenericArray::<u8, C::BlockSize>::default();
+        cipher.encrypt_block(&mut l);
+        let k1 = Self::dbl(&l);
+        let k2 = Self::dbl(&k1);
+
+        Some(Self{ cipher, k1, k2 })
+    }
+
+    fn compute(&self, data: &[&[u8]]) -> Vec<u8> {
+        let blocklen = C::block_size();
+        let message = data.concat();
+
+        // Number of blocks to be processed, an empty message is processed as one incomplete block (steps 2-3).
+        let n = if message.is_empty() { 1 } else { message.len().div_ceil(blocklen) };
+        let last_complete = !message.is_empty() && message.len().is_multiple_of(blocklen);
+
+        // Building the last block M_n using the appropriate subkey (step 4).
+        let mut last = GenericArray::<u8, C::BlockSize>::default();
+        let last_start = (n - 1) * blocklen;
+        let last_data = &message[last_start..];
+        last[..last_data.len()].copy_from_slice(last_data);
+        if last_complete {
+            for (b, k) in last.iter_mut().zip(self.k1.iter()) {
+                *b ^= k;
+            }
+        }
+        else {
+            last[last_data.len()] = 0x80;
+            for (b, k) in last.iter_mut().zip(self.k2.iter()) {
+                *b ^= k;
+            }
+        }
+
+        // Chaining the blocks through the cipher (steps 5-6).
+        let mut c = GenericArray::<u8, C::BlockSize>::default();
+        for block in message[..last_start].chunks(blocklen).chain(std::iter::once(last.as_slice())) {
+            for (b, m) in c.iter_mut().zip(block) {
+                *b ^= m;
+            }
+            self.cipher.encrypt_block(&mut c);
+        }
+
+        // Returning the full length tag (step 7).
+        c.to_vec()
+    }
+
+    fn output_len() -> usize {
+        C::block_size()
+    }
+
+    fn prf_name() -> String {
+        "CMAC".to_string()
+    }
+}
@@ -1,4 +1,8 @@
 pub mod drbg;
 pub mod mechs;
 pub mod self_tests;
-pub mod demos;
\ No newline at end of file
+pub mod demos;
+pub mod kdf;
+pub mod cli;
+pub mod stats;
+pub mod ffi;
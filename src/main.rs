@@ -1,11 +1,9 @@
 extern crate rust_nist_drbg;
 
-fn main(){  
-    let res = rust_nist_drbg::self_tests::run_tests::run_all();
-    if  res > 0
-    {
-        panic!("MAIN: FATAL ERROR - {res} self-tests have failed, plese check testing log from more information.")
-    }
+use clap::Parser;
+use rust_nist_drbg::cli::{self, Cli};
+use std::process::ExitCode;
 
-    rust_nist_drbg::demos::run_demo();
-}
\ No newline at end of file
+fn main() -> ExitCode {
+    ExitCode::from(cli::run(Cli::parse()))
+}
@@ -1,5 +1,6 @@
-use super::gen_mech::DRBG_Mechanism_Functions;
+use super::gen_mech::{DRBG_Mechanism_Functions, WorkingState};
 use generic_array::ArrayLength;
+use generic_array::typenum::Unsigned;
 use std::any::TypeId;
 use super::utility::*;
 use aes::cipher::{
@@ -7,9 +8,12 @@ use aes::cipher::{
     generic_array::GenericArray,
 };
 
-/*  The life of each generated seed of this DRBG. */
+/*  The default life of each generated seed of this DRBG (see DRBG_Mechanism_Functions::set_seed_life). */
 const SEED_LIFE: usize = 1000;
 
+/*  The maximum reseed interval allowed for this DRBG by table 2 (or 3) of section 10 of the SP (2^48 requests). */
+const MAX_SEED_LIFE: u64 = 1 << 48;
+
 /*  The length of the counter used by the block cipher in bytes. */
 const CTR_LEN: usize = 4;
 
@@ -20,10 +24,12 @@ const CTR_LEN: usize = 4;
     - k: key of the underlying block cipher
     - v: vector used for block encryptions
     - count: reseed counter
+    - reseed_interval: the maximum number of generate requests that can be served between reseedings
     - zeroized: indicates whether the instance has been zeroized (a new instance is needed)
     - seedlen: length of the parameters used by this mechanism (=> blocklen + keylen)
     - blocklen: length of the input/output blocks of the block cipher
-    - keylen: length of the key of the blockcipher */
+    - keylen: length of the key of the blockcipher
+    - cipher: the block cipher keyed with k (the key schedule is computed once per key change) */
 pub struct CtrDrbgMech<D: 'static>
 where
     D: BlockCipher + BlockEncrypt + BlockDecrypt + KeyInit,
@@ -33,10 +39,12 @@ where
     k: GenericArray<u8, D::KeySize>,
     v: GenericArray<u8, D::BlockSize>,
     count: usize,
+    reseed_interval: usize,
     zeroized: bool,
     seedlen: usize,
     blocklen: usize,
     keylen: usize,
+    cipher: D,
 }
 
 /*  Implementing functions that are specific of the CTR-DRBG mechanism. */
@@ -57,71 +65,21 @@ where
             return;
         }
 
-        // Init local variables (step 1)
-        let mut temp = Vec::<u8>::new();
-        let cipher = self.block_cipher();
-
-        // Fill temporary vector block by block until seedlen is reached (step 2)
-        let mut i: usize = 0;
-        while i < self.seedlen {
-            // Appropriately increment the counter based on his size (step 2.1)
-            if CTR_LEN < self.blocklen {
-                let mid_point = self.blocklen - CTR_LEN;
-                
-                // Increment the rigth-most CTR_LEN bytes of V (step 2.1.1)
-                let mut right_v = self.v[mid_point..].to_vec();
-                modular_add(&mut right_v, 0x01);
-
-                // Creating a clone of V with the incremented right-most CTR_LEN bytes
-                let mut v_clone = GenericArray::<u8, D::BlockSize>::default();
-                let (left, right) = v_clone.split_at_mut(mid_point);
-                left.clone_from_slice(&self.v[..mid_point]);
-                right.clone_from_slice(&right_v.as_slice());
-
-                // Update V (step 2.1.2)
-                self.v.clone_from(&v_clone);
-            }
-            else {
-                // Increment V (step 2.1 alternative)
-                let mut v_clone = self.v.to_vec();
-                modular_add(&mut v_clone, 0x01);
-
-                // Update V
-                self.v.clone_from_slice(&v_clone);
-            }
-
-            // Encrypt V (step 2.2)
-            let mut block = self.v.clone();
-            cipher.encrypt_block(&mut block);
-
-            // Append encrypted block to temporary vector (step 2.3)
-            temp.append(&mut block.to_vec());
-
-            // Increment counter
-            i += self.blocklen;
-        }
-
-        // Taking only seedlen bytes (step 3)
-        temp.resize(self.seedlen, 0x00);
+        // Fill temporary vector block by block until seedlen bytes are produced (step 1,2)
+        let mut temp = Vec::<u8>::with_capacity(self.seedlen);
+        ctr_generate(&self.cipher, &mut self.v, CTR_LEN, &mut temp, self.seedlen);
 
         // Performing temp XOR provided_data (step 4)
         xor_vecs(&mut temp, provided_data);
 
-        // Update K (step 5)
+        // Update K and the related key schedule (step 5)
         self.k.clone_from_slice(&temp[..self.keylen]);
+        self.cipher = D::new(&self.k);
 
         // Update V (step 6)
         self.v.clone_from_slice(&temp[self.keylen..]);
     }
 
-    /*  Retrieves and instance of the hmac primitive that uses self.k as a key.
-    
-        Return values:
-            - a pointer to an hmac primitive */
-    fn block_cipher(&self) -> D {
-        D::new(&self.k)
-    }
-
     /*  Takes a vector in input and adjusts it to be exactly seedlen bytes long. If a shorter (or empty) vector is received
         0's padding is added. */
     fn to_be_len(vec: &[u8], len: usize) -> Vec<u8>{
@@ -195,14 +153,17 @@ where
             v[i] = 0x0;
         }
 
+        let cipher = D::new(&k);
         let mut this = Self{
             k,
             v,
             count: 1,
+            reseed_interval: SEED_LIFE,
             zeroized: false,
             seedlen: seed_len,
             blocklen: block_len,
             keylen: key_len,
+            cipher,
         };
 
         // Updating the internal state using the entropy and given personalization string (step 3,6)
@@ -227,7 +188,7 @@ where
         }
         
         // Reached reseed interval (ERROR_FLAG=2, step 1)
-        if self.count >= SEED_LIFE{
+        if self.count > self.reseed_interval {
             return 2;
         }
 
@@ -246,49 +207,8 @@ where
             }
         }
 
-        // Generating blocklen bytes at a time using the underlying block cipher (step 3,4).
-        let cipher = self.block_cipher();
-        let mut i: usize = 0;
-        while i < req_bytes {
-            // Appropriately increment the counter based on his size (step 4.1)
-            if CTR_LEN < self.blocklen {
-                let mid_point = self.blocklen - CTR_LEN;
-
-                // Increment the rigth-most CTR_LEN bytes of V (step 4.1.1)
-                let mut right_v = self.v[mid_point..].to_vec();
-                modular_add(&mut right_v, 0x01);
-
-                // Creating a clone of V with the incremented right-most CTR_LEN bytes
-                let mut v_clone = GenericArray::<u8, D::BlockSize>::default();
-                let (left, right) = v_clone.split_at_mut(mid_point);
-                left.clone_from_slice(&self.v[..mid_point]);
-                right.clone_from_slice(&right_v.as_slice());
-
-                // Update V (step 4.1.2)
-                self.v.clone_from(&v_clone);
-            }
-            else {
-                // Increment V (step 4.1 alternative)
-                let mut v_clone = self.v.to_vec();
-                modular_add(&mut v_clone, 0x01);
-
-                // Update V
-                self.v.clone_from_slice(&v_clone);
-            }
-
-            // Encrypt V (step 4.2)
-            let mut block = self.v.clone();
-            cipher.encrypt_block(&mut block);
-
-            // Append encrypted block to temporary vector (step 4.3)
-            result.append(&mut block.to_vec());
-
-            // Increment counter
-            i += self.blocklen;
-        }
-
-        // Taking only req_bytes (step 5)
-        result.resize(req_bytes, 0x00);
+        // Generating the requested bytes by encrypting batches of counter blocks (step 3,4,5).
+        ctr_generate(&self.cipher, &mut self.v, CTR_LEN, result, req_bytes);
 
         // Updating internal state (step 6)
         self.update(&new_add_in);
@@ -355,6 +275,9 @@ where
             self.v[i] = 0x0;
         }
 
+        // Overwriting the key schedule with the one of the zeroized key
+        self.cipher = D::new(&self.k);
+
         self.count = 0;
         self.seedlen = 0;
         self.keylen = 0;
@@ -363,12 +286,70 @@ where
         0
     }
 
+    fn export_state(&self) -> Option<WorkingState> {
+        if self.zeroized {
+            return None;
+        }
+
+        Some(WorkingState{
+            v: self.v.to_vec(),
+            c: Vec::new(),
+            key: self.k.to_vec(),
+            count: self.count,
+            reseed_interval: self.reseed_interval,
+        })
+    }
+
+    fn import_state(state: &WorkingState) -> Option<Self> {
+        let block_len: usize = 16;
+
+        // Runtime check on the use of any unallowed block cipher and according key length.
+        let this_id = TypeId::of::<D>();
+        let key_len;
+        if this_id == TypeId::of::<aes::Aes128>() {
+            key_len = 16;
+        }
+        else if this_id == TypeId::of::<aes::Aes192>() {
+            key_len = 24;
+        }
+        else if this_id == TypeId::of::<aes::Aes256>() {
+            key_len = 32;
+        }
+        else {
+            return None;
+        }
+
+        // Snapshots that do not carry a reseed interval use the default one.
+        let reseed_interval = if state.reseed_interval == 0 { SEED_LIFE } else { state.reseed_interval };
+
+        // K and V must be keylen and blocklen bytes long and the reseed counter and the reseed interval must be valid.
+        if state.key.len() != key_len || state.v.len() != block_len || !state.c.is_empty() ||
+            reseed_interval > Self::max_seed_life() || state.count == 0 || state.count - 1 > reseed_interval {
+            return None;
+        }
+
+        let k = GenericArray::<u8, D::KeySize>::clone_from_slice(&state.key);
+        let cipher = D::new(&k);
+
+        Some(Self{
+            k,
+            v: GenericArray::<u8, D::BlockSize>::clone_from_slice(&state.v),
+            count: state.count,
+            reseed_interval,
+            zeroized: false,
+            seedlen: block_len + key_len,
+            blocklen: block_len,
+            keylen: key_len,
+            cipher,
+        })
+    }
+
     fn count(&self) -> usize {
         self.count
     }
 
     fn reseed_needed(&self) -> bool{
-        self.count >= SEED_LIFE
+        self.count > self.reseed_interval
     }
 
     fn _is_zeroized(&self) -> bool{
@@ -379,7 +360,24 @@ where
         return "CTR-DRBG".to_string();
     }
 
-    fn seed_life() -> usize {
-        return SEED_LIFE;
+    fn seed_life(&self) -> usize {
+        self.reseed_interval
+    }
+
+    fn set_seed_life(&mut self, seed_life: usize) -> usize {
+        if seed_life == 0 || seed_life > Self::max_seed_life() {
+            return 1;
+        }
+
+        self.reseed_interval = seed_life;
+        0
+    }
+
+    fn max_seed_life() -> usize {
+        usize::try_from(MAX_SEED_LIFE).unwrap_or(usize::MAX)
+    }
+
+    fn mech_id() -> String {
+        format!("{} AES-{}", Self::drbg_name(), D::KeySize::to_usize() * 8)
     }
 }
\ No newline at end of file
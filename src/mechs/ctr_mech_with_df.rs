@@ -1,15 +1,20 @@
-use super::gen_mech::DRBG_Mechanism_Functions;
+use super::gen_mech::{DRBG_Mechanism_Functions, WorkingState};
 use generic_array::ArrayLength;
+use generic_array::typenum::Unsigned;
 use std::any::TypeId;
 use super::utility::*;
+use super::derivation;
 use aes::cipher::{
     BlockCipher, BlockEncrypt, BlockDecrypt, KeyInit,
     generic_array::GenericArray,
 };
 
-/*  The life of each generated seed of this DRBG. */
+/*  The default life of each generated seed of this DRBG (see DRBG_Mechanism_Functions::set_seed_life). */
 const SEED_LIFE: usize = 1000;
 
+/*  The maximum reseed interval allowed for this DRBG by table 2 (or 3) of section 10 of the SP (2^48 requests). */
+const MAX_SEED_LIFE: u64 = 1 << 48;
+
 /*  The length of the counter used by the block cipher in bytes. */
 const CTR_LEN: usize = 4;
 
@@ -20,10 +25,12 @@ const CTR_LEN: usize = 4;
     - k: key of the underlying block cipher
     - v: vector used for block encryptions
     - count: reseed counter
+    - reseed_interval: the maximum number of generate requests that can be served between reseedings
     - zeroized: indicates whether the instance has been zeroized (a new instance is needed)
     - seedlen: computes as blocklen + keylen and is used for block operations inside this mechanism
     - blocklen: length of the input/output blocks of the block cipher
-    - keylen: length of the key of the blockcipher */
+    - keylen: length of the key of the blockcipher
+    - cipher: the block cipher keyed with k (the key schedule is computed once per key change) */
 
 #[allow(non_camel_case_types)]
 pub struct CtrDrbgMech_DF<D: 'static>
@@ -35,10 +42,12 @@ where
     k: GenericArray<u8, D::KeySize>,
     v: GenericArray<u8, D::BlockSize>,
     count: usize,
+    reseed_interval: usize,
     zeroized: bool,
     seedlen: usize,
     blocklen: usize,
     keylen: usize,
+    cipher: D,
 }
 
 /*  Implementing functions that are specific of the CTR-DRBG mechanism with DF. */
@@ -48,138 +57,6 @@ where
     D::BlockSize: ArrayLength<u8>,
     D::KeySize: ArrayLength<u8>,
 {   
-    /*  This function is used to produce an output block by encrypting input data as a chain of input blocks.
-        (see NIST SP 800-90A, section 10.3.3)
-        
-        Parameters:
-            - data: the input data to be encrypted
-            
-        Outputs:
-            - output_block: the returned encrypted block. */
-    fn bcc(&self, key: &GenericArray::<u8, D::KeySize>, data: &Vec<u8> ) -> Option<GenericArray::<u8, D::BlockSize>>{
-        // Initializing the first chaining value to a 0 vector (step 1)
-        let mut chaining_value = GenericArray::<u8, D::BlockSize>::default();
-        chaining_value.fill(0);
-        let test_block = chaining_value.clone();
-
-        // Number of blocks to be processed (step 2)
-        let n = data.len() / (self.blocklen);
-        let cipher = D::new(key);
-
-        // Processing data block by block (step 3,4)
-        for i in 1..n+1 {
-            // XORing the chaining value with the n-th block of the received data (step 4.1)
-            let mut chain_clone = chaining_value.to_vec();
-            xor_vecs(&mut chain_clone, &data[0+self.blocklen*(i-1)..self.blocklen*i].to_vec());
-            chaining_value.clone_from_slice(&chain_clone);
-
-            // Encrypting the chaining value (step 4.2)
-            let mut block = chaining_value.clone();
-            cipher.encrypt_block(&mut block);
-
-            chaining_value.clone_from_slice(block.as_slice());
-        }
-
-        // Input data is too short and no block could be encrypted
-        if test_block == chaining_value {
-            None
-        }
-        else {
-            // Returning the chaining value as an output block (step 5,6)
-            Some(chaining_value)
-        }
-    }
-
-    /*  This function is used by a CTR-DRBG to derive the seed material used for each operation it is supposed to do.
-        (see NIST SP 800-90A, section 10.3.3) 
-        
-        Parameters:
-            - input: the input data to be used by the derivation function
-            - num_bytes: the number of bytes to be produced by the derivation function
-        
-        Return values:
-            - output_bytes: eventual bytes produced by the DF (None if error happened) */
-    fn block_cipher_df(&mut self, input: Vec<u8>, num_bytes: usize) -> Option<Vec<u8>>{
-        const MAX_BYTES: usize = 64;
-
-        // Requested too many bytes (step 1)
-        if num_bytes > MAX_BYTES {return None;}
-
-        // Initializing variables for the DF (steps 2,3,4,5).
-        let l = &input.len().to_be_bytes()[4..];
-        let n = &num_bytes.to_be_bytes()[4..];
-        let mut s = Vec::<u8>::new();
-        s.append(&mut l.to_vec());
-        s.append(&mut n.to_vec());
-        s.append(&mut input.clone());
-        s.push(0x80);
-
-        while (s.len() % self.blocklen) > 0 {
-            s.push(0x00);
-        }
-
-        // Steps 6-7-8
-        let mut temp = Vec::<u8>::new();
-        let mut k = GenericArray::<u8, D::KeySize>::default();
-        let mut counter:u8 = 0x00;
-        let mut i: usize =0;
-        while i < self.keylen {
-            k[i]= counter;
-            counter += 1;
-            i += 1;
-        }
-
-        // Generating bytes using the data derived before (step 9)
-        let mut i: u32 = 0;
-        let mut iv = Vec::<u8>::new();
-        while temp.len() < (self.keylen + self.blocklen) {
-            iv.clear();
-            iv.append(&mut i.to_be_bytes().to_vec());
-            while iv.len() < self.blocklen {
-                iv.push(0x00);
-            }
-
-            // Encrypting the IV using the BCC function (step 9.2)
-            iv.append(&mut s.clone());
-
-            let res_bcc = self.bcc(&k,&iv);
-            let out_block;
-            match res_bcc {
-                None => {
-                    return None;
-                }
-                Some(inst) => {
-                    out_block = inst;
-                }
-            }
-            temp.append(&mut out_block.to_vec());
-
-            // Incrementing the counter (step 9.3)
-            i += 1;
-        }
-
-        // Saving temp bytes (steps 10-11)
-        let k = GenericArray::<u8, D::KeySize>::from_slice(&temp[..self.keylen]);
-        let mut x = temp[self.keylen..self.keylen+self.blocklen].to_vec();
-
-        // Clearing temp and starting a block_encrypt cicle (steps 12-13)
-        let mut temp = Vec::<u8>::new();
-        let cipher = D::new(k);
-        while temp.len() < num_bytes {
-            // Encrypting x and updating its value (step 13.1)
-            let mut x_copy = GenericArray::<u8, D::BlockSize>::default();
-            x_copy.clone_from_slice(&x);
-            cipher.encrypt_block(&mut x_copy);
-            x.clone_from(&x_copy.to_vec());
-
-            // Appending the new value of x to temp (step 13.2)
-            temp.append(&mut x.to_vec());
-        }
-
-        // Returning the exact number of requested bytes (steps 14-15)
-        Some(temp.clone()[..num_bytes].to_vec())
-    }
-
     /*  This function is used to update the internal state of the CTR-DRBG.
         (see NIST SP 800-90A, section 10.2.1.2)
         
@@ -191,70 +68,21 @@ where
             return;
         }
 
-        // Init local variables (step 1)
-        let mut temp = Vec::<u8>::new();
-        let cipher = self.block_cipher();
-
-        // Fill temporary vector block by block until seedlen is reached (step 2)
-        let mut i: usize = 0;
-        while i < self.seedlen {
-            // Appropriately increment the counter based on his size (step 2.1)
-            if CTR_LEN < self.blocklen {
-                let mid_point = self.blocklen - CTR_LEN;
-                
-                // Increment the rigth-most CTR_LEN bytes of V (step 2.1.1)
-                let mut right_v = self.v[mid_point..].to_vec();
-                modular_add(&mut right_v, 0x01);
-
-                // Creating a clone of V with the incremented right-most CTR_LEN bytes
-                let mut v_clone = GenericArray::<u8, D::BlockSize>::default();
-                let (left, right) = v_clone.split_at_mut(mid_point);
-                left.clone_from_slice(&self.v[..mid_point]);
-                right.clone_from_slice(&right_v.as_slice());
-
-                // Update V (step 2.1.2)
-                self.v.clone_from(&v_clone);
-            }
-            else {
-                // Increment V (step 2.1 alternative)
-                let mut v_clone = self.v.to_vec();
-                modular_add(&mut v_clone, 0x01);
-
-                // Update V
-                self.v.clone_from_slice(&v_clone);
-            }
-
-            // Encrypt V (step 2.2)
-            let mut block = self.v.clone();
-            cipher.encrypt_block(&mut block);
-
-            // Append encrypted block to temporary vector (step 2.3)
-            temp.append(&mut block.to_vec());
-
-            // Increment counter
-            i += self.blocklen;
-        }
-
-        // Taking only seedlen bytes (step 3)
-        temp.resize(self.seedlen, 0x00);
+        // Fill temporary vector block by block until seedlen bytes are produced (step 1,2)
+        let mut temp = Vec::<u8>::with_capacity(self.seedlen);
+        ctr_generate(&self.cipher, &mut self.v, CTR_LEN, &mut temp, self.seedlen);
 
         // Performing temp XOR provided_data (step 4)
         xor_vecs(&mut temp, provided_data);
 
-        // Update K (step 5)
+        // Update K and the related key schedule (step 5)
         self.k.clone_from_slice(&temp[..self.keylen]);
+        self.cipher = D::new(&self.k);
 
         // Update V (step 6)
         self.v.clone_from_slice(&temp[self.keylen..]);
     }
 
-    /*  Retrieves and instance of the hmac primitive that uses self.k as a key.
-    
-        Return values:
-            - a pointer to an hmac primitive */
-    fn block_cipher(&self) -> D {
-        D::new(&self.k)
-    }
 }
 
 /*  Implementing common DRBG mechanism functions taken from the DRBG_Mechanism_Functions trait (see 'gen_mech'). */
@@ -317,25 +145,27 @@ where
             v[i] = 0x0;
         }
 
+        let cipher = D::new(&k);
         let mut this = Self{
             k,
             v,
             count: 1,       // step 6
+            reseed_interval: SEED_LIFE,
             zeroized: false,
             seedlen: seed_len,
             blocklen: block_len,
             keylen: key_len,
+            cipher,
         };
 
         // Deriving the actual seedlen seed from the DF (step 2)
-        let res_seed = this.block_cipher_df(seed_material, seed_len);
+        let res_seed = derivation::block_cipher_df::<D>(&seed_material, seed_len);
         match res_seed {
-            None => {
+            Err(_) => {
                 // Derivation function failed unexpectedly
-                println!("DF FAILED");
                 return None;
             }
-            Some(inst) => {
+            Ok(inst) => {
                 seed_material = inst;
             }
         }
@@ -360,7 +190,7 @@ where
         }
         
         // Reached reseed interval (ERROR_FLAG=2, step 1)
-        if self.count >= SEED_LIFE{
+        if self.count > self.reseed_interval {
             return 2;
         }
 
@@ -373,13 +203,13 @@ where
                 }
             }
             Some(add_in) => {
-                let res_df = self.block_cipher_df(add_in.to_vec(), self.seedlen);
+                let res_df = derivation::block_cipher_df::<D>(add_in, self.seedlen);
 
                 match res_df {
-                    None => {
+                    Err(_) => {
                         return 3;
                     }
-                    Some(inst) => {
+                    Ok(inst) => {
                         new_add_in = inst;
                     }
                 }
@@ -388,49 +218,8 @@ where
             }
         }
 
-        // Generating blocklen bytes at a time using the underlying block cipher (step 3,4).
-        let cipher = self.block_cipher();
-        let mut i: usize = 0;
-        while i < req_bytes {
-            // Appropriately increment the counter based on his size (step 4.1)
-            if CTR_LEN < self.blocklen {
-                let mid_point = self.blocklen - CTR_LEN;
-
-                // Increment the rigth-most CTR_LEN bytes of V (step 4.1.1)
-                let mut right_v = self.v[mid_point..].to_vec();
-                modular_add(&mut right_v, 0x01);
-
-                // Creating a clone of V with the incremented right-most CTR_LEN bytes
-                let mut v_clone = GenericArray::<u8, D::BlockSize>::default();
-                let (left, right) = v_clone.split_at_mut(mid_point);
-                left.clone_from_slice(&self.v[..mid_point]);
-                right.clone_from_slice(&right_v.as_slice());
-
-                // Update V (step 4.1.2)
-                self.v.clone_from(&v_clone);
-            }
-            else {
-                // Increment V (step 4.1.2 alternative)
-                let mut v_clone = self.v.to_vec();
-                modular_add(&mut v_clone, 0x01);
-
-                // Update V
-                self.v.clone_from_slice(&v_clone);
-            }
-
-            // Encrypt V (step 4.2)
-            let mut block = self.v.clone();
-            cipher.encrypt_block(&mut block);
-
-            // Append encrypted block to temporary vector (step 4.3)
-            result.append(&mut block.to_vec());
-
-            // Increment counter
-            i += self.blocklen;
-        }
-
-        // Taking only req_bytes (step 5)
-        result.resize(req_bytes, 0x00);
+        // Generating the requested bytes by encrypting batches of counter blocks (step 3,4,5).
+        ctr_generate(&self.cipher, &mut self.v, CTR_LEN, result, req_bytes);
 
         // Updating internal state (step 6)
         self.update(&new_add_in);
@@ -463,13 +252,13 @@ where
         }    
 
         // Deriving the actual seedlen seed from the DF (step 2)
-        let res_seed = self.block_cipher_df(seed_material, self.seedlen);
+        let res_seed = derivation::block_cipher_df::<D>(&seed_material, self.seedlen);
         match res_seed {
-            None => {
+            Err(_) => {
                 // Derivation function failed unexpectedly
                 return 3;
             }
-            Some(inst) => {
+            Ok(inst) => {
                 seed_material = inst;
             }
         }
@@ -498,6 +287,9 @@ where
             self.v[i] = 0x0;
         }
 
+        // Overwriting the key schedule with the one of the zeroized key
+        self.cipher = D::new(&self.k);
+
         self.count = 0;
         self.seedlen = 0;
         self.keylen = 0;
@@ -506,12 +298,70 @@ where
         0
     }
 
+    fn export_state(&self) -> Option<WorkingState> {
+        if self.zeroized {
+            return None;
+        }
+
+        Some(WorkingState{
+            v: self.v.to_vec(),
+            c: Vec::new(),
+            key: self.k.to_vec(),
+            count: self.count,
+            reseed_interval: self.reseed_interval,
+        })
+    }
+
+    fn import_state(state: &WorkingState) -> Option<Self> {
+        let block_len: usize = 16;
+
+        // Runtime check on the use of any unallowed block cipher and according key length.
+        let this_id = TypeId::of::<D>();
+        let key_len;
+        if this_id == TypeId::of::<aes::Aes128>() {
+            key_len = 16;
+        }
+        else if this_id == TypeId::of::<aes::Aes192>() {
+            key_len = 24;
+        }
+        else if this_id == TypeId::of::<aes::Aes256>() {
+            key_len = 32;
+        }
+        else {
+            return None;
+        }
+
+        // Snapshots that do not carry a reseed interval use the default one.
+        let reseed_interval = if state.reseed_interval == 0 { SEED_LIFE } else { state.reseed_interval };
+
+        // K and V must be keylen and blocklen bytes long and the reseed counter and the reseed interval must be valid.
+        if state.key.len() != key_len || state.v.len() != block_len || !state.c.is_empty() ||
+            reseed_interval > Self::max_seed_life() || state.count == 0 || state.count - 1 > reseed_interval {
+            return None;
+        }
+
+        let k = GenericArray::<u8, D::KeySize>::clone_from_slice(&state.key);
+        let cipher = D::new(&k);
+
+        Some(Self{
+            k,
+            v: GenericArray::<u8, D::BlockSize>::clone_from_slice(&state.v),
+            count: state.count,
+            reseed_interval,
+            zeroized: false,
+            seedlen: block_len + key_len,
+            blocklen: block_len,
+            keylen: key_len,
+            cipher,
+        })
+    }
+
     fn count(&self) -> usize {
         self.count
     }
 
     fn reseed_needed(&self) -> bool{
-        self.count >= SEED_LIFE
+        self.count > self.reseed_interval
     }
 
     fn _is_zeroized(&self) -> bool{
@@ -522,7 +372,24 @@ where
         return "CTR-DRBG-DF".to_string();
     }
 
-    fn seed_life() -> usize {
-        return SEED_LIFE;
+    fn seed_life(&self) -> usize {
+        self.reseed_interval
+    }
+
+    fn set_seed_life(&mut self, seed_life: usize) -> usize {
+        if seed_life == 0 || seed_life > Self::max_seed_life() {
+            return 1;
+        }
+
+        self.reseed_interval = seed_life;
+        0
+    }
+
+    fn max_seed_life() -> usize {
+        usize::try_from(MAX_SEED_LIFE).unwrap_or(usize::MAX)
+    }
+
+    fn mech_id() -> String {
+        format!("{} AES-{}", Self::drbg_name(), D::KeySize::to_usize() * 8)
     }
 }
\ No newline at end of file
@@ -0,0 +1,201 @@
+use digest::{FixedOutput, Reset, Update};
+use generic_array::typenum::Unsigned;
+use aes::cipher::{
+    BlockCipher, BlockEncrypt, KeyInit,
+    generic_array::GenericArray,
+};
+
+/*  This module exposes the derivation functions defined in section 10.3 of NIST SP 800-90A as standalone functions.
+    Both functions are instance-free: they do not depend on the internal state of a live mechanism and can therefore be
+    used by external applications (e.g. for entropy conditioning or key derivation) as well as by the mechanisms of this
+    crate (see 'hash_mech' and 'ctr_mech_with_df').
+
+    Limits are enforced by returning an error flag >0 instead of silently truncating the output:
+        FUNCTION                MAXIMUM OUTPUT                                  MAXIMUM INPUT
+        hash_df, hash_df_into   255 * outlen bytes                              2^32 - 1 bytes
+        block_cipher_df         64 bytes (512 bits)                             2^32 - 1 bytes (L must fit the 32-bit field)
+
+    Note that the CTR-DRBG (DF) mechanism only ever requests seedlen bytes, which is always within the 512 bits. */
+
+/*  This is the Hash_df derivation function defined in section 10.3.1 of the SP.
+
+    Parameters:
+        - input: the string to be hashed by the df
+        - num_bytes: the number of bytes to be returned by the df
+
+    Return values:
+        - Ok(bytes): SUCCESS, a vector of exactly num_bytes bytes
+        - Err(1): ERROR, too many bytes requested (max 255 * outlen)
+        - Err(2): ERROR, the input string is too long (max 2^32 - 1 bytes) */
+pub fn hash_df<D>(input: &[u8], num_bytes: usize) -> Result<Vec<u8>, usize>
+where
+    D: Update + FixedOutput + Reset + Default,
+{
+    let mut result = vec![0u8; num_bytes];
+    hash_df_into::<D>(&[input], &mut result)?;
+    Ok(result)
+}
+
+/*  Allocation-free variant of hash_df. The input string is given as a list of parts that are fed to the hash function
+    one after the other (input_string = parts[0] || parts[1] || ...), so that callers do not need to concatenate them.
+    The output buffer is filled completely (num_bytes = out.len()).
+
+    Parameters:
+        - parts: the parts of the string to be hashed by the df
+        - out: the output buffer
+
+    Return values:
+        - Ok(()): SUCCESS, out has been filled
+        - Err(1): ERROR, too many bytes requested (max 255 * outlen)
+        - Err(2): ERROR, the input string is too long (max 2^32 - 1 bytes) */
+pub fn hash_df_into<D>(parts: &[&[u8]], out: &mut [u8]) -> Result<(), usize>
+where
+    D: Update + FixedOutput + Reset + Default,
+{
+    let outlen = D::OutputSize::to_usize();
+    let num_bytes = out.len();
+
+    // No more that 255 * outlen bytes can be requested (step 1 and the 8-bit counter of step 4).
+    if num_bytes > outlen * 255 {
+        return Err(1);
+    }
+
+    // Input strings longer than the max_length of table 2 (2^35 bits) are rejected.
+    if parts.iter().map(|part| part.len()).sum::<usize>() > u32::MAX as usize {
+        return Err(2);
+    }
+
+    // Initial setup (step 1-2-3).
+    let no_of_bits_to_return = ((num_bytes * 8) as u32).to_be_bytes();
+    let mut counter: u8 = 0x01;
+    let mut hash_fun = D::default();
+
+    // Generating outlen bytes at a time, the last chunk is truncated (step 4-5).
+    for chunk in out.chunks_mut(outlen) {
+        // Hashing counter || no_of_bits_to_return || input_string (step 4.1).
+        hash_fun.update([counter]);
+        hash_fun.update(no_of_bits_to_return);
+        for part in parts {
+            hash_fun.update(part);
+        }
+        let digest = hash_fun.finalize_fixed_reset();
+        chunk.copy_from_slice(&digest[..chunk.len()]);
+
+        // Updating the counter (step 4.2).
+        counter = counter.wrapping_add(0x01);
+    }
+
+    // Returning the leftmost num_bytes bytes (step 6).
+    Ok(())
+}
+
+/*  The maximum number of bytes returned by Block_Cipher_df (512 bits, section 10.3.2 of the SP). */
+pub const MAX_BLOCK_CIPHER_DF_BYTES: usize = 64;
+
+/*  This is the Block_Cipher_df derivation function defined in section 10.3.2 of the SP.
+
+    Parameters:
+        - input: the string to be processed by the df
+        - num_bytes: the number of bytes to be returned by the df
+
+    Return values:
+        - Ok(bytes): SUCCESS, a vector of exactly num_bytes bytes
+        - Err(1): ERROR, too many bytes requested (max MAX_BLOCK_CIPHER_DF_BYTES)
+        - Err(2): ERROR, the input string is too long (L must fit the 32-bit field) */
+pub fn block_cipher_df<C>(input: &[u8], num_bytes: usize) -> Result<Vec<u8>, usize>
+where
+    C: BlockCipher + BlockEncrypt + KeyInit,
+{
+    let blocklen = C::block_size();
+    let keylen = C::key_size();
+
+    // Requested too many bytes, no_of_bits_to_return <= 512 (step 1).
+    if num_bytes > MAX_BLOCK_CIPHER_DF_BYTES {
+        return Err(1);
+    }
+
+    // The length of the input string must be representable in 32 bits (step 2).
+    if input.len() > u32::MAX as usize {
+        return Err(2);
+    }
+
+    // S = L || N || input_string || 0x80, padded with zeros to a multiple of blocklen (steps 2-5).
+    let mut s = Vec::<u8>::with_capacity(input.len() + 9 + blocklen);
+    s.extend_from_slice(&(input.len() as u32).to_be_bytes());
+    s.extend_from_slice(&(num_bytes as u32).to_be_bytes());
+    s.extend_from_slice(input);
+    s.push(0x80);
+    while !s.len().is_multiple_of(blocklen) {
+        s.push(0x00);
+    }
+
+    // K = leftmost keylen bytes of 0x00010203...1D1E1F (steps 6-8).
+    let mut k = GenericArray::<u8, C::KeySize>::default();
+    for (i, byte) in k.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    let cipher = C::new(&k);
+
+    // Generating keylen + blocklen bytes through the BCC function (step 9).
+    let mut temp = Vec::<u8>::with_capacity(keylen + 2 * blocklen);
+    let mut i: u32 = 0;
+    let mut iv = vec![0u8; blocklen];
+    while temp.len() < keylen + blocklen {
+        // IV = i || 0^(outlen - len(i)) (step 9.1).
+        iv.fill(0x00);
+        iv[..4].copy_from_slice(&i.to_be_bytes());
+
+        // temp = temp || BCC(K, IV || S) (step 9.2).
+        temp.extend_from_slice(&bcc(&cipher, &iv, &s));
+
+        // Incrementing the counter (step 9.3).
+        i += 1;
+    }
+
+    // Deriving the new key and the first block X (steps 10-11).
+    let cipher = C::new(GenericArray::from_slice(&temp[..keylen]));
+    let mut x = GenericArray::<u8, C::BlockSize>::clone_from_slice(&temp[keylen..keylen + blocklen]);
+
+    // Encrypting X until enough bytes are produced (steps 12-13).
+    let mut result = Vec::<u8>::with_capacity(num_bytes + blocklen);
+    while result.len() < num_bytes {
+        cipher.encrypt_block(&mut x);
+        result.extend_from_slice(&x);
+    }
+
+    // Returning the leftmost num_bytes bytes (steps 14-15).
+    result.truncate(num_bytes);
+    Ok(result)
+}
+
+/*  This is the BCC function defined in section 10.3.3 of the SP. It processes IV || data as a chain of blocks and returns
+    the last chaining value. The data is expected to be a multiple of blocklen bytes (which is guaranteed by block_cipher_df).
+
+    Parameters:
+        - cipher: the block cipher already keyed with K
+        - iv: the first block of the chain
+        - data: the remaining blocks of the chain
+
+    Return values:
+        - the output block of the chain */
+fn bcc<C>(cipher: &C, iv: &[u8], data: &[u8]) -> GenericArray<u8, C::BlockSize>
+where
+    C: BlockCipher + BlockEncrypt,
+{
+    // Initializing the chaining value to a 0 vector (step 1).
+    let mut chaining_value = GenericArray::<u8, C::BlockSize>::default();
+
+    // Processing the data block by block (steps 2-4).
+    for block in iv.chunks(C::block_size()).chain(data.chunks(C::block_size())) {
+        // XORing the chaining value with the current block (step 4.1).
+        for (c, b) in chaining_value.iter_mut().zip(block) {
+            *c ^= b;
+        }
+
+        // Encrypting the chaining value (step 4.2).
+        cipher.encrypt_block(&mut chaining_value);
+    }
+
+    // Returning the last chaining value as the output block (steps 5-6).
+    chaining_value
+}
@@ -6,6 +6,32 @@
     entropy inputs, nonces, and others) are directly derived by the function envelopes according to the needs of the
     specific mechanism.
 */
+use serde::{Deserialize, Serialize};
+
+/*  The working state of an instance of a mechanism, as exported by export_state and restored by import_state.
+    Each mechanism only uses the values that are part of its internal state (the others are left empty):
+        - Hash-DRBG: v, c
+        - HMAC-DRBG: v, key
+        - CTR-DRBG (with and without DF): v, key
+    The count is the reseed counter of the instance and reseed_interval its seed life (0 in snapshots taken before the
+    reseed interval was configurable, meaning the default one). The values are overwritten when the state is dropped. */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct WorkingState {
+    pub v: Vec<u8>,
+    pub c: Vec<u8>,
+    pub key: Vec<u8>,
+    pub count: usize,
+    #[serde(default)]
+    pub reseed_interval: usize,
+}
+
+impl Drop for WorkingState {
+    fn drop(&mut self) {
+        self.v.fill(0x00);
+        self.c.fill(0x00);
+        self.key.fill(0x00);
+    }
+}
 
 #[allow(non_camel_case_types)]
 pub trait DRBG_Mechanism_Functions: {
@@ -60,7 +86,7 @@ pub trait DRBG_Mechanism_Functions: {
             - 0: SUCCESS, instantiation successfully reseeded
             - 1: ERROR, instantiation cannot be reseeded
             - 2: ERROR, the passed entropy is too short to be used
-            - 3: ERROR, (CTR-DRGB with DF) the DF function failed unexpectedly
+            - 3: ERROR, (Hash-DRBG and CTR-DRGB with DF) the DF function failed unexpectedly
     */
     fn reseed(&mut self, entropy: &[u8], add: Option<&[u8]>) -> usize;
 
@@ -77,6 +103,28 @@ pub trait DRBG_Mechanism_Functions: {
     */
     fn zeroize(&mut self) -> usize;
 
+    /*  Exports the working state of this instance, so that it can be persisted and later restored with import_state.
+        This function is called by DRBG::export_state, which is responsible for protecting the exported values.
+
+        Return values:
+            - Some(state): SUCCESS, the working state of this instance
+            - None: ERROR, this instance has been zeroized
+    */
+    fn export_state(&self) -> Option<WorkingState>;
+
+    /*  Restores an instance from a working state previously returned by export_state.
+        This function is called by DRBG::import_state, which is responsible for checking that the state was exported
+        by the same mechanism.
+
+        Parameters:
+            - state: the working state to be restored
+
+        Return values:
+            - Some(inst): where 'inst' is the restored instance
+            - None: the working state is not valid for this mechanism (e.g. wrong lengths or reseed counter)
+    */
+    fn import_state(state: &WorkingState) -> Option<Self> where Self: Sized;
+
     /*** FROM HERE WE HAVE UTILITY FUNCTIONS DEFINED FOR ALL MECHANISMS THAT COULD BE ALSO CALLED FROM OUTSIDE DRBG ENVELOPES */
 
     /*  Returns the reseed counter of this instance.
@@ -100,6 +148,22 @@ pub trait DRBG_Mechanism_Functions: {
     /*  Function that retrieves the name of the DRBG implementation. */
     fn drbg_name() -> String;
 
-    /*  Function that retrieves the seed life of the DRBG implementation. */
-    fn seed_life() -> usize;
+    /*  Function that retrieves the seed life of this instance, i.e. its reseed interval: the number of generate
+        requests that can be served before a reseed is forced. It is SEED_LIFE of the mechanism unless set by
+        set_seed_life. */
+    fn seed_life(&self) -> usize;
+
+    /*  Sets the reseed interval of this instance, which must be in [1, max_seed_life()].
+        This function is called by the DRBG_Functions::new_with_reseed_interval envelope right after the instantiation.
+
+        Return values:
+            - 0: SUCCESS, the reseed interval has been set
+            - 1: ERROR, invalid reseed interval (the previous one is kept) */
+    fn set_seed_life(&mut self, seed_life: usize) -> usize;
+
+    /*  Function that retrieves the maximum reseed interval allowed by the SP for the DRBG implementation. */
+    fn max_seed_life() -> usize;
+
+    /*  Function that retrieves the identifier of the mechanism and of its primitive (e.g. "HMAC-DRBG SHA-256"). */
+    fn mech_id() -> String;
 }
\ No newline at end of file
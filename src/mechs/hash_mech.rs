@@ -1,22 +1,30 @@
 
-use super::gen_mech::DRBG_Mechanism_Functions;
-use std::{any::TypeId, ops::Add};
-use digest::{BlockInput, FixedOutput, Reset, Update, Digest};
+use super::gen_mech::{DRBG_Mechanism_Functions, WorkingState};
+use std::any::TypeId;
+use digest::{BlockInput, FixedOutput, Reset, Update};
 use generic_array::ArrayLength;
+use generic_array::typenum::Unsigned;
 use super::utility::*;
+use super::derivation;
 
-/*  The life of each generated seed of this DRBG. */
+/*  The default life of each generated seed of this DRBG (see DRBG_Mechanism_Functions::set_seed_life). */
 const SEED_LIFE: usize = 255;
 
+/*  The maximum reseed interval allowed for this DRBG by table 2 (or 3) of section 10 of the SP (2^48 requests). */
+const MAX_SEED_LIFE: u64 = 1 << 48;
+
+/*  The largest seedlen supported by this mechanism (Sha512), used to size the internal state buffers. */
+const MAX_SEEDLEN: usize = 111;
+
 /*  Implementation of the Hash-DRBG mechanism. This mechanism can be instantiated only using Sha256 or Sha512
     (see FIPS 140-3 IG section D.R). Since both hashing algorithms support a security strength of 256 bits
     (see NIST SP 800-57pt1r5), this mechanism offers a security strength of max 256 bits.
 
-    - v,c: internal state secret value that are used for the generation of pseudorandom bytes
+    - v,c: internal state secret value that are used for the generation of pseudorandom bytes (only the first seedlen
+      bytes of each buffer are used)
     - count: the reseed counter
     - reseed_interval: the maximum number of generate requests that can be served between reseedings
     - zeroized: boolean flag indicating whether the particular instance has been zeroized
-    - outlen: length of the output block produced by the hash function
     - seed_len: lengths of the internal state values that depends on the hash function that is used
     - hash_fun: handle to the hash function that is used.
 */
@@ -26,11 +34,11 @@ where
     D::BlockSize: ArrayLength<u8>,
     D::OutputSize: ArrayLength<u8>,
 {
-    v: Vec<u8>,
-    c: Vec<u8>,
+    v: [u8; MAX_SEEDLEN],
+    c: [u8; MAX_SEEDLEN],
     count: usize,
+    reseed_interval: usize,
     zeroized: bool,
-    outlen: usize,
     seedlen: usize,
     hash_fun: D,
     sec_str: usize,
@@ -43,58 +51,6 @@ where
     D::BlockSize: ArrayLength<u8>,
     D::OutputSize: ArrayLength<u8>,
 {
-    /*  This is a derivation function used by the mechanism to generate bytes from the underlying Hash function.
-        (NIST SP 800-90A, section 10.3.1)
-    
-        Parameters:
-            - result: the output vector for the generated bytes
-            - input: string to be hashed by the df
-            - num_bytes: the number of bytes to be produced by the df.
-    */
-    fn hash_df(&mut self, result: &mut Vec<u8>, input: Vec<u8>, num_bytes: usize){
-        // An empty output vector is required for the generated bytes.
-        if !result.is_empty() {
-            result.clear();
-        }
-
-        // No more that 255 * outlen bytes can be requested per SP 800-90Ar1 section 10.3.1
-        if num_bytes > self.outlen * 255 {
-            return;
-        }
-
-        // Initial setup (step 1-2-3)
-        let mut counter: u8 = 0x01;
-        let num_bits_return = num_bytes*8;
-        let string_bytes = &mut num_bits_return.to_be_bytes()[3..];
-        string_bytes[0] = counter;
-
-        // Generating hash_len byted at a time (step 4)
-        let mut i: usize = 0;
-        while i < num_bytes {
-            // Hashing the input data and appending the hash to the output vector (step 4.1)
-            // self.hash_fun.update(counter.to_string());
-            self.hash_fun.update(&string_bytes);
-            self.hash_fun.update(&input);
-            let hash = self.hash_fun.finalize_reset().to_vec();
-            let hash_len = hash.len();
-
-            for j in 0..hash_len {
-                // The requested number of bytes has beem reached (step 5)
-                if j+i >= num_bytes {
-                    return;
-                }
-
-                result.push(hash[j]);
-            }
-
-            i += hash_len;
-
-            // Updating the counter (step 4.2)
-            counter = counter.add(0x01);
-            string_bytes[0] = counter;
-        }
-    }
-
     /*  This function is used by the generation algorithm to generate pseudo-random bytes from the underlying hash function 
         (NIST SP 800-90A, section 10.1.1.4).
         
@@ -107,34 +63,51 @@ where
         if !result.is_empty() {
             result.clear();
         }
+        result.reserve(num_bytes);
         
         // Initial data (step 1-2-3)
-        let mut data = self.v.clone();
+        let mut data = self.v;
+        let data = &mut data[..self.seedlen];
 
         // Generate the requested bytes hash_len bytes at a time (step 4)
-        let mut i: usize = 0;
-        while i < num_bytes {
+        let mut remaining = num_bytes;
+        while remaining > 0 {
             // Hashing the data (step 4.1)
-            self.hash_fun.update(&data);
-            let w = self.hash_fun.finalize_reset().to_vec();
-            let hash_len = w.len();
-
-            // Appending the hash to the output vector (step 4.2)
-            for j in 0..hash_len {
-                // Required number of bytes has been reached (step 5)
-                if j+i >= num_bytes {
-                    return;
-                }
+            self.hash_fun.update(&*data);
+            let w = self.hash_fun.finalize_fixed_reset();
 
-                result.push(w[j]);
-            }
+            // Appending the hash to the output vector, the last one may be truncated (step 4.2, 5)
+            let take = remaining.min(w.len());
+            result.extend_from_slice(&w[..take]);
+            remaining -= take;
 
             // Incrementing the data for the hash (step 4.3)
-            modular_add(&mut data, 1);
+            increment_be(data);
+        }
 
-            // Updating the number of generated bytes
-            i += hash_len;
+        // Wiping the local copy of V
+        data.fill(0x00);
+    }
+
+    /*  Computes V and C from the given seed material parts (NIST SP 800-90A, sections 10.1.1.2 and 10.1.1.3).
+        The parts are passed to the derivation function as they are, without concatenating them.
+
+        Return values:
+            - true: SUCCESS
+            - false: ERROR, the derivation function has failed */
+    fn derive_v_c(&mut self, seed_material: &[&[u8]]) -> bool {
+        let seedlen = self.seedlen;
+
+        // Derive V using a temporary buffer, seed_material may contain the old V (step 1-2-3 of instantiate and reseed).
+        let mut new_v = [0u8; MAX_SEEDLEN];
+        if derivation::hash_df_into::<D>(seed_material, &mut new_v[..seedlen]).is_err() {
+            return false;
         }
+        self.v = new_v;
+        new_v.fill(0x00);
+
+        // Derive C from 0x00 || V (step 4).
+        derivation::hash_df_into::<D>(&[&[0x00], &self.v[..seedlen]], &mut self.c[..seedlen]).is_ok()
     }
 }
 
@@ -173,29 +146,20 @@ where
 
         // Init internal state.
         let mut this = Self{ 
-            v: Vec::<u8>::new(), 
-            c: Vec::<u8>::new(), 
+            v: [0u8; MAX_SEEDLEN], 
+            c: [0u8; MAX_SEEDLEN], 
             count: 1,
+            reseed_interval: SEED_LIFE,
             zeroized: false,
-            outlen: D::output_size(),
             seedlen, 
-            hash_fun: D::new(),
+            hash_fun: D::default(),
             sec_str: *req_str,
         };
 
-        // Derive V (step 1-2-3).
-        let mut res = Vec::<u8>::new();
-        let mut seed_material = entropy.clone().to_vec();
-        seed_material.append(&mut nonce.to_vec());
-        seed_material.append(&mut pers.to_vec());
-        this.hash_df(&mut res, seed_material, seedlen);
-        this.v.append(&mut res);
-
-        // Derive C (step 4).
-        let mut seed_material = this.v.clone();
-        seed_material.insert(0, 0x00);
-        this.hash_df(&mut res, seed_material, seedlen);
-        this.c.append(&mut res);
+        // Derive V and C from entropy || nonce || pers (step 1-2-3-4).
+        if !this.derive_v_c(&[entropy, nonce, pers]) {
+            return None;
+        }
 
         // Return instance (step 5-6)
         Some(this)
@@ -214,49 +178,35 @@ where
         }
         
         // Reached reseed interval (ERROR_FLAG=2, step 1)
-        if self.count >= SEED_LIFE{
+        if self.count > self.reseed_interval {
             return 2;
         }
 
+        let seedlen = self.seedlen;
+
         // Updating internal state using additional input (step 2)
         if let Some(add) = add {
-            let mut seed_material = self.v.clone();
-            seed_material.insert(0, 0x02);
-            seed_material.append(&mut add.to_vec());
-            self.hash_fun.update(seed_material);
-            let w = self.hash_fun.finalize_reset().to_vec();
+            self.hash_fun.update([0x02]);
+            self.hash_fun.update(&self.v[..seedlen]);
+            self.hash_fun.update(add);
+            let w = self.hash_fun.finalize_fixed_reset();
 
             // V = (V+w) mod 2^seedlen
-            let mut v_clone = self.v.clone();
-            modular_add_vec(&mut v_clone, w);
-            self.v.clear();
-            self.v.append(&mut v_clone);
+            modular_add_slice(&mut self.v[..seedlen], &w);
         }
 
         // Generating the requested bytes (step 3)
         self.hashgen(result, req_bytes);
 
         // Updating V (step 4-5)
-        let mut seed_material = self.v.clone();
-        seed_material.insert(0, 0x03);
-        self.hash_fun.update(seed_material);
-        let w = self.hash_fun.finalize_reset().to_vec();
+        self.hash_fun.update([0x03]);
+        self.hash_fun.update(&self.v[..seedlen]);
+        let w = self.hash_fun.finalize_fixed_reset();
 
         // V = (V+w+C+counter) mod 2^seedlen
-        let mut v_clone = self.v.clone();
-        modular_add_vec(&mut v_clone, w);
-        self.v.clear();
-        self.v.append(&mut v_clone);       
-
-        let mut v_clone = self.v.clone();
-        modular_add_vec(&mut v_clone, self.c.clone());
-        self.v.clear();
-        self.v.append(&mut v_clone);
-        
-        let mut v_clone = self.v.clone();
-        modular_add(&mut v_clone, self.count.try_into().unwrap());
-        self.v.clear();
-        self.v.append(&mut v_clone);
+        modular_add_slice(&mut self.v[..seedlen], &w);
+        modular_add_slice(&mut self.v[..seedlen], &self.c[..seedlen]);
+        modular_add_slice(&mut self.v[..seedlen], &self.count.to_be_bytes());
 
         // Updating the reseed counter (step 6)
         self.count += 1;
@@ -276,29 +226,14 @@ where
             return 2;
         }
 
-        // Derive V (step 1-2-3).
-        let mut res = Vec::<u8>::new();
-        let mut seed_material = self.v.clone();
-        seed_material.insert(0, 0x01);
-        seed_material.append(&mut entropy.to_vec());
-        match add {
-            None => {}
-            Some(add_in) => {
-                seed_material.append(&mut add_in.to_vec());
-            }
+        // Derive V and C from 0x01 || V || entropy || add (step 1-2-3-4).
+        let seedlen = self.seedlen;
+        let mut old_v = self.v;
+        let derived = self.derive_v_c(&[&[0x01], &old_v[..seedlen], entropy, add.unwrap_or(&[])]);
+        old_v.fill(0x00);
+        if !derived {
+            return 3;
         }
-        self.hash_df(&mut res, seed_material, self.seedlen);
-        self.v.clear();
-        self.v.append(&mut res);
-
-        // Derive C (step 4).
-        res.clear();
-        let mut seed_material = Vec::<u8>::new();
-        seed_material.push(0x00);
-        seed_material.append(&mut self.v.clone());
-        self.hash_df(&mut res, seed_material, self.seedlen);
-        self.c.clear();
-        self.c.append(&mut res);
 
         // Re-init reseed counter (step 5).
         self.count = 1;
@@ -313,13 +248,8 @@ where
         }
         
         // Zeroizing internal state values
-        for i in 0..self.v.as_slice().len() {
-            self.v[i] = 0x0;
-        }
-
-        for i in 0..self.c.as_slice().len() {
-            self.c[i] = 0x0;
-        }
+        self.v.fill(0x0);
+        self.c.fill(0x0);
 
         self.count = 0;
         self.zeroized = true;
@@ -329,12 +259,65 @@ where
         0
     }
 
+    fn export_state(&self) -> Option<WorkingState> {
+        if self.zeroized {
+            return None;
+        }
+
+        Some(WorkingState{
+            v: self.v[..self.seedlen].to_vec(),
+            c: self.c[..self.seedlen].to_vec(),
+            key: Vec::new(),
+            count: self.count,
+            reseed_interval: self.reseed_interval,
+        })
+    }
+
+    fn import_state(state: &WorkingState) -> Option<Self> {
+        // Runtime check on the use of any unallowed hash function and according seedlen.
+        let this_id = TypeId::of::<D>();
+        let seedlen;
+        if this_id == TypeId::of::<sha2::Sha256>() {
+            seedlen = 55;
+        }
+        else if this_id == TypeId::of::<sha2::Sha512>() {
+            seedlen = 111;
+        }
+        else {
+            return None;
+        }
+
+        // Snapshots that do not carry a reseed interval use the default one.
+        let reseed_interval = if state.reseed_interval == 0 { SEED_LIFE } else { state.reseed_interval };
+
+        // V and C must be seedlen bytes long and the reseed counter and the reseed interval must be valid.
+        if state.v.len() != seedlen || state.c.len() != seedlen || !state.key.is_empty() ||
+            reseed_interval > Self::max_seed_life() || state.count == 0 || state.count - 1 > reseed_interval {
+            return None;
+        }
+
+        let mut this = Self{
+            v: [0u8; MAX_SEEDLEN],
+            c: [0u8; MAX_SEEDLEN],
+            count: state.count,
+            reseed_interval,
+            zeroized: false,
+            seedlen,
+            hash_fun: D::default(),
+            sec_str: 32,
+        };
+        this.v[..seedlen].copy_from_slice(&state.v);
+        this.c[..seedlen].copy_from_slice(&state.c);
+
+        Some(this)
+    }
+
     fn count(&self) -> usize {
         self.count
     }
 
     fn reseed_needed(&self) -> bool{
-        self.count >= SEED_LIFE
+        self.count > self.reseed_interval
     }
 
     fn _is_zeroized(&self) -> bool{
@@ -345,7 +328,24 @@ where
         return "Hash-DRBG".to_string();
     }
 
-    fn seed_life() -> usize {
-        return SEED_LIFE;
+    fn seed_life(&self) -> usize {
+        self.reseed_interval
+    }
+
+    fn set_seed_life(&mut self, seed_life: usize) -> usize {
+        if seed_life == 0 || seed_life > Self::max_seed_life() {
+            return 1;
+        }
+
+        self.reseed_interval = seed_life;
+        0
+    }
+
+    fn max_seed_life() -> usize {
+        usize::try_from(MAX_SEED_LIFE).unwrap_or(usize::MAX)
+    }
+
+    fn mech_id() -> String {
+        format!("{} SHA-{}", Self::drbg_name(), D::OutputSize::to_usize() * 8)
     }
 }
\ No newline at end of file
@@ -1,12 +1,18 @@
-use super::gen_mech::DRBG_Mechanism_Functions;
+use super::gen_mech::{DRBG_Mechanism_Functions, WorkingState};
 use digest::{BlockInput, FixedOutput, Reset, Update};
 use generic_array::{ArrayLength, GenericArray};
+use generic_array::typenum::Unsigned;
 use hmac::{Hmac, Mac, NewMac};
 use std::any::TypeId;
+use std::mem::ManuallyDrop;
+use std::sync::atomic::{compiler_fence, Ordering};
 
-/*  The life of each generated seed of this DRBG. */
+/*  The default life of each generated seed of this DRBG (see DRBG_Mechanism_Functions::set_seed_life). */
 const SEED_LIFE: usize = 1000;
 
+/*  The maximum reseed interval allowed for this DRBG by table 2 (or 3) of section 10 of the SP (2^48 requests). */
+const MAX_SEED_LIFE: u64 = 1 << 48;
+
 /*  Implementation of the HMAC-DRBG mechanism. This mechanism can be instantiated only using Sha256 or Sha512
     (see FIPS 140-3 IG section D.R). Since both hashing algorithms support a security strength of 256 bits
     (see NIST SP 800-57pt1r5), this mechanism offers a security strength of max 256 bits.
@@ -15,18 +21,81 @@ const SEED_LIFE: usize = 1000;
     - count: the reseed counter
     - reseed_interval: the maximum number of generate requests that can be served between reseedings
     - zeroized: boolean flag indicating whether the particular instance has been zeroized 
-    - sec_str: the security strength supported by this instance. */
+    - sec_str: the security strength supported by this instance
+    - mac: the hmac primitive keyed with k (inner and outer pad contexts are computed once per key change), wiped on drop */
 pub struct HmacDrbgMech<D: 'static>
 where
-    D: Update + BlockInput + FixedOutput + Default,
+    D: Update + BlockInput + FixedOutput + Reset + Clone + Default,
     D::BlockSize: ArrayLength<u8>,
     D::OutputSize: ArrayLength<u8>,
 {
     k: GenericArray<u8, D::OutputSize>,
     v: GenericArray<u8, D::OutputSize>,
     count: usize,
+    reseed_interval: usize,
     zeroized: bool,
     sec_str: usize,
+    mac: KeyedHmac<D>,
+}
+
+/*  An hmac primitive whose memory is overwritten with zeros when it is dropped. Its state (the key XORed with the inner
+    pad and the digest contexts keyed with it) is equivalent to K, so both the cached primitive and its clones are wiped
+    like K and V. The temporary copies made inside the 'hmac' crate while finalizing cannot be reached and are not wiped. */
+struct KeyedHmac<D>(ManuallyDrop<Hmac<D>>)
+where
+    D: Update + BlockInput + FixedOutput + Reset + Clone + Default,
+    D::BlockSize: ArrayLength<u8>,
+    D::OutputSize: ArrayLength<u8>;
+
+impl<D> KeyedHmac<D>
+where
+    D: Update + BlockInput + FixedOutput + Reset + Clone + Default,
+    D::BlockSize: ArrayLength<u8>,
+    D::OutputSize: ArrayLength<u8>,
+{
+    /*  Returns the primitive keyed with k. */
+    fn new(k: &[u8]) -> Self {
+        Self(ManuallyDrop::new(Hmac::new_varkey(k).expect("Smaller and larger key size are handled by default")))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    /*  Returns the hmac of the data passed to update, the primitive is then wiped. */
+    fn finalize(mut self) -> GenericArray<u8, D::OutputSize> {
+        self.0.finalize_reset().into_bytes()
+    }
+}
+
+impl<D> Clone for KeyedHmac<D>
+where
+    D: Update + BlockInput + FixedOutput + Reset + Clone + Default,
+    D::BlockSize: ArrayLength<u8>,
+    D::OutputSize: ArrayLength<u8>,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<D> Drop for KeyedHmac<D>
+where
+    D: Update + BlockInput + FixedOutput + Reset + Clone + Default,
+    D::BlockSize: ArrayLength<u8>,
+    D::OutputSize: ArrayLength<u8>,
+{
+    fn drop(&mut self) {
+        // The primitive is dropped first, its memory is then overwritten (volatile writes are not optimized away).
+        unsafe {
+            ManuallyDrop::drop(&mut self.0);
+            let ptr = &mut *self.0 as *mut Hmac<D> as *mut u8;
+            for i in 0..std::mem::size_of::<Hmac<D>>() {
+                std::ptr::write_volatile(ptr.add(i), 0x00);
+            }
+        }
+        compiler_fence(Ordering::SeqCst);
+    }
 }
 
 /*  Implementing functions that are specific of the HMAC-DRBG mechanism. */
@@ -52,12 +121,12 @@ where
                 kmac.update(seed);
             }
         }
-        self.k = kmac.finalize().into_bytes();
+        self.set_key(kmac.finalize());
 
         // Updating V (step 2).
         let mut vmac = self.hmac();
         vmac.update(&self.v);
-        self.v = vmac.finalize().into_bytes();
+        self.v = vmac.finalize();
 
         // If no additional seeds are given, we have done everything needed (step 3).
         if seeds.is_none() {
@@ -74,20 +143,30 @@ where
         for seed in seeds {
             kmac.update(seed);
         }
-        self.k = kmac.finalize().into_bytes();
+        self.set_key(kmac.finalize());
         
         // Updating V (step 5).
         let mut vmac = self.hmac();
         vmac.update(&self.v);
-        self.v = vmac.finalize().into_bytes();
+        self.v = vmac.finalize();
     }
 
-    /*  Retrieves and instance of the hmac primitive that uses self.k as a key.
+    /*  Retrieves and instance of the hmac primitive that uses self.k as a key. The keyed state is cloned from the cached
+        one, so that the padded key is not hashed again at every invocation.
     
         Return values:
             - a pointer to an hmac primitive */
-    fn hmac(&self) -> Hmac<D> {
-        Hmac::new_varkey(&self.k).expect("Smaller and larger key size are handled by default")
+    fn hmac(&self) -> KeyedHmac<D> {
+        self.mac.clone()
+    }
+
+    /*  Sets a new value for K and refreshes the cached hmac primitive accordingly.
+
+        Parameters:
+            - k: the new key */
+    fn set_key(&mut self, k: GenericArray<u8, D::OutputSize>) {
+        self.k = k;
+        self.mac = KeyedHmac::new(&self.k);
     }
 }
 
@@ -129,7 +208,8 @@ where
             v[i] = 0x01;
         }
 
-        let mut this = Self { k, v, count: 0 , zeroized: false, sec_str: *req_str};
+        let mac = KeyedHmac::new(&k);
+        let mut this = Self { k, v, count: 0, reseed_interval: SEED_LIFE, zeroized: false, sec_str: *req_str, mac};
 
         // Updating the internal state using the passed parameters (step 1,4).
         this.update(Some(&[entropy, nonce, pers]));
@@ -153,7 +233,7 @@ where
         }
         
         // Reached reseed interval (ERROR_FLAG=2, step 1)
-        if self.count >= SEED_LIFE{
+        if self.count > self.reseed_interval {
             return 2;
         }
 
@@ -167,15 +247,11 @@ where
         while i < req_bytes {
             let mut vmac = self.hmac();
             vmac.update(&self.v);
-            self.v = vmac.finalize().into_bytes();
+            self.v = vmac.finalize();
 
-            for j in 0..self.v.len() {
-                if i+j >= req_bytes{
-                    break;
-                }
-                result.push(self.v[j]);
-            }
-            i += self.v.len();
+            let take = self.v.len().min(req_bytes - i);
+            result.extend_from_slice(&self.v[..take]);
+            i += take;
         }
         
         // Updating the internal state one final time (step 6)
@@ -220,9 +296,8 @@ where
         }
         
         // Zeroizing internal state values
-        for i in 0..self.k.as_slice().len() {
-            self.k[i] = 0x0;
-        }
+        // Zeroizing K also replaces the cached keyed hmac primitive, which is wiped on drop
+        self.set_key(GenericArray::<u8, D::OutputSize>::default());
 
         for i in 0..self.v.as_slice().len() {
             self.v[i] = 0x0;
@@ -234,12 +309,57 @@ where
         return 0;
     }
 
+    fn export_state(&self) -> Option<WorkingState> {
+        if self.zeroized {
+            return None;
+        }
+
+        Some(WorkingState{
+            v: self.v.to_vec(),
+            c: Vec::new(),
+            key: self.k.to_vec(),
+            count: self.count,
+            reseed_interval: self.reseed_interval,
+        })
+    }
+
+    fn import_state(state: &WorkingState) -> Option<Self> {
+        // Runtime check on the use of any unallowed hash function.
+        let this_id = TypeId::of::<D>();
+        if this_id != TypeId::of::<sha2::Sha256>() && this_id != TypeId::of::<sha2::Sha512>() {
+            return None;
+        }
+
+        // Snapshots that do not carry a reseed interval use the default one.
+        let reseed_interval = if state.reseed_interval == 0 { SEED_LIFE } else { state.reseed_interval };
+
+        // K and V must be outlen bytes long and the reseed counter and the reseed interval must be valid.
+        let outlen = D::OutputSize::to_usize();
+        if state.v.len() != outlen || state.key.len() != outlen || !state.c.is_empty() ||
+            reseed_interval > Self::max_seed_life() || state.count == 0 || state.count - 1 > reseed_interval {
+            return None;
+        }
+
+        let k = GenericArray::<u8, D::OutputSize>::clone_from_slice(&state.key);
+        let mac = KeyedHmac::new(&k);
+
+        Some(Self{
+            k,
+            v: GenericArray::<u8, D::OutputSize>::clone_from_slice(&state.v),
+            count: state.count,
+            reseed_interval,
+            zeroized: false,
+            sec_str: 32,
+            mac,
+        })
+    }
+
     fn count(&self) -> usize {
         self.count
     }
 
     fn reseed_needed(&self) -> bool{
-        self.count >= SEED_LIFE
+        self.count > self.reseed_interval
     }
 
     fn _is_zeroized(&self) -> bool{
@@ -250,7 +370,24 @@ where
         return "HMAC-DRBG".to_string();
     }
 
-    fn seed_life() -> usize {
-        return SEED_LIFE;
+    fn seed_life(&self) -> usize {
+        self.reseed_interval
+    }
+
+    fn set_seed_life(&mut self, seed_life: usize) -> usize {
+        if seed_life == 0 || seed_life > Self::max_seed_life() {
+            return 1;
+        }
+
+        self.reseed_interval = seed_life;
+        0
+    }
+
+    fn max_seed_life() -> usize {
+        usize::try_from(MAX_SEED_LIFE).unwrap_or(usize::MAX)
+    }
+
+    fn mech_id() -> String {
+        format!("{} SHA-{}", Self::drbg_name(), D::OutputSize::to_usize() * 8)
     }
 }
\ No newline at end of file
@@ -3,4 +3,5 @@ pub mod hmac_mech;
 pub mod hash_mech;
 pub mod ctr_mech;
 pub mod ctr_mech_with_df;
-pub mod utility;
\ No newline at end of file
+pub mod utility;
+pub mod derivation;
\ No newline at end of file
@@ -1,67 +1,40 @@
 /*  This module defines general purpose functions that are used by some of the DRBG mechanisms that are defined
     in this crate. To avoid code reuse, we separated this module from the actual code of the aformentioned mechanisms.
 */
+use aes::cipher::{BlockEncrypt, generic_array::GenericArray};
 
-/*  Performs a modular addition between a vector of bytes and a single byte. */
-pub fn modular_add(num: &mut Vec<u8>, rhs: u8) {
-    if num.is_empty() {
-        return;
-    }
-
-    let len = num.len();
-    let mut j = len-1;
-    let (mut res, mut carry) = num[j].overflowing_add(rhs);
-    num[j] = res;
-
-    if j>=1 {
-        j -= 1;
-        while carry && j>0 {
-            (res, carry) = num[j].overflowing_add(1);
-            num[j] = res;
-            j -= 1;
-        }
+/*  Number of counter blocks that are encrypted at once by ctr_generate. Batching the blocks allows the block cipher
+    implementation to pipeline them (e.g. with AES-NI, which is detected at runtime by the 'aes' crate). */
+const CTR_BATCH: usize = 16;
 
-        if carry {
-            res= num[0].wrapping_add(1);
-            num[0] = res;
-        }   
-    }     
+/*  Performs an in-place modular addition between a big-endian number represented as a slice of bytes and a single
+    byte (mod 2^(8*len)). */
+pub fn modular_add(num: &mut [u8], rhs: u8) {
+    modular_add_slice(num, &[rhs]);
 }
 
-/*  This function performs a modular addition between two numbers represented as byte vectors.
-    The reference module is of num1. We expect num1 to be longer or equal to num2. */
-pub fn modular_add_vec(num1: &mut Vec<u8>, num2: Vec<u8>) {
-    if num1.is_empty() || num2.is_empty() {
-        return;
-    }
-
+/*  Performs an in-place modular addition between two big-endian numbers represented as slices of bytes.
+    The reference module is that of num1 (mod 2^(8*len1)). We expect num1 to be longer or equal to num2, otherwise num1
+    is left untouched. */
+pub fn modular_add_slice(num1: &mut [u8], num2: &[u8]) {
     let len1 = num1.len();
     let len2 = num2.len();
-    
+
     if len2 > len1 {
         return;
     }
 
-    let mut i = len2;
-    let mut j = len1;
-    #[allow(unused_comparisons)]
-    while i > 0 {
-        let (res, carry) = num1[j-1].overflowing_add(num2[i-1]);
-
-        num1[j-1] = res;
-        if carry && i > 0{
-            let mut num1_copy = num1[..j-1].to_vec();
-            let mut num1_rem = num1[j-1..].to_vec();
-
-            modular_add(&mut num1_copy, 1);
-
-            num1.clear();
-            num1.append(&mut num1_copy);
-            num1.append(&mut num1_rem);
-        }
+    // Adding the bytes of num2 to the right-most bytes of num1.
+    let mut carry = false;
+    for (byte, rhs) in num1[len1 - len2..].iter_mut().rev().zip(num2.iter().rev()) {
+        let sum = *byte as u16 + *rhs as u16 + carry as u16;
+        *byte = sum as u8;
+        carry = sum > 0xff;
+    }
 
-        i -= 1;
-        j -= 1;
+    // Propagating the carry through the remaining bytes of num1.
+    if carry {
+        increment_be(&mut num1[..len1 - len2]);
     }
 }
 
@@ -74,4 +47,71 @@ pub fn xor_vecs(vec1: &mut Vec<u8>, vec2: &Vec<u8>) {
     for i in 0..vec1.len() {
         vec1[i] = vec1[i] ^ vec2[i];
     }
-}
\ No newline at end of file
+}
+
+/*  Increments by one a big-endian number represented as a slice of bytes (mod 2^(8*len)). */
+#[inline]
+pub fn increment_be(num: &mut [u8]) {
+    for byte in num.iter_mut().rev() {
+        let (res, carry) = byte.overflowing_add(1);
+        *byte = res;
+
+        if !carry {
+            return;
+        }
+    }
+}
+
+/*  Produces num_bytes bytes by encrypting successive values of the counter block V, as done by the update and generate
+    functions of the CTR-DRBG (NIST SP 800-90A, sections 10.2.1.2 and 10.2.1.5). Only the right-most ctr_len bytes of V
+    are incremented. The generated bytes are appended to out and V is left at the last value that has been encrypted.
+
+    Parameters:
+        - cipher: the block cipher already keyed with the current key
+        - v: the counter block
+        - ctr_len: the length of the counter in bytes
+        - out: the output vector
+        - num_bytes: the number of bytes to be produced */
+pub fn ctr_generate<D: BlockEncrypt>(cipher: &D, v: &mut GenericArray<u8, D::BlockSize>, ctr_len: usize, out: &mut Vec<u8>, num_bytes: usize) {
+    let blocklen = v.len();
+    let ctr_start = blocklen - ctr_len.min(blocklen);
+    let mut blocks: [GenericArray<u8, D::BlockSize>; CTR_BATCH] = Default::default();
+
+    out.reserve(num_bytes);
+    let mut remaining = num_bytes;
+    while remaining > 0 {
+        // Preparing the next batch of counter blocks (V = V + 1).
+        let n = remaining.div_ceil(blocklen).min(CTR_BATCH);
+        if blocklen - ctr_start == 4 {
+            // Fast path for the 32-bit counter used by this crate, the increment is done on a native integer.
+            let mut ctr = u32::from_be_bytes([v[ctr_start], v[ctr_start + 1], v[ctr_start + 2], v[ctr_start + 3]]);
+            for block in blocks[..n].iter_mut() {
+                ctr = ctr.wrapping_add(1);
+                block[..ctr_start].copy_from_slice(&v[..ctr_start]);
+                block[ctr_start..].copy_from_slice(&ctr.to_be_bytes());
+            }
+            v[ctr_start..].copy_from_slice(&ctr.to_be_bytes());
+        }
+        else {
+            for block in blocks[..n].iter_mut() {
+                increment_be(&mut v[ctr_start..]);
+                block.copy_from_slice(v);
+            }
+        }
+
+        // Encrypting the whole batch at once.
+        cipher.encrypt_blocks(&mut blocks[..n]);
+
+        // Appending the encrypted blocks, the last one may be truncated.
+        for block in blocks[..n].iter() {
+            let take = remaining.min(blocklen);
+            out.extend_from_slice(&block[..take]);
+            remaining -= take;
+        }
+    }
+
+    // Wiping the batch buffer, it may contain key material when called by update.
+    for block in blocks.iter_mut() {
+        block.fill(0x00);
+    }
+}
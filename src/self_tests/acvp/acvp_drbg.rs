@@ -0,0 +1,276 @@
+use crate::mechs::{gen_mech::DRBG_Mechanism_Functions, hash_mech::HashDrbgMech, hmac_mech::HmacDrbgMech,
+                   ctr_mech::CtrDrbgMech, ctr_mech_with_df::CtrDrbgMech_DF};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::fs;
+use std::path::Path;
+use sha2::*;
+use aes::*;
+
+/*  Offline harness for the ACVP DRBG test vectors (algorithms "hashDRBG", "hmacDRBG" and "ctrDRBG", revision 1.0).
+
+    A vector set (prompt) file is read, each test case is run against the matching mechanism of this crate and the
+    response file to be submitted is produced. Expected results files can be used to regression-test a response.
+    Every test case is processed as mandated by the ACVP DRBG specification:
+
+        - the DRBG is instantiated with entropyInput, nonce and persoString
+        - the otherInput entries are processed in order:
+            - reSeed: the DRBG is reseeded with entropyInput and additionalInput
+            - generate: if predResistance is set, the DRBG is reseeded with entropyInput and additionalInput and then
+              asked for returnedBitsLen bits, otherwise returnedBitsLen bits are requested using additionalInput
+        - returnedBits is the output of the last generate call
+
+    Empty strings are treated as Null inputs. Both the wrapped format used by the ACVP server
+    ([{"acvVersion": ...}, {vector set}]) and a bare vector set object are accepted.
+
+    Error flags returned by the functions of this module:
+        1: a file could not be read or written
+        2: the JSON content is malformed or it is not a DRBG vector set
+        3: the algorithm or the mode of a test group is not supported by this crate
+        4: a test case contains an invalid hex string
+        5: a DRBG function failed unexpectedly while running a test case */
+
+/*  The ACVP protocol version written in the response files. */
+pub const ACV_VERSION: &str = "1.0";
+
+/*  An element of the otherInput sequence of a test case. */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AcvpOtherInput {
+    pub intended_use: String,
+    #[serde(default)]
+    pub additional_input: String,
+    #[serde(default)]
+    pub entropy_input: String,
+}
+
+/*  A test case of a vector set. */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AcvpTestCase {
+    pub tc_id: usize,
+    pub entropy_input: String,
+    #[serde(default)]
+    pub nonce: String,
+    #[serde(default)]
+    pub perso_string: String,
+    #[serde(default)]
+    pub other_input: Vec<AcvpOtherInput>,
+}
+
+/*  A test group of a vector set, the lengths are expressed in bits. */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AcvpTestGroup {
+    pub tg_id: usize,
+    #[serde(default)]
+    pub test_type: String,
+    #[serde(default)]
+    pub der_func: bool,
+    #[serde(default)]
+    pub re_seed: bool,
+    pub pred_resistance: bool,
+    pub entropy_input_len: usize,
+    pub nonce_len: usize,
+    pub perso_string_len: usize,
+    pub additional_input_len: usize,
+    pub returned_bits_len: usize,
+    pub mode: String,
+    pub tests: Vec<AcvpTestCase>,
+}
+
+/*  A DRBG vector set as sent by the ACVP server. */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AcvpVectorSet {
+    pub vs_id: usize,
+    pub algorithm: String,
+    pub revision: String,
+    #[serde(default)]
+    pub is_sample: bool,
+    pub test_groups: Vec<AcvpTestGroup>,
+}
+
+/*  The result of a test case. */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AcvpTestResult {
+    pub tc_id: usize,
+    pub returned_bits: String,
+}
+
+/*  The results of a test group. */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AcvpGroupResult {
+    pub tg_id: usize,
+    pub tests: Vec<AcvpTestResult>,
+}
+
+/*  A response to a vector set, this is also the format of the expected results files. */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AcvpResponse {
+    pub vs_id: usize,
+    pub algorithm: String,
+    pub revision: String,
+    pub test_groups: Vec<AcvpGroupResult>,
+}
+
+/*  The header that precedes the vector set in the files exchanged with the ACVP server. */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct AcvpVersion {
+    acv_version: String,
+}
+
+/*  Parses a vector set (prompt) file.
+
+    Return values:
+        - Ok(vector_set): SUCCESS
+        - Err(2): ERROR, the content is malformed */
+pub fn parse_vector_set(json: &str) -> Result<AcvpVectorSet, usize> {
+    parse_acvp(json)
+}
+
+/*  Parses a response or expected results file.
+
+    Return values:
+        - Ok(response): SUCCESS
+        - Err(2): ERROR, the content is malformed */
+pub fn parse_response(json: &str) -> Result<AcvpResponse, usize> {
+    parse_acvp(json)
+}
+
+/*  Serializes a response in the format expected by the ACVP server ([{"acvVersion": ...}, {response}]). */
+pub fn response_to_json(response: &AcvpResponse) -> String {
+    let version = AcvpVersion { acv_version: ACV_VERSION.to_string() };
+
+    serde_json::to_string_pretty(&(version, response)).unwrap_or_default()
+}
+
+/*  Runs every test case of a vector set and builds the related response.
+
+    Return values:
+        - Ok(response): SUCCESS
+        - Err(3), Err(4), Err(5): ERROR, see the error flags of this module */
+pub fn run_vector_set(vector_set: &AcvpVectorSet) -> Result<AcvpResponse, usize> {
+    let mut test_groups = Vec::<AcvpGroupResult>::new();
+
+    for group in vector_set.test_groups.iter() {
+        let mut tests = Vec::<AcvpTestResult>::new();
+        for test in group.tests.iter() {
+            let returned_bits = run_test(&vector_set.algorithm, group, test)?;
+            tests.push(AcvpTestResult { tc_id: test.tc_id, returned_bits: hex::encode_upper(returned_bits) });
+        }
+        test_groups.push(AcvpGroupResult { tg_id: group.tg_id, tests });
+    }
+
+    Ok(AcvpResponse {
+        vs_id: vector_set.vs_id,
+        algorithm: vector_set.algorithm.clone(),
+        revision: vector_set.revision.clone(),
+        test_groups,
+    })
+}
+
+/*  Reads a vector set from prompt_path, runs it and writes the response to response_path.
+
+    Return values:
+        - Ok(response): SUCCESS, the response that has been written
+        - Err(n): ERROR, see the error flags of this module */
+pub fn run_files(prompt_path: &Path, response_path: &Path) -> Result<AcvpResponse, usize> {
+    let prompt = fs::read_to_string(prompt_path).map_err(|_| 1usize)?;
+    let response = run_vector_set(&parse_vector_set(&prompt)?)?;
+    fs::write(response_path, response_to_json(&response)).map_err(|_| 1usize)?;
+
+    Ok(response)
+}
+
+/*  Compares a response against the expected results of its vector set.
+
+    Return values:
+        - the tcIds of the test cases whose returnedBits are wrong or missing (empty if the response is correct) */
+pub fn compare_response(response: &AcvpResponse, expected: &AcvpResponse) -> Vec<usize> {
+    let mut mismatches = Vec::<usize>::new();
+
+    for exp_group in expected.test_groups.iter() {
+        let group = response.test_groups.iter().find(|group| group.tg_id == exp_group.tg_id);
+        for exp_test in exp_group.tests.iter() {
+            let test = group.and_then(|group| group.tests.iter().find(|test| test.tc_id == exp_test.tc_id));
+            match test {
+                Some(test) if test.returned_bits.eq_ignore_ascii_case(&exp_test.returned_bits) => {}
+                _ => mismatches.push(exp_test.tc_id),
+            }
+        }
+    }
+
+    mismatches
+}
+
+/*  Parses an ACVP file, either wrapped with the version header or as a bare object. */
+fn parse_acvp<T: DeserializeOwned>(json: &str) -> Result<T, usize> {
+    let value: serde_json::Value = serde_json::from_str(json).map_err(|_| 2usize)?;
+
+    let body = match value {
+        serde_json::Value::Array(mut elements) => {
+            let idx = elements.iter().position(|element| element.get("vsId").is_some()).ok_or(2usize)?;
+            elements.swap_remove(idx)
+        }
+        body => body,
+    };
+
+    serde_json::from_value(body).map_err(|_| 2usize)
+}
+
+/*  Selects the mechanism that matches the algorithm and the test group and runs a test case. */
+fn run_test(algorithm: &str, group: &AcvpTestGroup, test: &AcvpTestCase) -> Result<Vec<u8>, usize> {
+    match (algorithm, group.mode.as_str(), group.der_func) {
+        ("hashDRBG", "SHA2-256", _) => run_test_with::<HashDrbgMech<Sha256>>(group, test, 32),
+        ("hashDRBG", "SHA2-512", _) => run_test_with::<HashDrbgMech<Sha512>>(group, test, 32),
+        ("hmacDRBG", "SHA2-256", _) => run_test_with::<HmacDrbgMech<Sha256>>(group, test, 32),
+        ("hmacDRBG", "SHA2-512", _) => run_test_with::<HmacDrbgMech<Sha512>>(group, test, 32),
+        ("ctrDRBG", "AES-128", false) => run_test_with::<CtrDrbgMech<Aes128>>(group, test, 16),
+        ("ctrDRBG", "AES-192", false) => run_test_with::<CtrDrbgMech<Aes192>>(group, test, 24),
+        ("ctrDRBG", "AES-256", false) => run_test_with::<CtrDrbgMech<Aes256>>(group, test, 32),
+        ("ctrDRBG", "AES-128", true) => run_test_with::<CtrDrbgMech_DF<Aes128>>(group, test, 16),
+        ("ctrDRBG", "AES-192", true) => run_test_with::<CtrDrbgMech_DF<Aes192>>(group, test, 24),
+        ("ctrDRBG", "AES-256", true) => run_test_with::<CtrDrbgMech_DF<Aes256>>(group, test, 32),
+        _ => Err(3),
+    }
+}
+
+/*  Runs a test case against the mechanism T and returns the output of the last generate call. */
+fn run_test_with<T: DRBG_Mechanism_Functions>(group: &AcvpTestGroup, test: &AcvpTestCase, mut strength: usize) -> Result<Vec<u8>, usize> {
+    let decode = |value: &str| hex::decode(value).map_err(|_| 4usize);
+    let req_bytes = group.returned_bits_len / 8;
+
+    // Instantiating the DRBG.
+    let mut drbg = T::new(&decode(&test.entropy_input)?, &decode(&test.nonce)?, &decode(&test.perso_string)?,
+                            &mut strength).ok_or(5usize)?;
+
+    // Processing the otherInput sequence.
+    let mut result = Vec::<u8>::new();
+    for other in test.other_input.iter() {
+        let entropy = decode(&other.entropy_input)?;
+        let add = decode(&other.additional_input)?;
+        let add = if add.is_empty() { None } else { Some(add.as_slice()) };
+
+        let res = match (other.intended_use.as_str(), group.pred_resistance) {
+            ("reSeed", _) => drbg.reseed(&entropy, add),
+            ("generate", true) => {
+                match drbg.reseed(&entropy, add) {
+                    0 => drbg.generate(&mut result, req_bytes, None),
+                    err => err,
+                }
+            }
+            ("generate", false) => drbg.generate(&mut result, req_bytes, add),
+            _ => return Err(5),
+        };
+
+        if res != 0 {
+            return Err(5);
+        }
+    }
+
+    Ok(result)
+}
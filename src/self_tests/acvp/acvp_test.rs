@@ -0,0 +1,173 @@
+use crate::self_tests::acvp::acvp_drbg::*;
+use crate::self_tests::formats::*;
+use std::fs;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "ACVP-TESTS::acvp_test";
+
+/*  The sample vector sets bundled with this crate: (algorithm, prompt file, expected results file). */
+const VECTOR_SETS: [(&str, &str, &str); 3] = [
+    ("hashDRBG", include_str!("vectors/hashDRBG_prompt.json"), include_str!("vectors/hashDRBG_expected.json")),
+    ("hmacDRBG", include_str!("vectors/hmacDRBG_prompt.json"), include_str!("vectors/hmacDRBG_expected.json")),
+    ("ctrDRBG", include_str!("vectors/ctrDRBG_prompt.json"), include_str!("vectors/ctrDRBG_expected.json")),
+];
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    malformed_files() +
+        unsupported_mode() +
+        invalid_hex() +
+        wrong_returned_bits() +
+        response_format() +
+        files_round_trip() +
+        sample_vector_sets()
+}
+
+/*  Verifying that files that do not contain a DRBG vector set are refused. */
+fn malformed_files() -> usize {
+    let res = (parse_vector_set("not json").map(|_| ()),
+                parse_vector_set("[{\"acvVersion\": \"1.0\"}]").map(|_| ()),
+                parse_vector_set("{\"vsId\": 1, \"algorithm\": \"hashDRBG\"}").map(|_| ()),
+                parse_response("{}").map(|_| ()));
+
+    check_res(res, (Err(2), Err(2), Err(2), Err(2)),
+        "malformed_files".to_string(),
+        AL_NAME.to_string(),
+        "a malformed ACVP file was accepted.".to_string(),
+        "malformed ACVP files were refused as expected.".to_string())
+}
+
+/*  Verifying that test groups of modes that are not supported are refused. */
+fn unsupported_mode() -> usize {
+    let mut vector_set = match load_sample(0) {
+        None => return 1,
+        Some(vector_set) => vector_set,
+    };
+    vector_set.test_groups[0].mode = "SHA-1".to_string();
+    let mut wrong_algorithm = vector_set.clone();
+    wrong_algorithm.algorithm = "drbg".to_string();
+
+    let res = (run_vector_set(&vector_set).map(|_| ()), run_vector_set(&wrong_algorithm).map(|_| ()));
+
+    check_res(res, (Err(3), Err(3)),
+        "unsupported_mode".to_string(),
+        AL_NAME.to_string(),
+        "a vector set with an unsupported mode or algorithm was run.".to_string(),
+        "vector sets with an unsupported mode or algorithm were refused as expected.".to_string())
+}
+
+/*  Verifying that test cases with invalid hex strings are refused. */
+fn invalid_hex() -> usize {
+    let mut vector_set = match load_sample(1) {
+        None => return 1,
+        Some(vector_set) => vector_set,
+    };
+    vector_set.test_groups[0].tests[0].nonce = "XYZ".to_string();
+
+    check_res(run_vector_set(&vector_set).map(|_| ()), Err(4),
+        "invalid_hex".to_string(),
+        AL_NAME.to_string(),
+        "a test case with an invalid hex string was run.".to_string(),
+        "a test case with an invalid hex string was refused as expected.".to_string())
+}
+
+/*  Verifying that wrong or missing results are reported by compare_response. */
+fn wrong_returned_bits() -> usize {
+    let (_, _, expected) = VECTOR_SETS[2];
+    let expected = match parse_response(expected) {
+        Err(_) => return 1,
+        Ok(expected) => expected,
+    };
+
+    let mut response = expected.clone();
+    let wrong_id = response.test_groups[0].tests[1].tc_id;
+    response.test_groups[0].tests[1].returned_bits.replace_range(..2, "00");
+    let missing_id = response.test_groups[1].tests.pop().map(|test| test.tc_id).unwrap_or(0);
+
+    check_res(compare_response(&response, &expected), vec![wrong_id, missing_id],
+        "wrong_returned_bits".to_string(),
+        AL_NAME.to_string(),
+        "wrong or missing results were not reported.".to_string(),
+        "wrong and missing results were reported as expected.".to_string())
+}
+
+/*  Verifying that the response is wrapped with the version header and only contains the expected fields. */
+fn response_format() -> usize {
+    let response = match load_sample(0).map(|vector_set| run_vector_set(&vector_set)) {
+        Some(Ok(response)) => response,
+        _ => return 1,
+    };
+
+    let json = response_to_json(&response);
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap_or_default();
+    let test = &value[1]["testGroups"][0]["tests"][0];
+    let res = (value[0]["acvVersion"].as_str() == Some(ACV_VERSION),
+                value[1]["isSample"].is_null(),
+                test.as_object().map(|test| test.len()),
+                parse_response(&json) == Ok(response));
+
+    check_res(res, (true, true, Some(2), true),
+        "response_format".to_string(),
+        AL_NAME.to_string(),
+        "the response file is not in the format expected by the ACVP server.".to_string(),
+        "the response file is in the format expected by the ACVP server.".to_string())
+}
+
+/*  Verifying that run_files reads the prompt and writes a response that matches the expected results. */
+fn files_round_trip() -> usize {
+    let (_, prompt, expected) = VECTOR_SETS[1];
+    let dir = std::env::temp_dir();
+    let prompt_path = dir.join(format!("acvp_prompt_{}.json", std::process::id()));
+    let response_path = dir.join(format!("acvp_response_{}.json", std::process::id()));
+
+    let res = fs::write(&prompt_path, prompt).map_err(|_| 1usize)
+        .and_then(|_| run_files(&prompt_path, &response_path))
+        .and_then(|_| fs::read_to_string(&response_path).map_err(|_| 1usize))
+        .and_then(|json| parse_response(&json))
+        .and_then(|response| parse_response(expected).map(|expected| compare_response(&response, &expected)));
+    let _ = fs::remove_file(&prompt_path);
+    let _ = fs::remove_file(&response_path);
+
+    check_res(res, Ok(Vec::new()),
+        "files_round_trip".to_string(),
+        AL_NAME.to_string(),
+        "the response written by run_files does not match the expected results.".to_string(),
+        "the response written by run_files matches the expected results.".to_string())
+}
+
+/*  Running the bundled sample vector sets and comparing them against their expected results. */
+fn sample_vector_sets() -> usize {
+    let mut failures = 0;
+
+    for (i, (algorithm, _, expected)) in VECTOR_SETS.iter().enumerate() {
+        let res = match (load_sample(i).map(|vector_set| run_vector_set(&vector_set)), parse_response(expected)) {
+            (Some(Ok(response)), Ok(expected)) => Ok(compare_response(&response, &expected)),
+            (Some(Err(err)), _) | (_, Err(err)) => Err(err),
+            (None, _) => Err(2),
+        };
+
+        let mut test_name = "sample_vector_sets ".to_string();
+        test_name.push_str(algorithm);
+        failures += check_res(res.clone(), Ok(Vec::new()),
+            test_name,
+            AL_NAME.to_string(),
+            format!("vector set failed (result: {:?}).", res),
+            "all test cases match the expected results.".to_string());
+    }
+
+    failures
+}
+
+/*  Loads one of the bundled vector sets, logging a failure if it cannot be parsed. */
+fn load_sample(idx: usize) -> Option<AcvpVectorSet> {
+    let (algorithm, prompt, _) = VECTOR_SETS[idx];
+    match parse_vector_set(prompt) {
+        Ok(vector_set) => Some(vector_set),
+        Err(_) => {
+            let mut message = "could not parse the bundled vector set ".to_string();
+            message.push_str(algorithm);
+            write_to_log(format_message(true, AL_NAME.to_string(), "load_sample".to_string(), message));
+            None
+        }
+    }
+}
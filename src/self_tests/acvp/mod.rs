@@ -0,0 +1,3 @@
+pub mod acvp_drbg;
+pub mod acvp_test;
+pub mod run_all;
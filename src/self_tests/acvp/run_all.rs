@@ -0,0 +1,6 @@
+use super::*;
+
+/*  Runs the tests of the ACVP harness and the bundled sample vector sets (see 'acvp_drbg'). */
+pub fn run_tests() -> usize {
+    acvp_test::run_tests()
+}
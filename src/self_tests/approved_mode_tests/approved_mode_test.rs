@@ -0,0 +1,189 @@
+use crate::drbg::approved_mode::*;
+use crate::drbg::buffered::BufferedDrbg;
+use crate::drbg::entropy::{Entropy_Source_Functions, FixedEntropySource, SystemEntropySource};
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use crate::self_tests::formats::*;
+use crate::self_tests::report::capture;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "APPROVED-MODE-TESTS::approved_mode_test";
+
+/*  An approved entropy source that only returns half of the requested bytes. */
+struct ShortEntropySource;
+
+impl Entropy_Source_Functions for ShortEntropySource {
+    fn get_entropy(&mut self, result: &mut Vec<u8>, bytes: usize) -> usize {
+        SystemEntropySource.get_entropy(result, bytes / 2)
+    }
+
+    fn is_approved(&self) -> bool {
+        true
+    }
+}
+
+/*  Aggregator that runs all the tests in this file. The approved mode is enabled by some tests and restored before they
+    return. */
+pub fn run_tests<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    // The gate must be tested before any other instantiation in approved mode.
+    self_test_gate::<T>(strength) +
+        approved_services::<T>(strength) +
+        test_entropy_source::<T>(strength) +
+        short_entropy::<T>(strength) +
+        approved_mode_enforced::<T>(strength) +
+        buffered_enforced::<T>(strength)
+}
+
+/*  Verifying that the services of an approved instance are indicated as approved. */
+fn approved_services<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut bytes = Vec::<u8>::new();
+
+    let res = match DRBG::<T>::new(strength, None) {
+        Err(_) => None,
+        Ok(mut drbg) => {
+            let new_ind = (drbg.get_service_indicator(), last_service_indicator());
+            let gen_res = drbg.generate(&mut bytes, 16, strength, true, None);
+            let gen_ind = (drbg.get_service_indicator(), last_service_indicator());
+            let res_reseed = drbg.reseed(None);
+            let reseed_ind = drbg.get_service_indicator();
+            let failed_gen = drbg.generate(&mut bytes, 1024, strength, false, None);
+            Some((new_ind, gen_res, gen_ind, res_reseed, reseed_ind, failed_gen, drbg.get_service_indicator()))
+        }
+    };
+
+    let approved = (ServiceIndicator::Approved, ServiceIndicator::Approved);
+    check_res(res, Some((approved, 0, approved, 0, ServiceIndicator::Approved, 2, ServiceIndicator::NonApproved)),
+        "approved_services".to_string(),
+        AL_NAME.to_string(),
+        "the services of an approved instance were not indicated as expected.".to_string(),
+        "the services of an approved instance were indicated as approved.".to_string())
+}
+
+/*  Verifying that an instance using a test entropy source is not approved and is rejected in approved mode. */
+fn test_entropy_source<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let chunks = || Box::new(FixedEntropySource::new(vec![vec![0x5a; 48]; 4]));
+    let mut bytes = Vec::<u8>::new();
+
+    let non_approved = match DRBG::<T>::new_with_entropy(strength, None, chunks()) {
+        Err(_) => None,
+        Ok(mut drbg) => {
+            let gen_res = drbg.generate(&mut bytes, 16, strength, false, None);
+            Some((drbg.get_service_indicator(), gen_res, drbg.get_service_indicator()))
+        }
+    };
+
+    let previous = set_approved_mode(true);
+    let rejected = DRBG::<T>::new_with_entropy(strength, None, chunks()).err();
+    let last = last_service_indicator();
+    set_approved_mode(previous);
+
+    check_res((non_approved, rejected, last),
+        (Some((ServiceIndicator::NonApproved, 0, ServiceIndicator::NonApproved)), Some(6), ServiceIndicator::NonApproved),
+        "test_entropy_source".to_string(),
+        AL_NAME.to_string(),
+        "an instance using a test entropy source was approved.".to_string(),
+        "an instance using a test entropy source was not approved.".to_string())
+}
+
+/*  Verifying that short entropy inputs from an approved source are rejected in approved mode. */
+fn short_entropy<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let previous = set_approved_mode(true);
+    let res = (DRBG::<T>::new_with_entropy(strength, None, Box::new(ShortEntropySource)).err(), last_service_indicator());
+    set_approved_mode(previous);
+
+    // The mechanisms reject short entropy inputs before the approval of the instance is checked.
+    check_res(res, (Some(3), ServiceIndicator::NonApproved),
+        "short_entropy".to_string(),
+        AL_NAME.to_string(),
+        "an instance using short entropy inputs was accepted in approved mode.".to_string(),
+        "an instance using short entropy inputs was rejected in approved mode.".to_string())
+}
+
+/*  Verifying that non-approved instances created before the approved mode was enabled can no longer be used. */
+fn approved_mode_enforced<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut bytes = Vec::<u8>::new();
+    let res = match DRBG::<T>::new_with_entropy(strength, None, Box::new(FixedEntropySource::new(vec![vec![0x5a; 48]; 4]))) {
+        Err(_) => None,
+        Ok(mut drbg) => {
+            let previous = set_approved_mode(true);
+            let res = (drbg.generate(&mut bytes, 16, strength, false, None), drbg.reseed(None));
+            set_approved_mode(previous);
+            Some(res)
+        }
+    };
+
+    check_res(res, Some((6, 4)),
+        "approved_mode_enforced".to_string(),
+        AL_NAME.to_string(),
+        "a non-approved instance was used in approved mode.".to_string(),
+        "a non-approved instance could not be used in approved mode.".to_string())
+}
+
+/*  Verifying that the pre-generated bytes of a non-approved buffered instance are discarded instead of served once the
+    approved mode is enabled, and that the requests served from the block of an approved instance are indicated. */
+fn buffered_enforced<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let (non_approved, approved) = match (DRBG::<T>::new_with_entropy(strength, None, Box::new(FixedEntropySource::new(vec![vec![0x5a; 48]; 4]))),
+                                          DRBG::<T>::new(strength, None)) {
+        (Ok(non_approved), Ok(approved)) => (non_approved, approved),
+        _ => {
+            write_to_log(format_message(true, AL_NAME.to_string(), "buffered_enforced".to_string(),
+                                        "failed to instantiate DRBG.".to_string()));
+            return 1;
+        }
+    };
+    let (mut non_approved, mut approved) = (BufferedDrbg::new(non_approved, 64), BufferedDrbg::new(approved, 64));
+    let mut out = [0u8; 16];
+    let prefetched = (non_approved.prefetch(), approved.prefetch());
+
+    let previous = set_approved_mode(true);
+    let rejected = (non_approved.generate(&mut out, strength, false, None), out == [0u8; 16], non_approved.buffered(),
+                    last_service_indicator());
+    let served = (approved.generate(&mut out, strength, false, None), approved.buffered(), last_service_indicator());
+    set_approved_mode(previous);
+
+    check_res((prefetched, rejected, served),
+        ((0, 0), (6, true, 0, ServiceIndicator::NonApproved), (0, 48, ServiceIndicator::Approved)),
+        "buffered_enforced".to_string(),
+        AL_NAME.to_string(),
+        "a non-approved buffered instance served pre-generated bytes in approved mode.".to_string(),
+        "a non-approved buffered instance served no pre-generated bytes in approved mode.".to_string())
+}
+
+/*  Verifying that in approved mode the self-tests of a mechanism are run before its first use, even after the overall
+    self-tests. The first use self-tests are found in the report of the instantiations. */
+fn self_test_gate<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    // The first use self-tests may have already been run by a previous run of the overall self-tests.
+    let previous = set_approved_mode(true);
+    DRBG::<T>::reset_first_time();
+    let mut indicators = Vec::<Result<ServiceIndicator, usize>>::new();
+    let report = capture("APPROVED-MODE-TESTS", || {
+        indicators.push(DRBG::<T>::new(strength, None).map(|drbg| drbg.get_service_indicator()));
+        indicators.push(DRBG::<T>::new(strength, None).map(|drbg| drbg.get_service_indicator()));
+        0
+    });
+    set_approved_mode(previous);
+
+    let first_use = report.mechanisms.iter()
+        .filter(|mech| mech.name.ends_with("self-tests for first time use") && !mech.tests.is_empty())
+        .count();
+
+    check_res((indicators, first_use, report.failures()), (vec![Ok(ServiceIndicator::Approved); 2], 1, 0),
+        "self_test_gate".to_string(),
+        AL_NAME.to_string(),
+        "the self-tests were not run once before the first use in approved mode.".to_string(),
+        "the self-tests were run once before the first use in approved mode.".to_string())
+}
+
+/*  Verifying that a panic of the self-tests, once caught, does not leave the approved mode disabled on the thread. */
+pub fn scope_after_panic() -> usize {
+    let previous = set_approved_mode(true);
+    let panicked = std::panic::catch_unwind(|| self_test_scope(|| panic!("injected panic"))).is_err();
+    let enforced = approved_mode();
+    set_approved_mode(previous);
+
+    check_res((panicked, enforced), (true, true),
+        "scope_after_panic".to_string(),
+        AL_NAME.to_string(),
+        "the approved mode was still disabled after a panic of the self-tests.".to_string(),
+        "the approved mode was enforced again after a panic of the self-tests.".to_string())
+}
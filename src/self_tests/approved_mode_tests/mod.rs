@@ -0,0 +1,2 @@
+pub mod approved_mode_test;
+pub mod run_all;
@@ -0,0 +1,13 @@
+use crate::mechs::{hash_mech::HashDrbgMech, hmac_mech::HmacDrbgMech, ctr_mech::CtrDrbgMech, ctr_mech_with_df::CtrDrbgMech_DF};
+use super::*;
+use sha2::*;
+use aes::*;
+
+/*  Runs the tests of the approved mode on a mechanism of each family. */
+pub fn run_tests() -> usize {
+    approved_mode_test::scope_after_panic() +
+        approved_mode_test::run_tests::<HashDrbgMech<Sha256>>(32) +
+        approved_mode_test::run_tests::<HmacDrbgMech<Sha512>>(32) +
+        approved_mode_test::run_tests::<CtrDrbgMech<Aes128>>(16) +
+        approved_mode_test::run_tests::<CtrDrbgMech_DF<Aes256>>(32)
+}
@@ -0,0 +1,4 @@
+pub mod rsp_parser;
+pub mod rsp_runner;
+pub mod rsp_test;
+pub mod run_all;
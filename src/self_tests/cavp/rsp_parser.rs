@@ -0,0 +1,151 @@
+/*  Parser for the CAVP response files of the DRBG validation system (drbgvectors_pr_true, drbgvectors_pr_false and
+    drbgvectors_no_reseed archives: Hash_DRBG.rsp, HMAC_DRBG.rsp and CTR_DRBG.rsp).
+
+    A response file is a list of sections. Each section starts with the name of the tested function (e.g. [SHA-256] or
+    [AES-128 use df]) followed by its parameters (e.g. [PredictionResistance = True], [EntropyInputLen = 256]) and by
+    the test cases, each of them introduced by a COUNT line:
+
+        COUNT = 0
+        EntropyInput = ...
+        Nonce = ...
+        PersonalizationString = ...
+        EntropyInputReseed = ...            (pr_false files only)
+        AdditionalInputReseed = ...         (pr_false files only)
+        AdditionalInput = ...               (once per generate call)
+        EntropyInputPR = ...                (pr_true files only, once per generate call)
+        ReturnedBits = ...
+
+    Comments (#) and empty lines are ignored, as well as unknown keys and parameters. */
+
+/*  A single test case (COUNT) of a response file. All the lengths are in bytes.
+
+    - count: the COUNT value of the test case
+    - entropy, nonce, pers: the instantiation parameters
+    - entropy_reseed, add_reseed: the parameters of the explicit reseed (only present in pr_false files)
+    - add_in: the additional inputs, one per generate call
+    - entropy_pr: the entropy inputs used for prediction resistance, one per generate call (only present in pr_true files)
+    - returned_bits: the output expected from the last generate call */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RspCase {
+    pub count: usize,
+    pub entropy: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub pers: Vec<u8>,
+    pub entropy_reseed: Option<Vec<u8>>,
+    pub add_reseed: Option<Vec<u8>>,
+    pub add_in: Vec<Vec<u8>>,
+    pub entropy_pr: Vec<Vec<u8>>,
+    pub returned_bits: Vec<u8>,
+}
+
+/*  A section of a response file. The declared lengths are in bits, as in the file.
+
+    - algorithm: the name of the tested function (e.g. "SHA-256", "AES-128 use df")
+    - prediction_resistance: whether the test cases use prediction resistance
+    - entropy_len, nonce_len, pers_len, add_len, returned_bits_len: the declared lengths of the parameters
+    - cases: the test cases of the section */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RspSection {
+    pub algorithm: String,
+    pub prediction_resistance: bool,
+    pub entropy_len: usize,
+    pub nonce_len: usize,
+    pub pers_len: usize,
+    pub add_len: usize,
+    pub returned_bits_len: usize,
+    pub cases: Vec<RspCase>,
+}
+
+impl RspSection {
+    /*  Returns a description of the section in the same format used by the response files. */
+    pub fn describe(&self) -> String {
+        format!("[{}] [PredictionResistance = {}] [EntropyInputLen = {}] [NonceLen = {}] [PersonalizationStringLen = {}] \
+                [AdditionalInputLen = {}] [ReturnedBitsLen = {}]",
+                self.algorithm, if self.prediction_resistance { "True" } else { "False" }, self.entropy_len,
+                self.nonce_len, self.pers_len, self.add_len, self.returned_bits_len)
+    }
+}
+
+/*  Parses the content of a CAVP DRBG response file.
+
+    Parameters:
+        - content: the content of the response file
+
+    Return values:
+        - Ok(sections): SUCCESS, the sections of the file in the order in which they appear
+        - Err(n): ERROR, line n (starting from 1) is malformed (unknown line format, invalid number or hex string,
+          parameter or test case outside of any section, test value outside of any test case) */
+pub fn parse_rsp(content: &str) -> Result<Vec<RspSection>, usize> {
+    let mut sections = Vec::<RspSection>::new();
+
+    for (idx, raw_line) in content.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = raw_line.trim();
+
+        // Skipping comments and empty lines.
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // Section names and parameters.
+        if let Some(inner) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            match inner.split_once('=') {
+                None => {
+                    sections.push(RspSection { algorithm: inner.trim().to_string(), ..Default::default() });
+                }
+                Some((key, value)) => {
+                    let section = sections.last_mut().ok_or(line_no)?;
+                    let value = value.trim();
+                    match key.trim() {
+                        "PredictionResistance" => {
+                            section.prediction_resistance = match value {
+                                "True" => true,
+                                "False" => false,
+                                _ => return Err(line_no),
+                            };
+                        }
+                        "EntropyInputLen" => { section.entropy_len = parse_len(value).ok_or(line_no)?; }
+                        "NonceLen" => { section.nonce_len = parse_len(value).ok_or(line_no)?; }
+                        "PersonalizationStringLen" => { section.pers_len = parse_len(value).ok_or(line_no)?; }
+                        "AdditionalInputLen" => { section.add_len = parse_len(value).ok_or(line_no)?; }
+                        "ReturnedBitsLen" => { section.returned_bits_len = parse_len(value).ok_or(line_no)?; }
+                        _ => {}
+                    }
+                }
+            }
+            continue;
+        }
+
+        // Test case values.
+        let (key, value) = line.split_once('=').ok_or(line_no)?;
+        let (key, value) = (key.trim(), value.trim());
+        let section = sections.last_mut().ok_or(line_no)?;
+
+        if key == "COUNT" {
+            let count = parse_len(value).ok_or(line_no)?;
+            section.cases.push(RspCase { count, ..Default::default() });
+            continue;
+        }
+
+        let case = section.cases.last_mut().ok_or(line_no)?;
+        let decode = || hex::decode(value).map_err(|_| line_no);
+        match key {
+            "EntropyInput" => { case.entropy = decode()?; }
+            "Nonce" => { case.nonce = decode()?; }
+            "PersonalizationString" => { case.pers = decode()?; }
+            "EntropyInputReseed" => { case.entropy_reseed = Some(decode()?); }
+            "AdditionalInputReseed" => { case.add_reseed = Some(decode()?); }
+            "AdditionalInput" => { case.add_in.push(decode()?); }
+            "EntropyInputPR" => { case.entropy_pr.push(decode()?); }
+            "ReturnedBits" => { case.returned_bits = decode()?; }
+            _ => {}
+        }
+    }
+
+    Ok(sections)
+}
+
+/*  Parses a decimal length or counter. */
+fn parse_len(value: &str) -> Option<usize> {
+    value.parse::<usize>().ok()
+}
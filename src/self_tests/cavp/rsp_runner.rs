@@ -0,0 +1,175 @@
+use crate::mechs::{gen_mech::DRBG_Mechanism_Functions, hash_mech::HashDrbgMech, hmac_mech::HmacDrbgMech,
+                   ctr_mech::CtrDrbgMech, ctr_mech_with_df::CtrDrbgMech_DF};
+use super::rsp_parser::*;
+use std::fs;
+use std::path::Path;
+use sha2::*;
+use aes::*;
+
+/*  Runner for the CAVP DRBG response files (see 'rsp_parser'). Every test case of a section is executed against the
+    matching mechanism of this crate following the CAVP procedure:
+
+        pr_false:   instantiate, reseed (if EntropyInputReseed is present), generate, generate
+        pr_true:    instantiate, reseed with EntropyInputPR + generate, reseed with EntropyInputPR + generate
+
+    and the output of the last generate call is compared against ReturnedBits. Empty additional inputs are treated as
+    Null inputs, as required by the SP. Sections whose function is not supported by this crate (e.g. [SHA-1] or
+    [3KeyTDEA use df]) are reported as skipped. */
+
+/*  The result of the execution of a section.
+
+    - section: the description of the section (see RspSection::describe)
+    - mechanism: the mechanism that has been used to run the section (e.g. "CTR-DRBG-DF AES-128"), None if the
+      section has been skipped
+    - passed: the number of test cases that have passed
+    - failed_counts: the COUNT values of the test cases that have failed */
+#[derive(Debug, Clone, PartialEq)]
+pub struct RspSectionResult {
+    pub section: String,
+    pub mechanism: Option<String>,
+    pub passed: usize,
+    pub failed_counts: Vec<usize>,
+}
+
+impl RspSectionResult {
+    /*  Returns true if the section has been skipped. */
+    pub fn skipped(&self) -> bool {
+        self.mechanism.is_none()
+    }
+
+    /*  Returns the number of test cases that have failed. */
+    pub fn failed(&self) -> usize {
+        self.failed_counts.len()
+    }
+}
+
+/*  Runs a CAVP response file stored at the given path. The mechanism family is inferred from the name of the file,
+    which must be one of Hash_DRBG.rsp, HMAC_DRBG.rsp or CTR_DRBG.rsp (as in the CAVP archives).
+
+    Return values:
+        - Ok(results): SUCCESS, the file has been run and the result of each section is returned
+        - Err(1): ERROR, the file could not be read
+        - Err(2): ERROR, the name of the file does not identify a supported mechanism
+        - Err(3): ERROR, the file is malformed */
+pub fn run_rsp_file(path: &Path) -> Result<Vec<RspSectionResult>, usize> {
+    let content = fs::read_to_string(path).map_err(|_| 1usize)?;
+    let mech = match path.file_name().and_then(|name| name.to_str()) {
+        Some("Hash_DRBG.rsp") => "Hash-DRBG",
+        Some("HMAC_DRBG.rsp") => "HMAC-DRBG",
+        Some("CTR_DRBG.rsp") => "CTR-DRBG",
+        _ => return Err(2),
+    };
+
+    run_rsp(mech, &content).map_err(|_| 3usize)
+}
+
+/*  Runs the content of a CAVP response file.
+
+    Parameters:
+        - mech: the mechanism family of the file ("Hash-DRBG", "HMAC-DRBG" or "CTR-DRBG", the use of the DF is
+          selected by each CTR-DRBG section)
+        - content: the content of the response file
+
+    Return values:
+        - Ok(results): SUCCESS, the result of each section in the order in which they appear in the file
+        - Err(n): ERROR, line n of the file is malformed (see parse_rsp) */
+pub fn run_rsp(mech: &str, content: &str) -> Result<Vec<RspSectionResult>, usize> {
+    let sections = parse_rsp(content)?;
+
+    Ok(sections.iter().map(|section| run_section(mech, section)).collect())
+}
+
+/*  Selects the mechanism that matches a section and runs it. */
+fn run_section(mech: &str, section: &RspSection) -> RspSectionResult {
+    match (mech, section.algorithm.as_str()) {
+        ("Hash-DRBG", "SHA-256") => run_section_with::<HashDrbgMech<Sha256>>(section, 32),
+        ("Hash-DRBG", "SHA-512") => run_section_with::<HashDrbgMech<Sha512>>(section, 32),
+        ("HMAC-DRBG", "SHA-256") => run_section_with::<HmacDrbgMech<Sha256>>(section, 32),
+        ("HMAC-DRBG", "SHA-512") => run_section_with::<HmacDrbgMech<Sha512>>(section, 32),
+        ("CTR-DRBG", "AES-128 no df") => run_section_with::<CtrDrbgMech<Aes128>>(section, 16),
+        ("CTR-DRBG", "AES-192 no df") => run_section_with::<CtrDrbgMech<Aes192>>(section, 24),
+        ("CTR-DRBG", "AES-256 no df") => run_section_with::<CtrDrbgMech<Aes256>>(section, 32),
+        ("CTR-DRBG", "AES-128 use df") => run_section_with::<CtrDrbgMech_DF<Aes128>>(section, 16),
+        ("CTR-DRBG", "AES-192 use df") => run_section_with::<CtrDrbgMech_DF<Aes192>>(section, 24),
+        ("CTR-DRBG", "AES-256 use df") => run_section_with::<CtrDrbgMech_DF<Aes256>>(section, 32),
+        _ => RspSectionResult {
+            section: section.describe(),
+            mechanism: None,
+            passed: 0,
+            failed_counts: Vec::new(),
+        },
+    }
+}
+
+/*  Runs every test case of a section against the mechanism T. */
+fn run_section_with<T: DRBG_Mechanism_Functions>(section: &RspSection, strength: usize) -> RspSectionResult {
+    let mut passed = 0;
+    let mut failed_counts = Vec::new();
+
+    for case in section.cases.iter() {
+        if run_case::<T>(case, section.prediction_resistance, strength) {
+            passed += 1;
+        }
+        else {
+            failed_counts.push(case.count);
+        }
+    }
+
+    RspSectionResult {
+        section: section.describe(),
+        mechanism: Some(format!("{} {}", T::drbg_name(), section.algorithm.split(' ').next().unwrap_or(""))),
+        passed,
+        failed_counts,
+    }
+}
+
+/*  Runs a single test case, returns true if the output of the last generate call matches ReturnedBits. */
+fn run_case<T: DRBG_Mechanism_Functions>(case: &RspCase, pr: bool, mut strength: usize) -> bool {
+    let mut drbg = match T::new(&case.entropy, &case.nonce, &case.pers, &mut strength) {
+        None => return false,
+        Some(inst) => inst,
+    };
+
+    let req_bytes = case.returned_bits.len();
+    let mut result = Vec::<u8>::new();
+    let calls = case.add_in.len().max(2);
+
+    // Explicit reseed of the pr_false test cases.
+    if !pr {
+        if let Some(entropy_reseed) = &case.entropy_reseed {
+            if drbg.reseed(entropy_reseed, null_if_empty(case.add_reseed.as_deref())) != 0 {
+                return false;
+            }
+        }
+    }
+
+    for i in 0..calls {
+        let add = null_if_empty(case.add_in.get(i).map(|add| add.as_slice()));
+
+        let res = if pr {
+            // Prediction resistance: the additional input is consumed by the reseed.
+            let entropy_pr = match case.entropy_pr.get(i) {
+                None => return false,
+                Some(entropy_pr) => entropy_pr,
+            };
+            if drbg.reseed(entropy_pr, add) != 0 {
+                return false;
+            }
+            drbg.generate(&mut result, req_bytes, None)
+        }
+        else {
+            drbg.generate(&mut result, req_bytes, add)
+        };
+
+        if res != 0 {
+            return false;
+        }
+    }
+
+    result == case.returned_bits
+}
+
+/*  Maps empty inputs to Null inputs. */
+fn null_if_empty(input: Option<&[u8]>) -> Option<&[u8]> {
+    input.filter(|input| !input.is_empty())
+}
@@ -0,0 +1,172 @@
+use crate::self_tests::cavp::{rsp_parser::*, rsp_runner::*};
+use crate::self_tests::formats::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "CAVP-TESTS::rsp_test";
+
+/*  The response files bundled with this crate, grouped by mechanism family. Copies of the complete CAVP archives can
+    be run with rsp_runner::run_rsp_file. */
+const RSP_FILES: [(&str, &str, &str); 6] = [
+    ("Hash-DRBG", "drbgvectors_pr_false/Hash_DRBG.rsp", include_str!("vectors/drbgvectors_pr_false/Hash_DRBG.rsp")),
+    ("Hash-DRBG", "drbgvectors_pr_true/Hash_DRBG.rsp", include_str!("vectors/drbgvectors_pr_true/Hash_DRBG.rsp")),
+    ("HMAC-DRBG", "drbgvectors_pr_false/HMAC_DRBG.rsp", include_str!("vectors/drbgvectors_pr_false/HMAC_DRBG.rsp")),
+    ("HMAC-DRBG", "drbgvectors_pr_true/HMAC_DRBG.rsp", include_str!("vectors/drbgvectors_pr_true/HMAC_DRBG.rsp")),
+    ("CTR-DRBG", "drbgvectors_pr_false/CTR_DRBG.rsp", include_str!("vectors/drbgvectors_pr_false/CTR_DRBG.rsp")),
+    ("CTR-DRBG", "drbgvectors_pr_true/CTR_DRBG.rsp", include_str!("vectors/drbgvectors_pr_true/CTR_DRBG.rsp")),
+];
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    parse_sections() +
+        malformed_lines() +
+        unsupported_section() +
+        wrong_output() +
+        bundled_files()
+}
+
+/*  Verifying that section parameters and repeated keys of a test case are parsed correctly. */
+fn parse_sections() -> usize {
+    let content = "# comment\r\n[SHA-256]\r\n[PredictionResistance = True]\r\n[EntropyInputLen = 256]\r\n\
+                   [NonceLen = 128]\r\n[PersonalizationStringLen = 0]\r\n[AdditionalInputLen = 8]\r\n\
+                   [ReturnedBitsLen = 1024]\r\n\r\nCOUNT = 7\r\nEntropyInput = 00ff\r\nNonce = 01\r\n\
+                   PersonalizationString = \r\nAdditionalInput = 02\r\nEntropyInputPR = 03\r\n\
+                   AdditionalInput = 04\r\nEntropyInputPR = 05\r\nReturnedBits = 06\r\n";
+
+    let expected = vec![RspSection {
+        algorithm: "SHA-256".to_string(),
+        prediction_resistance: true,
+        entropy_len: 256,
+        nonce_len: 128,
+        pers_len: 0,
+        add_len: 8,
+        returned_bits_len: 1024,
+        cases: vec![RspCase {
+            count: 7,
+            entropy: vec![0x00, 0xff],
+            nonce: vec![0x01],
+            pers: Vec::new(),
+            entropy_reseed: None,
+            add_reseed: None,
+            add_in: vec![vec![0x02], vec![0x04]],
+            entropy_pr: vec![vec![0x03], vec![0x05]],
+            returned_bits: vec![0x06],
+        }],
+    }];
+
+    check_res(parse_rsp(content), Ok(expected),
+        "parse_sections".to_string(),
+        AL_NAME.to_string(),
+        "response file section was not parsed correctly.".to_string(),
+        "response file section was parsed correctly.".to_string())
+}
+
+/*  Verifying that malformed lines are reported with their line number. */
+fn malformed_lines() -> usize {
+    let res = (parse_rsp("COUNT = 0\n"),
+                parse_rsp("[SHA-256]\nEntropyInput = 00\n"),
+                parse_rsp("[SHA-256]\nCOUNT = 0\nEntropyInput = 0g\n"),
+                parse_rsp("[SHA-256]\n[PredictionResistance = Maybe]\n"),
+                parse_rsp("[SHA-256]\n\nCOUNT = x\n"),
+                parse_rsp("[SHA-256]\nCOUNT = 0\nnot a line\n"));
+
+    check_res(res, (Err(1), Err(2), Err(3), Err(2), Err(3), Err(3)),
+        "malformed_lines".to_string(),
+        AL_NAME.to_string(),
+        "malformed response file lines were not reported correctly.".to_string(),
+        "malformed response file lines were reported correctly.".to_string())
+}
+
+/*  Verifying that sections of functions that are not supported are skipped. */
+fn unsupported_section() -> usize {
+    let content = "[SHA-1]\n[PredictionResistance = False]\n\nCOUNT = 0\nEntropyInput = 00\nReturnedBits = 00\n";
+    let res = run_rsp("Hash-DRBG", content)
+        .map(|results| results.iter().map(|res| (res.skipped(), res.passed, res.failed())).collect::<Vec<_>>());
+
+    check_res(res, Ok(vec![(true, 0, 0)]),
+        "unsupported_section".to_string(),
+        AL_NAME.to_string(),
+        "a section of an unsupported function was not skipped.".to_string(),
+        "a section of an unsupported function was skipped as expected.".to_string())
+}
+
+/*  Verifying that a wrong ReturnedBits value is reported as a failure of its test case. */
+fn wrong_output() -> usize {
+    let (mech, _, content) = RSP_FILES[0];
+    let sections = parse_rsp(content).unwrap_or_default();
+    let mut section = match sections.into_iter().next() {
+        None => {
+            write_to_log(format_message(true, AL_NAME.to_string(),
+                                "wrong_output".to_string(),
+                                "bundled response file has no sections.".to_string()
+                            )
+            );
+            return 1;
+        }
+        Some(section) => section,
+    };
+    section.cases.truncate(2);
+    section.cases[1].returned_bits[0] ^= 0x01;
+
+    // Rebuilding the response file from the modified section.
+    let mut modified = format!("[{}]\n[PredictionResistance = {}]\n", section.algorithm,
+                                if section.prediction_resistance { "True" } else { "False" });
+    for case in section.cases.iter() {
+        modified.push_str(&format!("COUNT = {}\nEntropyInput = {}\nNonce = {}\nPersonalizationString = {}\n",
+                                    case.count, hex::encode(&case.entropy), hex::encode(&case.nonce),
+                                    hex::encode(&case.pers)));
+        if let Some(entropy_reseed) = &case.entropy_reseed {
+            modified.push_str(&format!("EntropyInputReseed = {}\n", hex::encode(entropy_reseed)));
+        }
+        if let Some(add_reseed) = &case.add_reseed {
+            modified.push_str(&format!("AdditionalInputReseed = {}\n", hex::encode(add_reseed)));
+        }
+        for add in case.add_in.iter() {
+            modified.push_str(&format!("AdditionalInput = {}\n", hex::encode(add)));
+        }
+        modified.push_str(&format!("ReturnedBits = {}\n", hex::encode(&case.returned_bits)));
+    }
+
+    let res = run_rsp(mech, &modified)
+        .map(|results| results.iter().map(|res| (res.passed, res.failed_counts.clone())).collect::<Vec<_>>());
+
+    check_res(res, Ok(vec![(1, vec![section.cases[1].count])]),
+        "wrong_output".to_string(),
+        AL_NAME.to_string(),
+        "a wrong ReturnedBits value was not reported as a failure.".to_string(),
+        "a wrong ReturnedBits value was reported as a failure as expected.".to_string())
+}
+
+/*  Running every section of the bundled response files, a single result is logged for each section. */
+fn bundled_files() -> usize {
+    let mut failures = 0;
+
+    for (mech, name, content) in RSP_FILES {
+        let results = match run_rsp(mech, content) {
+            Err(line) => {
+                write_to_log(format_message(true, AL_NAME.to_string(),
+                                    name.to_string(),
+                                    format!("response file is malformed at line {}.", line)
+                                )
+                );
+                failures += 1;
+                continue;
+            }
+            Ok(results) => results,
+        };
+
+        for res in results {
+            let total = res.passed + res.failed();
+            let mut test_name = name.to_string();
+            test_name.push(' ');
+            test_name.push_str(&res.section);
+
+            failures += check_res((res.skipped(), res.failed()), (false, 0),
+                test_name,
+                AL_NAME.to_string(),
+                format!("{}/{} counts passed, failed counts: {:?}.", res.passed, total, res.failed_counts),
+                format!("{}/{} counts passed.", res.passed, total));
+        }
+    }
+
+    failures
+}
@@ -0,0 +1,6 @@
+use super::*;
+
+/*  Runs the parser tests and the bundled CAVP response files (see 'rsp_parser' and 'rsp_runner'). */
+pub fn run_tests() -> usize {
+    rsp_test::run_tests()
+}
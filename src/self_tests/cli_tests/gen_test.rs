@@ -0,0 +1,93 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::Parser;
+use crate::cli::{Cli, Command, Mechanism, gen::*};
+use crate::drbg::drbg_conf::MAX_PRB;
+use crate::self_tests::formats::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "CLI-TESTS::gen_test";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    parse_args() +
+        chunked_output() +
+        exit_codes()
+}
+
+/*  Parses the arguments of the 'gen' command. */
+fn gen_args(args: &[&str]) -> Option<GenArgs> {
+    match Cli::try_parse_from(["drbg", "gen"].iter().chain(args.iter())) {
+        Ok(Cli { command: Some(Command::Gen(args)) }) => Some(args),
+        _ => None,
+    }
+}
+
+/*  Runs the 'gen' command with the given arguments, returning its exit code and output. */
+fn run_gen(args: &[&str]) -> (u8, Vec<u8>) {
+    let mut out = Vec::<u8>::new();
+    match gen_args(args) {
+        Some(args) => (run_to(&args, &mut out).err().map_or(crate::cli::EXIT_SUCCESS, |err| err.code), out),
+        None => (crate::cli::EXIT_USAGE, out),
+    }
+}
+
+/*  Verifying the parsing of the arguments of the 'gen' command. */
+fn parse_args() -> usize {
+    let args = gen_args(&["--mech", "ctr-df-aes192", "--strength", "192", "--bytes", "1M", "--format", "base64", "--pr",
+                          "--pers", "0011", "--add", "aabbcc", "-o", "out.bin"]);
+    let parsed = args.map(|args| (args.mech, args.strength, args.bytes, args.format, args.pr, args.pers, args.add,
+                                  args.output.map(|path| path.display().to_string())));
+    let defaults = gen_args(&["--bytes", "2k"]).map(|args| (args.mech, args.strength, args.bytes, args.format, args.pr));
+    let invalid = [
+        gen_args(&["--bytes", "1Q"]).is_none(),
+        gen_args(&["--bytes", "8", "--strength", "100"]).is_none(),
+        gen_args(&["--bytes", "8", "--pers", "zz"]).is_none(),
+        gen_args(&["--bytes", "8", "--mech", "hash-md5"]).is_none(),
+        gen_args(&[]).is_none(),
+    ];
+
+    check_res((parsed, defaults, invalid),
+        (Some((Mechanism::CtrDfAes192, Some(24), 1 << 20, OutputFormat::Base64, true, Some(HexBytes(vec![0x00, 0x11])),
+               Some(HexBytes(vec![0xaa, 0xbb, 0xcc])), Some("out.bin".to_string()))),
+         Some((Mechanism::HmacSha512, None, 2048, OutputFormat::Hex, false)), [true; 5]),
+        "parse_args".to_string(),
+        AL_NAME.to_string(),
+        "the arguments of the gen command were not parsed as expected.".to_string(),
+        "the arguments of the gen command were parsed as expected.".to_string())
+}
+
+/*  Verifying that requests larger than MAX_PRB are served in every output format. */
+fn chunked_output() -> usize {
+    let len = 3 * MAX_PRB + 1;
+    let bytes = len.to_string();
+    let raw = run_gen(&["--mech", "hash-sha256", "--bytes", &bytes, "--format", "raw", "--pr"]);
+    let hex = run_gen(&["--mech", "ctr-aes128", "--bytes", &bytes, "--format", "hex", "--add", "0102"]);
+    let base64 = run_gen(&["--mech", "ctr-df-aes256", "--bytes", &bytes, "--format", "base64", "--pers", "abcd"]);
+
+    let decoded_hex = std::str::from_utf8(&hex.1).ok().and_then(|text| hex::decode(text.trim_end()).ok());
+    let decoded_base64 = std::str::from_utf8(&base64.1).ok().and_then(|text| STANDARD.decode(text.trim_end()).ok());
+
+    check_res((raw.0, raw.1.len(), hex.0, decoded_hex.map(|bytes| bytes.len()), base64.0, decoded_base64.map(|bytes| bytes.len())),
+        (0, len, 0, Some(len), 0, Some(len)),
+        "chunked_output".to_string(),
+        AL_NAME.to_string(),
+        "the gen command did not write the requested number of bytes.".to_string(),
+        "the gen command wrote the requested number of bytes in every format.".to_string())
+}
+
+/*  Verifying that the errors of the DRBG are mapped to the documented exit codes. */
+fn exit_codes() -> usize {
+    let long_input = "ab".repeat(33);
+    let res = (
+        run_gen(&["--mech", "ctr-aes128", "--strength", "256", "--bytes", "8"]).0,
+        run_gen(&["--mech", "hmac-sha256", "--strength", "128", "--pers", &long_input, "--bytes", "8"]).0,
+        run_gen(&["--mech", "hash-sha512", "--add", &long_input, "--bytes", "8"]).0,
+        run_gen(&["--bytes", "0"]),
+    );
+
+    check_res(res, (13, 12, 24, (0, b"\n".to_vec())),
+        "exit_codes".to_string(),
+        AL_NAME.to_string(),
+        "the errors of the gen command were not mapped to the expected exit codes.".to_string(),
+        "the errors of the gen command were mapped to the expected exit codes.".to_string())
+}
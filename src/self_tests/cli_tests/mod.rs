@@ -0,0 +1,5 @@
+pub mod gen_test;
+pub mod selftest_test;
+pub mod stream_test;
+pub mod vectors_test;
+pub mod run_all;
@@ -0,0 +1,9 @@
+use super::*;
+
+/*  Runs the tests of the command line interface (see 'cli'). */
+pub fn run_tests() -> usize {
+    gen_test::run_tests() +
+        stream_test::run_tests() +
+        selftest_test::run_tests() +
+        vectors_test::run_tests()
+}
@@ -0,0 +1,72 @@
+use clap::Parser;
+use crate::cli::{Cli, Command, EXIT_TESTS_FAILED, write_report, selftest::run_to};
+use crate::self_tests::formats::*;
+use crate::self_tests::report::{SelfTestReport, TestCaseReport, TestStatus, uncaptured};
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "CLI-TESTS::selftest_test";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    mechanism_report() +
+        report_output()
+}
+
+/*  Verifying that 'selftest --mech X --json' runs the self-tests of a single mechanism and prints their report. The
+    nested self-tests are not part of the report of the running ones. */
+fn mechanism_report() -> usize {
+    let section = current_section();
+    let res = uncaptured(|| match Cli::try_parse_from(["drbg", "selftest", "--mech", "hmac-sha256", "--json"]) {
+        Ok(Cli { command: Some(Command::Selftest(args)) }) => {
+            let mut out = Vec::<u8>::new();
+            let res = run_to(&args, &mut out).map_err(|err| err.code);
+            Some((res, String::from_utf8(out).unwrap_or_default()))
+        }
+        _ => None,
+    });
+    set_section(&section);
+
+    let report = res.as_ref().and_then(|(_, json)| SelfTestReport::from_json(json).ok());
+    let res = (res.map(|res| res.0), report.map(|report| (report.passed(), report.total() > 0,
+               report.mechanisms.iter().map(|mech| mech.name.clone()).collect::<Vec<String>>())));
+
+    check_res(res, (Some(Ok(())), Some((true, true, vec!["HMAC-DRBG Sha-256 self-tests".to_string()]))),
+        "mechanism_report".to_string(),
+        AL_NAME.to_string(),
+        "the self-tests of a single mechanism were not run or reported as expected.".to_string(),
+        "the self-tests of a single mechanism were run and reported as expected.".to_string())
+}
+
+/*  Verifying the summary of a report and the exit code of failed or empty reports. */
+fn report_output() -> usize {
+    let mut report = SelfTestReport::new("probe");
+    let test = |name: &str, status: TestStatus| TestCaseReport {
+        module: "PROBE".to_string(), name: name.to_string(), status, message: String::new(), duration_us: 0 };
+
+    let mut outputs = Vec::<(Result<(), u8>, String)>::new();
+    let mut write = |report: &SelfTestReport| {
+        let mut out = Vec::<u8>::new();
+        let res = write_report(report, false, &mut out).map_err(|err| err.code);
+        outputs.push((res, String::from_utf8(out).unwrap_or_default()));
+    };
+
+    write(&report);
+    report.add_test("mech", test("first", TestStatus::Passed));
+    write(&report);
+    report.add_test("mech", test("second", TestStatus::Failed));
+    write(&report);
+
+    let res: Vec<(Result<(), u8>, Vec<&str>)> = outputs.iter()
+        .map(|(res, out)| (*res, out.lines().map(|line| line.split(" (").next().unwrap_or_default()).collect()))
+        .collect();
+
+    check_res(res, vec![
+            (Err(EXIT_TESTS_FAILED), vec!["probe: 0 tests, 0 passed, 0 failed"]),
+            (Ok(()), vec!["probe: 1 tests, 1 passed, 0 failed"]),
+            (Err(EXIT_TESTS_FAILED), vec!["probe: 2 tests, 1 passed, 1 failed", "FAILED mech: PROBE - second"]),
+        ],
+        "report_output".to_string(),
+        AL_NAME.to_string(),
+        "the summary of the reports or their exit codes were not the expected ones.".to_string(),
+        "the summary of the reports and their exit codes were the expected ones.".to_string())
+}
@@ -0,0 +1,40 @@
+use clap::Parser;
+use crate::cli::{Cli, Command, EXIT_USAGE, stream::run_to};
+use crate::self_tests::formats::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "CLI-TESTS::stream_test";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    bounded_stream()
+}
+
+/*  Runs the 'stream' command with the given arguments, returning its result and the length of its output. */
+fn run_stream(args: &[&str]) -> (Result<(), u8>, usize) {
+    match Cli::try_parse_from(["drbg", "stream"].iter().chain(args.iter())) {
+        Ok(Cli { command: Some(Command::Stream(args)) }) => {
+            let mut out = Vec::<u8>::new();
+            (run_to(&args, &mut out).map_err(|err| err.code), out.len())
+        }
+        _ => (Err(EXIT_USAGE), 0),
+    }
+}
+
+/*  Verifying that a stream limited by --bytes is written whatever the size of the buffer, and the errors of the
+    command. */
+fn bounded_stream() -> usize {
+    let res = [
+        run_stream(&["--bytes", "1000", "--buffer", "7"]),
+        run_stream(&["--mech", "ctr-df-aes128", "--bytes", "64K", "--pr-interval", "3"]),
+        run_stream(&["--bytes", "0"]),
+        run_stream(&["--buffer", "0"]),
+        run_stream(&["--mech", "ctr-aes192", "--strength", "256", "--bytes", "8"]),
+    ];
+
+    check_res(res, [(Ok(()), 1000), (Ok(()), 65536), (Ok(()), 0), (Err(EXIT_USAGE), 0), (Err(13), 0)],
+        "bounded_stream".to_string(),
+        AL_NAME.to_string(),
+        "the stream command did not write the expected number of bytes.".to_string(),
+        "the stream command wrote the expected number of bytes.".to_string())
+}
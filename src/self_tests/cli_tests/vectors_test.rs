@@ -0,0 +1,119 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use clap::Parser;
+use crate::cli::{Cli, Command, EXIT_TESTS_FAILED, EXIT_USAGE, vectors::run_to};
+use crate::self_tests::acvp::acvp_drbg::{parse_response, compare_response};
+use crate::self_tests::formats::*;
+use crate::self_tests::report::SelfTestReport;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "CLI-TESTS::vectors_test";
+
+/*  The files used by the tests, they are copied to a temporary directory with the names expected by the runners. */
+const HMAC_RSP: &str = include_str!("../cavp/vectors/drbgvectors_pr_false/HMAC_DRBG.rsp");
+const HASH_PROMPT: &str = include_str!("../acvp/vectors/hashDRBG_prompt.json");
+const HASH_EXPECTED: &str = include_str!("../acvp/vectors/hashDRBG_expected.json");
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    let dir = std::env::temp_dir().join(format!("drbg_cli_vectors_{}", std::process::id()));
+    let res = match fs::create_dir_all(&dir) {
+        Ok(()) => cavp_file(&dir) + acvp_files(&dir) + invalid_files(&dir),
+        Err(_) => check_res(false, true, "run_tests".to_string(), AL_NAME.to_string(),
+                            "the temporary directory could not be created.".to_string(), String::new()),
+    };
+    let _ = fs::remove_dir_all(&dir);
+
+    res
+}
+
+/*  Runs the 'vectors' command with the given arguments, returning its result and output. */
+fn run_vectors(args: &[&str]) -> (Result<(), u8>, String) {
+    match Cli::try_parse_from(["drbg", "vectors"].iter().chain(args.iter())) {
+        Ok(Cli { command: Some(Command::Vectors(args)) }) => {
+            let mut out = Vec::<u8>::new();
+            let res = run_to(&args, &mut out).map_err(|err| err.code);
+            (res, String::from_utf8(out).unwrap_or_default())
+        }
+        _ => (Err(EXIT_USAGE), String::new()),
+    }
+}
+
+/*  Returns the number of tests and failures of a JSON report. */
+fn report_counts(json: &str) -> Option<(usize, usize)> {
+    SelfTestReport::from_json(json).ok().map(|report| (report.total(), report.failures()))
+}
+
+/*  Writes a file in the temporary directory, returning its path. */
+fn write_file(dir: &Path, name: &str, content: &str) -> String {
+    let path: PathBuf = dir.join(name);
+    let _ = fs::write(&path, content);
+    path.display().to_string()
+}
+
+/*  Verifying that a CAVP response file is run and that a wrong ReturnedBits fails the command. */
+fn cavp_file(dir: &Path) -> usize {
+    let correct = write_file(dir, "HMAC_DRBG.rsp", HMAC_RSP);
+    let (res, out) = run_vectors(&[&correct, "--json"]);
+    let passed = (res, report_counts(&out).map(|(total, failures)| (total > 0, failures)));
+
+    let wrong_dir = dir.join("wrong");
+    let _ = fs::create_dir_all(&wrong_dir);
+    let bits_start = HMAC_RSP.find("ReturnedBits = ").map_or(0, |idx| idx + "ReturnedBits = ".len());
+    let flipped = if HMAC_RSP[bits_start..].starts_with('0') { "1" } else { "0" };
+    let wrong = write_file(&wrong_dir, "HMAC_DRBG.rsp", &format!("{}{}{}", &HMAC_RSP[..bits_start], flipped, &HMAC_RSP[bits_start + 1..]));
+    let (res, out) = run_vectors(&[&wrong, "--json"]);
+    let failed = (res, report_counts(&out).map(|(_, failures)| failures));
+
+    check_res((passed, failed), ((Ok(()), Some((true, 0))), (Err(EXIT_TESTS_FAILED), Some(1))),
+        "cavp_file".to_string(),
+        AL_NAME.to_string(),
+        "the CAVP response file was not run as expected.".to_string(),
+        "the CAVP response file was run and a wrong output was reported.".to_string())
+}
+
+/*  Verifying that an ACVP vector set is run, compared with the expected results and that its response is written. */
+fn acvp_files(dir: &Path) -> usize {
+    let prompt = write_file(dir, "hashDRBG_prompt.json", HASH_PROMPT);
+    let expected = write_file(dir, "hashDRBG_expected.json", HASH_EXPECTED);
+    let response_path = dir.join("hashDRBG_response.json");
+    let response = response_path.display().to_string();
+
+    let (res, out) = run_vectors(&[&prompt, "--expected", &expected, "--response", &response, "--json"]);
+    let compared = (res, report_counts(&out).map(|(total, failures)| (total > 0, failures)));
+    let written = match (fs::read_to_string(&response_path).ok(), parse_response(HASH_EXPECTED)) {
+        (Some(json), Ok(expected)) => parse_response(&json).ok().map(|response| compare_response(&response, &expected).is_empty()),
+        _ => None,
+    };
+    let (res, out) = run_vectors(&[&prompt, "--json"]);
+    let printed = (res, parse_response(&out).ok().map(|response| response.vs_id));
+
+    check_res((compared, written, printed), ((Ok(()), Some((true, 0))), Some(true), (Ok(()), Some(1001))),
+        "acvp_files".to_string(),
+        AL_NAME.to_string(),
+        "the ACVP vector set was not run as expected.".to_string(),
+        "the ACVP vector set was run, compared and its response was written.".to_string())
+}
+
+/*  Verifying that unsupported or missing files are rejected with the documented exit codes. */
+fn invalid_files(dir: &Path) -> usize {
+    let unnamed = write_file(dir, "vectors.rsp", HMAC_RSP);
+    let text = write_file(dir, "vectors.txt", HMAC_RSP);
+    let not_acvp = write_file(dir, "vectors.json", "{\"vsId\": 1}");
+    let missing = dir.join("missing.json").display().to_string();
+    let rsp = write_file(dir, "Hash_DRBG.rsp", "");
+
+    let res = [
+        run_vectors(&[&unnamed]).0,
+        run_vectors(&[&text]).0,
+        run_vectors(&[&not_acvp]).0,
+        run_vectors(&[&missing]).0,
+        run_vectors(&[&rsp, "--expected", &unnamed]).0,
+    ];
+
+    check_res(res, [Err(EXIT_USAGE), Err(EXIT_USAGE), Err(EXIT_USAGE), Err(1), Err(EXIT_USAGE)],
+        "invalid_files".to_string(),
+        AL_NAME.to_string(),
+        "invalid vector files were not rejected as expected.".to_string(),
+        "invalid vector files were rejected with the expected exit codes.".to_string())
+}
@@ -0,0 +1,47 @@
+use crate::mechs::derivation::{block_cipher_df, MAX_BLOCK_CIPHER_DF_BYTES};
+use crate::self_tests::formats::*;
+use crate::self_tests::constants::*;
+use aes::cipher::{BlockCipher, BlockEncrypt, KeyInit};
+use aes::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "DERIVATION-TESTS::block_cipher_df_test";
+
+/*  Expected outputs of Block_Cipher_df over ENTROPY || NONCE for seedlen bytes (keylen + blocklen). */
+const KAT_AES_128: &str = "effc9c728b1e9646aee7c7ba0b4c53fad46691a3f18735edc33a863394d13f2b";
+const KAT_AES_192: &str = "612e73c7265a473ad2e849f2d16980f5ce9a354456788993d879962af8ce00eb463c6f3fa365d576";
+const KAT_AES_256: &str = "808cde01c4462fc285d01b4cf508e2573a4958c7a17aacf1a05be25be9e4004285b80a24a2c55645e0443ea6633a9390";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    kat::<Aes128>("kat_aes_128", 32, KAT_AES_128) +
+        kat::<Aes192>("kat_aes_192", 40, KAT_AES_192) +
+        kat::<Aes256>("kat_aes_256", 48, KAT_AES_256) +
+        too_many_bytes()
+}
+
+/*  Verifying the output of Block_Cipher_df against a known answer. */
+fn kat<C: BlockCipher + BlockEncrypt + KeyInit>(test_name: &str, num_bytes: usize, expected: &str) -> usize {
+    let mut input = ENTROPY.to_vec();
+    input.extend_from_slice(&NONCE);
+
+    let res = block_cipher_df::<C>(&input, num_bytes);
+
+    check_res(res, Ok(hex::decode(expected).unwrap()),
+        test_name.to_string(),
+        AL_NAME.to_string(),
+        "Block_Cipher_df output does not match the known answer.".to_string(),
+        "Block_Cipher_df output matches the known answer.".to_string())
+}
+
+/*  Verifying that up to 512 bits are returned and that more bytes are refused with an error. */
+fn too_many_bytes() -> usize {
+    let res = (block_cipher_df::<Aes128>(&ENTROPY, MAX_BLOCK_CIPHER_DF_BYTES).map(|bytes| bytes.len()),
+               block_cipher_df::<Aes128>(&ENTROPY, MAX_BLOCK_CIPHER_DF_BYTES + 1));
+
+    check_res(res, (Ok(MAX_BLOCK_CIPHER_DF_BYTES), Err(1)),
+        "too_many_bytes".to_string(),
+        AL_NAME.to_string(),
+        "Block_Cipher_df accepted more than 512 bits.".to_string(),
+        "Block_Cipher_df refused more than 512 bits as expected.".to_string())
+}
@@ -0,0 +1,56 @@
+use crate::mechs::derivation::hash_df;
+use crate::self_tests::formats::*;
+use crate::self_tests::constants::*;
+use sha2::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "DERIVATION-TESTS::hash_df_test";
+
+/*  Expected outputs of Hash_df over ENTROPY || NONCE for seedlen bytes (55 for Sha 256, 111 for Sha 512). */
+const KAT_SHA_256: &str = "90a5e2dc38b795e34372758a747acd163c56b9e8925b22e064689b44b0fd06ce54e78477141dd1a591d31033c058d3b088c88ff4a416d8";
+const KAT_SHA_512: &str = "22e999df5a8b38032b7b96c285144f72134d68d42bea834d51c030acfa92b3f7bd3858304f0b4a570bfda1f9acda4d356938e731b62befb205734a3aaaf0636013971c1ed2c73ba987b0060874f5989937d0e408ba8fc73b49952845ce60046bd35bee9afb786f918f7cf291bfbc03";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    kat::<Sha256>("kat_sha_256", 55, KAT_SHA_256) +
+        kat::<Sha512>("kat_sha_512", 111, KAT_SHA_512) +
+        max_output::<Sha256>(32) +
+        too_many_bytes::<Sha256>(32) +
+        too_many_bytes::<Sha512>(64)
+}
+
+/*  Verifying the output of Hash_df against a known answer. */
+fn kat<D: digest::Update + digest::FixedOutput + digest::Reset + Default>(test_name: &str, num_bytes: usize, expected: &str) -> usize {
+    let mut input = ENTROPY.to_vec();
+    input.extend_from_slice(&NONCE);
+
+    let res = hash_df::<D>(&input, num_bytes);
+
+    check_res(res, Ok(hex::decode(expected).unwrap()),
+        test_name.to_string(),
+        AL_NAME.to_string(),
+        "Hash_df output does not match the known answer.".to_string(),
+        "Hash_df output matches the known answer.".to_string())
+}
+
+/*  Verifying that exactly 255 * outlen bytes can be requested. */
+fn max_output<D: digest::Update + digest::FixedOutput + digest::Reset + Default>(outlen: usize) -> usize {
+    let res = hash_df::<D>(&ENTROPY, 255 * outlen);
+
+    check_res(res.map(|bytes| bytes.len()), Ok(255 * outlen),
+        "max_output".to_string(),
+        AL_NAME.to_string(),
+        "Hash_df failed to return 255 * outlen bytes.".to_string(),
+        "Hash_df returned 255 * outlen bytes as expected.".to_string())
+}
+
+/*  Verifying that requesting more than 255 * outlen bytes is refused with an error. */
+fn too_many_bytes<D: digest::Update + digest::FixedOutput + digest::Reset + Default>(outlen: usize) -> usize {
+    let res = hash_df::<D>(&ENTROPY, 255 * outlen + 1);
+
+    check_res(res, Err(1),
+        "too_many_bytes".to_string(),
+        AL_NAME.to_string(),
+        "Hash_df succeeded to return more than 255 * outlen bytes.".to_string(),
+        "Hash_df refused to return more than 255 * outlen bytes as expected.".to_string())
+}
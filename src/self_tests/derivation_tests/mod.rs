@@ -0,0 +1,3 @@
+pub mod hash_df_test;
+pub mod block_cipher_df_test;
+pub mod run_all;
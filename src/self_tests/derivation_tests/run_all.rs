@@ -0,0 +1,7 @@
+use super::*;
+
+/*  Runs the known-answer tests of the standalone derivation functions (see 'mechs/derivation'). */
+pub fn run_tests() -> usize {
+    hash_df_test::run_tests() +
+        block_cipher_df_test::run_tests()
+}
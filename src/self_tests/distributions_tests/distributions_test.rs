@@ -0,0 +1,227 @@
+use std::collections::BTreeMap;
+use sha2::Sha256;
+use crate::drbg::distributions::{Distribution_Functions, INVALID_ARGUMENT};
+use crate::drbg::entropy::FixedEntropySource;
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::mechs::hmac_mech::HmacDrbgMech;
+use crate::self_tests::formats::*;
+use crate::stats::special::igamc;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "DISTRIBUTIONS-TESTS::distributions_test";
+
+/*  The significance level of the chi-square tests: with a deterministic entropy source the outcome of each test is
+    fixed, the level only tells how unlikely the observed counts have to be for the draws to be considered biased. */
+const ALPHA: f64 = 0.001;
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    range() +
+        below() +
+        long_bound() +
+        shuffle() +
+        sample() +
+        choose() +
+        errors()
+}
+
+/*  Returns a deterministic instance, whose entropy source provides enough strings for the reseeds of the tests. */
+fn fixed_drbg(test: &str) -> Option<DRBG<HmacDrbgMech<Sha256>>> {
+    let chunks = (0..=255u8).map(|i| vec![i; 48]).collect();
+    let res = DRBG::<HmacDrbgMech<Sha256>>::new_with_entropy(32, Some(test.as_bytes()), Box::new(FixedEntropySource::new(chunks))).ok();
+    if res.is_none() {
+        write_to_log(format_message(true, AL_NAME.to_string(), test.to_string(), "failed to instantiate DRBG.".to_string()));
+    }
+    res
+}
+
+/*  Returns the p-value of the chi-square test of the given counts against a uniform distribution over cells cells
+    (the cells that were never hit are not in counts). */
+fn chi_square_p<K>(counts: &BTreeMap<K, usize>, cells: usize) -> f64 {
+    let total: usize = counts.values().sum();
+    let expected = total as f64 / cells as f64;
+    let missing = (cells - counts.len()) as f64 * expected;
+    let chi2 = counts.values().map(|count| (*count as f64 - expected).powi(2) / expected).sum::<f64>() + missing;
+
+    igamc((cells - 1) as f64 / 2.0, chi2 / 2.0)
+}
+
+/*  Counts the occurrences of each value. */
+fn count<K: Ord, I: IntoIterator<Item = K>>(values: I) -> BTreeMap<K, usize> {
+    let mut counts = BTreeMap::new();
+    for value in values {
+        *counts.entry(value).or_insert(0) += 1;
+    }
+    counts
+}
+
+/*  Verifying that the integers of a range are uniform, also when reducing the output modulo the size of the range would
+    be strongly biased (which the chi-square test detects). */
+fn range() -> usize {
+    let mut drbg = match fixed_drbg("range") {
+        Some(drbg) => drbg,
+        None => return 1,
+    };
+
+    let small: Result<Vec<u64>, usize> = (0..10000).map(|_| drbg.gen_range(10, 20)).collect();
+    let small = small.unwrap_or_default();
+
+    // With a range of 3 * 2^62, x mod range falls in the first quarter of the range for half of the 64-bit values.
+    let range = 3u64 << 62;
+    let large: Result<Vec<u64>, usize> = (0..3000).map(|_| drbg.gen_range(0, range)).collect();
+    let large = large.unwrap_or_default();
+    let modulo: Vec<u64> = (0..3000).map(|_| {
+        let mut bytes = Vec::<u8>::new();
+        drbg.generate(&mut bytes, 8, 32, false, None);
+        let mut value = [0u8; 8];
+        value.copy_from_slice(&bytes);
+        u64::from_be_bytes(value) % range
+    }).collect();
+
+    let res = (small.len(), small.iter().all(|value| (10..20).contains(value)), chi_square_p(&count(small.iter()), 10) >= ALPHA,
+               large.len(), large.iter().all(|value| *value < range), chi_square_p(&count(large.iter().map(|value| value >> 62)), 3) >= ALPHA,
+               chi_square_p(&count(modulo.iter().map(|value| value >> 62)), 3) < ALPHA);
+
+    check_res(res, (10000, true, true, 3000, true, true, true),
+        "range".to_string(),
+        AL_NAME.to_string(),
+        "the integers of the range were not uniform.".to_string(),
+        "the integers of the range were uniform.".to_string())
+}
+
+/*  Verifying that the big integers below a bound are uniform, below the bound and of its length. */
+fn below() -> usize {
+    let mut drbg = match fixed_drbg("below") {
+        Some(drbg) => drbg,
+        None => return 1,
+    };
+
+    // A 16-byte bound of 5 * 2^112 (with a leading zero byte), the values are counted by their top bits.
+    let mut bound = vec![0u8; 16];
+    bound[1] = 0x05;
+    let values: Result<Vec<Vec<u8>>, usize> = (0..5000).map(|_| drbg.gen_below(&bound)).collect();
+    let values = values.unwrap_or_default();
+
+    let res = (values.len(), values.iter().all(|value| value.len() == 16 && value < &bound),
+               chi_square_p(&count(values.iter().map(|value| value[1])), 5) >= ALPHA,
+               drbg.gen_below(&[0x00, 0x01]), drbg.gen_below(&[0x00, 0x00]), drbg.gen_below(&[]));
+
+    check_res(res, (5000, true, true, Ok(vec![0x00, 0x00]), Err(INVALID_ARGUMENT), Err(INVALID_ARGUMENT)),
+        "below".to_string(),
+        AL_NAME.to_string(),
+        "the integers below the bound were not uniform.".to_string(),
+        "the integers below the bound were uniform.".to_string())
+}
+
+/*  Verifying that the integers below a bound longer than the maximum number of bytes per generate call (here 512 bytes,
+    as the modulus of an RSA-4096 key) are uniform, below the bound and of its length. */
+fn long_bound() -> usize {
+    let mut drbg = match fixed_drbg("long_bound") {
+        Some(drbg) => drbg,
+        None => return 1,
+    };
+
+    // A bound of 5 * 2^4088, the values are counted by their top byte.
+    let mut bound = vec![0u8; 512];
+    bound[0] = 0x05;
+    let values: Result<Vec<Vec<u8>>, usize> = (0..1000).map(|_| drbg.gen_below(&bound)).collect();
+    let values = values.unwrap_or_default();
+
+    let res = (512 > drbg.get_max_pbr(), values.len(), values.iter().all(|value| value.len() == 512 && value < &bound),
+               chi_square_p(&count(values.iter().map(|value| value[0])), 5) >= ALPHA,
+               values.iter().all(|value| value[256..] != value[..256]));
+
+    check_res(res, (true, 1000, true, true, true),
+        "long_bound".to_string(),
+        AL_NAME.to_string(),
+        "the integers below a long bound were not uniform.".to_string(),
+        "the integers below a long bound were uniform.".to_string())
+}
+
+/*  Verifying that every permutation is equally likely. */
+fn shuffle() -> usize {
+    let mut drbg = match fixed_drbg("shuffle") {
+        Some(drbg) => drbg,
+        None => return 1,
+    };
+
+    let mut res_shuffles = Ok(());
+    let permutations: Vec<[u8; 4]> = (0..4800).map(|_| {
+        let mut items = [1u8, 2, 3, 4];
+        res_shuffles = res_shuffles.and(drbg.shuffle(&mut items));
+        items
+    }).collect();
+    let mut empty: [u8; 0] = [];
+
+    let res = (res_shuffles, permutations.iter().all(|items| { let mut sorted = *items; sorted.sort(); sorted == [1, 2, 3, 4] }),
+               chi_square_p(&count(permutations.iter()), 24) >= ALPHA, drbg.shuffle(&mut empty));
+
+    check_res(res, (Ok(()), true, true, Ok(())),
+        "shuffle".to_string(),
+        AL_NAME.to_string(),
+        "the permutations were not uniform.".to_string(),
+        "the permutations were uniform.".to_string())
+}
+
+/*  Verifying that the samples are made of distinct items and that every ordered sample is equally likely. */
+fn sample() -> usize {
+    let mut drbg = match fixed_drbg("sample") {
+        Some(drbg) => drbg,
+        None => return 1,
+    };
+
+    let items = ['a', 'b', 'c', 'd', 'e'];
+    let samples: Result<Vec<Vec<&char>>, usize> = (0..4000).map(|_| drbg.sample(&items, 2)).collect();
+    let samples = samples.unwrap_or_default();
+
+    let res = (samples.len(), samples.iter().all(|sample| sample.len() == 2 && sample[0] != sample[1]),
+               chi_square_p(&count(samples.iter()), 20) >= ALPHA,
+               drbg.sample(&items, 5).map(|sample| count(sample).len()), drbg.sample(&items, 0).map(|sample| sample.len()),
+               drbg.sample(&items, 6).err());
+
+    check_res(res, (4000, true, true, Ok(5), Ok(0), Some(INVALID_ARGUMENT)),
+        "sample".to_string(),
+        AL_NAME.to_string(),
+        "the samples were not uniform.".to_string(),
+        "the samples were uniform.".to_string())
+}
+
+/*  Verifying that the items are chosen uniformly. */
+fn choose() -> usize {
+    let mut drbg = match fixed_drbg("choose") {
+        Some(drbg) => drbg,
+        None => return 1,
+    };
+
+    let items = [3u16, 1, 4, 15, 9, 2, 6];
+    let chosen: Result<Vec<&u16>, usize> = (0..7000).map(|_| drbg.choose(&items)).collect();
+    let chosen = chosen.unwrap_or_default();
+    let empty: [u16; 0] = [];
+
+    let res = (chosen.len(), chi_square_p(&count(chosen), 7) >= ALPHA, drbg.choose(&empty).err());
+
+    check_res(res, (7000, true, Some(INVALID_ARGUMENT)),
+        "choose".to_string(),
+        AL_NAME.to_string(),
+        "the items were not chosen uniformly.".to_string(),
+        "the items were chosen uniformly.".to_string())
+}
+
+/*  Verifying that invalid ranges are rejected and that the errors of the DRBG are returned. */
+fn errors() -> usize {
+    let mut drbg = match fixed_drbg("errors") {
+        Some(drbg) => drbg,
+        None => return 1,
+    };
+
+    let valid = (drbg.gen_range(7, 8), drbg.gen_range(8, 8).err(), drbg.gen_range(9, 8).err(),
+                 drbg.gen_range(0, u64::MAX).is_ok());
+    drbg.uninstantiate();
+    let failed = (drbg.gen_range(0, 10).err(), drbg.choose(&[1, 2]).err(), drbg.shuffle(&mut [1, 2]).err());
+
+    check_res((valid, failed), ((Ok(7), Some(INVALID_ARGUMENT), Some(INVALID_ARGUMENT), true), (Some(1), Some(1), Some(1))),
+        "errors".to_string(),
+        AL_NAME.to_string(),
+        "the errors were not reported.".to_string(),
+        "the errors were reported.".to_string())
+}
@@ -0,0 +1,2 @@
+pub mod distributions_test;
+pub mod run_all;
@@ -0,0 +1,6 @@
+use super::*;
+
+/*  Runs the tests of the unbiased random values drawn from a DRBG (see 'drbg/distributions.rs'). */
+pub fn run_tests() -> usize {
+    distributions_test::run_tests()
+}
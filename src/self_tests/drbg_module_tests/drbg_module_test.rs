@@ -0,0 +1,121 @@
+use aes::Aes128;
+use sha2::{Sha256, Sha512};
+use crate::drbg::drbg_conf::MAX_INSTANCES;
+use crate::drbg::drbg_module::DrbgModule;
+use crate::drbg::entropy::FixedEntropySource;
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::mechs::ctr_mech_with_df::CtrDrbgMech_DF;
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use crate::mechs::hash_mech::HashDrbgMech;
+use crate::mechs::hmac_mech::HmacDrbgMech;
+use crate::self_tests::formats::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "DRBG-MODULE-TESTS::drbg_module_test";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    handles() +
+        max_instances() +
+        bulk_uninstantiate() +
+        same_output()
+}
+
+/*  Verifying that instances of different mechanisms are used through their handles and enumerated, and that the handles
+    of uninstantiated instances are no longer valid. */
+fn handles() -> usize {
+    let mut module = DrbgModule::default();
+    let hash = module.instantiate::<HashDrbgMech<Sha256>>(32, None);
+    let ctr = module.instantiate::<CtrDrbgMech_DF<Aes128>>(16, Some(b"module"));
+    let (hash, ctr) = match (hash, ctr) {
+        (Ok(hash), Ok(ctr)) => (hash, ctr),
+        _ => {
+            write_to_log(format_message(true, AL_NAME.to_string(), "handles".to_string(),
+                                        "failed to instantiate DRBG.".to_string()));
+            return 1;
+        }
+    };
+
+    let mut bytes = Vec::<u8>::new();
+    let generated = (module.generate(hash, &mut bytes, 32, 32, false, None), bytes.len(),
+                     module.generate(ctr, &mut bytes, 16, 16, true, None), module.reseed(ctr, None),
+                     module.generate(ctr, &mut bytes, 1000, 16, false, None));
+    let instances: Vec<(String, usize, usize)> = module.instances().into_iter()
+        .map(|info| (info.mechanism, info.security_strength, info.count)).collect();
+
+    let released = (module.uninstantiate(hash), module.uninstantiate(hash), module.generate(hash, &mut bytes, 32, 32, false, None),
+                    module.reseed(hash, None), bytes.is_empty(), module.handles() == vec![ctr], module.info(hash).is_none());
+
+    check_res((generated, instances, released, module.max_instances()),
+              ((0, 32, 0, 0, 2), vec![(HashDrbgMech::<Sha256>::mech_id(), 32, 2), (CtrDrbgMech_DF::<Aes128>::mech_id(), 16, 1)],
+               (0, 1, 1, 1, true, true, true), MAX_INSTANCES),
+        "handles".to_string(),
+        AL_NAME.to_string(),
+        "the instances of the module were not used through their handles.".to_string(),
+        "the instances of the module were used through their handles.".to_string())
+}
+
+/*  Verifying that the number of instances is bounded and that the internal states are released by uninstantiate and by
+    failed instantiations. */
+fn max_instances() -> usize {
+    let mut module = DrbgModule::new(2);
+    let first = module.instantiate::<HmacDrbgMech<Sha512>>(32, None);
+    let invalid = module.instantiate::<HmacDrbgMech<Sha512>>(64, None).err();
+    let second = module.instantiate::<HmacDrbgMech<Sha256>>(32, None);
+    let full = module.instantiate::<HmacDrbgMech<Sha256>>(32, None).err();
+    let inserted = DRBG::<HmacDrbgMech<Sha256>>::new(32, None).map(|drbg| module.insert(drbg).is_err());
+
+    let reused = match first {
+        Ok(first) => module.uninstantiate(first) == 0 && module.instantiate::<HmacDrbgMech<Sha256>>(32, None).is_ok_and(|handle| handle != first),
+        Err(_) => false,
+    };
+
+    check_res((second.is_ok(), invalid, full, inserted, reused, module.len()), (true, Some(1), Some(8), Ok(true), true, 2),
+        "max_instances".to_string(),
+        AL_NAME.to_string(),
+        "the number of instances of the module was not bounded.".to_string(),
+        "the number of instances of the module was bounded.".to_string())
+}
+
+/*  Verifying that all the instances are uninstantiated at once and that handles are only valid in their module. */
+fn bulk_uninstantiate() -> usize {
+    let (mut module, mut other) = (DrbgModule::new(4), DrbgModule::new(4));
+    let handles: Vec<_> = (0..3).filter_map(|_| module.instantiate::<HashDrbgMech<Sha512>>(32, None).ok()).collect();
+    let foreign = other.instantiate::<HashDrbgMech<Sha512>>(32, None);
+
+    let mut bytes = Vec::<u8>::new();
+    let foreign_use = foreign.map(|handle| (module.generate(handle, &mut bytes, 16, 32, false, None), module.uninstantiate(handle)));
+    let res = (handles.len(), foreign_use, module.uninstantiate_all(), module.is_empty(),
+               handles.iter().all(|handle| module.generate(*handle, &mut bytes, 16, 32, false, None) == 1), other.len());
+
+    check_res(res, (3, Ok((1, 1)), 3, true, true, 1),
+        "bulk_uninstantiate".to_string(),
+        AL_NAME.to_string(),
+        "the instances of the module were not uninstantiated at once.".to_string(),
+        "the instances of the module were uninstantiated at once.".to_string())
+}
+
+/*  Verifying that an instance held by the module generates the same bytes as the same instance used directly. */
+fn same_output() -> usize {
+    let entropy = || Box::new(FixedEntropySource::new(vec![vec![0x3c; 32]; 2]));
+    let mut module = DrbgModule::default();
+    let handle = module.instantiate_with_entropy::<HmacDrbgMech<Sha256>>(32, Some(b"pers"), entropy());
+    let drbg = DRBG::<HmacDrbgMech<Sha256>>::new_with_entropy(32, Some(b"pers"), entropy());
+
+    let res = match (handle, drbg) {
+        (Ok(handle), Ok(mut drbg)) => {
+            let (mut bytes, mut expected) = (Vec::<u8>::new(), Vec::<u8>::new());
+            (0..3).all(|_| {
+                module.generate(handle, &mut bytes, 64, 32, false, Some(b"add")) == 0 &&
+                    drbg.generate(&mut expected, 64, 32, false, Some(b"add")) == 0 && bytes == expected
+            })
+        }
+        _ => false,
+    };
+
+    check_res(res, true,
+        "same_output".to_string(),
+        AL_NAME.to_string(),
+        "the instance held by the module did not match the direct instance.".to_string(),
+        "the instance held by the module matched the direct instance.".to_string())
+}
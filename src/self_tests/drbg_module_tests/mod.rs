@@ -0,0 +1,2 @@
+pub mod drbg_module_test;
+pub mod run_all;
@@ -0,0 +1,6 @@
+use super::*;
+
+/*  Runs the tests of the registry of DRBG instances (see 'drbg/drbg_module.rs'). */
+pub fn run_tests() -> usize {
+    drbg_module_test::run_tests()
+}
@@ -0,0 +1,149 @@
+use crate::drbg::buffered::BufferedDrbg;
+use crate::drbg::entropy::FixedEntropySource;
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::drbg::reseed_policy::ReseedPolicy;
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use crate::self_tests::formats::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "DRBG_TESTS::buffered_test";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    same_stream::<T>(strength) +
+        bypass::<T>(strength, true) +
+        bypass::<T>(strength, false) +
+        discarded::<T>(strength) +
+        policy_limits::<T>(strength)
+}
+
+/*  Returns a deterministic instance whose entropy source provides the instantiation inputs and extra more strings. */
+fn fixed_drbg<T: DRBG_Mechanism_Functions + 'static>(strength: usize, extra: usize) -> Option<DRBG<T>> {
+    let needed = if T::drbg_name() == "CTR-DRBG" { 1 } else { 2 };
+    DRBG::<T>::new_with_entropy(strength, None, Box::new(FixedEntropySource::new(vec![vec![0x3c; 48]; needed + extra]))).ok()
+}
+
+/*  Logs the failure of a test whose instance could not be created. */
+fn instantiation_failed(test: &str) -> usize {
+    write_to_log(format_message(true, AL_NAME.to_string(), test.to_string(), "failed to instantiate DRBG.".to_string()));
+    1
+}
+
+/*  Verifying that small requests are served with the bytes of consecutive blocks, each one generated by generate calls
+    of at most MAX_PRB bytes. */
+fn same_stream<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let (mut buffered, mut drbg) = match (fixed_drbg::<T>(strength, 0), fixed_drbg::<T>(strength, 0)) {
+        (Some(first), Some(second)) => (BufferedDrbg::new(first, 300), second),
+        _ => return instantiation_failed("same_stream"),
+    };
+
+    let mut served = Vec::<u8>::new();
+    let mut nonce = [0u8; 16];
+    let mut res = 0;
+    for _ in 0..40 {
+        res += buffered.generate(&mut nonce, strength, false, None);
+        served.extend_from_slice(&nonce);
+    }
+
+    let mut expected = Vec::<u8>::new();
+    let mut bytes = Vec::<u8>::new();
+    for len in [256, 44, 256, 44, 256, 44] {
+        drbg.generate(&mut bytes, len, strength, false, None);
+        expected.extend_from_slice(&bytes);
+    }
+    expected.truncate(served.len());
+
+    check_res((res, served == expected, buffered.buffered(), buffered.get_ref().get_count()), (0, true, 260, 7),
+        "same_stream".to_string(),
+        AL_NAME.to_string(),
+        "the buffered requests did not match the output of the DRBG.".to_string(),
+        "the buffered requests matched the output of the DRBG.".to_string())
+}
+
+/*  Verifying that a request with additional input is never served from an old block: it either bypasses the block or
+    is served from a new block generated with the additional input. */
+fn bypass<T: DRBG_Mechanism_Functions + 'static>(strength: usize, bypass: bool) -> usize {
+    // The CTR-DRBG without DF pads the additional input with bytes of the entropy source.
+    let (mut buffered, mut drbg) = match (fixed_drbg::<T>(strength, 1), fixed_drbg::<T>(strength, 1)) {
+        (Some(first), Some(second)) => (BufferedDrbg::new(first, 64), second),
+        _ => return instantiation_failed("bypass"),
+    };
+    buffered.set_bypass(bypass);
+
+    let (mut first, mut second, mut third) = ([0u8; 16], [0u8; 16], [0u8; 16]);
+    let res = (buffered.generate(&mut first, strength, false, None),
+               buffered.generate(&mut second, strength, false, Some(b"nonce")),
+               buffered.buffered(),
+               buffered.generate(&mut third, strength, false, None));
+
+    // The same requests through the DRBG.
+    let mut bytes = Vec::<u8>::new();
+    let mut expected = Vec::<u8>::new();
+    drbg.generate(&mut bytes, 64, strength, false, None);
+    expected.extend_from_slice(&bytes[..16]);
+    if bypass {
+        drbg.generate(&mut bytes, 16, strength, false, Some(b"nonce"));
+        expected.extend_from_slice(&bytes);
+        drbg.generate(&mut bytes, 64, strength, false, None);
+        expected.extend_from_slice(&bytes[..16]);
+    }
+    else {
+        drbg.generate(&mut bytes, 64, strength, false, Some(b"nonce"));
+        expected.extend_from_slice(&bytes[..32]);
+    }
+
+    check_res((res, buffered.get_bypass(), [first, second, third].concat() == expected),
+              ((0, 0, if bypass { 0 } else { 48 }, 0), bypass, true),
+        format!("bypass ({})", bypass),
+        AL_NAME.to_string(),
+        "a request with additional input was served from an old block.".to_string(),
+        "the request with additional input was served from fresh bytes.".to_string())
+}
+
+/*  Verifying that the block is discarded on reseed and uninstantiate, and that large or unsupported requests are
+    passed to the instance. */
+fn discarded<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut buffered = match DRBG::<T>::new(strength, None) {
+        Ok(drbg) => BufferedDrbg::new(drbg, 64),
+        Err(_) => return instantiation_failed("discarded"),
+    };
+
+    let (mut nonce, mut large) = ([0u8; 16], [0u8; 100]);
+    let res = (buffered.generate(&mut nonce, strength, false, None), buffered.buffered(),
+               buffered.reseed(None), buffered.buffered(),
+               buffered.prefetch(), buffered.buffered(),
+               buffered.generate(&mut large, strength, false, None), buffered.buffered(),
+               buffered.generate(&mut nonce, strength + 1, false, None),
+               buffered.uninstantiate(), buffered.buffered(),
+               buffered.generate(&mut nonce, strength, false, None));
+
+    check_res(res, (0, 48, 0, 0, 0, 64, 0, 64, 3, 0, 0, 1),
+        "discarded".to_string(),
+        AL_NAME.to_string(),
+        "the block was not discarded or a request was not passed to the DRBG.".to_string(),
+        "the block was discarded and the requests were passed to the DRBG.".to_string())
+}
+
+/*  Verifying that the blocks are bounded by the max_bytes of the reseed policy and that a block is discarded once the
+    max_duration of the policy has elapsed since its seed. */
+fn policy_limits<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let policy = ReseedPolicy { max_bytes: Some(40), max_duration: Some(std::time::Duration::from_millis(50)), ..ReseedPolicy::default() };
+    let mut buffered = match DRBG::<T>::new(strength, None) {
+        Ok(mut drbg) => {
+            drbg.set_reseed_policy(policy);
+            BufferedDrbg::new(drbg, 256)
+        }
+        Err(_) => return instantiation_failed("policy_limits"),
+    };
+
+    let mut nonce = [0u8; 16];
+    let first = (buffered.block_size(), buffered.generate(&mut nonce, strength, false, None), buffered.buffered());
+    std::thread::sleep(std::time::Duration::from_millis(60));
+    let expired = (buffered.generate(&mut nonce, strength, false, None), buffered.buffered());
+
+    check_res((first, expired), ((40, 0, 24), (0, 24)),
+        "policy_limits".to_string(),
+        AL_NAME.to_string(),
+        "the block exceeded the limits of the reseed policy.".to_string(),
+        "the block was bounded by the limits of the reseed policy.".to_string())
+}
@@ -0,0 +1,21 @@
+use crate::drbg::health::run_health_tests;
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use crate::self_tests::formats::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "DRBG_TESTS::health_test";
+
+/*  Aggregator that runs all the tests in this file. The tests that change the configuration of the health tests or the
+    state of the module are in 'health_tests', which are not part of the first use and on-demand self-tests. */
+pub fn run_tests<T: DRBG_Mechanism_Functions + 'static>() -> usize {
+    known_answer::<T>()
+}
+
+/*  Verifying that the health tests of the mechanism pass. */
+fn known_answer<T: DRBG_Mechanism_Functions + 'static>() -> usize {
+    check_res(run_health_tests::<T>(), 0,
+        "known_answer".to_string(),
+        AL_NAME.to_string(),
+        "the health tests of the mechanism failed.".to_string(),
+        "the health tests of the mechanism passed.".to_string())
+}
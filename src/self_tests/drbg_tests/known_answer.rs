@@ -0,0 +1,171 @@
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::drbg::entropy::FixedEntropySource;
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use crate::self_tests::formats::*;
+use crate::self_tests::mech_tests::nist_vectors;
+use serde::Deserialize;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "DRBG_TESTS::known_answer_test";
+
+/*  General structure of a NIST vector, both prr and no_prr files are supported. */
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct Fixture {
+    entropy: Option<String>,
+    nonce: Option<String>,
+    pers: Option<String>,
+    entropy_reseed: Option<String>,
+    add_in_reseed: Option<String>,
+    add_in_gen: Option<String>,
+    entropy_pr: Option<String>,
+    add_in_gen2: Option<String>,
+    entropy_pr2: Option<String>,
+    expected: Option<String>,
+}
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let (prr_file, no_prr_file) = match nist_vectors::fun_id::<T>() {
+        None => return 1,
+        Some(fun_id) => nist_vectors::get_files::<T>(fun_id),
+    };
+
+    entropy_source_failure::<T>(strength) +
+        test_vectors::<T>(no_prr_file, false, strength) +
+        test_vectors::<T>(prr_file, true, strength)
+}
+
+/*  Verifying that a failure of the entropy source is reported by every DRBG function that needs entropy. */
+fn entropy_source_failure<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    // Enough entropy for the instantiation (entropy input and eventual nonce) and a single reseed.
+    let needed = if T::drbg_name() == "CTR-DRBG" { 2 } else { 3 };
+    let chunks = vec![vec![0x5a; 48]; needed];
+    let res_new = DRBG::<T>::new_with_entropy(strength, None, Box::new(FixedEntropySource::new(Vec::new())));
+
+    let (res_reseed, res_gen) = match DRBG::<T>::new_with_entropy(strength, None, Box::new(FixedEntropySource::new(chunks))) {
+        Err(_) => (None, None),
+        Ok(mut drbg) => {
+            // Consuming the last chunk so that the source is empty.
+            drbg.reseed(None);
+            let mut bytes = Vec::<u8>::new();
+            (Some(drbg.reseed(None)), Some(drbg.generate(&mut bytes, 16, strength, true, None)))
+        }
+    };
+
+    check_res((res_new.is_err_and(|err| err == 3), res_reseed, res_gen), (true, Some(3), Some(5)),
+        "entropy_source_failure".to_string(),
+        AL_NAME.to_string(),
+        "a failure of the entropy source was not reported.".to_string(),
+        "failures of the entropy source were reported as expected.".to_string())
+}
+
+/*  Runs the NIST vectors through the DRBG envelope. The entropy inputs, nonces and reseed entropy of each vector are
+    injected through a FixedEntropySource, in the same order in which the DRBG requests them.
+
+    The DRBG only accepts personalization strings and additional inputs of up to security_strength bytes. For the
+    CTR-DRBG without DF, the longer inputs of the vectors are split: the first security_strength bytes are passed to the
+    DRBG and the rest is injected as the random padding that the DRBG appends to them. For the other mechanisms, vectors
+    with longer inputs are not applicable and are skipped. */
+fn test_vectors<T: DRBG_Mechanism_Functions + 'static>(file: &str, prr: bool, strength: usize) -> usize {
+    let test_name = if prr { "test_vectors_prr" } else { "test_vectors_no_prr" };
+    let tests: Vec<Fixture> = serde_json::from_str(file).unwrap_or_default();
+    let mut passed = 0;
+    let mut failed = 0;
+    let mut skipped = 0;
+
+    for test in tests.iter() {
+        match run_vector::<T>(test, prr, strength) {
+            None => skipped += 1,
+            Some(true) => passed += 1,
+            Some(false) => failed += 1,
+        }
+    }
+
+    check_res((failed, passed > 0), (0, true),
+        test_name.to_string(),
+        AL_NAME.to_string(),
+        format!("{} nist vectors failed through the DRBG envelope ({} passed, {} not applicable).", failed, passed, skipped),
+        format!("{} nist vectors passed through the DRBG envelope ({} not applicable).", passed, skipped))
+}
+
+/*  Runs a single vector, returns None if the vector is not applicable to the DRBG envelope. */
+fn run_vector<T: DRBG_Mechanism_Functions + 'static>(test: &Fixture, prr: bool, strength: usize) -> Option<bool> {
+    let decode = |value: &Option<String>| hex::decode(value.as_deref().unwrap_or("")).unwrap_or_default();
+    let no_df = T::drbg_name() == "CTR-DRBG";
+    let expected = decode(&test.expected);
+    let mut chunks = Vec::<Vec<u8>>::new();
+
+    // Instantiation: padding of the personalization string, entropy input and nonce.
+    let pers = split_input(&decode(&test.pers), strength, no_df, &mut chunks)?;
+    chunks.push(decode(&test.entropy));
+    if !no_df {
+        chunks.push(decode(&test.nonce));
+    }
+
+    // Reseed and generate calls: padding of the additional input followed by the entropy input (if needed).
+    let mut calls = Vec::<(bool, Option<Vec<u8>>)>::new();
+    if prr {
+        for (add, entropy) in [(&test.add_in_gen, &test.entropy_pr), (&test.add_in_gen2, &test.entropy_pr2)] {
+            let add = split_input(&decode(add), strength, no_df, &mut chunks)?;
+            chunks.push(decode(entropy));
+            calls.push((true, add));
+        }
+    }
+    else {
+        let add_reseed = split_input(&decode(&test.add_in_reseed), strength, no_df, &mut chunks)?;
+        chunks.push(decode(&test.entropy_reseed));
+        calls.push((false, add_reseed));
+        for add in [&test.add_in_gen, &test.add_in_gen2] {
+            let add = split_input(&decode(add), strength, no_df, &mut chunks)?;
+            calls.push((true, add));
+        }
+    }
+
+    let mut drbg = match DRBG::<T>::new_with_entropy(strength, pers.as_deref(), Box::new(FixedEntropySource::new(chunks))) {
+        Err(_) => return Some(false),
+        Ok(drbg) => drbg,
+    };
+
+    let mut result = Vec::<u8>::new();
+    for (is_generate, add) in calls {
+        let res = if is_generate {
+            drbg.generate(&mut result, expected.len(), strength, prr, add.as_deref())
+        }
+        else {
+            drbg.reseed(add.as_deref())
+        };
+
+        if res != 0 {
+            return Some(false);
+        }
+    }
+
+    Some(result == expected)
+}
+
+/*  Prepares a personalization string or additional input to be passed to the DRBG, eventually queueing the padding that
+    the DRBG will request for it.
+
+    Return values:
+        - None: the input cannot be passed to the DRBG (too long)
+        - Some(None): the input is empty and is not passed to the DRBG
+        - Some(Some(input)): the input to be passed to the DRBG */
+fn split_input(input: &[u8], strength: usize, no_df: bool, chunks: &mut Vec<Vec<u8>>) -> Option<Option<Vec<u8>>> {
+    if input.is_empty() {
+        return Some(None);
+    }
+
+    if input.len() <= strength && !no_df {
+        return Some(Some(input.to_vec()));
+    }
+
+    if !no_df {
+        return None;
+    }
+
+    // The CTR-DRBG without DF pads the input to seedlen bytes with bytes taken from the entropy source.
+    let split = input.len().min(strength);
+    chunks.push(input[split..].to_vec());
+    Some(Some(input[..split].to_vec()))
+}
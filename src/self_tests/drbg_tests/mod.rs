@@ -2,4 +2,11 @@ pub mod instantiation;
 pub mod reseed;
 pub mod run_all;
 pub mod generate;
-pub mod uninstantiate;
\ No newline at end of file
+pub mod uninstantiate;
+pub mod known_answer;
+pub mod health_test;
+pub mod reader;
+pub mod persist;
+pub mod reseed_interval;
+pub mod reseed_policy;
+pub mod buffered;
\ No newline at end of file
@@ -0,0 +1,236 @@
+use sha2::Sha256;
+use crate::drbg::entropy::FixedEntropySource;
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::drbg::persist::{FileCounter, MemoryCounter, Monotonic_Counter_Functions, WrappingKey};
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use crate::mechs::hash_mech::HashDrbgMech;
+use crate::mechs::hmac_mech::HmacDrbgMech;
+use crate::self_tests::formats::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "DRBG_TESTS::persist_test";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    round_trip::<T>(strength) +
+        replay::<T>(strength) +
+        unbound_replay::<T>(strength) +
+        failed_counter::<T>(strength) +
+        tampering::<T>(strength) +
+        file_counter::<T>(strength)
+}
+
+/*  Returns a deterministic instance. */
+fn fixed_drbg<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> Option<DRBG<T>> {
+    let needed = if T::drbg_name() == "CTR-DRBG" { 1 } else { 2 };
+    DRBG::<T>::new_with_entropy(strength, None, Box::new(FixedEntropySource::new(vec![vec![0x6b; 48]; needed]))).ok()
+}
+
+/*  Logs the failure of a test whose instance could not be created. */
+fn instantiation_failed(test: &str) -> usize {
+    write_to_log(format_message(true, AL_NAME.to_string(), test.to_string(), "failed to instantiate DRBG.".to_string()));
+    1
+}
+
+/*  Verifying that a restored instance continues the output of the exported one, with both wrapping algorithms, and
+    that the exported instance is uninstantiated. */
+fn round_trip<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut res = Vec::new();
+    for key in [WrappingKey::aes_gcm(&[0x42; 32]), WrappingKey::aes_kw(&[0x24; 16])].into_iter().flatten() {
+        let (mut exported, mut reference) = match (fixed_drbg::<T>(strength), fixed_drbg::<T>(strength)) {
+            (Some(first), Some(second)) => (first, second),
+            _ => return instantiation_failed("round_trip"),
+        };
+
+        let (mut bytes, mut expected) = (Vec::<u8>::new(), Vec::<u8>::new());
+        exported.generate(&mut bytes, 32, strength, false, None);
+        reference.generate(&mut expected, 32, strength, false, None);
+
+        let blob = exported.export_state(&key, None);
+        let uninstantiated = exported.get_count() == 0 && exported.export_state(&key, None) == Err(1);
+        let restored = blob.ok().and_then(|blob| DRBG::<T>::import_state(&blob, &key, None).ok());
+
+        match restored {
+            None => res.push((uninstantiated, false, false)),
+            Some(mut restored) => {
+                restored.generate(&mut bytes, 64, strength, false, None);
+                reference.generate(&mut expected, 64, strength, false, None);
+                res.push((uninstantiated, bytes == expected,
+                          restored.get_count() == reference.get_count() && restored.get_sec_str() == reference.get_sec_str()));
+            }
+        }
+    }
+
+    check_res(res, vec![(true, true, true); 2],
+        "round_trip".to_string(),
+        AL_NAME.to_string(),
+        "the restored instance did not continue the output of the exported one.".to_string(),
+        "the restored instance continued the output of the exported one.".to_string())
+}
+
+/*  Verifying that a snapshot bound to a monotonic counter can be restored only once and only if it is the last one. */
+fn replay<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut drbg = match fixed_drbg::<T>(strength) {
+        Some(drbg) => drbg,
+        None => return instantiation_failed("replay"),
+    };
+    let key = match WrappingKey::aes_gcm(&[0x42; 16]) {
+        Some(key) => key,
+        None => return 1,
+    };
+    let mut counter = MemoryCounter::new(0);
+
+    let first = drbg.export_state(&key, Some(&mut counter)).unwrap_or_default();
+    let mut restored = DRBG::<T>::import_state(&first, &key, Some(&mut counter));
+    let replayed = DRBG::<T>::import_state(&first, &key, Some(&mut counter)).err();
+
+    let second = match restored.as_mut() {
+        Ok(restored) => restored.export_state(&key, Some(&mut counter)).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+    let unbound = DRBG::<T>::import_state(&second, &key, None).err();
+    let stale = DRBG::<T>::import_state(&first, &key, Some(&mut counter)).err();
+    let last = DRBG::<T>::import_state(&second, &key, Some(&mut counter)).is_ok();
+
+    check_res((restored.is_ok(), replayed, unbound, stale, last, counter.current()), (true, Some(4), Some(4), Some(4), true, Some(4)),
+        "replay".to_string(),
+        AL_NAME.to_string(),
+        "a snapshot was restored twice or out of order.".to_string(),
+        "the snapshots were restored only once and in order.".to_string())
+}
+
+/*  Verifying that, as documented, a snapshot exported without a counter can be restored more than once and that the
+    restored instances then generate the same output. */
+fn unbound_replay<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut drbg = match fixed_drbg::<T>(strength) {
+        Some(drbg) => drbg,
+        None => return instantiation_failed("unbound_replay"),
+    };
+    let key = match WrappingKey::aes_gcm(&[0x42; 16]) {
+        Some(key) => key,
+        None => return 1,
+    };
+
+    let blob = drbg.export_state(&key, None).unwrap_or_default();
+    let mut outputs = Vec::<Vec<u8>>::new();
+    for _ in 0..2 {
+        let mut bytes = Vec::<u8>::new();
+        if let Ok(mut restored) = DRBG::<T>::import_state(&blob, &key, None) {
+            restored.generate(&mut bytes, 32, strength, false, None);
+        }
+        outputs.push(bytes);
+    }
+
+    check_res((outputs[0].len(), outputs[0] == outputs[1]), (32, true),
+        "unbound_replay".to_string(),
+        AL_NAME.to_string(),
+        "a snapshot exported without a counter was not restored as documented.".to_string(),
+        "the snapshot exported without a counter was restored twice, as documented.".to_string())
+}
+
+/*  Monotonic counter that can be read but never advanced. */
+struct StuckCounter;
+
+impl Monotonic_Counter_Functions for StuckCounter {
+    fn current(&mut self) -> Option<u64> {
+        Some(7)
+    }
+
+    fn advance(&mut self) -> Option<u64> {
+        None
+    }
+}
+
+/*  Verifying that an export whose counter cannot be read or advanced fails and leaves the instance instantiated, and
+    that a failed export does not advance the counter. */
+fn failed_counter<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut drbg = match fixed_drbg::<T>(strength) {
+        Some(drbg) => drbg,
+        None => return instantiation_failed("failed_counter"),
+    };
+    let key = match WrappingKey::aes_gcm(&[0x42; 16]) {
+        Some(key) => key,
+        None => return 1,
+    };
+    let mut counter = MemoryCounter::new(u64::MAX);
+
+    let mut bytes = Vec::<u8>::new();
+    let res = (drbg.export_state(&key, Some(&mut StuckCounter)).err(),
+               drbg.export_state(&key, Some(&mut counter)).err(),
+               counter.current(),
+               drbg.generate(&mut bytes, 16, strength, false, None));
+
+    check_res(res, (Some(2), Some(2), Some(u64::MAX), 0),
+        "failed_counter".to_string(),
+        AL_NAME.to_string(),
+        "a failed export advanced the counter or uninstantiated the instance.".to_string(),
+        "the failed exports left the counter and the instance unchanged.".to_string())
+}
+
+/*  Verifying that modified blobs, wrong keys and snapshots of other mechanisms are rejected. */
+fn tampering<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut drbg = match fixed_drbg::<T>(strength) {
+        Some(drbg) => drbg,
+        None => return instantiation_failed("tampering"),
+    };
+    let (key, wrong_key, kw_key) = match (WrappingKey::aes_gcm(&[0x42; 24]), WrappingKey::aes_gcm(&[0x43; 24]), WrappingKey::aes_kw(&[0x42; 24])) {
+        (Some(key), Some(wrong_key), Some(kw_key)) => (key, wrong_key, kw_key),
+        _ => return 1,
+    };
+
+    let blob = drbg.export_state(&key, None).unwrap_or_default();
+    let mut modified = blob.clone();
+    if let Some(last) = modified.last_mut() {
+        *last ^= 0x01;
+    }
+
+    // Restoring the snapshot with another mechanism.
+    let other = if T::mech_id() == HmacDrbgMech::<Sha256>::mech_id() {
+        DRBG::<HashDrbgMech<Sha256>>::import_state(&blob, &key, None).err()
+    }
+    else {
+        DRBG::<HmacDrbgMech<Sha256>>::import_state(&blob, &key, None).err()
+    };
+
+    let res = (DRBG::<T>::import_state(&modified, &key, None).err(),
+               DRBG::<T>::import_state(&blob, &wrong_key, None).err(),
+               DRBG::<T>::import_state(&blob, &kw_key, None).err(),
+               DRBG::<T>::import_state(&blob[..3], &key, None).err(),
+               other,
+               WrappingKey::aes_kw(&[0x42; 20]).is_none(),
+               DRBG::<T>::import_state(&blob, &key, None).is_ok());
+
+    check_res(res, (Some(2), Some(2), Some(1), Some(1), Some(3), true, true),
+        "tampering".to_string(),
+        AL_NAME.to_string(),
+        "an invalid snapshot was restored.".to_string(),
+        "the invalid snapshots were rejected.".to_string())
+}
+
+/*  Verifying that a counter stored in a file keeps its value across instances of the counter (i.e. restarts). */
+fn file_counter<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut drbg = match fixed_drbg::<T>(strength) {
+        Some(drbg) => drbg,
+        None => return instantiation_failed("file_counter"),
+    };
+    let key = match WrappingKey::aes_kw(&[0x42; 32]) {
+        Some(key) => key,
+        None => return 1,
+    };
+
+    let name = T::mech_id().replace(' ', "_");
+    let path = std::env::temp_dir().join(format!("drbg_counter_{}_{}", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+
+    let blob = drbg.export_state(&key, Some(&mut FileCounter::new(&path))).unwrap_or_default();
+    let restored = DRBG::<T>::import_state(&blob, &key, Some(&mut FileCounter::new(&path))).is_ok();
+    let replayed = DRBG::<T>::import_state(&blob, &key, Some(&mut FileCounter::new(&path))).err();
+    let value = FileCounter::new(&path).current();
+    let _ = std::fs::remove_file(&path);
+
+    check_res((restored, replayed, value), (true, Some(4), Some(2)),
+        "file_counter".to_string(),
+        AL_NAME.to_string(),
+        "the file counter did not protect the snapshot across restarts.".to_string(),
+        "the file counter protected the snapshot across restarts.".to_string())
+}
@@ -0,0 +1,103 @@
+use std::io::Read;
+use crate::drbg::entropy::FixedEntropySource;
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::drbg::reader::DrbgReader;
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use crate::self_tests::formats::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "DRBG_TESTS::reader_test";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    chunked_reads::<T>(strength) +
+        seed_life::<T>(strength) +
+        pr_interval::<T>(strength)
+}
+
+/*  Returns a deterministic instance whose entropy source provides the instantiation inputs and extra more strings. */
+fn fixed_drbg<T: DRBG_Mechanism_Functions + 'static>(strength: usize, extra: usize) -> Option<DRBG<T>> {
+    let needed = if T::drbg_name() == "CTR-DRBG" { 1 } else { 2 };
+    DRBG::<T>::new_with_entropy(strength, None, Box::new(FixedEntropySource::new(vec![vec![0x5a; 48]; needed + extra]))).ok()
+}
+
+/*  Verifying that the stream does not depend on the size of the reads and that it matches consecutive generate calls of
+    MAX_PRB bytes. */
+fn chunked_reads<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let (mut reader, mut drbg) = match (fixed_drbg::<T>(strength, 0), fixed_drbg::<T>(strength, 0)) {
+        (Some(first), Some(second)) => (DrbgReader::new(first, None), second),
+        _ => {
+            write_to_log(format_message(true, AL_NAME.to_string(), "chunked_reads".to_string(),
+                                        "failed to instantiate DRBG.".to_string()));
+            return 1;
+        }
+    };
+
+    let len = 3 * drbg.get_max_pbr() + 7;
+    let mut streamed = vec![0u8; len];
+    let mut res_reads = Ok(());
+    for chunk in streamed.chunks_mut(13) {
+        res_reads = res_reads.and(reader.read_exact(chunk));
+    }
+
+    let mut expected = Vec::<u8>::new();
+    let mut bytes = Vec::<u8>::new();
+    while expected.len() < len {
+        drbg.generate(&mut bytes, drbg.get_max_pbr(), drbg.get_sec_str(), false, None);
+        expected.extend_from_slice(&bytes);
+    }
+    expected.truncate(len);
+
+    check_res((res_reads.is_ok(), streamed == expected, reader.generates(), reader.read(&mut []).ok()), (true, true, 4, Some(0)),
+        "chunked_reads".to_string(),
+        AL_NAME.to_string(),
+        "the stream of the reader did not match the output of the DRBG.".to_string(),
+        "the stream of the reader matched the output of the DRBG.".to_string())
+}
+
+/*  Verifying that the instance is reseeded when its seed life is over while streaming. */
+fn seed_life<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut reader = match DRBG::<T>::new(strength, None) {
+        Ok(drbg) => DrbgReader::new(drbg, None),
+        Err(_) => {
+            write_to_log(format_message(true, AL_NAME.to_string(), "seed_life".to_string(),
+                                        "failed to instantiate DRBG.".to_string()));
+            return 1;
+        }
+    };
+
+    let seed_life = reader.get_ref().get_seed_life();
+    let mut chunk = vec![0u8; reader.get_ref().get_max_pbr()];
+    let mut res_reads = Ok(());
+    for _ in 0..seed_life + 2 {
+        res_reads = res_reads.and(reader.read_exact(&mut chunk));
+    }
+
+    check_res((res_reads.is_ok(), reader.generates() == seed_life + 2, reader.get_ref().get_count() < seed_life), (true, true, true),
+        "seed_life".to_string(),
+        AL_NAME.to_string(),
+        "the reader did not keep streaming across the seed life of the instance.".to_string(),
+        "the reader kept streaming across the seed life of the instance.".to_string())
+}
+
+/*  Verifying that prediction resistance is requested every pr_interval generate calls: the entropy source only has two
+    strings for the reseeds, so the stream fails on the sixth generate call. */
+fn pr_interval<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut reader = match fixed_drbg::<T>(strength, 2) {
+        Some(drbg) => DrbgReader::new(drbg, Some(2)),
+        None => {
+            write_to_log(format_message(true, AL_NAME.to_string(), "pr_interval".to_string(),
+                                        "failed to instantiate DRBG.".to_string()));
+            return 1;
+        }
+    };
+
+    let mut chunk = vec![0u8; reader.get_ref().get_max_pbr()];
+    let res_reads: Vec<bool> = (0..6).map(|_| reader.read_exact(&mut chunk).is_ok()).collect();
+
+    check_res((res_reads, reader.generates(), reader.last_error()), (vec![true, true, true, true, true, false], 5, Some(5)),
+        "pr_interval".to_string(),
+        AL_NAME.to_string(),
+        "prediction resistance was not requested at the expected interval.".to_string(),
+        "prediction resistance was requested at the expected interval.".to_string())
+}
@@ -0,0 +1,89 @@
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::drbg::persist::WrappingKey;
+use crate::drbg::reseed_policy::ReseedPolicy;
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use crate::self_tests::formats::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "DRBG_TESTS::reseed_interval_test";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    custom_interval::<T>(strength) +
+        interval_limits::<T>(strength) +
+        interval_persisted::<T>(strength)
+}
+
+/*  Returns a new instance using the given reseed interval. */
+fn interval_drbg<T: DRBG_Mechanism_Functions + 'static>(test: &str, strength: usize, reseed_interval: usize) -> Option<DRBG<T>> {
+    let res = DRBG::<T>::new_with_reseed_interval(strength, None, reseed_interval).ok();
+    if res.is_none() {
+        write_to_log(format_message(true, AL_NAME.to_string(), test.to_string(),
+                                    "failed to instantiate DRBG.".to_string()));
+    }
+    res
+}
+
+/*  Verifying that the instance is reseeded after the configured number of requests and that the reseed policy is
+    bounded by the configured interval. */
+fn custom_interval<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut drbg = match interval_drbg::<T>("custom_interval", strength, 3) {
+        Some(drbg) => drbg,
+        None => return 1,
+    };
+
+    let mut bytes = Vec::<u8>::new();
+    let counts: Vec<usize> = (0..5).map(|_| {
+        drbg.generate(&mut bytes, 16, strength, false, None);
+        drbg.get_count()
+    }).collect();
+    let policies = (drbg.set_reseed_policy(ReseedPolicy { max_requests: Some(4), ..ReseedPolicy::default() }),
+                    drbg.set_reseed_policy(ReseedPolicy { max_requests: Some(3), ..ReseedPolicy::default() }));
+
+    check_res((drbg.get_seed_life(), counts, policies), (3, vec![2, 3, 4, 2, 3], (1, 0)),
+        "custom_interval".to_string(),
+        AL_NAME.to_string(),
+        "the instance did not use the configured reseed interval.".to_string(),
+        "the instance used the configured reseed interval.".to_string())
+}
+
+/*  Verifying that the reseed interval is bounded by the maximum of the mechanism and that the default one is the seed
+    life of the mechanism. */
+fn interval_limits<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let default = if T::drbg_name() == "Hash-DRBG" { 255 } else { 1000 };
+    let above_max = T::max_seed_life().checked_add(1).map_or(Some(7), |interval| {
+        DRBG::<T>::new_with_reseed_interval(strength, None, interval).err()
+    });
+
+    let res = (DRBG::<T>::new_with_reseed_interval(strength, None, 0).err(),
+               above_max,
+               DRBG::<T>::new_with_reseed_interval(strength, None, T::max_seed_life()).map(|drbg| drbg.get_seed_life()).ok(),
+               DRBG::<T>::new(strength, None).map(|drbg| drbg.get_seed_life()).ok(),
+               T::max_seed_life() as u64 >= 1 << 32);
+
+    check_res(res, (Some(7), Some(7), Some(T::max_seed_life()), Some(default), true),
+        "interval_limits".to_string(),
+        AL_NAME.to_string(),
+        "the reseed interval was not bounded by the mechanism.".to_string(),
+        "the reseed interval was bounded by the mechanism.".to_string())
+}
+
+/*  Verifying that a restored instance keeps the reseed interval of the exported one. */
+fn interval_persisted<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut drbg = match interval_drbg::<T>("interval_persisted", strength, 5) {
+        Some(drbg) => drbg,
+        None => return 1,
+    };
+    let key = match WrappingKey::aes_gcm(&[0x42; 32]) {
+        Some(key) => key,
+        None => return 1,
+    };
+
+    let restored = drbg.export_state(&key, None).ok().and_then(|blob| DRBG::<T>::import_state(&blob, &key, None).ok());
+
+    check_res(restored.map(|drbg| drbg.get_seed_life()), Some(5),
+        "interval_persisted".to_string(),
+        AL_NAME.to_string(),
+        "the restored instance did not keep the reseed interval.".to_string(),
+        "the restored instance kept the reseed interval.".to_string())
+}
@@ -0,0 +1,150 @@
+use std::time::Duration;
+use crate::drbg::entropy::FixedEntropySource;
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::drbg::reseed_policy::ReseedPolicy;
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use crate::self_tests::formats::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "DRBG_TESTS::reseed_policy_test";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    request_limit::<T>(strength) +
+        byte_limit::<T>(strength) +
+        time_limit::<T>(strength) +
+        failed_reseed::<T>(strength) +
+        invalid_policies::<T>(strength)
+}
+
+/*  Returns a new instance using the given policy. */
+fn policy_drbg<T: DRBG_Mechanism_Functions + 'static>(test: &str, strength: usize, policy: ReseedPolicy) -> Option<DRBG<T>> {
+    let mut drbg = DRBG::<T>::new(strength, None).ok();
+    if drbg.as_mut().map(|drbg| drbg.set_reseed_policy(policy)) != Some(0) {
+        write_to_log(format_message(true, AL_NAME.to_string(), test.to_string(),
+                                    "failed to instantiate DRBG.".to_string()));
+        return None;
+    }
+    drbg
+}
+
+/*  Generates a request of the given length for each entry of requests and returns the reseed counter after each one
+    (the counter starts again from 1 at every reseed). */
+fn counts<T: DRBG_Mechanism_Functions + 'static>(drbg: &mut DRBG<T>, requests: &[usize]) -> Vec<usize> {
+    let mut bytes = Vec::<u8>::new();
+    requests.iter().map(|len| {
+        drbg.generate(&mut bytes, *len, drbg.get_sec_str(), false, None);
+        drbg.get_count()
+    }).collect()
+}
+
+/*  Verifying that the instance is reseeded after the number of requests of the policy, also after an explicit reseed. */
+fn request_limit<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut drbg = match policy_drbg::<T>("request_limit", strength, ReseedPolicy { max_requests: Some(3), ..ReseedPolicy::default() }) {
+        Some(drbg) => drbg,
+        None => return 1,
+    };
+
+    let mut res = counts(&mut drbg, &[16; 5]);
+    drbg.reseed(None);
+    res.extend(counts(&mut drbg, &[16; 4]));
+
+    check_res(res, vec![2, 3, 4, 2, 3, 2, 3, 4, 2],
+        "request_limit".to_string(),
+        AL_NAME.to_string(),
+        "the instance was not reseeded after the requests of the policy.".to_string(),
+        "the instance was reseeded after the requests of the policy.".to_string())
+}
+
+/*  Verifying that the instance is reseeded before a request would exceed the bytes of the policy. */
+fn byte_limit<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut drbg = match policy_drbg::<T>("byte_limit", strength, ReseedPolicy { max_bytes: Some(100), ..ReseedPolicy::default() }) {
+        Some(drbg) => drbg,
+        None => return 1,
+    };
+
+    check_res(counts(&mut drbg, &[64, 36, 1, 100, 100]), vec![2, 3, 2, 2, 2],
+        "byte_limit".to_string(),
+        AL_NAME.to_string(),
+        "the instance was not reseeded before exceeding the bytes of the policy.".to_string(),
+        "the instance was reseeded before exceeding the bytes of the policy.".to_string())
+}
+
+/*  Verifying that the instance is reseeded once the time of the policy has elapsed. */
+fn time_limit<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let policy = ReseedPolicy { max_duration: Some(Duration::from_millis(20)), ..ReseedPolicy::default() };
+    let mut drbg = match policy_drbg::<T>("time_limit", strength, policy) {
+        Some(drbg) => drbg,
+        None => return 1,
+    };
+
+    let mut res = counts(&mut drbg, &[16, 16]);
+    std::thread::sleep(Duration::from_millis(30));
+    res.extend(counts(&mut drbg, &[16]));
+
+    check_res(res, vec![2, 3, 2],
+        "time_limit".to_string(),
+        AL_NAME.to_string(),
+        "the instance was not reseeded after the time of the policy.".to_string(),
+        "the instance was reseeded after the time of the policy.".to_string())
+}
+
+/*  Verifying that a reseed of the policy that fails serves no output and is attempted again by the next request, instead
+    of restarting the limits of the policy. */
+fn failed_reseed<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    // The instantiation inputs, then an empty entropy input that makes the reseed fail and a valid one.
+    let needed = if T::drbg_name() == "CTR-DRBG" { 1 } else { 2 };
+    let mut chunks = vec![vec![0x4d; 48]; needed];
+    chunks.push(Vec::new());
+    chunks.push(vec![0x4d; 48]);
+
+    let policy = ReseedPolicy { max_requests: Some(1), ..ReseedPolicy::default() };
+    let mut drbg = match DRBG::<T>::new_with_entropy(strength, None, Box::new(FixedEntropySource::new(chunks))) {
+        Ok(mut drbg) => {
+            drbg.set_reseed_policy(policy);
+            drbg
+        }
+        Err(_) => {
+            write_to_log(format_message(true, AL_NAME.to_string(), "failed_reseed".to_string(),
+                                        "failed to instantiate DRBG.".to_string()));
+            return 1;
+        }
+    };
+
+    let mut bytes = Vec::<u8>::new();
+    let mut res = Vec::new();
+    for _ in 0..3 {
+        let gen = drbg.generate(&mut bytes, 16, strength, false, None);
+        res.push((gen, bytes.len(), drbg.get_count()));
+    }
+
+    check_res(res, vec![(0, 16, 2), (5, 0, 2), (0, 16, 2)],
+        "failed_reseed".to_string(),
+        AL_NAME.to_string(),
+        "a failed reseed of the policy served output or restarted the policy.".to_string(),
+        "a failed reseed of the policy served no output and was attempted again.".to_string())
+}
+
+/*  Verifying that invalid policies are rejected and the previous policy is kept. */
+fn invalid_policies<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut drbg = match policy_drbg::<T>("invalid_policies", strength, ReseedPolicy::default()) {
+        Some(drbg) => drbg,
+        None => return 1,
+    };
+    let seed_life = drbg.get_seed_life();
+    let valid = ReseedPolicy { max_requests: Some(seed_life), max_duration: Some(Duration::from_secs(600)), max_bytes: Some(1 << 30) };
+
+    let res = (drbg.set_reseed_policy(valid),
+               drbg.set_reseed_policy(ReseedPolicy { max_requests: Some(0), ..valid }),
+               drbg.set_reseed_policy(ReseedPolicy { max_requests: Some(seed_life + 1), ..valid }),
+               drbg.set_reseed_policy(ReseedPolicy { max_duration: Some(Duration::ZERO), ..valid }),
+               drbg.set_reseed_policy(ReseedPolicy { max_bytes: Some(0), ..valid }),
+               drbg.get_reseed_policy() == valid,
+               drbg.set_reseed_policy(ReseedPolicy::default()), drbg.get_reseed_policy() == ReseedPolicy::default());
+
+    check_res(res, (0, 1, 1, 2, 3, true, 0, true),
+        "invalid_policies".to_string(),
+        AL_NAME.to_string(),
+        "invalid reseed policies were accepted.".to_string(),
+        "invalid reseed policies were rejected.".to_string())
+}
@@ -1,9 +1,19 @@
 use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use crate::self_tests::report::{self, SelfTestReport};
 use super::*;
 
-pub fn run_tests<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
-    return instantiation::run_tests::<T>(strength) +
+pub fn run_tests<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> SelfTestReport {
+    report::capture("DRBG-TESTS", || {
+        instantiation::run_tests::<T>(strength) +
             reseed::run_tests::<T>(strength) +
             generate::run_tests::<T>(strength) +
-            uninstantiate::run_tests::<T>(strength);
-}
\ No newline at end of file
+            uninstantiate::run_tests::<T>(strength) +
+            known_answer::run_tests::<T>(strength) +
+            health_test::run_tests::<T>() +
+            reader::run_tests::<T>(strength) +
+            persist::run_tests::<T>(strength) +
+            reseed_policy::run_tests::<T>(strength) +
+            reseed_interval::run_tests::<T>(strength) +
+            buffered::run_tests::<T>(strength)
+    })
+}
@@ -0,0 +1,80 @@
+use std::ptr;
+use sha2::Sha256;
+use crate::drbg::entropy::FixedEntropySource;
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::ffi::*;
+use crate::mechs::hmac_mech::HmacDrbgMech;
+use crate::self_tests::formats::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "FFI-TESTS::ffi_test";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    same_output() +
+        invalid_arguments()
+}
+
+/*  Verifying that an instance created through the C interface produces the output of the Rust API. */
+fn same_output() -> usize {
+    let entropy: Vec<u8> = (0..96u8).collect();
+    let mut handle = ptr::null_mut();
+    let mut out = [0u8; 48];
+
+    let source = FixedEntropySource::new(entropy.chunks(32).map(|chunk| chunk.to_vec()).collect());
+
+    let res = unsafe {
+        (new_handle(DRBG_MECH_HMAC_SHA256, 32, b"ffi".as_ptr(), 3, Some(source), &mut handle),
+         drbg_generate(handle, out.as_mut_ptr(), out.len(), 32, 1, b"add".as_ptr(), 3))
+    };
+    unsafe { drbg_free(handle) };
+
+    let chunks = entropy.chunks(32).map(|chunk| chunk.to_vec()).collect();
+    let mut expected = Vec::<u8>::new();
+    if let Ok(mut drbg) = DRBG::<HmacDrbgMech<Sha256>>::new_with_entropy(32, Some(b"ffi"), Box::new(FixedEntropySource::new(chunks))) {
+        drbg.generate(&mut expected, 48, 32, true, Some(b"add"));
+    }
+
+    check_res((res, out.to_vec()), ((DRBG_OK, DRBG_OK), expected),
+        "same_output".to_string(),
+        AL_NAME.to_string(),
+        "the C interface did not produce the output of the Rust API.".to_string(),
+        "the C interface produced the output of the Rust API.".to_string())
+}
+
+/*  Verifying that invalid pointers and mechanisms are rejected and that the errors of the DRBG are mapped to the codes
+    of the interface. */
+fn invalid_arguments() -> usize {
+    let mut handle = ptr::null_mut();
+    let mut out = [0u8; 16];
+
+    let res = unsafe {
+        let unknown = drbg_new(99, 32, ptr::null(), 0, &mut handle);
+        let null_handle = drbg_new(DRBG_MECH_HASH_SHA256, 32, ptr::null(), 0, ptr::null_mut());
+        let null_pers = drbg_new(DRBG_MECH_HASH_SHA256, 32, ptr::null(), 8, &mut handle);
+        let created = drbg_new(DRBG_MECH_CTR_DF_AES128, 16, ptr::null(), 0, &mut handle);
+        let codes = vec![
+            drbg_generate(handle, ptr::null_mut(), 16, 16, 0, ptr::null(), 0),
+            drbg_generate(handle, out.as_mut_ptr(), out.len(), 32, 0, ptr::null(), 0),
+            drbg_generate(handle, out.as_mut_ptr(), out.len(), 16, 0, ptr::null(), 17),
+            drbg_reseed(handle, ptr::null(), 0),
+            drbg_uninstantiate(handle),
+            drbg_generate(handle, out.as_mut_ptr(), out.len(), 16, 0, ptr::null(), 0),
+            drbg_uninstantiate(handle),
+            drbg_reseed(ptr::null_mut(), ptr::null(), 0),
+            drbg_selftest(99),
+        ];
+        drbg_free(handle);
+        drbg_free(ptr::null_mut());
+
+        (unknown, null_handle, null_pers, created, codes)
+    };
+
+    check_res(res, (DRBG_ERR_INVALID_ARGUMENT, DRBG_ERR_INVALID_ARGUMENT, DRBG_ERR_INVALID_ARGUMENT, DRBG_OK,
+                    vec![DRBG_ERR_INVALID_ARGUMENT, DRBG_ERR_GENERATE + 3, DRBG_ERR_INVALID_ARGUMENT, DRBG_OK, DRBG_OK,
+                         DRBG_ERR_GENERATE + 1, DRBG_ERR_UNINSTANTIATE + 1, DRBG_ERR_INVALID_ARGUMENT, DRBG_ERR_INVALID_ARGUMENT]),
+        "invalid_arguments".to_string(),
+        AL_NAME.to_string(),
+        "the C interface did not report the expected error codes.".to_string(),
+        "the C interface reported the expected error codes.".to_string())
+}
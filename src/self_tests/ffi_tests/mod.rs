@@ -0,0 +1,2 @@
+pub mod ffi_test;
+pub mod run_all;
@@ -0,0 +1,6 @@
+use super::*;
+
+/*  Runs the tests of the C interface (see 'ffi'). */
+pub fn run_tests() -> usize {
+    ffi_test::run_tests()
+}
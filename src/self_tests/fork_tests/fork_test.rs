@@ -0,0 +1,209 @@
+use std::fs::File;
+use std::io::Read;
+use std::os::unix::io::FromRawFd;
+use aes::Aes128;
+use sha2::Sha256;
+use crate::drbg::buffered::BufferedDrbg;
+use crate::drbg::fork::{self, ForkPolicy};
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::drbg::persist::WrappingKey;
+use crate::mechs::ctr_mech::CtrDrbgMech;
+use crate::mechs::ctr_mech_with_df::CtrDrbgMech_DF;
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use crate::mechs::hash_mech::HashDrbgMech;
+use crate::mechs::hmac_mech::HmacDrbgMech;
+use crate::self_tests::formats::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "FORK-TESTS::fork_test";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    generation() +
+        reseed_in_child::<HashDrbgMech<Sha256>>(32) +
+        reseed_in_child::<HmacDrbgMech<Sha256>>(32) +
+        reseed_in_child::<CtrDrbgMech<Aes128>>(16) +
+        reseed_in_child::<CtrDrbgMech_DF<Aes128>>(16) +
+        refuse_in_child::<HmacDrbgMech<Sha256>>(32) +
+        buffered_in_child::<HashDrbgMech<Sha256>>(32) +
+        export_in_child::<HmacDrbgMech<Sha256>>(32)
+}
+
+/*  Runs the given function in a forked child and returns the bytes it produced, or None if the fork failed or the
+    child did not exit normally. The child must not write to the log, which is shared with the parent. */
+fn in_child<F: FnOnce() -> Vec<u8>>(child: F) -> Option<Vec<u8>> {
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return None;
+    }
+
+    match unsafe { libc::fork() } {
+        -1 => {
+            unsafe {
+                libc::close(fds[0]);
+                libc::close(fds[1]);
+            }
+            None
+        }
+        0 => {
+            let out = child();
+            unsafe {
+                libc::close(fds[0]);
+                let written = libc::write(fds[1], out.as_ptr() as *const libc::c_void, out.len());
+                libc::_exit(if written == out.len() as isize { 0 } else { 1 });
+            }
+        }
+        pid => {
+            unsafe { libc::close(fds[1]) };
+            let mut out = Vec::new();
+            let read = unsafe { File::from_raw_fd(fds[0]) }.read_to_end(&mut out);
+
+            let mut status = 0;
+            let waited = unsafe { libc::waitpid(pid, &mut status, 0) } == pid;
+            if read.is_ok() && waited && libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0 { Some(out) } else { None }
+        }
+    }
+}
+
+/*  Verifying that the generation of the process is stable in the parent and changes in a forked child. */
+fn generation() -> usize {
+    let registered = fork::register_atfork();
+    let parent = fork::generation();
+    let child = in_child(|| fork::generation().to_be_bytes().to_vec());
+
+    let res = (registered, fork::register_atfork(), fork::generation() == parent,
+               child.map(|child| child != parent.to_be_bytes().to_vec()));
+
+    check_res(res, (0, 0, true, Some(true)),
+        "generation".to_string(),
+        AL_NAME.to_string(),
+        "the fork was not detected in the child.".to_string(),
+        "the fork was detected in the child.".to_string())
+}
+
+/*  Verifying that an instance copied into a forked child is reseeded before its first generate call, so that the child
+    does not repeat the output of the parent. */
+fn reseed_in_child<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let test = format!("reseed_in_child {}", T::mech_id());
+    let mut drbg = match DRBG::<T>::new(strength, None) {
+        Ok(drbg) => drbg,
+        Err(_) => {
+            write_to_log(format_message(true, AL_NAME.to_string(), test, "failed to instantiate DRBG.".to_string()));
+            return 1;
+        }
+    };
+
+    let mut bytes = Vec::<u8>::new();
+    drbg.generate(&mut bytes, 32, strength, false, None);
+
+    // The child returns the count of the instance after the generate call followed by the generated bytes.
+    let child = in_child(|| {
+        let mut bytes = Vec::<u8>::new();
+        let res = drbg.generate(&mut bytes, 32, strength, false, None);
+        let mut out = vec![res as u8, drbg.get_count() as u8];
+        out.append(&mut bytes);
+        out
+    });
+    let parent = (drbg.generate(&mut bytes, 32, strength, false, None), drbg.get_count());
+
+    let res = (parent, child.as_ref().map(|child| child[..2].to_vec()), child.map(|child| child[2..] != bytes[..]));
+
+    check_res(res, ((0, 3), Some(vec![0, 2]), Some(true)),
+        test,
+        AL_NAME.to_string(),
+        "the child repeated the output of the parent.".to_string(),
+        "the instance was reseeded in the child.".to_string())
+}
+
+/*  Verifying that an instance with the Refuse policy does not generate in a forked child until it is reseeded, while
+    the parent is not affected. */
+fn refuse_in_child<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut drbg = match DRBG::<T>::new(strength, None) {
+        Ok(drbg) => drbg,
+        Err(_) => {
+            write_to_log(format_message(true, AL_NAME.to_string(), "refuse_in_child".to_string(),
+                                        "failed to instantiate DRBG.".to_string()));
+            return 1;
+        }
+    };
+    drbg.set_fork_policy(ForkPolicy::Refuse);
+
+    let child = in_child(|| {
+        let mut bytes = Vec::<u8>::new();
+        let refused = drbg.generate(&mut bytes, 32, strength, false, None);
+        let empty = bytes.is_empty();
+        let again = drbg.generate(&mut bytes, 32, strength, true, None);
+        let reseed = drbg.reseed(None);
+        let generated = drbg.generate(&mut bytes, 32, strength, false, None);
+        vec![refused as u8, empty as u8, again as u8, reseed as u8, generated as u8, bytes.len() as u8]
+    });
+    let mut bytes = Vec::<u8>::new();
+    let parent = drbg.generate(&mut bytes, 32, strength, false, None);
+
+    check_res((drbg.get_fork_policy(), parent, child), (ForkPolicy::Refuse, 0, Some(vec![7, 1, 7, 0, 0, 32])),
+        "refuse_in_child".to_string(),
+        AL_NAME.to_string(),
+        "the instance generated in the child before being reseeded.".to_string(),
+        "the instance refused to generate in the child until reseeded.".to_string())
+}
+
+/*  Verifying that the pre-generated bytes of a BufferedDrbg are discarded in a forked child, so that parent and child do
+    not serve the same bytes. */
+fn buffered_in_child<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut buffered = match DRBG::<T>::new(strength, None) {
+        Ok(drbg) => BufferedDrbg::new(drbg, 256),
+        Err(_) => {
+            write_to_log(format_message(true, AL_NAME.to_string(), "buffered_in_child".to_string(),
+                                        "failed to instantiate DRBG.".to_string()));
+            return 1;
+        }
+    };
+
+    let mut nonce = [0u8; 16];
+    buffered.generate(&mut nonce, strength, false, None);
+
+    let child = in_child(|| {
+        let mut nonce = [0u8; 16];
+        let res = buffered.generate(&mut nonce, strength, false, None);
+        let mut out = vec![res as u8];
+        out.extend_from_slice(&nonce);
+        out
+    });
+    let parent = buffered.generate(&mut nonce, strength, false, None);
+
+    check_res((parent, buffered.buffered(), child.map(|child| (child[0], child[1..] != nonce))), (0, 224, Some((0, true))),
+        "buffered_in_child".to_string(),
+        AL_NAME.to_string(),
+        "the child served the pre-generated bytes of the parent.".to_string(),
+        "the pre-generated bytes were discarded in the child.".to_string())
+}
+
+/*  Verifying that the state of an instance copied into a forked child is not exported until it is reseeded, since the
+    state itself is not wiped in the child (see 'fork'). */
+fn export_in_child<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let mut drbg = match DRBG::<T>::new(strength, None) {
+        Ok(drbg) => drbg,
+        Err(_) => {
+            write_to_log(format_message(true, AL_NAME.to_string(), "export_in_child".to_string(),
+                                        "failed to instantiate DRBG.".to_string()));
+            return 1;
+        }
+    };
+    let key = match WrappingKey::aes_gcm(&[0x42; 16]) {
+        Some(key) => key,
+        None => return 1,
+    };
+
+    let child = in_child(|| {
+        let refused = drbg.export_state(&key, None).err().unwrap_or(0);
+        let reseed = drbg.reseed(None);
+        let exported = drbg.export_state(&key, None).is_ok();
+        vec![refused as u8, reseed as u8, exported as u8]
+    });
+
+    check_res((drbg.get_count(), child), (1, Some(vec![1, 0, 1])),
+        "export_in_child".to_string(),
+        AL_NAME.to_string(),
+        "the state of the parent was exported in the child.".to_string(),
+        "the state was only exported in the child after a reseed.".to_string())
+}
@@ -0,0 +1,3 @@
+#[cfg(target_os = "linux")]
+pub mod fork_test;
+pub mod run_all;
@@ -0,0 +1,12 @@
+#[cfg(target_os = "linux")]
+use super::*;
+
+/*  Runs the tests of the fork detection (see 'drbg/fork.rs'), which fork the process and are therefore only run on
+    Linux. */
+pub fn run_tests() -> usize {
+    #[cfg(target_os = "linux")]
+    return fork_test::run_tests();
+
+    #[cfg(not(target_os = "linux"))]
+    0
+}
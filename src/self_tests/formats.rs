@@ -1,92 +1,307 @@
+use std::collections::VecDeque;
 use std::io::prelude::*;
 use std::fs::*;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
 
-/*  Function used to format a single log message. */
-pub fn format_message(failed: bool, alg_name: String, test_name: String, message: String) -> String{
-    let mut res = String::new();
-
-    if failed {
-        res.push_str("TEST FAILED (");
-        res.push_str(&alg_name);
-        res.push_str(") - ");
-        res.push_str(&test_name);
-        res.push_str(": ");
-        res.push_str(&message);
-        res.push_str("\n");
+/*  Logging facilities of the self-tests.
+
+    Every log entry is a structured record (see LogRecord) that is sent to the sink selected at runtime:
+        SINK                    DESTINATION
+        File(path)              appended to the file at path, created with permissions 0600 (symbolic links are not
+                                followed on Unix)
+        Stderr                  written to the standard error
+        Memory                  kept in memory (at most MAX_MEMORY_RECORDS, the oldest are dropped), see take_memory_log
+        Facade                  forwarded to the 'log' facade (target "rust_nist_drbg::self_tests"), tracing subscribers
+                                can collect these records through the tracing-log bridge
+        Disabled                discarded (default)
+
+    Records are written either in the human readable format of the original test log (LogFormat::Text) or as JSON Lines
+    (LogFormat::JsonLines, one JSON object per line). The Memory and Facade sinks ignore the format.
+
+    Unless set_log_sink is called, the sink is taken from the DRBG_TEST_LOG environment variable ("stderr", "memory",
+    "log", "off", "repo" or "file:<path>") and the format from DRBG_TEST_LOG_FORMAT ("text" or "jsonl"). Without
+    DRBG_TEST_LOG the log is disabled: the periodic health tests log for the whole life of the process, so no file is
+    written unless one is selected. The "repo" value selects the test log of the source tree (REPO_LOG_PATH, relative
+    to the working directory), which is only meant for runs from a checkout of the crate.
+
+    Failures of the sink never interrupt the self-tests: they are counted and can be inspected through log_write_failures. */
+
+/*  The maximum number of records kept by the Memory sink. */
+pub const MAX_MEMORY_RECORDS: usize = 10_000;
+
+/*  The path of the test log of the source tree (selected with DRBG_TEST_LOG=repo). */
+pub const REPO_LOG_PATH: &str = "src/self_tests/logs/test_log.log";
+
+/*  The destinations of the log records. */
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogSink {
+    File(PathBuf),
+    Stderr,
+    Memory,
+    Facade,
+    Disabled,
+}
+
+/*  The formats of the records written to the File and Stderr sinks. */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    Text,
+    JsonLines,
+}
+
+/*  A structured log record.
+
+    - Section: a group of tests is starting (title is e.g. "Hash-DRBG Sha-256 self-tests")
+    - Test: the result of a single test; mechanism is the title of the current section, module is the test module
+      (e.g. "MECH-TESTS::nist_vectors")
+    - Note: any other free-form message */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "record", rename_all = "snake_case")]
+pub enum LogRecord {
+    Section {
+        timestamp: String,
+        title: String,
+    },
+    Test {
+        timestamp: String,
+        mechanism: String,
+        module: String,
+        test: String,
+        passed: bool,
+        message: String,
+    },
+    Note {
+        timestamp: String,
+        message: String,
+    },
+}
+
+impl LogRecord {
+    /*  Formats the record in the format of the original test log. */
+    pub fn to_text(&self) -> String {
+        match self {
+            LogRecord::Section { timestamp, title } => {
+                format!("\n*** {}: STARTING {} ***\n", &timestamp[..19.min(timestamp.len())].replace('T', " "), title)
+            }
+            LogRecord::Test { module, test, passed, message, .. } => {
+                format!("TEST {} ({}) - {}: {}\n", if *passed { "PASSED" } else { "FAILED" }, module, test, message)
+            }
+            LogRecord::Note { message, .. } => {
+                let mut res = message.clone();
+                if !res.ends_with('\n') {
+                    res.push('\n');
+                }
+                res
+            }
+        }
     }
-    else {
-        res.push_str("TEST PASSED (");
-        res.push_str(&alg_name);
-        res.push_str(") - ");
-        res.push_str(&test_name);
-        res.push_str(": ");
-        res.push_str(&message);
-        res.push_str("\n");
+
+    /*  Formats the record as a single JSON line. */
+    pub fn to_json(&self) -> String {
+        let mut res = serde_json::to_string(self).unwrap_or_default();
+        res.push('\n');
+        res
     }
 
-    res
+    /*  Returns true if the record is the result of a failed test. */
+    pub fn is_failure(&self) -> bool {
+        matches!(self, LogRecord::Test { passed: false, .. })
+    }
 }
 
-/*  Checks if the result passed is equal to the expected value and shows the appropriate desired message. */
-pub fn check_res<T: std::cmp::PartialEq>(result: T, expected: T, test_name: String, module_name: String, fail_msg: String, succ_msg: String) -> usize {
-    let mut file;
-    match OpenOptions::new().append(true).create(true).open("src/self_tests/logs/test_log.log") {
-        Err(err) => {
-            panic!("Couldn't open {module_name} test log! (err: {})", err);
-        }
-        Ok(handle) => {
-            file = handle;
-        }
+/*  Free-form messages are logged as notes. */
+impl From<String> for LogRecord {
+    fn from(message: String) -> Self {
+        LogRecord::Note { timestamp: timestamp(), message }
+    }
+}
+
+/*  The state of the logger.
+
+    - sink, format: the current configuration (None until it is set or read from the environment)
+    - section: the title of the current section
+    - memory: the records collected by the Memory sink
+    - failures: the number of records that could not be written */
+struct LogState {
+    sink: Option<(LogSink, LogFormat)>,
+    section: String,
+    memory: VecDeque<LogRecord>,
+    failures: usize,
+}
+
+static LOG_STATE: Mutex<LogState> = Mutex::new(LogState {
+    sink: None,
+    section: String::new(),
+    memory: VecDeque::new(),
+    failures: 0,
+});
+
+/*  Runs f on the logger state, a poisoned lock is recovered since the state is always consistent. */
+fn with_state<R>(f: impl FnOnce(&mut LogState) -> R) -> R {
+    let mut state = LOG_STATE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut state)
+}
+
+/*  Returns the current time as an RFC 3339 timestamp. */
+fn timestamp() -> String {
+    chrono::offset::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false)
+}
+
+/*  Returns the sink selected by the given value of DRBG_TEST_LOG (None if the variable is not set). */
+pub fn parse_log_sink(value: Option<&str>) -> LogSink {
+    match value {
+        None | Some("off") => LogSink::Disabled,
+        Some("stderr") => LogSink::Stderr,
+        Some("repo") => LogSink::File(PathBuf::from(REPO_LOG_PATH)),
+        Some("memory") => LogSink::Memory,
+        Some("log") => LogSink::Facade,
+        Some(other) => LogSink::File(PathBuf::from(other.strip_prefix("file:").unwrap_or(other))),
+    }
+}
+
+/*  Reads the sink configuration from the environment (see the module description). */
+fn sink_from_env() -> (LogSink, LogFormat) {
+    let sink = parse_log_sink(std::env::var("DRBG_TEST_LOG").ok().as_deref());
+
+    let format = match std::env::var("DRBG_TEST_LOG_FORMAT").as_deref() {
+        Ok("jsonl") => LogFormat::JsonLines,
+        _ => LogFormat::Text,
+    };
+
+    (sink, format)
+}
+
+/*  Selects the sink and the format of the log records.
+
+    Return values:
+        - the previous configuration, so that it can be restored */
+pub fn set_log_sink(sink: LogSink, format: LogFormat) -> (LogSink, LogFormat) {
+    with_state(|state| {
+        let previous = state.sink.take().unwrap_or_else(sink_from_env);
+        state.sink = Some((sink, format));
+        previous
+    })
+}
+
+/*  Returns the current sink and format. */
+pub fn log_sink() -> (LogSink, LogFormat) {
+    with_state(|state| state.sink.get_or_insert_with(sink_from_env).clone())
+}
+
+/*  Returns (and removes) the records collected by the Memory sink. */
+pub fn take_memory_log() -> Vec<LogRecord> {
+    with_state(|state| std::mem::take(&mut state.memory).into())
+}
+
+/*  Returns the number of records that could not be written since the start of the program. */
+pub fn log_write_failures() -> usize {
+    with_state(|state| state.failures)
+}
+
+/*  Function used to format a single log message. */
+pub fn format_message(failed: bool, alg_name: String, test_name: String, message: String) -> LogRecord {
+    LogRecord::Test {
+        timestamp: timestamp(),
+        mechanism: with_state(|state| state.section.clone()),
+        module: alg_name,
+        test: test_name,
+        passed: !failed,
+        message,
     }
+}
+
+/*  Logs the start of a new section of tests, the following test records refer to its title. */
+pub fn start_section(title: &str) {
+    set_section(title);
+    write_to_log(LogRecord::Section { timestamp: timestamp(), title: title.to_string() });
+}
 
-    let mut test_id = "".to_string();
-    test_id.push_str(test_name.as_str());
+/*  Returns the title of the current section. */
+pub fn current_section() -> String {
+    with_state(|state| state.section.clone())
+}
+
+/*  Changes the title of the current section without logging it (e.g. to restore a previous section). */
+pub fn set_section(title: &str) {
+    with_state(|state| state.section = title.to_string());
+}
+
+/*  Checks if the result passed is equal to the expected value and shows the appropriate desired message. */
+pub fn check_res<T: std::cmp::PartialEq>(result: T, expected: T, test_name: String, module_name: String, fail_msg: String, succ_msg: String) -> usize {
     if result != expected {
-        match file.write_all(format_message(true, module_name.clone(), test_id, fail_msg).as_bytes()) {
-            Err(err) => {
-                panic!("Couldn't write to {} log file! (err: {})", module_name, err);
-            }
-            Ok(_) => {}
-        }
-        return 1;
+        write_to_log(format_message(true, module_name, test_name, fail_msg));
+        1
     }
     else {
-        match file.write_all(format_message(false, module_name.clone(), test_id, succ_msg).as_bytes()) {
-            Err(err) => {
-                panic!("Couldn't write to {} log file! (err: {})", module_name, err);
-            }
-            Ok(_) => {}
-        }
-        return 0;
+        write_to_log(format_message(false, module_name, test_name, succ_msg));
+        0
     }
 }
 
-// Writes a into the desired log.
-pub fn write_to_log(message: String) {
-    let mut file;
-    match OpenOptions::new().append(true).create(true).open("src/self_tests/logs/test_log.log") {
-        Err(err) => {
-            panic!("Couldn't open log! (err: {})", err);
-        }
-        Ok(handle) => {
-            file = handle;
-        }
+/*  Writes a record into the log. Failures of the sink are counted (see log_write_failures) and do not stop the caller.
+    Test records are also added to the active reports (see 'report'). */
+pub fn write_to_log<R: Into<LogRecord>>(record: R) {
+    let record = record.into();
+    super::report::collect(&record);
+
+    if try_write_to_log(record).is_err() {
+        with_state(|state| state.failures += 1);
     }
+}
 
-    // Adding timestamp to first logging entry
-    let mut actual_mes = message.clone();
-    let time = chrono::offset::Local::now().to_string();
-    let mut day_hour = time[..19].to_string();
-    day_hour.insert_str(day_hour.len(), ": ");
-    
-    if message.contains("*** STARTING") {
-        actual_mes.insert_str(5, &day_hour);
+/*  Opens the log file at path for appending, creating it readable and writable only by its owner. On Unix a symbolic
+    link is not followed, so that a link planted in a shared directory cannot redirect the log. */
+fn open_log_file(path: &Path) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.append(true).create(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600).custom_flags(libc::O_NOFOLLOW);
     }
 
-    match file.write_all(actual_mes.as_bytes()) {
-        Err(err) => {
-            panic!("Couldn't write to log file! (err: {})", err);
-        }
-        Ok(_) => {}
+    options.open(path)
+}
+
+/*  Writes a record into the log.
+
+    Return values:
+        - Ok(()): SUCCESS
+        - Err(1): ERROR, the log file could not be opened
+        - Err(2): ERROR, the record could not be written */
+pub fn try_write_to_log<R: Into<LogRecord>>(record: R) -> Result<(), usize> {
+    let record = record.into();
+    let (sink, format) = log_sink();
+    let line = match format {
+        LogFormat::Text => record.to_text(),
+        LogFormat::JsonLines => record.to_json(),
     };
-}
\ No newline at end of file
+
+    match sink {
+        LogSink::File(path) => {
+            let mut file = open_log_file(&path).map_err(|_| 1usize)?;
+            file.write_all(line.as_bytes()).map_err(|_| 2usize)
+        }
+        LogSink::Stderr => {
+            std::io::stderr().write_all(line.as_bytes()).map_err(|_| 2usize)
+        }
+        LogSink::Memory => {
+            with_state(|state| {
+                if state.memory.len() >= MAX_MEMORY_RECORDS {
+                    state.memory.pop_front();
+                }
+                state.memory.push_back(record);
+            });
+            Ok(())
+        }
+        LogSink::Facade => {
+            let level = if record.is_failure() { log::Level::Error } else { log::Level::Info };
+            log::log!(target: "rust_nist_drbg::self_tests", level, "{}", record.to_text().trim());
+            Ok(())
+        }
+        LogSink::Disabled => Ok(()),
+    }
+}
@@ -0,0 +1,103 @@
+use std::time::Duration;
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::drbg::health::*;
+use crate::drbg::module_state::*;
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use crate::self_tests::formats::*;
+use crate::self_tests::report::capture;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "HEALTH-TESTS::health_test";
+
+/*  Aggregator that runs all the tests in this file. The periodicity of the health tests is changed by some tests and
+    restored before they return, the module error state is latched and recovered by latched_error. */
+pub fn run_tests<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    generate_interval::<T>(strength) +
+        time_interval::<T>(strength) +
+        latched_error::<T>(strength)
+}
+
+/*  Verifying that the health tests are run every generate_interval generate calls, on a separate instance. */
+fn generate_interval<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let previous = set_health_test_config(HealthTestConfig { generate_interval: 2, time_interval: None });
+    let mut bytes = Vec::<u8>::new();
+
+    let res = match DRBG::<T>::new(strength, None) {
+        Err(_) => None,
+        Ok(mut drbg) => {
+            let runs = health_test_runs();
+            let gen_res: usize = (0..4).map(|_| drbg.generate(&mut bytes, 16, strength, false, None)).sum();
+            Some((gen_res, health_test_runs() - runs, drbg.get_count()))
+        }
+    };
+    set_health_test_config(previous);
+
+    check_res(res, Some((0, 2, 5)),
+        "generate_interval".to_string(),
+        AL_NAME.to_string(),
+        "the health tests were not run at the expected generate calls.".to_string(),
+        "the health tests were run at the expected generate calls on a separate instance.".to_string())
+}
+
+/*  Verifying that the health tests are run once time_interval has elapsed. */
+fn time_interval<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    let previous = set_health_test_config(HealthTestConfig { generate_interval: 0, time_interval: Some(Duration::ZERO) });
+    let mut bytes = Vec::<u8>::new();
+
+    let res = match DRBG::<T>::new(strength, None) {
+        Err(_) => None,
+        Ok(mut drbg) => {
+            let runs = health_test_runs();
+            let gen_res = drbg.generate(&mut bytes, 16, strength, false, None);
+            Some((gen_res, health_test_runs() - runs))
+        }
+    };
+    set_health_test_config(previous);
+
+    check_res(res, Some((0, 1)),
+        "time_interval".to_string(),
+        AL_NAME.to_string(),
+        "the health tests were not run after the time interval.".to_string(),
+        "the health tests were run after the time interval.".to_string())
+}
+
+/*  Verifying that a failed health test latches the module error state, which blocks every instance until recovery. The
+    records of the injected failure are logged to memory. The recovery runs the health tests only (see 'module_state'),
+    this test is skipped while the module is recovering. */
+fn latched_error<T: DRBG_Mechanism_Functions + 'static>(strength: usize) -> usize {
+    if recovering() {
+        return check_res(0, 0, "latched_error".to_string(), AL_NAME.to_string(), String::new(),
+                         "skipped while the module is recovering.".to_string());
+    }
+
+    let mut drbg = match DRBG::<T>::new(strength, None) {
+        Err(_) => {
+            write_to_log(format_message(true, AL_NAME.to_string(), "latched_error".to_string(),
+                                        "failed to instantiate DRBG.".to_string()));
+            return 1;
+        }
+        Ok(drbg) => drbg,
+    };
+    let mut bytes = Vec::<u8>::new();
+    let health_tests = || capture("HEALTH-TESTS", run_all_health_tests);
+
+    let previous = set_log_sink(LogSink::Memory, LogFormat::Text);
+    take_memory_log();
+    set_fault_injection(true);
+    let failed = (run_health_tests::<T>(), module_state());
+    let blocked = (drbg.generate(&mut bytes, 16, strength, false, None), drbg.reseed(None),
+                   DRBG::<T>::new(strength, None).err());
+    let failed_recovery = (recover_with(health_tests), module_state());
+    set_fault_injection(false);
+    let logged = take_memory_log().iter().any(|record| record.to_text().starts_with("MODULE ERROR STATE"));
+    set_log_sink(previous.0, previous.1);
+
+    let recovery = (recover_with(health_tests), module_state(), drbg.generate(&mut bytes, 16, strength, false, None));
+
+    check_res((failed, logged, blocked, failed_recovery, recovery),
+        ((3, ModuleState::Error), true, (1, 1, Some(5)), (1, ModuleState::Error), (0, ModuleState::Operational, 0)),
+        "latched_error".to_string(),
+        AL_NAME.to_string(),
+        "the module error state was not latched or not recovered as expected.".to_string(),
+        "the module error state was latched and recovered as expected.".to_string())
+}
@@ -0,0 +1,2 @@
+pub mod health_test;
+pub mod run_all;
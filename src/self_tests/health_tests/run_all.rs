@@ -0,0 +1,13 @@
+use crate::mechs::{hash_mech::HashDrbgMech, hmac_mech::HmacDrbgMech, ctr_mech::CtrDrbgMech, ctr_mech_with_df::CtrDrbgMech_DF};
+use super::*;
+use sha2::*;
+use aes::*;
+
+/*  Runs the tests of the periodic health tests and of the error state they latch (see 'drbg/health.rs') on a mechanism of
+    each family. */
+pub fn run_tests() -> usize {
+    health_test::run_tests::<HashDrbgMech<Sha256>>(32) +
+        health_test::run_tests::<HmacDrbgMech<Sha512>>(32) +
+        health_test::run_tests::<CtrDrbgMech<Aes128>>(16) +
+        health_test::run_tests::<CtrDrbgMech_DF<Aes256>>(32)
+}
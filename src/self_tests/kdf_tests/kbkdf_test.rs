@@ -0,0 +1,84 @@
+use crate::kdf::kbkdf::*;
+use crate::kdf::prf::{HmacPrf, CmacPrf};
+use crate::self_tests::formats::*;
+use crate::self_tests::constants::*;
+use aes::*;
+use sha2::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "KDF-TESTS::kbkdf_test";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    invalid_key() +
+        invalid_rlen() +
+        invalid_location() +
+        counter_wraps() +
+        empty_output()
+}
+
+/*  Verifying that a key of the wrong length for the PRF is refused. */
+fn invalid_key() -> usize {
+    let ctr = CtrConfig{ location: CtrLocation::BeforeFixed, rlen: 32 };
+    let res = kdf_counter::<CmacPrf<Aes128>>(&ENTROPY, &NONCE, 16, &ctr);
+
+    check_res(res, Err(1),
+        "invalid_key".to_string(),
+        AL_NAME.to_string(),
+        "KDF accepted a key of the wrong length for the PRF.".to_string(),
+        "KDF refused a key of the wrong length for the PRF as expected.".to_string())
+}
+
+/*  Verifying that counter widths other than 8, 16, 24 and 32 bits are refused. */
+fn invalid_rlen() -> usize {
+    let ctr = CtrConfig{ location: CtrLocation::BeforeFixed, rlen: 12 };
+    let res = kdf_counter::<HmacPrf<Sha256>>(&ENTROPY, &NONCE, 16, &ctr);
+
+    check_res(res, Err(2),
+        "invalid_rlen".to_string(),
+        AL_NAME.to_string(),
+        "KDF accepted a counter width that is not supported.".to_string(),
+        "KDF refused a counter width that is not supported as expected.".to_string())
+}
+
+/*  Verifying that counter locations that are not defined for a mode are refused. */
+fn invalid_location() -> usize {
+    let iter_ctr = CtrConfig{ location: CtrLocation::BeforeIter, rlen: 8 };
+    let fixed_ctr = CtrConfig{ location: CtrLocation::BeforeFixed, rlen: 8 };
+    let middle_ctr = CtrConfig{ location: CtrLocation::MiddleFixed(NONCE.len() + 1), rlen: 8 };
+
+    let res = (kdf_counter::<HmacPrf<Sha256>>(&ENTROPY, &NONCE, 16, &iter_ctr),
+                kdf_counter::<HmacPrf<Sha256>>(&ENTROPY, &NONCE, 16, &middle_ctr),
+                kdf_feedback::<HmacPrf<Sha256>>(&ENTROPY, &[], &NONCE, 16, Some(&fixed_ctr)),
+                kdf_double_pipeline::<HmacPrf<Sha256>>(&ENTROPY, &NONCE, 16, Some(&fixed_ctr)));
+
+    check_res(res, (Err(3), Err(3), Err(3), Err(3)),
+        "invalid_location".to_string(),
+        AL_NAME.to_string(),
+        "KDF accepted a counter location that is not defined for the mode.".to_string(),
+        "KDF refused counter locations that are not defined for the mode as expected.".to_string())
+}
+
+/*  Verifying that requests that would make the counter wrap are refused (n > 2^r - 1). */
+fn counter_wraps() -> usize {
+    let ctr = CtrConfig{ location: CtrLocation::AfterFixed, rlen: 8 };
+    let max = kdf_counter::<HmacPrf<Sha256>>(&ENTROPY, &NONCE, 255 * 32, &ctr).map(|ko| ko.len());
+    let over = kdf_counter::<HmacPrf<Sha256>>(&ENTROPY, &NONCE, 255 * 32 + 1, &ctr);
+
+    check_res((max, over), (Ok(255 * 32), Err(4)),
+        "counter_wraps".to_string(),
+        AL_NAME.to_string(),
+        "KDF did not enforce the limit imposed by the counter width.".to_string(),
+        "KDF enforced the limit imposed by the counter width as expected.".to_string())
+}
+
+/*  Verifying that requesting no keying material returns an empty vector. */
+fn empty_output() -> usize {
+    let res = kdf_double_pipeline::<HmacPrf<Sha512>>(&ENTROPY, &NONCE, 0, None);
+
+    check_res(res, Ok(Vec::new()),
+        "empty_output".to_string(),
+        AL_NAME.to_string(),
+        "KDF returned keying material when none was requested.".to_string(),
+        "KDF returned no keying material as expected.".to_string())
+}
@@ -0,0 +1,120 @@
+use crate::kdf::kbkdf::*;
+use crate::kdf::prf::{PRF_Functions, HmacPrf, CmacPrf};
+use crate::self_tests::formats::*;
+use aes::*;
+use camellia::{Camellia128, Camellia256};
+use sha2::*;
+use serde::Deserialize;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "KDF-TESTS::kbkdf_vectors";
+
+/*  General structure of a KBKDF vector test. The fields follow the naming of the CAVP KBKDF response files:
+    the PRF, the counter location (NONE in the iteration modes without counter) and width, the key KI, the IV of the
+    feedback mode, the fixed input data, L (in bytes) and KO. The source field of every vector gives where it comes from:
+        - counter.json: the CAVP KDFCTR_gen.rsp file (section and count), plus multi-block and MIDDLE_FIXED vectors
+          cross-checked with the KBKDF of pyca/cryptography
+        - feedback.json: the key derivation test vectors of RFC 6803 (CMAC with Camellia, multi-block for 256-bit keys),
+          plus vectors for the other counter locations computed with an independent implementation of the SP
+        - double_pipeline.json: vectors computed with an independent implementation of the SP */
+#[derive(Deserialize, Debug)]
+struct Fixture {
+    name: String,
+    source: String,
+    prf: String,
+    ctr_location: String,
+    rlen: usize,
+    ctr_offset: Option<usize>,
+    ki: String,
+    #[serde(default)]
+    iv: String,
+    fixed: String,
+    l: usize,
+    ko: String,
+}
+
+/*  The KBKDF mode of a vector file. */
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Counter,
+    Feedback,
+    DoublePipeline,
+}
+
+/*  This test is designed to perform KATs over the CAVP KBKDF vectors of every PRF. */
+pub fn test_vectors() -> usize {
+    test_file("test_vectors_counter", Mode::Counter, include_str!("kbkdf_vectors/counter.json")) +
+        test_file("test_vectors_feedback", Mode::Feedback, include_str!("kbkdf_vectors/feedback.json")) +
+        test_file("test_vectors_double_pipeline", Mode::DoublePipeline, include_str!("kbkdf_vectors/double_pipeline.json")) +
+        unknown_location()
+}
+
+/*  Verifying that a vector with an unknown counter location is rejected instead of being run with another location. */
+fn unknown_location() -> usize {
+    let mut tests: Vec<Fixture> = serde_json::from_str(include_str!("kbkdf_vectors/counter.json")).unwrap();
+    let mut test = tests.remove(0);
+    test.ctr_location = "BEFORE_ITER".to_string();
+
+    check_res(run_vector::<CmacPrf<Aes128>>(&test, Mode::Counter), Err(0),
+        "unknown_location".to_string(),
+        AL_NAME.to_string(),
+        "a vector with an unknown counter location was run.".to_string(),
+        "a vector with an unknown counter location was rejected.".to_string())
+}
+
+/*  Runs every vector of a file and logs the first failure (if any). */
+fn test_file(test_name: &str, mode: Mode, file: &str) -> usize {
+    let tests: Vec<Fixture> = serde_json::from_str(file).unwrap();
+
+    for test in tests {
+        let res = match test.prf.as_str() {
+            "HMAC_SHA256" => run_vector::<HmacPrf<Sha256>>(&test, mode),
+            "HMAC_SHA512" => run_vector::<HmacPrf<Sha512>>(&test, mode),
+            "CMAC_AES128" => run_vector::<CmacPrf<Aes128>>(&test, mode),
+            "CMAC_AES192" => run_vector::<CmacPrf<Aes192>>(&test, mode),
+            "CMAC_AES256" => run_vector::<CmacPrf<Aes256>>(&test, mode),
+            "CMAC_CAMELLIA128" => run_vector::<CmacPrf<Camellia128>>(&test, mode),
+            "CMAC_CAMELLIA256" => run_vector::<CmacPrf<Camellia256>>(&test, mode),
+            _ => Err(0),
+        };
+
+        if res != Ok(hex::decode(&test.ko).unwrap()) {
+            let mut message = "kbkdf vector ".to_string();
+            message.push_str(&test.name);
+            message.push_str(" (");
+            message.push_str(&test.source);
+            message.push_str(") failed unexpectedly.");
+            write_to_log(format_message(true, AL_NAME.to_string(), test_name.to_string(), message));
+            return 1;
+        }
+    }
+
+    write_to_log(format_message(false, AL_NAME.to_string(), test_name.to_string(), "all kbkdf vectors have passed.".to_string()));
+    0
+}
+
+/*  Derives the keying material of a single vector in the given mode. */
+fn run_vector<P: PRF_Functions>(test: &Fixture, mode: Mode) -> Result<Vec<u8>, usize> {
+    let ki = hex::decode(&test.ki).unwrap();
+    let iv = hex::decode(&test.iv).unwrap();
+    let fixed = hex::decode(&test.fixed).unwrap();
+    let iteration = mode != Mode::Counter;
+    let location = match (test.ctr_location.as_str(), test.ctr_offset) {
+        ("BEFORE_FIXED", None) if !iteration => Some(CtrLocation::BeforeFixed),
+        ("AFTER_FIXED", None) => Some(CtrLocation::AfterFixed),
+        ("MIDDLE_FIXED", Some(offset)) if !iteration => Some(CtrLocation::MiddleFixed(offset)),
+        ("BEFORE_ITER", None) if iteration => Some(CtrLocation::BeforeIter),
+        ("AFTER_ITER", None) if iteration => Some(CtrLocation::AfterIter),
+        ("NONE", None) if iteration => None,
+        // Unknown location, or one that does not match the mode or the offset: the vector is rejected.
+        _ => return Err(0),
+    };
+    let ctr = location.map(|location| CtrConfig{ location, rlen: test.rlen });
+
+    match (mode, ctr) {
+        (Mode::Counter, Some(ctr)) => kdf_counter::<P>(&ki, &fixed, test.l, &ctr),
+        (Mode::Feedback, ctr) => kdf_feedback::<P>(&ki, &iv, &fixed, test.l, ctr.as_ref()),
+        (Mode::DoublePipeline, ctr) => kdf_double_pipeline::<P>(&ki, &fixed, test.l, ctr.as_ref()),
+        _ => Err(0),
+    }
+}
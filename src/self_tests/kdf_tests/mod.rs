@@ -0,0 +1,4 @@
+pub mod prf_test;
+pub mod kbkdf_test;
+pub mod kbkdf_vectors;
+pub mod run_all;
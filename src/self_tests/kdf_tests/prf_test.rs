@@ -0,0 +1,73 @@
+use crate::kdf::prf::{PRF_Functions, HmacPrf, CmacPrf};
+use crate::self_tests::formats::*;
+use aes::*;
+use sha2::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "KDF-TESTS::prf_test";
+
+/*  CMAC-AES128 examples taken from RFC 4493, section 4 (same as NIST SP 800-38B, appendix D.1). */
+const CMAC_KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+const CMAC_MSG: &str = "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e5130c81c46a35ce411";
+const CMAC_TAG_0: &str = "bb1d6929e95937287fa37d129b756746";
+const CMAC_TAG_16: &str = "070a16b46b4d4144f79bdd9dd04a287c";
+const CMAC_TAG_40: &str = "dfa66747de9ae63030ca32611497c827";
+
+/*  HMAC-SHA256 example taken from RFC 4231, test case 2. */
+const HMAC_TAG: &str = "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    cmac_kat("cmac_kat_empty", 0, CMAC_TAG_0) +
+        cmac_kat("cmac_kat_one_block", 16, CMAC_TAG_16) +
+        cmac_kat("cmac_kat_partial_block", 40, CMAC_TAG_40) +
+        cmac_split_input() +
+        cmac_wrong_key_len() +
+        hmac_kat()
+}
+
+/*  Verifying CMAC against the known answers of RFC 4493. */
+fn cmac_kat(test_name: &str, msg_len: usize, expected: &str) -> usize {
+    let prf = CmacPrf::<Aes128>::new(&hex::decode(CMAC_KEY).unwrap()).unwrap();
+    let msg = hex::decode(CMAC_MSG).unwrap();
+
+    check_res(prf.compute(&[&msg[..msg_len]]), hex::decode(expected).unwrap(),
+        test_name.to_string(),
+        AL_NAME.to_string(),
+        "CMAC tag does not match the known answer.".to_string(),
+        "CMAC tag matches the known answer.".to_string())
+}
+
+/*  Verifying that splitting the input in several strings does not change the tag. */
+fn cmac_split_input() -> usize {
+    let prf = CmacPrf::<Aes128>::new(&hex::decode(CMAC_KEY).unwrap()).unwrap();
+    let msg = hex::decode(CMAC_MSG).unwrap();
+
+    check_res(prf.compute(&[&msg[..7], &msg[7..23], &msg[23..]]), hex::decode(CMAC_TAG_40).unwrap(),
+        "cmac_split_input".to_string(),
+        AL_NAME.to_string(),
+        "CMAC tag over a split input does not match the known answer.".to_string(),
+        "CMAC tag over a split input matches the known answer.".to_string())
+}
+
+/*  Verifying that CMAC refuses keys that are not exactly keylen bytes long. */
+fn cmac_wrong_key_len() -> usize {
+    let key = hex::decode(CMAC_KEY).unwrap();
+
+    check_res((CmacPrf::<Aes128>::new(&key[..15]).is_none(), CmacPrf::<Aes256>::new(&key).is_none()), (true, true),
+        "cmac_wrong_key_len".to_string(),
+        AL_NAME.to_string(),
+        "CMAC accepted a key of the wrong length.".to_string(),
+        "CMAC refused keys of the wrong length as expected.".to_string())
+}
+
+/*  Verifying HMAC against the known answer of RFC 4231. */
+fn hmac_kat() -> usize {
+    let prf = HmacPrf::<Sha256>::new("Jefe".as_bytes()).unwrap();
+
+    check_res(prf.compute(&["what do ya want ".as_bytes(), "for nothing?".as_bytes()]), hex::decode(HMAC_TAG).unwrap(),
+        "hmac_kat".to_string(),
+        AL_NAME.to_string(),
+        "HMAC tag does not match the known answer.".to_string(),
+        "HMAC tag matches the known answer.".to_string())
+}
@@ -0,0 +1,8 @@
+use super::*;
+
+/*  Runs the self-tests of the SP 800-108 KDFs and of the PRFs they are built on (see 'kdf'). */
+pub fn run_tests() -> usize {
+    prf_test::run_tests() +
+        kbkdf_test::run_tests() +
+        kbkdf_vectors::test_vectors()
+}
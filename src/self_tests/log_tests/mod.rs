@@ -0,0 +1,2 @@
+pub mod sink_test;
+pub mod run_all;
@@ -0,0 +1,6 @@
+use super::*;
+
+/*  Runs the tests of the log sinks (see 'formats'). */
+pub fn run_tests() -> usize {
+    sink_test::run_tests()
+}
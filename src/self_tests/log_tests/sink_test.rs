@@ -0,0 +1,155 @@
+use crate::self_tests::formats::*;
+use crate::self_tests::report::uncaptured;
+use std::fs;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "LOG-TESTS::sink_test";
+
+/*  Aggregator that runs all the tests in this file. Every test temporarily replaces the sink of the log and restores
+    it before logging its own result, the records written to the temporary sinks are not part of the reports. */
+pub fn run_tests() -> usize {
+    memory_sink() +
+        json_lines() +
+        unwritable_file() +
+        symlink_refused() +
+        memory_bound() +
+        default_sink()
+}
+
+/*  Verifying that the Memory sink collects structured records. */
+fn memory_sink() -> usize {
+    let previous = set_log_sink(LogSink::Memory, LogFormat::Text);
+    take_memory_log();
+    let section = current_section();
+    let failures = uncaptured(|| {
+        start_section("log sink");
+        check_res(1, 2, "probe".to_string(), "PROBE".to_string(), "fail".to_string(), "pass".to_string())
+    });
+    let records = take_memory_log();
+    set_log_sink(previous.0, previous.1);
+    set_section(&section);
+
+    let res = match records.as_slice() {
+        [LogRecord::Section { title, .. }, LogRecord::Test { mechanism, module, test, passed, message, .. }] =>
+            (title.as_str(), mechanism.as_str(), module.as_str(), test.as_str(), *passed, message.as_str()),
+        _ => ("", "", "", "", true, ""),
+    };
+
+    check_res((failures, res), (1, ("log sink", "log sink", "PROBE", "probe", false, "fail")),
+        "memory_sink".to_string(),
+        AL_NAME.to_string(),
+        "the memory sink did not collect the expected records.".to_string(),
+        "the memory sink collected the expected records.".to_string())
+}
+
+/*  Verifying that the JSON Lines format writes one parsable record per line. */
+fn json_lines() -> usize {
+    let path = std::env::temp_dir().join(format!("drbg_test_log_{}.jsonl", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    let previous = set_log_sink(LogSink::File(path.clone()), LogFormat::JsonLines);
+    let section = current_section();
+    uncaptured(|| {
+        start_section("json lines");
+        write_to_log(format_message(false, "PROBE".to_string(), "probe".to_string(), "pass".to_string()));
+        write_to_log("note".to_string());
+    });
+    set_log_sink(previous.0, previous.1);
+    set_section(&section);
+
+    let content = fs::read_to_string(&path).unwrap_or_default();
+    let _ = fs::remove_file(&path);
+    let records: Vec<Option<LogRecord>> = content.lines().map(|line| serde_json::from_str(line).ok()).collect();
+    let kinds: Vec<&str> = records.iter().map(|record| match record {
+        Some(LogRecord::Section { .. }) => "section",
+        Some(LogRecord::Test { passed: true, .. }) => "test",
+        Some(LogRecord::Note { .. }) => "note",
+        _ => "invalid",
+    }).collect();
+
+    check_res(kinds, vec!["section", "test", "note"],
+        "json_lines".to_string(),
+        AL_NAME.to_string(),
+        "the JSON Lines log does not contain the expected records.".to_string(),
+        "the JSON Lines log contains the expected records.".to_string())
+}
+
+/*  Verifying that a log file that cannot be opened is reported as an error instead of a panic. */
+fn unwritable_file() -> usize {
+    let path = std::env::temp_dir().join("drbg_missing_dir").join("missing").join("test_log.log");
+    let failures = log_write_failures();
+
+    let previous = set_log_sink(LogSink::File(path), LogFormat::Text);
+    let res = try_write_to_log("unwritable".to_string());
+    write_to_log("unwritable".to_string());
+    let new_failures = log_write_failures() - failures;
+    set_log_sink(previous.0, previous.1);
+
+    check_res((res, new_failures), (Err(1), 1),
+        "unwritable_file".to_string(),
+        AL_NAME.to_string(),
+        "a log file that cannot be opened was not reported as an error.".to_string(),
+        "a log file that cannot be opened was reported as an error as expected.".to_string())
+}
+
+/*  Verifying that the log file is not opened through a symbolic link and is only accessible by its owner. */
+#[cfg(unix)]
+fn symlink_refused() -> usize {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = std::env::temp_dir();
+    let (target, link, file) = (dir.join(format!("drbg_log_target_{}", std::process::id())),
+                                dir.join(format!("drbg_log_link_{}", std::process::id())),
+                                dir.join(format!("drbg_log_file_{}", std::process::id())));
+    let _ = (fs::remove_file(&target), fs::remove_file(&link), fs::remove_file(&file));
+    let linked = fs::write(&target, b"").is_ok() && std::os::unix::fs::symlink(&target, &link).is_ok();
+
+    let previous = set_log_sink(LogSink::File(link.clone()), LogFormat::Text);
+    let res_link = try_write_to_log("symlink".to_string());
+    set_log_sink(LogSink::File(file.clone()), LogFormat::Text);
+    let res_file = try_write_to_log("file".to_string());
+    set_log_sink(previous.0, previous.1);
+
+    let target_len = fs::metadata(&target).map(|meta| meta.len()).ok();
+    let mode = fs::metadata(&file).map(|meta| meta.permissions().mode() & 0o777).ok();
+    let _ = (fs::remove_file(&target), fs::remove_file(&link), fs::remove_file(&file));
+
+    check_res((linked, res_link, target_len, res_file, mode), (true, Err(1), Some(0), Ok(()), Some(0o600)),
+        "symlink_refused".to_string(),
+        AL_NAME.to_string(),
+        "the log file was opened through a symbolic link or is accessible by other users.".to_string(),
+        "the log file was not opened through a symbolic link and is only accessible by its owner.".to_string())
+}
+
+#[cfg(not(unix))]
+fn symlink_refused() -> usize {
+    0
+}
+
+/*  Verifying that the Memory sink keeps at most MAX_MEMORY_RECORDS records, dropping the oldest. */
+fn memory_bound() -> usize {
+    let previous = set_log_sink(LogSink::Memory, LogFormat::Text);
+    take_memory_log();
+    for i in 0..MAX_MEMORY_RECORDS + 10 {
+        let _ = try_write_to_log(i.to_string());
+    }
+    let records = take_memory_log();
+    set_log_sink(previous.0, previous.1);
+
+    let ends = (records.first().map(LogRecord::to_text), records.last().map(LogRecord::to_text));
+    check_res((records.len(), ends), (MAX_MEMORY_RECORDS, (Some("10\n".to_string()), Some(format!("{}\n", MAX_MEMORY_RECORDS + 9)))),
+        "memory_bound".to_string(),
+        AL_NAME.to_string(),
+        "the memory sink did not keep the latest MAX_MEMORY_RECORDS records.".to_string(),
+        "the memory sink kept the latest MAX_MEMORY_RECORDS records.".to_string())
+}
+
+/*  Verifying that no log is written unless a sink is selected. */
+fn default_sink() -> usize {
+    check_res((parse_log_sink(None), parse_log_sink(Some("off")), parse_log_sink(Some("file:x.log"))),
+              (LogSink::Disabled, LogSink::Disabled, LogSink::File("x.log".into())),
+        "default_sink".to_string(),
+        AL_NAME.to_string(),
+        "the log is not disabled by default.".to_string(),
+        "the log is disabled by default.".to_string())
+}
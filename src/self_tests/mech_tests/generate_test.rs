@@ -132,7 +132,7 @@ fn generate_on_seed_expired<T: DRBG_Mechanism_Functions>(mut strength: usize) ->
     let mut bytes = Vec::<u8>::new();
     let mut res;
 
-    while drbg.count() < T::seed_life() {
+    while drbg.count() <= drbg.seed_life() {
         res = drbg.generate(&mut bytes, MIN_BYTES, Some(&ADD_IN_256[..strength]));
 
         if res != 0 {
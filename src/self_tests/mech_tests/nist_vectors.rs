@@ -1,6 +1,10 @@
-use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use crate::mechs::{gen_mech::DRBG_Mechanism_Functions, hash_mech::HashDrbgMech, hmac_mech::HmacDrbgMech,
+                   ctr_mech::CtrDrbgMech, ctr_mech_with_df::CtrDrbgMech_DF};
 use crate::self_tests::formats::*;
 use serde::Deserialize;
+use std::any::TypeId;
+use sha2::*;
+use aes::*;
 
 /*  The name of the test module to be printed in the log. */
 const AL_NAME: &str = "MECH-TESTS::nist_vectors";
@@ -10,13 +14,68 @@ const AL_NAME: &str = "MECH-TESTS::nist_vectors";
 pub fn test_vectors<T: DRBG_Mechanism_Functions>(fun_id: &str, strength: usize) -> usize{
     let (prr_file, no_prr_file) = get_files::<T>(fun_id);
 
-    return test_vectors_no_prr::<T>(no_prr_file, strength) +
-            test_vectors_prr::<T>(prr_file, strength);
+    check_coverage(no_prr_file, "test_vectors_no_prr") +
+        check_coverage(prr_file, "test_vectors_prr") +
+        test_vectors_no_prr::<T>(no_prr_file, strength) +
+        test_vectors_prr::<T>(prr_file, strength)
+}
+
+/*  Verifies that a vectors file covers every CAVP combination of personalization string and additional input lengths
+    (both empty, only one of them, both present), so that the padding and update logic that handles these inputs is
+    always exercised against known answers. */
+fn check_coverage(file: &str, test_name: &str) -> usize {
+    /*  The fields that determine the combination of a vector. */
+    #[derive(Deserialize, Debug)]
+    struct Fixture {
+        pers: Option<String>,
+        add_in_gen: Option<String>,
+    }
+
+    let tests: Vec<Fixture> = serde_json::from_str(file).unwrap_or_default();
+    let mut covered = [[false; 2]; 2];
+    for test in tests {
+        let has_pers = !test.pers.unwrap_or_default().is_empty();
+        let has_add = !test.add_in_gen.unwrap_or_default().is_empty();
+        covered[has_pers as usize][has_add as usize] = true;
+    }
+
+    let mut name = "check_coverage ".to_string();
+    name.push_str(test_name);
+    check_res(covered, [[true; 2]; 2],
+        name,
+        AL_NAME.to_string(),
+        "nist vectors do not cover every personalization string/additional input combination.".to_string(),
+        "nist vectors cover every personalization string/additional input combination.".to_string())
+}
+
+/*  This is a utility function that returns the identifier of the function used by a mechanism, which is used by
+    get_files to select the vectors (None if the mechanism is not supported). */
+pub fn fun_id<T: DRBG_Mechanism_Functions + 'static>() -> Option<&'static str> {
+    let this_id = TypeId::of::<T>();
+
+    if this_id == TypeId::of::<HashDrbgMech<Sha256>>() || this_id == TypeId::of::<HmacDrbgMech<Sha256>>() {
+        Some("Sha 256")
+    }
+    else if this_id == TypeId::of::<HashDrbgMech<Sha512>>() || this_id == TypeId::of::<HmacDrbgMech<Sha512>>() {
+        Some("Sha 512")
+    }
+    else if this_id == TypeId::of::<CtrDrbgMech<Aes128>>() || this_id == TypeId::of::<CtrDrbgMech_DF<Aes128>>() {
+        Some("AES 128")
+    }
+    else if this_id == TypeId::of::<CtrDrbgMech<Aes192>>() || this_id == TypeId::of::<CtrDrbgMech_DF<Aes192>>() {
+        Some("AES 192")
+    }
+    else if this_id == TypeId::of::<CtrDrbgMech<Aes256>>() || this_id == TypeId::of::<CtrDrbgMech_DF<Aes256>>() {
+        Some("AES 256")
+    }
+    else {
+        None
+    }
 }
 
 /*  This is a utility function whose purpose is to select the rigth nist_vectors file for the DRBG
-    mechanism that is being tested. */
-fn get_files<T: DRBG_Mechanism_Functions>(fun_id: &str) -> (&str, &str) {
+    mechanism that is being tested. It returns the prr and no_prr files, in this order. */
+pub fn get_files<T: DRBG_Mechanism_Functions>(fun_id: &str) -> (&str, &str) {
     let prr_file;
     let no_prr_file;
     if T::drbg_name() == "Hash-DRBG" {
@@ -119,8 +178,8 @@ fn test_vectors_prr<T: DRBG_Mechanism_Functions>(prr_file: &str, mut strength: u
         let full_len = expected.len();
         let ent_pr = hex::decode(&test.entropy_pr).unwrap();
         let ent_pr2 = hex::decode(&test.entropy_pr2).unwrap();
-        let add0 = test.add_in_gen.as_ref().map(|v| hex::decode(&v).unwrap());
-        let add1 = test.add_in_gen2.as_ref().map(|v| hex::decode(&v).unwrap());
+        let add0 = test.add_in_gen.as_ref().filter(|v| !v.is_empty()).map(|v| hex::decode(v).unwrap());
+        let add1 = test.add_in_gen2.as_ref().filter(|v| !v.is_empty()).map(|v| hex::decode(v).unwrap());
 
         drbg.reseed(&ent_pr, match add0 {
                                     Some(ref add) => Some(add.as_ref()),
@@ -200,9 +259,9 @@ fn test_vectors_no_prr<T: DRBG_Mechanism_Functions>(no_prr_file: &str, mut stren
         let mut result = Vec::new();
         let full_len = expected.len();
         let ent_reseed = hex::decode(&test.entropy_reseed).unwrap();
-        let add_reseed = test.add_in_reseed.as_ref().map(|v| hex::decode(&v).unwrap());
-        let add0 = test.add_in_gen.as_ref().map(|v| hex::decode(&v).unwrap());
-        let add1 = test.add_in_gen2.as_ref().map(|v| hex::decode(&v).unwrap());
+        let add_reseed = test.add_in_reseed.as_ref().filter(|v| !v.is_empty()).map(|v| hex::decode(v).unwrap());
+        let add0 = test.add_in_gen.as_ref().filter(|v| !v.is_empty()).map(|v| hex::decode(v).unwrap());
+        let add1 = test.add_in_gen2.as_ref().filter(|v| !v.is_empty()).map(|v| hex::decode(v).unwrap());
 
         drbg.reseed(&ent_reseed, match add_reseed {
                                     Some(ref add) => Some(add.as_ref()),
@@ -1,5 +1,21 @@
 pub mod drbg_tests;
 pub mod mech_tests;
+pub mod derivation_tests;
+pub mod kdf_tests;
+pub mod cavp;
+pub mod acvp;
+pub mod log_tests;
+pub mod report_tests;
+pub mod approved_mode_tests;
+pub mod health_tests;
+pub mod module_state_tests;
+pub mod drbg_module_tests;
+pub mod fork_tests;
+pub mod cli_tests;
+pub mod ffi_tests;
+pub mod stats_tests;
+pub mod distributions_tests;
 pub mod run_tests;
 pub mod formats;
+pub mod report;
 pub mod constants;
\ No newline at end of file
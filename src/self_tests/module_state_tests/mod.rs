@@ -0,0 +1,2 @@
+pub mod module_state_test;
+pub mod run_all;
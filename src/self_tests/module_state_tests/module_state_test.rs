@@ -0,0 +1,168 @@
+use crate::drbg::approved_mode::{set_approved_mode, ServiceIndicator};
+use crate::drbg::buffered::BufferedDrbg;
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::drbg::health::{run_all_health_tests, set_fault_injection};
+use crate::drbg::module_state::*;
+use crate::mechs::hash_mech::HashDrbgMech;
+use crate::self_tests::formats::*;
+use crate::self_tests::report::{capture, uncaptured};
+use sha2::Sha256;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "MODULE-STATE-TESTS::module_state_test";
+
+/*  The mechanism used by these tests. */
+type Mech = HashDrbgMech<Sha256>;
+
+/*  Aggregator that runs all the tests in this file, in the order of the state machine: Operational, Error (on-demand
+    self-test failure), Operational (recovery), Error and Operational again (with a buffered instance), Error (first use
+    self-test failure), Error (panicking recovery), Operational (full recovery). */
+pub fn run_tests() -> usize {
+    if recovering() {
+        return check_res(0, 0, "run_tests".to_string(), AL_NAME.to_string(), String::new(),
+                         "skipped while the module is recovering.".to_string());
+    }
+
+    operational() +
+        on_demand_failure() +
+        buffered_failure() +
+        first_use_failure() +
+        panicking_recovery() +
+        full_recovery()
+}
+
+/*  Runs self-tests with injected failures: their records are logged to memory and are not part of the reports. */
+fn with_injected_failure<R>(f: impl FnOnce() -> R) -> R {
+    let previous = set_log_sink(LogSink::Memory, LogFormat::Text);
+    let section = current_section();
+    set_fault_injection(true);
+    let res = uncaptured(f);
+    set_fault_injection(false);
+    take_memory_log();
+    set_log_sink(previous.0, previous.1);
+    set_section(&section);
+
+    res
+}
+
+/*  A recovery that only runs the health tests. */
+fn health_tests() -> crate::self_tests::report::SelfTestReport {
+    capture("HEALTH-TESTS", run_all_health_tests)
+}
+
+/*  Verifying that the module is Operational and cannot be recovered. */
+fn operational() -> usize {
+    check_res((module_state(), module_error(), recover()), (ModuleState::Operational, false, 2),
+        "operational".to_string(),
+        AL_NAME.to_string(),
+        "the module is not in the Operational state.".to_string(),
+        "the module is in the Operational state and cannot be recovered.".to_string())
+}
+
+/*  Verifying that a failure of the on-demand self-tests halts every instance of the module, while instances can still
+    be zeroized. */
+fn on_demand_failure() -> usize {
+    let mut bytes = Vec::<u8>::new();
+    let (mut tested, mut other) = match (DRBG::<Mech>::new(32, None), DRBG::<Mech>::new(32, None)) {
+        (Ok(tested), Ok(other)) => (tested, other),
+        _ => {
+            write_to_log(format_message(true, AL_NAME.to_string(), "on_demand_failure".to_string(),
+                                        "failed to instantiate DRBG.".to_string()));
+            return 1;
+        }
+    };
+
+    let res_tests = with_injected_failure(|| tested.run_self_tests());
+    let status = module_status();
+    let halted = (tested.generate(&mut bytes, 16, 32, false, None), other.generate(&mut bytes, 16, 32, false, None),
+                  other.reseed(None), DRBG::<Mech>::new(32, None).err(), other.run_self_tests(), other.uninstantiate());
+    let recovery = (recover_with(health_tests), module_state());
+
+    check_res((res_tests, status.state, status.cause, halted, recovery),
+        (1, ModuleState::Error, Some(ModuleErrorCause::OnDemandSelfTest { mechanism: "Hash-DRBG Sha 256 on-demand self-tests".to_string() }),
+         (1, 1, 1, Some(5), 1, 0), (0, ModuleState::Operational)),
+        "on_demand_failure".to_string(),
+        AL_NAME.to_string(),
+        "a failure of the on-demand self-tests did not halt the module as expected.".to_string(),
+        "a failure of the on-demand self-tests halted the module until recovery.".to_string())
+}
+
+/*  Verifying that the pre-generated bytes of a buffered instance are discarded instead of served once the module is in
+    error state, and that the instance serves new bytes after recovery. */
+fn buffered_failure() -> usize {
+    let (drbg, mut tested) = match (DRBG::<Mech>::new(32, None), DRBG::<Mech>::new(32, None)) {
+        (Ok(drbg), Ok(tested)) => (drbg, tested),
+        _ => {
+            write_to_log(format_message(true, AL_NAME.to_string(), "buffered_failure".to_string(),
+                                        "failed to instantiate DRBG.".to_string()));
+            return 1;
+        }
+    };
+    let mut buffered = BufferedDrbg::new(drbg, 256);
+    let mut out = [0u8; 16];
+    let prefetched = (buffered.prefetch(), buffered.buffered());
+
+    with_injected_failure(|| tested.run_self_tests());
+    let halted = (buffered.generate(&mut out, 32, false, None), out == [0u8; 16], buffered.buffered(),
+                  buffered.get_ref().get_service_indicator(), buffered.prefetch());
+    let recovery = recover_with(health_tests);
+    let resumed = (buffered.generate(&mut out, 32, false, None), out != [0u8; 16], buffered.get_ref().get_service_indicator());
+
+    check_res((prefetched, halted, recovery, resumed),
+        ((0, 256), (1, true, 0, ServiceIndicator::NonApproved, 1), 0, (0, true, ServiceIndicator::Approved)),
+        "buffered_failure".to_string(),
+        AL_NAME.to_string(),
+        "a buffered instance served pre-generated bytes while the module was in error state.".to_string(),
+        "a buffered instance discarded its pre-generated bytes while the module was in error state.".to_string())
+}
+
+/*  Verifying that a failure of the first use self-tests puts the module in error state instead of allowing a retry. The
+    first use self-tests are forced through the approved mode. */
+fn first_use_failure() -> usize {
+    let previous = set_approved_mode(true);
+    DRBG::<Mech>::reset_first_time();
+    let res = with_injected_failure(|| DRBG::<Mech>::new(32, None).err());
+    let retry = DRBG::<Mech>::new(32, None).err();
+    set_approved_mode(previous);
+    let status = module_status();
+
+    check_res((res, retry, status.state, status.cause), (Some(4), Some(5), ModuleState::Error,
+        Some(ModuleErrorCause::FirstUseSelfTest { mechanism: "Hash-DRBG Sha 256 self-tests for first time use".to_string() })),
+        "first_use_failure".to_string(),
+        AL_NAME.to_string(),
+        "a failure of the first use self-tests did not put the module in error state.".to_string(),
+        "a failure of the first use self-tests put the module in error state.".to_string())
+}
+
+/*  Verifying that a recovery whose self-tests panic leaves the module in the Error state, and not Recovering. */
+fn panicking_recovery() -> usize {
+    let before = module_status();
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let res = std::panic::catch_unwind(|| recover_with(|| panic!("injected panic"))).is_err();
+    std::panic::set_hook(previous_hook);
+    let after = module_status();
+
+    check_res((res, after.state, recovering(), module_error(), after.failed_recoveries - before.failed_recoveries),
+        (true, ModuleState::Error, false, true, 1),
+        "panicking_recovery".to_string(),
+        AL_NAME.to_string(),
+        "a panicking recovery did not put the module back in the Error state.".to_string(),
+        "a panicking recovery put the module back in the Error state.".to_string())
+}
+
+/*  Verifying the documented recovery: the first use and on-demand self-tests are run again and the error is cleared. */
+fn full_recovery() -> usize {
+    let before = module_status();
+    let res = recover();
+    let after = module_status();
+    let summary = after.last_recovery.as_ref().map(|summary| (summary.suite.as_str(), summary.failed, summary.total > 0));
+    let json = serde_json::to_string(&after).ok().and_then(|json| serde_json::from_str::<ModuleStatus>(&json).ok());
+
+    check_res((res, after.state, after.recoveries - before.recoveries, summary, DRBG::<Mech>::new(32, None).is_ok(), json == Some(after.clone())),
+        (0, ModuleState::Operational, 1, Some(("self-tests", 0, true)), true, true),
+        "full_recovery".to_string(),
+        AL_NAME.to_string(),
+        "the module was not recovered by running the self-tests of the mechanisms.".to_string(),
+        "the module was recovered by running the self-tests of the mechanisms.".to_string())
+}
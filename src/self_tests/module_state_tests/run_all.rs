@@ -0,0 +1,7 @@
+use super::*;
+
+/*  Runs the tests of the module state machine (see 'drbg/module_state.rs'), they are skipped if the module is
+    recovering. */
+pub fn run_tests() -> usize {
+    module_state_test::run_tests()
+}
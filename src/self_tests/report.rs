@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+use super::formats::LogRecord;
+
+/*  Structured reports of the self-tests.
+
+    The self-tests only return the number of failures, the details of every test are sent to the log (see 'formats').
+    A report is built by capturing the test records that are logged while a group of tests is running (see capture):
+
+        SelfTestReport              a suite of tests (e.g. "self-tests", "MECH-TESTS")
+            MechanismReport         the tests run in a section of the log (e.g. "Hash-DRBG Sha-256 self-tests")
+                TestCaseReport      a single test (e.g. "MECH-TESTS::nist_vectors" - "test_vectors")
+
+    Captures can be nested: a record is added to every capture that is active on the thread that logs it. Reports can be
+    serialized to JSON and compared with each other (see SelfTestReport::diff) to track regressions across builds. */
+
+/*  The outcome of a test. */
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+}
+
+/*  The result of a single test, duration_us is the time elapsed since the previous test of the same capture. */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TestCaseReport {
+    pub module: String,
+    pub name: String,
+    pub status: TestStatus,
+    pub message: String,
+    pub duration_us: u64,
+}
+
+/*  The tests run for a mechanism (i.e. in a section of the log). */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MechanismReport {
+    pub name: String,
+    pub duration_us: u64,
+    pub tests: Vec<TestCaseReport>,
+}
+
+impl MechanismReport {
+    /*  Returns the number of tests that have failed. */
+    pub fn failures(&self) -> usize {
+        self.tests.iter().filter(|test| test.status == TestStatus::Failed).count()
+    }
+}
+
+/*  The report of a suite of self-tests. */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SelfTestReport {
+    pub suite: String,
+    pub timestamp: String,
+    pub duration_us: u64,
+    pub mechanisms: Vec<MechanismReport>,
+}
+
+/*  A summary of a report, failed_tests lists the failed tests as "mechanism: module - test". */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReportSummary {
+    pub suite: String,
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub duration_us: u64,
+    pub failed_tests: Vec<String>,
+}
+
+/*  A difference between two reports (see SelfTestReport::diff). Tests are identified by mechanism, module, name and
+    occurrence (the same test can be run more than once in a mechanism). */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum ReportDiff {
+    Added {
+        mechanism: String,
+        module: String,
+        test: String,
+        status: TestStatus,
+    },
+    Removed {
+        mechanism: String,
+        module: String,
+        test: String,
+        status: TestStatus,
+    },
+    StatusChanged {
+        mechanism: String,
+        module: String,
+        test: String,
+        before: TestStatus,
+        after: TestStatus,
+    },
+}
+
+impl SelfTestReport {
+    /*  Creates an empty report for the given suite. */
+    pub fn new(suite: &str) -> Self {
+        Self {
+            suite: suite.to_string(),
+            timestamp: chrono::offset::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false),
+            duration_us: 0,
+            mechanisms: Vec::new(),
+        }
+    }
+
+    /*  Returns the number of tests that have failed. */
+    pub fn failures(&self) -> usize {
+        self.mechanisms.iter().map(|mech| mech.failures()).sum()
+    }
+
+    /*  Returns the total number of tests. */
+    pub fn total(&self) -> usize {
+        self.mechanisms.iter().map(|mech| mech.tests.len()).sum()
+    }
+
+    /*  Returns true if no test has failed. */
+    pub fn passed(&self) -> bool {
+        self.failures() == 0
+    }
+
+    /*  Returns a summary of the report. */
+    pub fn summary(&self) -> ReportSummary {
+        let failed_tests = self.mechanisms.iter()
+            .flat_map(|mech| mech.tests.iter()
+                .filter(|test| test.status == TestStatus::Failed)
+                .map(move |test| format!("{}: {} - {}", mech.name, test.module, test.name)))
+            .collect();
+
+        ReportSummary {
+            suite: self.suite.clone(),
+            total: self.total(),
+            passed: self.total() - self.failures(),
+            failed: self.failures(),
+            duration_us: self.duration_us,
+            failed_tests,
+        }
+    }
+
+    /*  Appends the result of a test to the report, the mechanism is created if needed. */
+    pub fn add_test(&mut self, mechanism: &str, test: TestCaseReport) {
+        let idx = match self.mechanisms.iter().position(|mech| mech.name == mechanism) {
+            Some(idx) => idx,
+            None => {
+                self.mechanisms.push(MechanismReport { name: mechanism.to_string(), duration_us: 0, tests: Vec::new() });
+                self.mechanisms.len() - 1
+            }
+        };
+
+        self.mechanisms[idx].duration_us += test.duration_us;
+        self.mechanisms[idx].tests.push(test);
+    }
+
+    /*  Appends every mechanism of another report to this one. */
+    pub fn merge(&mut self, other: SelfTestReport) {
+        self.duration_us += other.duration_us;
+        for mech in other.mechanisms {
+            for test in mech.tests {
+                self.add_test(&mech.name, test);
+            }
+        }
+    }
+
+    /*  Serializes the report as pretty-printed JSON. */
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /*  Parses a report serialized with to_json.
+
+        Return values:
+            - Ok(report): SUCCESS
+            - Err(1): ERROR, the JSON content is malformed */
+    pub fn from_json(json: &str) -> Result<Self, usize> {
+        serde_json::from_str(json).map_err(|_| 1usize)
+    }
+
+    /*  Compares this report (e.g. of a previous build) with a newer one.
+
+        Return values:
+            - the tests that have been added, removed or whose status has changed, in the order of the reports */
+    pub fn diff(&self, newer: &SelfTestReport) -> Vec<ReportDiff> {
+        let old_tests = self.keyed_tests();
+        let new_tests = newer.keyed_tests();
+        let old_map: HashMap<_, _> = old_tests.iter().cloned().collect();
+        let new_map: HashMap<_, _> = new_tests.iter().cloned().collect();
+        let mut res = Vec::<ReportDiff>::new();
+
+        for (key, status) in old_tests.iter() {
+            let (mechanism, module, test, _) = key.clone();
+            match new_map.get(key) {
+                None => res.push(ReportDiff::Removed { mechanism, module, test, status: *status }),
+                Some(after) if after != status => {
+                    res.push(ReportDiff::StatusChanged { mechanism, module, test, before: *status, after: *after })
+                }
+                Some(_) => {}
+            }
+        }
+
+        for (key, status) in new_tests.iter() {
+            if !old_map.contains_key(key) {
+                let (mechanism, module, test, _) = key.clone();
+                res.push(ReportDiff::Added { mechanism, module, test, status: *status });
+            }
+        }
+
+        res
+    }
+
+    /*  Returns the status of every test identified by (mechanism, module, name, occurrence). */
+    fn keyed_tests(&self) -> Vec<((String, String, String, usize), TestStatus)> {
+        let mut occurrences = HashMap::<(String, String, String), usize>::new();
+
+        self.mechanisms.iter()
+            .flat_map(|mech| mech.tests.iter().map(move |test| (mech, test)))
+            .map(|(mech, test)| {
+                let id = (mech.name.clone(), test.module.clone(), test.name.clone());
+                let occurrence = occurrences.entry(id.clone()).or_insert(0);
+                *occurrence += 1;
+                ((id.0, id.1, id.2, *occurrence), test.status)
+            })
+            .collect()
+    }
+}
+
+/*  A capture that is collecting test records.
+
+    - thread: the thread that started the capture
+    - paused: the number of active calls of uncaptured on the thread
+    - last: the time of the last record (or of the start of the capture) */
+struct Capture {
+    thread: ThreadId,
+    paused: usize,
+    started: Instant,
+    last: Instant,
+    report: SelfTestReport,
+}
+
+static CAPTURES: Mutex<Vec<Capture>> = Mutex::new(Vec::new());
+
+/*  Runs f on the active captures, a poisoned lock is recovered since the captures are always consistent. */
+fn with_captures<R>(f: impl FnOnce(&mut Vec<Capture>) -> R) -> R {
+    let mut captures = CAPTURES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut captures)
+}
+
+/*  Runs a group of tests and builds the report of the records they log.
+
+    Parameters:
+        - suite: the name of the suite
+        - tests: the tests to be run, returning the number of failures
+
+    Return values:
+        - the report of the tests; if tests reports more failures than the failed records that have been logged, the
+          missing ones are added as a failed "run_tests" test so that the report never hides a failure */
+pub fn capture(suite: &str, tests: impl FnOnce() -> usize) -> SelfTestReport {
+    let now = Instant::now();
+    let thread = thread::current().id();
+    with_captures(|captures| captures.push(Capture {
+        thread,
+        paused: 0,
+        started: now,
+        last: now,
+        report: SelfTestReport::new(suite),
+    }));
+
+    let res = tests();
+
+    let capture = with_captures(|captures| {
+        let idx = captures.iter().rposition(|capture| capture.thread == thread);
+        idx.map(|idx| captures.remove(idx))
+    });
+
+    let (mut report, started) = match capture {
+        Some(capture) => (capture.report, capture.started),
+        None => (SelfTestReport::new(suite), now),
+    };
+    report.duration_us = started.elapsed().as_micros() as u64;
+
+    let missing = res.saturating_sub(report.failures());
+    if missing > 0 {
+        let mechanism = report.mechanisms.last().map(|mech| mech.name.clone()).unwrap_or_else(|| suite.to_string());
+        report.add_test(&mechanism, TestCaseReport {
+            module: suite.to_string(),
+            name: "run_tests".to_string(),
+            status: TestStatus::Failed,
+            message: format!("{} failures were not reported to the log.", missing),
+            duration_us: 0,
+        });
+    }
+
+    report
+}
+
+/*  Runs f without adding the records it logs to the active captures of the thread (e.g. records written by the tests of
+    the log itself). */
+pub fn uncaptured<R>(f: impl FnOnce() -> R) -> R {
+    let thread = thread::current().id();
+    let update = |pause: bool| with_captures(|captures| {
+        for capture in captures.iter_mut().filter(|capture| capture.thread == thread) {
+            capture.paused = if pause { capture.paused + 1 } else { capture.paused.saturating_sub(1) };
+        }
+    });
+
+    update(true);
+    let res = f();
+    update(false);
+
+    res
+}
+
+/*  Adds a test record to the active captures of the current thread, other records are ignored. */
+pub(crate) fn collect(record: &LogRecord) {
+    let (mechanism, module, test, passed, message) = match record {
+        LogRecord::Test { mechanism, module, test, passed, message, .. } => (mechanism, module, test, *passed, message),
+        _ => return,
+    };
+    let thread = thread::current().id();
+    let now = Instant::now();
+
+    with_captures(|captures| {
+        for capture in captures.iter_mut().filter(|capture| capture.thread == thread && capture.paused == 0) {
+            let duration_us = now.duration_since(capture.last).as_micros() as u64;
+            capture.last = now;
+            capture.report.add_test(mechanism, TestCaseReport {
+                module: module.clone(),
+                name: test.clone(),
+                status: if passed { TestStatus::Passed } else { TestStatus::Failed },
+                message: message.clone(),
+                duration_us,
+            });
+        }
+    });
+}
@@ -0,0 +1,2 @@
+pub mod report_test;
+pub mod run_all;
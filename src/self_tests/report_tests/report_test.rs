@@ -0,0 +1,106 @@
+use crate::self_tests::formats::*;
+use crate::self_tests::report::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "REPORT-TESTS::report_test";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    capture_tree() +
+        unreported_failures() +
+        json_round_trip() +
+        report_diff()
+}
+
+/*  Runs probe tests in a capture of their own: their records are neither written to the log nor added to the reports
+    of the caller. */
+fn probe_capture(tests: impl FnOnce() -> usize) -> SelfTestReport {
+    let previous = set_log_sink(LogSink::Disabled, LogFormat::Text);
+    let section = current_section();
+    let report = uncaptured(|| capture("PROBE-TESTS", tests));
+    set_log_sink(previous.0, previous.1);
+    set_section(&section);
+
+    report
+}
+
+/*  Probe tests on two mechanisms, the second one fails. */
+fn probe_tests() -> usize {
+    set_section("mech A");
+    let mut res = check_res(1, 1, "first".to_string(), "PROBE".to_string(), "fail".to_string(), "pass".to_string());
+    res += check_res(2, 2, "second".to_string(), "PROBE".to_string(), "fail".to_string(), "pass".to_string());
+    set_section("mech B");
+    res += check_res(1, 2, "third".to_string(), "PROBE".to_string(), "fail".to_string(), "pass".to_string());
+
+    res
+}
+
+/*  Verifying that the captured records are grouped by mechanism and summarized. */
+fn capture_tree() -> usize {
+    let report = probe_capture(probe_tests);
+    let summary = report.summary();
+    let names: Vec<(&str, usize)> = report.mechanisms.iter().map(|mech| (mech.name.as_str(), mech.tests.len())).collect();
+
+    check_res((names, summary.total, summary.passed, summary.failed, summary.failed_tests),
+        (vec![("mech A", 2), ("mech B", 1)], 3, 2, 1, vec!["mech B: PROBE - third".to_string()]),
+        "capture_tree".to_string(),
+        AL_NAME.to_string(),
+        "the captured report does not match the tests that have been run.".to_string(),
+        "the captured report matches the tests that have been run.".to_string())
+}
+
+/*  Verifying that failures that are not logged are still reported. */
+fn unreported_failures() -> usize {
+    let report = probe_capture(|| probe_tests() + 2);
+    let last = report.mechanisms.last().and_then(|mech| mech.tests.last());
+    let res = last.map(|test| (test.name.as_str(), test.status, test.message.as_str()));
+
+    check_res((report.failures(), res), (2, Some(("run_tests", TestStatus::Failed, "2 failures were not reported to the log."))),
+        "unreported_failures".to_string(),
+        AL_NAME.to_string(),
+        "failures that were not logged are missing from the report.".to_string(),
+        "failures that were not logged have been added to the report.".to_string())
+}
+
+/*  Verifying that a report survives a JSON round trip. */
+fn json_round_trip() -> usize {
+    let report = probe_capture(probe_tests);
+
+    check_res(SelfTestReport::from_json(&report.to_json()), Ok(report),
+        "json_round_trip".to_string(),
+        AL_NAME.to_string(),
+        "the report changed after a JSON round trip.".to_string(),
+        "the report is unchanged after a JSON round trip.".to_string())
+}
+
+/*  Verifying that the diff of two reports lists the changed, removed and added tests. */
+fn report_diff() -> usize {
+    let old = probe_capture(probe_tests);
+    let mut new = old.clone();
+    let test = |name: &str, status| TestCaseReport {
+        module: "PROBE".to_string(),
+        name: name.to_string(),
+        status,
+        message: String::new(),
+        duration_us: 0,
+    };
+
+    new.mechanisms[0].tests.remove(1);
+    new.mechanisms[1].tests[0].status = TestStatus::Passed;
+    new.add_test("mech C", test("fourth", TestStatus::Failed));
+
+    let expected = vec![
+        ReportDiff::Removed { mechanism: "mech A".to_string(), module: "PROBE".to_string(), test: "second".to_string(),
+            status: TestStatus::Passed },
+        ReportDiff::StatusChanged { mechanism: "mech B".to_string(), module: "PROBE".to_string(), test: "third".to_string(),
+            before: TestStatus::Failed, after: TestStatus::Passed },
+        ReportDiff::Added { mechanism: "mech C".to_string(), module: "PROBE".to_string(), test: "fourth".to_string(),
+            status: TestStatus::Failed },
+    ];
+
+    check_res((old.diff(&new), old.diff(&old).is_empty()), (expected, true),
+        "report_diff".to_string(),
+        AL_NAME.to_string(),
+        "the diff of two reports is wrong.".to_string(),
+        "the diff of two reports lists the expected changes.".to_string())
+}
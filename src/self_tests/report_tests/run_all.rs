@@ -0,0 +1,6 @@
+use super::*;
+
+/*  Runs the tests of the self-test reports (see 'report'). */
+pub fn run_tests() -> usize {
+    report_test::run_tests()
+}
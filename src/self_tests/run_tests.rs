@@ -1,8 +1,12 @@
 use crate::mechs::{hash_mech::HashDrbgMech, hmac_mech::HmacDrbgMech, ctr_mech::CtrDrbgMech, ctr_mech_with_df::CtrDrbgMech_DF};
-use super::{drbg_tests, mech_tests, formats};
+use super::{drbg_tests, mech_tests, derivation_tests, kdf_tests, cavp, acvp, log_tests, report_tests, approved_mode_tests, health_tests, module_state_tests, drbg_module_tests, fork_tests, cli_tests, ffi_tests, stats_tests, distributions_tests, formats, report};
+use super::report::SelfTestReport;
 use sha2::*;
 use aes::*;
+use crate::drbg::approved_mode;
 use crate::drbg::drbg_conf::*;
+use std::sync::atomic::Ordering;
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
 
 /*  Here we are running self-tests for every DRBG and every mechanism that is available in this crate.
     These self-tests include:
@@ -15,80 +19,214 @@ use crate::drbg::drbg_conf::*;
                     this trait defines functionalities that are common between every DRBG mechanism used in this crate.
                     The goal of these tests is to make sure that every mechanisms is working properly.
                     Inside these tests we also run the NIST vectors associated to each specific DRBG mechanism.
+
+        DERIVATION-TESTS: known-answer tests for the standalone derivation functions of the mechs/derivation.rs module.
+
+        KDF-TESTS: known-answer tests for the SP 800-108 KDFs of the kdf module and the PRFs they are built on.
+
+        CAVP-TESTS: every section of the CAVP response files bundled in cavp/vectors is run against the matching
+                    mechanism, together with the tests of the response file parser.
+
+        APPROVED-MODE-TESTS: tests of the approved mode policy and of the service indicator (see 'drbg/approved_mode.rs')
+                             on a representative set of mechanisms. These tests change the policy of the whole module
+                             and are therefore not part of the first use and on-demand self-tests.
+
+        HEALTH-TESTS: tests of the periodicity of the health tests and of the module error state latched by their failure
+                      (see 'drbg/health.rs'), on a representative set of mechanisms. These tests change the configuration
+                      of the health tests and the state of the whole module and are therefore not part of the first use
+                      and on-demand self-tests.
+
+        MODULE-STATE-TESTS: tests of the state machine of the module error state (see 'drbg/module_state.rs'), including
+                            a full recovery that runs the first use and on-demand self-tests again (see run_recovery).
+
+        DRBG-MODULE-TESTS: tests of the registry of DRBG instances addressed by state handles (see 'drbg/drbg_module.rs').
+
+        FORK-TESTS: tests of the fork detection (see 'drbg/fork.rs'), which fork the process and check the output of
+                    the instances in the child. These tests are only run on Linux and are not part of the first use and
+                    on-demand self-tests.
+
+        CLI-TESTS: tests of the command line interface of the 'drbg' binary (see 'cli').
+
+        FFI-TESTS: tests of the C interface (see 'ffi'), whose header and cdylib are also tested by a C program in
+                   tests/ffi.rs.
+
+        STATS-TESTS: the tests of the SP 800-22 statistical test suite against the worked examples of the SP, together
+                     with the tests of the runner and of its report (see 'stats').
+
+        DISTRIBUTIONS-TESTS: chi-square tests of the unbiased integers, shuffles, samples and choices drawn from a DRBG
+                             with a deterministic entropy source (see 'drbg/distributions.rs').
+
+        LOG-TESTS: tests of the sinks of the self-tests log (see 'formats').
+
+        REPORT-TESTS: tests of the self-test reports (see 'report').
+
+        ACVP-TESTS: the sample ACVP vector sets bundled in acvp/vectors are run and compared against their expected
+                    results, together with the tests of the offline ACVP harness.
+
+    The details of every test are returned in a report (see 'report'), whose failures() are the failed tests.
 */
-pub fn run_all() -> usize {
+pub fn run_all() -> SelfTestReport {
     /*  We set this variable to avoid that during self-testing the same tests are run after first
         instantiations of each mechanism. This variable is then unset once the execution of the
         overall self-tests is over.
     */
-    unsafe { OVERALL_TEST_RUN = true };
+    OVERALL_TEST_RUN.store(true, Ordering::SeqCst);
+
+    report::capture("self-tests", || {
+        /*  LOG SINKS */
+        formats::start_section("log sinks self-tests");
+        let res_log = log_tests::run_all::run_tests();
+
+        /*  SELF-TEST REPORTS */
+        formats::start_section("self-test reports self-tests");
+        let res_report = report_tests::run_all::run_tests();
+
+        /*  DERIVATION FUNCTIONS */
+        formats::start_section("derivation functions self-tests");
+        let res_df = derivation_tests::run_all::run_tests();
+
+        /*  SP 800-108 KDFs */
+        formats::start_section("SP 800-108 KDF self-tests");
+        let res_kdf = kdf_tests::run_all::run_tests();
+
+        /*  MECHANISMS */
+        let res_mechs = mechanism_tests();
 
-    let mut log_message;
+        /*  APPROVED MODE */
+        formats::start_section("approved mode self-tests");
+        let res_approved = approved_mode_tests::run_all::run_tests();
+
+        /*  HEALTH TESTS */
+        formats::start_section("health tests self-tests");
+        let res_health = health_tests::run_all::run_tests();
+
+        /*  MODULE STATE */
+        formats::start_section("module state self-tests");
+        let res_module = module_state_tests::run_all::run_tests();
+
+        /*  DRBG MODULE */
+        formats::start_section("DRBG module self-tests");
+        let res_drbg_module = drbg_module_tests::run_all::run_tests();
+
+        /*  FORK DETECTION */
+        formats::start_section("fork detection self-tests");
+        let res_fork = fork_tests::run_all::run_tests();
+
+        /*  COMMAND LINE INTERFACE */
+        formats::start_section("command line interface self-tests");
+        let res_cli = cli_tests::run_all::run_tests();
+
+        /*  C INTERFACE */
+        formats::start_section("C interface self-tests");
+        let res_ffi = ffi_tests::run_all::run_tests();
+
+        /*  STATISTICAL TESTS */
+        formats::start_section("SP 800-22 statistical tests self-tests");
+        let res_stats = stats_tests::run_all::run_tests();
+
+        /*  DISTRIBUTIONS */
+        formats::start_section("distributions self-tests");
+        let res_distributions = distributions_tests::run_all::run_tests();
+
+        /*  CAVP RESPONSE FILES */
+        formats::start_section("CAVP response files self-tests");
+        let res_cavp = cavp::run_all::run_tests();
+
+        /*  ACVP VECTOR SETS */
+        formats::start_section("ACVP vector sets self-tests");
+        let res_acvp = acvp::run_all::run_tests();
+
+        // OVERALL_TEST_RUN.store(false, Ordering::SeqCst);
+        res_log + res_report + res_df + res_kdf + res_mechs + res_approved + res_health + res_module + res_drbg_module + res_fork + res_cli + res_ffi + res_stats + res_distributions + res_cavp + res_acvp
+    })
+}
+
+/*  Runs the mechanism and DRBG self-tests of a single mechanism, as done by run_all for each mechanism. As run_recovery,
+    the global state of the module is not changed: the first use self-tests of the other mechanisms are still run on
+    their first instantiation.
+
+    Parameters:
+        - title: the title of the section of the log (e.g. "Hash-DRBG Sha-256 self-tests")
+        - sec_str: the security strength used by the tests (expressed in bytes)
+
+    Return values:
+        - the report of the tests (suite "self-tests") */
+pub fn run_mechanism<T: DRBG_Mechanism_Functions + 'static>(title: &str, sec_str: usize) -> SelfTestReport {
+    approved_mode::self_test_scope(|| report::capture("self-tests", || {
+        formats::start_section(title);
+        mech_tests::run_all::run_tests::<T>(sec_str).failures() + drbg_tests::run_all::run_tests::<T>(sec_str).failures()
+    }))
+}
+
+/*  Runs the first use and on-demand self-tests (the mechanism and DRBG self-tests) of every mechanism, without changing
+    the global state of the module: this is what a recovery of the module runs (see 'drbg/module_state.rs') and what
+    drbg_selftest runs for DRBG_MECH_ALL (see 'ffi').
+
+    Return values:
+        - the report of the tests (suite "self-tests") */
+pub fn run_recovery() -> SelfTestReport {
+    approved_mode::self_test_scope(|| report::capture("self-tests", mechanism_tests))
+}
+
+/*  Runs the mechanism and DRBG self-tests of every mechanism, each in its own section of the log.
+
+    Return values:
+        - the number of failed tests */
+fn mechanism_tests() -> usize {
     let mut res_hash;
     let mut res_hmac;
     let mut res_ctr;
     let mut res_ctr_df;
 
     /*  HASH-DRBG SHA-256 */
-    log_message = "\n*** STARTING Hash-DRBG Sha-256 self-tests ***\n".to_string();
-    formats::write_to_log(log_message);
-    res_hash =  mech_tests::run_all::run_tests::<HashDrbgMech<Sha256>>(32) +
-        drbg_tests::run_all::run_tests::<HashDrbgMech<Sha256>>(32);
-    
+    formats::start_section("Hash-DRBG Sha-256 self-tests");
+    res_hash =  mech_tests::run_all::run_tests::<HashDrbgMech<Sha256>>(32).failures() +
+        drbg_tests::run_all::run_tests::<HashDrbgMech<Sha256>>(32).failures();
+
     /*  HASH-DRBG SHA-512 */
-    log_message = "\n*** STARTING Hash-DRBG Sha-512 self-tests ***\n".to_string();
-    formats::write_to_log(log_message);
-    res_hash +=  mech_tests::run_all::run_tests::<HashDrbgMech<Sha512>>(32) +
-        drbg_tests::run_all::run_tests::<HashDrbgMech<Sha512>>(32);
+    formats::start_section("Hash-DRBG Sha-512 self-tests");
+    res_hash +=  mech_tests::run_all::run_tests::<HashDrbgMech<Sha512>>(32).failures() +
+        drbg_tests::run_all::run_tests::<HashDrbgMech<Sha512>>(32).failures();
 
     /*  HMAC-DRBG SHA-256 */
-    log_message = "\n*** STARTING HMAC-DRBG Sha-256 self-tests ***\n".to_string();
-    formats::write_to_log(log_message);
-    res_hmac =  mech_tests::run_all::run_tests::<HmacDrbgMech<Sha256>>(32) +
-        drbg_tests::run_all::run_tests::<HmacDrbgMech<Sha256>>(32);
+    formats::start_section("HMAC-DRBG Sha-256 self-tests");
+    res_hmac =  mech_tests::run_all::run_tests::<HmacDrbgMech<Sha256>>(32).failures() +
+        drbg_tests::run_all::run_tests::<HmacDrbgMech<Sha256>>(32).failures();
 
     /*  HMAC-DRBG SHA-512 */
-    log_message = "\n*** STARTING HMAC-DRBG Sha-512 self-tests ***\n".to_string();
-    formats::write_to_log(log_message);
-    res_hmac +=  mech_tests::run_all::run_tests::<HmacDrbgMech<Sha512>>(32) +
-        drbg_tests::run_all::run_tests::<HmacDrbgMech<Sha512>>(32);
+    formats::start_section("HMAC-DRBG Sha-512 self-tests");
+    res_hmac +=  mech_tests::run_all::run_tests::<HmacDrbgMech<Sha512>>(32).failures() +
+        drbg_tests::run_all::run_tests::<HmacDrbgMech<Sha512>>(32).failures();
 
     /*  CTR-DRBG (no DF) AES-128 */
-    log_message = "\n*** STARTING CTR-DRBG AES-128 (no DF) self-tests ***\n".to_string();
-    formats::write_to_log(log_message);
-    res_ctr =  mech_tests::run_all::run_tests::<CtrDrbgMech<Aes128>>(16) +
-        drbg_tests::run_all::run_tests::<CtrDrbgMech<Aes128>>(16);
+    formats::start_section("CTR-DRBG AES-128 (no DF) self-tests");
+    res_ctr =  mech_tests::run_all::run_tests::<CtrDrbgMech<Aes128>>(16).failures() +
+        drbg_tests::run_all::run_tests::<CtrDrbgMech<Aes128>>(16).failures();
 
     /*  CTR-DRBG (no DF) AES-192 */
-    log_message = "\n*** STARTING CTR-DRBG AES-192 (no DF) self-tests ***\n".to_string();
-    formats::write_to_log(log_message);
-    res_ctr +=  mech_tests::run_all::run_tests::<CtrDrbgMech<Aes192>>(24) +
-        drbg_tests::run_all::run_tests::<CtrDrbgMech<Aes192>>(24);                
+    formats::start_section("CTR-DRBG AES-192 (no DF) self-tests");
+    res_ctr +=  mech_tests::run_all::run_tests::<CtrDrbgMech<Aes192>>(24).failures() +
+        drbg_tests::run_all::run_tests::<CtrDrbgMech<Aes192>>(24).failures();                
 
     /*  CTR-DRBG (no DF) AES-256 */
-    log_message = "\n*** STARTING CTR-DRBG AES-256 (no DF) self-tests ***\n".to_string();
-    formats::write_to_log(log_message);
-    res_ctr +=  mech_tests::run_all::run_tests::<CtrDrbgMech<Aes256>>(32) +
-        drbg_tests::run_all::run_tests::<CtrDrbgMech<Aes256>>(32);
+    formats::start_section("CTR-DRBG AES-256 (no DF) self-tests");
+    res_ctr +=  mech_tests::run_all::run_tests::<CtrDrbgMech<Aes256>>(32).failures() +
+        drbg_tests::run_all::run_tests::<CtrDrbgMech<Aes256>>(32).failures();
 
     /*  CTR-DRBG (DF) AES-128 */
-    log_message = "\n*** STARTING CTR-DRBG AES-128 (DF) self-tests ***\n".to_string();
-    formats::write_to_log(log_message);
-    res_ctr_df =  mech_tests::run_all::run_tests::<CtrDrbgMech_DF<Aes128>>(16) +
-        drbg_tests::run_all::run_tests::<CtrDrbgMech_DF<Aes128>>(16);
+    formats::start_section("CTR-DRBG AES-128 (DF) self-tests");
+    res_ctr_df =  mech_tests::run_all::run_tests::<CtrDrbgMech_DF<Aes128>>(16).failures() +
+        drbg_tests::run_all::run_tests::<CtrDrbgMech_DF<Aes128>>(16).failures();
 
     /*  CTR-DRBG (DF) AES-192 */
-    log_message = "\n*** STARTING CTR-DRBG AES-192 (DF) self-tests ***\n".to_string();
-    formats::write_to_log(log_message);
-    res_ctr_df +=  mech_tests::run_all::run_tests::<CtrDrbgMech_DF<Aes192>>(24) +
-        drbg_tests::run_all::run_tests::<CtrDrbgMech_DF<Aes192>>(24);                
+    formats::start_section("CTR-DRBG AES-192 (DF) self-tests");
+    res_ctr_df +=  mech_tests::run_all::run_tests::<CtrDrbgMech_DF<Aes192>>(24).failures() +
+        drbg_tests::run_all::run_tests::<CtrDrbgMech_DF<Aes192>>(24).failures();                
 
     /*  CTR-DRBG (DF) AES-256 */
-    log_message = "\n*** STARTING CTR-DRBG AES-256 (DF) self-tests ***\n".to_string();
-    formats::write_to_log(log_message);
-    res_ctr_df +=  mech_tests::run_all::run_tests::<CtrDrbgMech_DF<Aes256>>(32) +
-        drbg_tests::run_all::run_tests::<CtrDrbgMech_DF<Aes256>>(32);
-
-    // unsafe { OVERALL_TEST_RUN = false };
-    return res_hash + res_hmac + res_ctr + res_ctr_df;         
-}
\ No newline at end of file
+    formats::start_section("CTR-DRBG AES-256 (DF) self-tests");
+    res_ctr_df +=  mech_tests::run_all::run_tests::<CtrDrbgMech_DF<Aes256>>(32).failures() +
+        drbg_tests::run_all::run_tests::<CtrDrbgMech_DF<Aes256>>(32).failures();
+
+    res_hash + res_hmac + res_ctr + res_ctr_df
+}
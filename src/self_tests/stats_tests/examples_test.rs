@@ -0,0 +1,156 @@
+use crate::self_tests::formats::*;
+use crate::stats::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "STATS_TESTS::examples_test";
+
+/*  The tolerance of the p-values, which are given with 6 decimal digits in the SP. */
+const TOLERANCE: f64 = 1e-6;
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    frequency_examples() +
+        longest_run_example() +
+        rank_example() +
+        dft_example() +
+        template_examples() +
+        universal_example() +
+        complexity_examples() +
+        cumulative_sums_example() +
+        excursions_examples()
+}
+
+/*  Returns true if the p-values match the expected ones. */
+fn matches(res: Result<Vec<f64>, usize>, expected: &[f64], tolerance: f64) -> bool {
+    match res {
+        Ok(p_values) => p_values.len() == expected.len() &&
+            p_values.iter().zip(expected).all(|(p, e)| (p - e).abs() < tolerance),
+        Err(_) => false,
+    }
+}
+
+/*  Testing the frequency, block frequency and runs tests against the examples of sections 2.1, 2.2 and 2.3 of the SP. */
+fn frequency_examples() -> usize {
+    let res = (matches(frequency::frequency(&bits_from_str("1011010101")), &[0.527089], TOLERANCE),
+               matches(frequency::block_frequency(&bits_from_str("0110011010"), 3), &[0.801252], TOLERANCE),
+               matches(frequency::runs(&bits_from_str("1001101011")), &[0.147232], TOLERANCE),
+               frequency::block_frequency(&bits_from_str("01"), 3), frequency::frequency(&[]));
+
+    check_res(res, (true, true, true, Err(1), Err(1)),
+        "frequency_examples".to_string(),
+        AL_NAME.to_string(),
+        "the frequency tests did not match the examples of the SP.".to_string(),
+        "the frequency tests matched the examples of the SP.".to_string())
+}
+
+/*  Testing the longest run of ones test against the example of section 2.4 of the SP, whose p-value is given with 4
+    significant digits only. */
+fn longest_run_example() -> usize {
+    let bits = bits_from_str("11001100000101010110110001001100111000000000001001001101010100010001001111010110100000001101011111001100111001101101100010110010");
+
+    check_res((matches(frequency::longest_run(&bits), &[0.180609], 1e-4), frequency::longest_run(&bits[..127])), (true, Err(1)),
+        "longest_run_example".to_string(),
+        AL_NAME.to_string(),
+        "the longest run test did not match the example of the SP.".to_string(),
+        "the longest run test matched the example of the SP.".to_string())
+}
+
+/*  Testing the binary matrix rank test against the example of section 2.5 of the SP. The p-value of the example is
+    computed with the probabilities of 32 x 32 matrices rather than 3 x 3 ones, so the ranks of its two matrices and
+    the probabilities of 32 x 32 matrices (section 3.5) are tested instead. */
+fn rank_example() -> usize {
+    let (full, minus_one) = (matrix::rank_probability(32, 32, 32), matrix::rank_probability(31, 32, 32));
+    let probabilities = [full, minus_one, 1.0 - full - minus_one];
+    let res = (matrix::gf2_rank(vec![0b010, 0b110, 0b010]), matrix::gf2_rank(vec![0b010, 0b101, 0b011]),
+               probabilities.iter().zip([0.288788, 0.577576, 0.133636]).all(|(p, e)| (p - e).abs() < TOLERANCE),
+               matrix::rank(&bits_from_str("01011001001010101101"), 3, 3).is_ok(), matrix::rank(&bits_from_str("0101"), 3, 3));
+
+    check_res(res, (2, 3, true, true, Err(1)),
+        "rank_example".to_string(),
+        AL_NAME.to_string(),
+        "the binary matrix rank test did not match the example of the SP.".to_string(),
+        "the binary matrix rank test matched the example of the SP.".to_string())
+}
+
+/*  Testing the discrete Fourier transform test on the example of section 2.6 of the SP, whose length is not a power of
+    2. The example counts N1 = 4 peaks below T = 5.473, while the moduli |X_0|..|X_4| are 0, 2, 4.47, 2 and 4.47, so
+    the p-value is that of N1 = 5 (d = 0.725476). */
+fn dft_example() -> usize {
+    check_res(matches(spectral::dft(&bits_from_str("1001010011")), &[0.468160], TOLERANCE), true,
+        "dft_example".to_string(),
+        AL_NAME.to_string(),
+        "the discrete Fourier transform test did not match the example of the SP.".to_string(),
+        "the discrete Fourier transform test matched the example of the SP.".to_string())
+}
+
+/*  Testing the non-overlapping template test against the example of section 2.7 of the SP, together with the number of
+    aperiodic templates of 9 bits used by the NIST STS. */
+fn template_examples() -> usize {
+    let res = (matches(template::non_overlapping_template(&bits_from_str("10100100101110010110"), &bits_from_str("001"), 2),
+                       &[0.344154], TOLERANCE),
+               template::aperiodic_templates(template::TEMPLATE_LEN).len(),
+               template::aperiodic_templates(2));
+
+    check_res(res, (true, 148, vec![vec![0, 1], vec![1, 0]]),
+        "template_examples".to_string(),
+        AL_NAME.to_string(),
+        "the template tests did not match the examples of the SP.".to_string(),
+        "the template tests matched the examples of the SP.".to_string())
+}
+
+/*  Testing Maurer's universal test against the example of section 2.9 of the SP. The p-value of the example omits the
+    correction factor c and the number of test blocks K from the standard deviation, so the statistic f_n is tested. */
+fn universal_example() -> usize {
+    let bits = bits_from_str("01011010011101010111");
+    let res = ((universal::universal_statistic(&bits, 2, 4) - 1.1949875).abs() < TOLERANCE,
+               universal::universal_with(&bits, 2, 4).is_ok(), universal::universal_with(&bits, 2, 10), universal::universal(&[0; 1000]));
+
+    check_res(res, (true, true, Err(1), Err(1)),
+        "universal_example".to_string(),
+        AL_NAME.to_string(),
+        "the universal test did not match the example of the SP.".to_string(),
+        "the universal test matched the example of the SP.".to_string())
+}
+
+/*  Testing the linear complexity, serial and approximate entropy tests against the examples of sections 2.10, 2.11 and
+    2.12 of the SP. */
+fn complexity_examples() -> usize {
+    let res = (complexity::berlekamp_massey(&bits_from_str("1101011110001")),
+               matches(complexity::serial(&bits_from_str("0011011101"), 3), &[0.808792, 0.670320], TOLERANCE),
+               matches(complexity::approximate_entropy(&bits_from_str("0100110101"), 3), &[0.261961], TOLERANCE),
+               complexity::serial(&bits_from_str("0011011101"), 1));
+
+    check_res(res, (4, true, true, Err(2)),
+        "complexity_examples".to_string(),
+        AL_NAME.to_string(),
+        "the complexity tests did not match the examples of the SP.".to_string(),
+        "the complexity tests matched the examples of the SP.".to_string())
+}
+
+/*  Testing the cumulative sums test against the example of section 2.13 of the SP (forward mode). */
+fn cumulative_sums_example() -> usize {
+    let res = frequency::cumulative_sums(&bits_from_str("1011010111")).map(|p| (p[0] - 0.4116588).abs() < TOLERANCE);
+
+    check_res(res, Ok(true),
+        "cumulative_sums_example".to_string(),
+        AL_NAME.to_string(),
+        "the cumulative sums test did not match the example of the SP.".to_string(),
+        "the cumulative sums test matched the example of the SP.".to_string())
+}
+
+/*  Testing the random excursions tests against the examples of sections 2.14 and 2.15 of the SP (state x = +1), the
+    example of the random excursions test uses probabilities rounded to 4 digits. */
+fn excursions_examples() -> usize {
+    let bits = bits_from_str("0110110101");
+    let state = |states: &[i64]| states.iter().position(|x| *x == 1).unwrap_or(0);
+    let res = (excursions::cycles(&bits),
+               excursions::random_excursions(&bits, 0).map(|p| (p[state(&excursions::EXCURSION_STATES)] - 0.502529).abs() < 1e-4),
+               excursions::random_excursions_variant(&bits, 0).map(|p| (p[state(&excursions::VARIANT_STATES)] - 0.683091).abs() < TOLERANCE),
+               excursions::random_excursions(&bits, 4));
+
+    check_res(res, (3, Ok(true), Ok(true), Err(3)),
+        "excursions_examples".to_string(),
+        AL_NAME.to_string(),
+        "the random excursions tests did not match the examples of the SP.".to_string(),
+        "the random excursions tests matched the examples of the SP.".to_string())
+}
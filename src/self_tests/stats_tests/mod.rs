@@ -0,0 +1,3 @@
+pub mod examples_test;
+pub mod runner_test;
+pub mod run_all;
@@ -0,0 +1,7 @@
+use super::*;
+
+/*  Runs the tests of the SP 800-22 statistical test suite (see 'stats'). */
+pub fn run_tests() -> usize {
+    examples_test::run_tests() +
+        runner_test::run_tests()
+}
@@ -0,0 +1,105 @@
+use std::path::Path;
+use sha2::Sha256;
+use crate::drbg::entropy::FixedEntropySource;
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::mechs::hmac_mech::HmacDrbgMech;
+use crate::self_tests::formats::*;
+use crate::stats::runner::*;
+
+/*  The name of the test module to be printed in the log. */
+const AL_NAME: &str = "STATS_TESTS::runner_test";
+
+/*  Aggregator that runs all the tests in this file. */
+pub fn run_tests() -> usize {
+    drbg_report() +
+        biased_bytes() +
+        evaluation()
+}
+
+/*  A configuration small enough for the self-tests: short sequences and templates of 5 bits. */
+fn small_config() -> StatsConfig {
+    StatsConfig { sequences: 4, sequence_bits: 1 << 16, template_len: 5, ..StatsConfig::default() }
+}
+
+/*  Returns a deterministic HMAC-DRBG instance. */
+fn fixed_drbg() -> Option<DRBG<HmacDrbgMech<Sha256>>> {
+    DRBG::new_with_entropy(32, None, Box::new(FixedEntropySource::new(vec![vec![0x3c; 48]; 2]))).ok()
+}
+
+/*  Testing the structure of the report of a DRBG: every test and variant is reported, the tests that need longer
+    sequences have no p-value and the output of the DRBG passes almost all of the tests. */
+fn drbg_report() -> usize {
+    let mut drbg = match fixed_drbg() {
+        Some(drbg) => drbg,
+        None => {
+            write_to_log(format_message(true, AL_NAME.to_string(), "drbg_report".to_string(),
+                                        "failed to instantiate DRBG.".to_string()));
+            return 1;
+        }
+    };
+
+    let config = small_config();
+    let report = match run_drbg(&mut drbg, &config) {
+        Ok(report) => report,
+        Err(_) => {
+            write_to_log(format_message(true, AL_NAME.to_string(), "drbg_report".to_string(),
+                                        "failed to run the statistical tests.".to_string()));
+            return 1;
+        }
+    };
+
+    let p_values: Vec<f64> = report.tests.iter().flat_map(|test| test.p_values.clone()).collect();
+    let passing = p_values.iter().filter(|p| **p >= config.alpha).count() as f64 / p_values.len() as f64;
+    let universal = report.tests.iter().find(|test| test.test == "Universal").map(|test| (test.p_values.len(), test.passed));
+    let templates = report.tests.iter().filter(|test| test.test == "NonOverlappingTemplate").count();
+
+    check_res((report.sequences, report.tests.len(), templates, universal, passing > 0.95, report.summary().lines().count()),
+              (4, 52, 12, Some((0, true)), true, 54),
+        "drbg_report".to_string(),
+        AL_NAME.to_string(),
+        "the report of the DRBG output was not as expected.".to_string(),
+        "the report of the DRBG output was as expected.".to_string())
+}
+
+/*  Testing that a biased byte string fails the frequency test, and the errors of the byte string and file runners. */
+fn biased_bytes() -> usize {
+    let mut drbg = match fixed_drbg() {
+        Some(drbg) => drbg,
+        None => {
+            write_to_log(format_message(true, AL_NAME.to_string(), "biased_bytes".to_string(),
+                                        "failed to instantiate DRBG.".to_string()));
+            return 1;
+        }
+    };
+
+    let config = StatsConfig { sequences: 2, ..small_config() };
+    let mut bytes = Vec::<u8>::new();
+    let mut chunk = Vec::<u8>::new();
+    while bytes.len() * 8 < config.sequences * config.sequence_bits {
+        drbg.generate(&mut chunk, drbg.get_max_pbr(), drbg.get_sec_str(), false, None);
+        bytes.extend(chunk.iter().map(|byte| byte | 0x01));
+    }
+
+    let frequency = run_bytes(&bytes, &config).ok()
+        .and_then(|report| report.tests.iter().find(|test| test.test == "Frequency").map(|test| (report.sequences, test.passed)));
+    let res = (frequency, run_bytes(&bytes[..100], &config).err(), run_file(Path::new("missing.bin"), &config).err());
+
+    check_res(res, (Some((2, false)), Some(2), Some(1)),
+        "biased_bytes".to_string(),
+        AL_NAME.to_string(),
+        "the runner did not detect the biased sequences.".to_string(),
+        "the runner detected the biased sequences.".to_string())
+}
+
+/*  Testing the proportion and uniformity checks on synthetic p-values: uniform p-values give a uniformity p-value of 1,
+    p-values in the same interval are rejected. */
+fn evaluation() -> usize {
+    let uniform: Vec<f64> = (0..100).map(|idx| (idx as f64 + 0.5) / 100.0).collect();
+    let clustered = vec![0.55; 100];
+
+    check_res(((uniformity(&uniform) - 1.0).abs() < 1e-9, uniformity(&clustered) < UNIFORMITY_THRESHOLD), (true, true),
+        "evaluation".to_string(),
+        AL_NAME.to_string(),
+        "the evaluation of the p-values was not as expected.".to_string(),
+        "the evaluation of the p-values was as expected.".to_string())
+}
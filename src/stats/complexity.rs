@@ -0,0 +1,172 @@
+use super::special::igamc;
+
+/*  Tests of SP 800-22 based on the complexity of the sequence and on the frequency of its overlapping patterns:
+
+        TEST                                    SECTION     P-VALUES
+        linear complexity                       2.10        1
+        serial                                  2.11        2 (first and second difference)
+        approximate entropy                     2.12        1
+
+    The patterns of the serial and approximate entropy tests wrap around the end of the sequence. */
+
+/*  The default parameters: block length of the linear complexity test and pattern lengths of the serial and
+    approximate entropy tests. */
+pub const LINEAR_COMPLEXITY_BLOCK_LEN: usize = 500;
+pub const SERIAL_LEN: usize = 16;
+pub const APPROXIMATE_ENTROPY_LEN: usize = 10;
+
+/*  The probabilities of the classes of the linear complexity test (section 3.10). */
+const LINEAR_COMPLEXITY_PROBABILITIES: [f64; 7] = [0.010417, 0.03125, 0.125, 0.5, 0.25, 0.0625, 0.020833];
+
+/*  Returns the linear complexity of a sequence of bits (length of the shortest LFSR that generates it), computed with
+    the Berlekamp-Massey algorithm. */
+pub fn berlekamp_massey(bits: &[u8]) -> usize {
+    let n = bits.len();
+    let mut c = vec![0u8; n + 1];
+    let mut b = vec![0u8; n + 1];
+    c[0] = 1;
+    b[0] = 1;
+    let (mut l, mut m) = (0usize, -1i64);
+
+    for idx in 0..n {
+        let d = (1..=l).fold(bits[idx], |d, i| d ^ (c[i] & bits[idx - i]));
+        if d == 1 {
+            let t = c.clone();
+            let shift = (idx as i64 - m) as usize;
+            for i in 0..=n - shift {
+                c[i + shift] ^= b[i];
+            }
+            if 2 * l <= idx {
+                l = idx + 1 - l;
+                m = idx as i64;
+                b = t;
+            }
+        }
+    }
+
+    l
+}
+
+/*  Linear complexity test.
+
+    Parameters:
+        - m: the length of each block (M)
+
+    Return values:
+        - Ok(p_values): SUCCESS
+        - Err(1): ERROR, the sequence is shorter than a block
+        - Err(2): ERROR, m is 0 */
+pub fn linear_complexity(bits: &[u8], m: usize) -> Result<Vec<f64>, usize> {
+    if m == 0 {
+        return Err(2);
+    }
+    let blocks = bits.len() / m;
+    if blocks == 0 {
+        return Err(1);
+    }
+
+    let mf = m as f64;
+    let sign = if m.is_multiple_of(2) { 1.0 } else { -1.0 };
+    let mu = mf / 2.0 + (9.0 - sign) / 36.0 - (mf / 3.0 + 2.0 / 9.0) / 2f64.powf(mf);
+
+    let mut nu = [0usize; 7];
+    for block in bits.chunks_exact(m) {
+        let t = sign * (berlekamp_massey(block) as f64 - mu) + 2.0 / 9.0;
+        let class = match t {
+            t if t <= -2.5 => 0,
+            t if t <= -1.5 => 1,
+            t if t <= -0.5 => 2,
+            t if t <= 0.5 => 3,
+            t if t <= 1.5 => 4,
+            t if t <= 2.5 => 5,
+            _ => 6,
+        };
+        nu[class] += 1;
+    }
+
+    let n = blocks as f64;
+    let chi_squared: f64 = nu.iter().zip(LINEAR_COMPLEXITY_PROBABILITIES.iter())
+        .map(|(nu, pi)| (*nu as f64 - n * pi).powi(2) / (n * pi))
+        .sum();
+
+    Ok(vec![igamc(3.0, chi_squared / 2.0)])
+}
+
+/*  Serial test.
+
+    Parameters:
+        - m: the length of the patterns (2 <= m < log2(n) - 2 is recommended)
+
+    Return values:
+        - Ok(p_values): SUCCESS, the p-values of the first and second difference
+        - Err(1): ERROR, the sequence is shorter than m bits
+        - Err(2): ERROR, m is lower than 2 or higher than 24 */
+pub fn serial(bits: &[u8], m: usize) -> Result<Vec<f64>, usize> {
+    if !(2..=24).contains(&m) {
+        return Err(2);
+    }
+    if bits.len() < m {
+        return Err(1);
+    }
+
+    let psi = |len: usize| {
+        let n = bits.len() as f64;
+        let sum: f64 = pattern_counts(bits, len).iter().map(|count| (*count as f64).powi(2)).sum();
+        if len == 0 { 0.0 } else { 2f64.powi(len as i32) / n * sum - n }
+    };
+    let (psi_m, psi_m1, psi_m2) = (psi(m), psi(m - 1), psi(m - 2));
+
+    Ok(vec![
+        igamc(2f64.powi(m as i32 - 2), (psi_m - psi_m1) / 2.0),
+        igamc(2f64.powi(m as i32 - 3), (psi_m - 2.0 * psi_m1 + psi_m2) / 2.0),
+    ])
+}
+
+/*  Approximate entropy test.
+
+    Parameters:
+        - m: the length of the patterns (m < log2(n) - 5 is recommended)
+
+    Return values:
+        - Ok(p_values): SUCCESS
+        - Err(1): ERROR, the sequence is shorter than m + 1 bits
+        - Err(2): ERROR, m is 0 or higher than 23 */
+pub fn approximate_entropy(bits: &[u8], m: usize) -> Result<Vec<f64>, usize> {
+    if m == 0 || m > 23 {
+        return Err(2);
+    }
+    if bits.len() <= m {
+        return Err(1);
+    }
+
+    let n = bits.len() as f64;
+    let phi = |len: usize| -> f64 {
+        pattern_counts(bits, len).iter()
+            .filter(|count| **count > 0)
+            .map(|count| { let pi = *count as f64 / n; pi * pi.ln() })
+            .sum()
+    };
+    let ap_en = phi(m) - phi(m + 1);
+    let chi_squared = 2.0 * n * (2f64.ln() - ap_en);
+
+    Ok(vec![igamc(2f64.powi(m as i32 - 1), chi_squared / 2.0)])
+}
+
+/*  Returns the number of occurrences of every pattern of len bits in the sequence, the patterns starting in the last
+    len - 1 bits wrap around to the beginning of the sequence. */
+fn pattern_counts(bits: &[u8], len: usize) -> Vec<usize> {
+    let mut counts = vec![0usize; 1 << len];
+    if len == 0 {
+        counts[0] = bits.len();
+        return counts;
+    }
+
+    let mask = (1usize << len) - 1;
+    let mut value = bits[..len - 1].iter().fold(0usize, |acc, bit| (acc << 1) | *bit as usize);
+    for bit in bits[len - 1..].iter().chain(bits[..len - 1].iter()) {
+        value = ((value << 1) | *bit as usize) & mask;
+        counts[value] += 1;
+    }
+
+    counts
+}
@@ -0,0 +1,124 @@
+use super::special::{erfc, igamc};
+
+/*  Random excursions tests of SP 800-22, based on the cycles of the random walk S_k = sum(2*bit - 1) (a cycle is a
+    sequence of steps between two consecutive zeros of the walk extended with a zero at both ends):
+
+        TEST                                    SECTION     P-VALUES
+        random excursions                       2.14        8 (states -4..-1, 1..4)
+        random excursions variant               2.15        18 (states -9..-1, 1..9)
+
+    The tests are only meaningful if the walk has enough cycles: the SP requires at least 500 of them, see
+    min_cycles. */
+
+/*  The states of the two tests. */
+pub const EXCURSION_STATES: [i64; 8] = [-4, -3, -2, -1, 1, 2, 3, 4];
+pub const VARIANT_STATES: [i64; 18] = [-9, -8, -7, -6, -5, -4, -3, -2, -1, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+
+/*  Returns the minimum number of cycles required by the tests for a sequence of n bits (max(500, 0.005 sqrt(n)), as
+    in the NIST STS). */
+pub fn min_cycles(n: usize) -> usize {
+    500usize.max((0.005 * (n as f64).sqrt()) as usize)
+}
+
+/*  Returns the number of cycles of the random walk of the sequence (a walk that ends in zero has no trailing cycle). */
+pub fn cycles(bits: &[u8]) -> usize {
+    let zeros = walk(bits).filter(|sum| *sum == 0).count();
+
+    if walk(bits).last() == Some(0) { zeros } else { zeros + 1 }
+}
+
+/*  Returns the partial sums of the random walk (without the leading and trailing zeros). */
+fn walk(bits: &[u8]) -> impl Iterator<Item = i64> + '_ {
+    bits.iter().scan(0i64, |sum, bit| { *sum += 2 * *bit as i64 - 1; Some(*sum) })
+}
+
+/*  Random excursions test.
+
+    Parameters:
+        - min_cycles: the minimum number of cycles of the walk (see min_cycles)
+
+    Return values:
+        - Ok(p_values): SUCCESS, the p-values of the states -4..-1, 1..4
+        - Err(1): ERROR, the sequence is empty
+        - Err(3): ERROR, the walk has fewer than min_cycles cycles */
+pub fn random_excursions(bits: &[u8], min_cycles: usize) -> Result<Vec<f64>, usize> {
+    if bits.is_empty() {
+        return Err(1);
+    }
+    let j = cycles(bits);
+    if j < min_cycles {
+        return Err(3);
+    }
+
+    // Number of visits of every state in the current cycle and number of cycles with k visits (k = 0..5, 5 means >= 5).
+    let mut visits = [0usize; 8];
+    let mut nu = [[0usize; 6]; 8];
+    let mut close_cycle = |visits: &mut [usize; 8]| {
+        for (state, count) in visits.iter_mut().enumerate() {
+            nu[state][(*count).min(5)] += 1;
+            *count = 0;
+        }
+    };
+    let mut last = 0;
+    for sum in walk(bits) {
+        if sum == 0 {
+            close_cycle(&mut visits);
+        }
+        else if let Some(state) = EXCURSION_STATES.iter().position(|state| *state == sum) {
+            visits[state] += 1;
+        }
+        last = sum;
+    }
+    if last != 0 {
+        close_cycle(&mut visits);
+    }
+
+    let j = j as f64;
+    Ok(EXCURSION_STATES.iter().zip(nu.iter()).map(|(x, nu)| {
+        let chi_squared: f64 = nu.iter().enumerate()
+            .map(|(k, nu)| { let pi = excursion_probability(*x, k); (*nu as f64 - j * pi).powi(2) / (j * pi) })
+            .sum();
+        igamc(2.5, chi_squared / 2.0)
+    }).collect())
+}
+
+/*  Returns the probability that state x is visited k times in a cycle (k = 5 means at least 5 times, section 3.14). */
+fn excursion_probability(x: i64, k: usize) -> f64 {
+    let a = 1.0 / (2.0 * x.abs() as f64);
+    match k {
+        0 => 1.0 - a,
+        5 => a * (1.0 - a).powi(4),
+        _ => a * a * (1.0 - a).powi(k as i32 - 1),
+    }
+}
+
+/*  Random excursions variant test.
+
+    Parameters:
+        - min_cycles: the minimum number of cycles of the walk (see min_cycles)
+
+    Return values:
+        - Ok(p_values): SUCCESS, the p-values of the states -9..-1, 1..9
+        - Err(1): ERROR, the sequence is empty
+        - Err(3): ERROR, the walk has fewer than min_cycles cycles */
+pub fn random_excursions_variant(bits: &[u8], min_cycles: usize) -> Result<Vec<f64>, usize> {
+    if bits.is_empty() {
+        return Err(1);
+    }
+    let j = cycles(bits);
+    if j < min_cycles {
+        return Err(3);
+    }
+
+    let mut visits = [0usize; 18];
+    for sum in walk(bits) {
+        if let Some(state) = VARIANT_STATES.iter().position(|state| *state == sum) {
+            visits[state] += 1;
+        }
+    }
+
+    let j = j as f64;
+    Ok(VARIANT_STATES.iter().zip(visits.iter()).map(|(x, xi)| {
+        erfc((*xi as f64 - j).abs() / (2.0 * j * (4.0 * x.abs() as f64 - 2.0)).sqrt())
+    }).collect())
+}
@@ -0,0 +1,171 @@
+use super::special::{erfc, igamc, normal_cdf};
+
+/*  Tests of SP 800-22 based on the proportion of ones and on the runs of the sequence:
+
+        TEST                                    SECTION     P-VALUES
+        frequency (monobit)                     2.1         1
+        frequency within a block                2.2         1
+        runs                                    2.3         1
+        longest run of ones in a block          2.4         1
+        cumulative sums                         2.13        2 (forward, backward)
+
+    The sequences are given as slices of bits (one bit, 0 or 1, per byte), see 'stats::bits_from_bytes'. */
+
+/*  The parameters of the longest run test (section 2.4, table of section 3.4): block length M, the classes of the
+    longest runs (the first class includes shorter runs, the last one longer runs) and their probabilities. */
+struct LongestRunParams {
+    m: usize,
+    first_class: usize,
+    probabilities: &'static [f64],
+}
+
+const LONGEST_RUN_8: LongestRunParams = LongestRunParams {
+    m: 8,
+    first_class: 1,
+    probabilities: &[0.21484375, 0.3671875, 0.23046875, 0.1875],
+};
+const LONGEST_RUN_128: LongestRunParams = LongestRunParams {
+    m: 128,
+    first_class: 4,
+    probabilities: &[0.1174035788, 0.242955959, 0.249363483, 0.17517706, 0.102701071, 0.112398847],
+};
+const LONGEST_RUN_10000: LongestRunParams = LongestRunParams {
+    m: 10000,
+    first_class: 10,
+    probabilities: &[0.0882, 0.2092, 0.2483, 0.1933, 0.1208, 0.0675, 0.0727],
+};
+
+/*  Frequency (monobit) test, section 2.1.
+
+    Return values:
+        - Ok(p_values): SUCCESS
+        - Err(1): ERROR, the sequence is empty */
+pub fn frequency(bits: &[u8]) -> Result<Vec<f64>, usize> {
+    if bits.is_empty() {
+        return Err(1);
+    }
+
+    let sum: i64 = bits.iter().map(|bit| 2 * *bit as i64 - 1).sum();
+    let s_obs = sum.abs() as f64 / (bits.len() as f64).sqrt();
+
+    Ok(vec![erfc(s_obs / std::f64::consts::SQRT_2)])
+}
+
+/*  Frequency test within a block, section 2.2.
+
+    Parameters:
+        - m: the length of each block (M)
+
+    Return values:
+        - Ok(p_values): SUCCESS
+        - Err(1): ERROR, the sequence is shorter than a block
+        - Err(2): ERROR, m is 0 */
+pub fn block_frequency(bits: &[u8], m: usize) -> Result<Vec<f64>, usize> {
+    if m == 0 {
+        return Err(2);
+    }
+    let blocks = bits.len() / m;
+    if blocks == 0 {
+        return Err(1);
+    }
+
+    let chi_squared = 4.0 * m as f64 * bits.chunks_exact(m)
+        .map(|block| block.iter().map(|bit| *bit as usize).sum::<usize>() as f64 / m as f64 - 0.5)
+        .map(|deviation| deviation * deviation)
+        .sum::<f64>();
+
+    Ok(vec![igamc(blocks as f64 / 2.0, chi_squared / 2.0)])
+}
+
+/*  Runs test, section 2.3. If the frequency prerequisite of the test fails (|pi - 1/2| >= 2/sqrt(n)), the p-value is
+    0 as mandated by the SP.
+
+    Return values:
+        - Ok(p_values): SUCCESS
+        - Err(1): ERROR, the sequence is empty */
+pub fn runs(bits: &[u8]) -> Result<Vec<f64>, usize> {
+    if bits.is_empty() {
+        return Err(1);
+    }
+
+    let n = bits.len() as f64;
+    let pi = bits.iter().map(|bit| *bit as usize).sum::<usize>() as f64 / n;
+    if (pi - 0.5).abs() >= 2.0 / n.sqrt() {
+        return Ok(vec![0.0]);
+    }
+
+    let v_obs = 1 + bits.windows(2).filter(|pair| pair[0] != pair[1]).count();
+    let num = (v_obs as f64 - 2.0 * n * pi * (1.0 - pi)).abs();
+    let den = 2.0 * (2.0 * n).sqrt() * pi * (1.0 - pi);
+
+    Ok(vec![erfc(num / den)])
+}
+
+/*  Test for the longest run of ones in a block, section 2.4. The block length is selected by the length of the
+    sequence: M = 8 for n < 6272, M = 128 for n < 750000, M = 10000 otherwise.
+
+    Return values:
+        - Ok(p_values): SUCCESS
+        - Err(1): ERROR, the sequence is shorter than 128 bits */
+pub fn longest_run(bits: &[u8]) -> Result<Vec<f64>, usize> {
+    let params = match bits.len() {
+        0..=127 => return Err(1),
+        128..=6271 => &LONGEST_RUN_8,
+        6272..=749_999 => &LONGEST_RUN_128,
+        _ => &LONGEST_RUN_10000,
+    };
+
+    let classes = params.probabilities.len();
+    let mut nu = vec![0usize; classes];
+    for block in bits.chunks_exact(params.m) {
+        let (mut longest, mut run) = (0, 0);
+        for bit in block {
+            run = if *bit == 1 { run + 1 } else { 0 };
+            longest = longest.max(run);
+        }
+        nu[longest.clamp(params.first_class, params.first_class + classes - 1) - params.first_class] += 1;
+    }
+
+    let blocks = (bits.len() / params.m) as f64;
+    let chi_squared: f64 = nu.iter().zip(params.probabilities.iter())
+        .map(|(nu, pi)| (*nu as f64 - blocks * pi).powi(2) / (blocks * pi))
+        .sum();
+
+    Ok(vec![igamc((classes - 1) as f64 / 2.0, chi_squared / 2.0)])
+}
+
+/*  Cumulative sums test, section 2.13, in forward and backward mode.
+
+    Return values:
+        - Ok(p_values): SUCCESS, the p-values of the forward and backward mode
+        - Err(1): ERROR, the sequence is empty */
+pub fn cumulative_sums(bits: &[u8]) -> Result<Vec<f64>, usize> {
+    if bits.is_empty() {
+        return Err(1);
+    }
+
+    let max_excursion = |steps: &mut dyn Iterator<Item = &u8>| {
+        let mut sum = 0i64;
+        steps.map(|bit| { sum += 2 * *bit as i64 - 1; sum.abs() }).max().unwrap_or(0)
+    };
+    let forward = max_excursion(&mut bits.iter());
+    let backward = max_excursion(&mut bits.iter().rev());
+
+    Ok(vec![cusum_p_value(bits.len() as i64, forward), cusum_p_value(bits.len() as i64, backward)])
+}
+
+/*  Computes the p-value of the cumulative sums test given the maximal excursion z (step 4 of section 2.13.4). */
+fn cusum_p_value(n: i64, z: i64) -> f64 {
+    if z == 0 {
+        return 1.0;
+    }
+
+    let sqrt_n = (n as f64).sqrt();
+    let z_f = z as f64;
+    let term = |k: i64, a: i64, b: i64| normal_cdf((4 * k + a) as f64 * z_f / sqrt_n) - normal_cdf((4 * k + b) as f64 * z_f / sqrt_n);
+
+    let first: f64 = ((-n / z + 1) / 4..=(n / z - 1) / 4).map(|k| term(k, 1, -1)).sum();
+    let second: f64 = ((-n / z - 3) / 4..=(n / z - 1) / 4).map(|k| term(k, 3, 1)).sum();
+
+    (1.0 - first + second).clamp(0.0, 1.0)
+}
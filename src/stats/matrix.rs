@@ -0,0 +1,87 @@
+use super::special::igamc;
+
+/*  Binary matrix rank test, section 2.5 of SP 800-22.
+
+    The sequence is split into N = n/(M*Q) matrices of M rows and Q columns (filled row by row) whose rank over GF(2)
+    is computed. The number of matrices of full rank M, of rank M-1 and of lower rank is compared against the
+    probabilities of section 3.5 with a chi-squared test with 2 degrees of freedom. The SP uses M = Q = 32. */
+
+/*  The default size of the matrices. */
+pub const RANK_M: usize = 32;
+pub const RANK_Q: usize = 32;
+
+/*  Binary matrix rank test.
+
+    Parameters:
+        - m, q: the number of rows and columns of each matrix (at most 64 columns)
+
+    Return values:
+        - Ok(p_values): SUCCESS
+        - Err(1): ERROR, the sequence is shorter than a matrix
+        - Err(2): ERROR, invalid matrix size */
+pub fn rank(bits: &[u8], m: usize, q: usize) -> Result<Vec<f64>, usize> {
+    if m == 0 || q == 0 || q > 64 {
+        return Err(2);
+    }
+    let matrices = bits.len() / (m * q);
+    if matrices == 0 {
+        return Err(1);
+    }
+
+    let full_rank = m.min(q);
+    let (mut f_full, mut f_minus_one) = (0usize, 0usize);
+    for matrix in bits.chunks_exact(m * q) {
+        let rows: Vec<u64> = matrix.chunks_exact(q)
+            .map(|row| row.iter().fold(0u64, |acc, bit| (acc << 1) | *bit as u64))
+            .collect();
+        let rank = gf2_rank(rows);
+        if rank == full_rank {
+            f_full += 1;
+        }
+        else if rank + 1 == full_rank {
+            f_minus_one += 1;
+        }
+    }
+
+    let n = matrices as f64;
+    let p_full = rank_probability(full_rank, m, q);
+    let p_minus_one = rank_probability(full_rank - 1, m, q);
+    let p_rest = 1.0 - p_full - p_minus_one;
+    let f_rest = (matrices - f_full - f_minus_one) as f64;
+
+    let chi_squared = (f_full as f64 - p_full * n).powi(2) / (p_full * n) +
+        (f_minus_one as f64 - p_minus_one * n).powi(2) / (p_minus_one * n) +
+        if p_rest > 0.0 { (f_rest - p_rest * n).powi(2) / (p_rest * n) } else { 0.0 };
+
+    Ok(vec![igamc(1.0, chi_squared / 2.0)])
+}
+
+/*  Returns the rank over GF(2) of a matrix given as rows of bits. */
+pub fn gf2_rank(mut rows: Vec<u64>) -> usize {
+    let mut rank = 0;
+    for bit in (0..64).rev() {
+        let mask = 1u64 << bit;
+        if let Some(pivot) = (rank..rows.len()).find(|idx| rows[*idx] & mask != 0) {
+            rows.swap(rank, pivot);
+            for idx in 0..rows.len() {
+                if idx != rank && rows[idx] & mask != 0 {
+                    rows[idx] ^= rows[rank];
+                }
+            }
+            rank += 1;
+        }
+    }
+
+    rank
+}
+
+/*  Returns the probability that a random M x Q binary matrix has rank r (section 3.5). */
+pub fn rank_probability(r: usize, m: usize, q: usize) -> f64 {
+    let exponent = (r * (q + m - r)) as f64 - (m * q) as f64;
+    let product: f64 = (0..r).map(|i| {
+        let i = i as f64;
+        (1.0 - 2f64.powf(i - q as f64)) * (1.0 - 2f64.powf(i - m as f64)) / (1.0 - 2f64.powf(i - r as f64))
+    }).product();
+
+    2f64.powf(exponent) * product
+}
@@ -0,0 +1,52 @@
+/*  Statistical test suite for the output of the DRBGs, implementing the 15 tests of NIST SP 800-22 rev. 1a so that the
+    output of the generators can be validated without the NIST STS:
+        TEST                                        SECTION     MODULE
+        frequency (monobit)                         2.1         frequency
+        frequency within a block                    2.2         frequency
+        runs                                        2.3         frequency
+        longest run of ones in a block              2.4         frequency
+        binary matrix rank                          2.5         matrix
+        discrete Fourier transform (spectral)       2.6         spectral
+        non-overlapping template matching           2.7         template
+        overlapping template matching               2.8         template
+        Maurer's universal statistical              2.9         universal
+        linear complexity                           2.10        complexity
+        serial                                      2.11        complexity
+        approximate entropy                         2.12        complexity
+        cumulative sums                             2.13        frequency
+        random excursions                           2.14        excursions
+        random excursions variant                   2.15        excursions
+
+    Every test takes a sequence of bits (one bit, 0 or 1, per byte, see bits_from_bytes) and returns its p-values. The
+    'runner' module runs the whole suite on many sequences (taken from a DRBG instance, a byte string or a file) and
+    applies the proportion and uniformity checks of section 4.2, producing a report. The p-values are computed with the
+    special functions of the 'special' module.
+
+    Error flags returned by the tests and the runner:
+        1: the sequence is too short for the test (or the file could not be read, see 'runner')
+        2: the parameters of the test are not valid (or there is not enough data for a sequence, see 'runner')
+        3: the random walk of the sequence has too few cycles (or the DRBG failed, see 'runner') */
+pub mod special;
+pub mod frequency;
+pub mod matrix;
+pub mod spectral;
+pub mod template;
+pub mod universal;
+pub mod complexity;
+pub mod excursions;
+pub mod runner;
+
+/*  Converts bytes to bits, most significant bit first. */
+pub fn bits_from_bytes(bytes: &[u8]) -> Vec<u8> {
+    bit_range(bytes, 0, bytes.len() * 8)
+}
+
+/*  Returns len bits of bytes starting from bit start (most significant bit first), the range must be within bytes. */
+pub fn bit_range(bytes: &[u8], start: usize, len: usize) -> Vec<u8> {
+    (start..start + len).map(|idx| (bytes[idx / 8] >> (7 - idx % 8)) & 1).collect()
+}
+
+/*  Converts a string of '0' and '1' characters to bits, other characters are ignored. */
+pub fn bits_from_str(bits: &str) -> Vec<u8> {
+    bits.chars().filter_map(|c| c.to_digit(2).map(|bit| bit as u8)).collect()
+}
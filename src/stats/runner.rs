@@ -0,0 +1,290 @@
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use crate::mechs::gen_mech::DRBG_Mechanism_Functions;
+use super::*;
+
+/*  Runner of the SP 800-22 statistical test suite.
+
+    Every test is run on each sequence and the p-values of each test (and of each variant of a test, e.g. the templates
+    of the non-overlapping template test) are evaluated as described in section 4.2 of the SP:
+        - proportion: the proportion of sequences whose p-value is >= alpha must not be lower than
+          p - 3 sqrt(p (1 - p) / s), with p = 1 - alpha and s the number of sequences
+        - uniformity: the p-values are grouped in 10 intervals and the p-value of the chi-squared test of their
+          distribution must be >= 0.0001; the check requires at least MIN_UNIFORMITY_SEQUENCES sequences
+
+    A test that cannot be run on a sequence (e.g. a sequence too short for the universal test, or a random walk with too
+    few cycles for the random excursions tests) does not count for that sequence; a test that could not be run on any
+    sequence is reported with no p-value and does not fail the report. */
+
+/*  The minimum number of sequences of the uniformity check. */
+pub const MIN_UNIFORMITY_SEQUENCES: usize = 55;
+
+/*  The minimum p-value of the uniformity check. */
+pub const UNIFORMITY_THRESHOLD: f64 = 0.0001;
+
+/*  The configuration of the test suite, the default values are those recommended by the SP.
+
+    - sequences: the number of sequences (s)
+    - sequence_bits: the length of each sequence (n)
+    - alpha: the significance level
+    - block_frequency_len, template_len, linear_complexity_len, serial_len, approximate_entropy_len: the parameters of
+      the tests (see 'frequency', 'template' and 'complexity') */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StatsConfig {
+    pub sequences: usize,
+    pub sequence_bits: usize,
+    pub alpha: f64,
+    pub block_frequency_len: usize,
+    pub template_len: usize,
+    pub linear_complexity_len: usize,
+    pub serial_len: usize,
+    pub approximate_entropy_len: usize,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            sequences: 100,
+            sequence_bits: 1_000_000,
+            alpha: 0.01,
+            block_frequency_len: 128,
+            template_len: template::TEMPLATE_LEN,
+            linear_complexity_len: complexity::LINEAR_COMPLEXITY_BLOCK_LEN,
+            serial_len: complexity::SERIAL_LEN,
+            approximate_entropy_len: complexity::APPROXIMATE_ENTROPY_LEN,
+        }
+    }
+}
+
+/*  The result of a test (or of a variant of a test) over all the sequences.
+
+    - test: the name of the test, as in the reports of the NIST STS (e.g. "NonOverlappingTemplate")
+    - variant: the variant of the test (e.g. the template "000000001", "forward" or "x = -4"), if any
+    - p_values: the p-value of each sequence on which the test could be run
+    - proportion, min_proportion: the proportion of p-values >= alpha and its minimum acceptable value
+    - uniformity: the p-value of the uniformity check, None if there are too few sequences
+    - passed: true if both checks passed (or the test could not be run on any sequence) */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StatTestReport {
+    pub test: String,
+    pub variant: Option<String>,
+    pub p_values: Vec<f64>,
+    pub proportion: f64,
+    pub min_proportion: f64,
+    pub uniformity: Option<f64>,
+    pub passed: bool,
+}
+
+/*  The report of the test suite. */
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StatsReport {
+    pub config: StatsConfig,
+    pub sequences: usize,
+    pub tests: Vec<StatTestReport>,
+}
+
+impl StatsReport {
+    /*  Returns true if every test passed. */
+    pub fn passed(&self) -> bool {
+        self.tests.iter().all(|test| test.passed)
+    }
+
+    /*  Returns the tests that have failed. */
+    pub fn failures(&self) -> Vec<&StatTestReport> {
+        self.tests.iter().filter(|test| !test.passed).collect()
+    }
+
+    /*  Serializes the report as pretty-printed JSON. */
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /*  Returns a human readable summary of the report, one line per test and variant. */
+    pub fn summary(&self) -> String {
+        let mut lines = vec![format!("{} sequences of {} bits, alpha = {}", self.sequences, self.config.sequence_bits, self.config.alpha),
+                             format!("{:<46}{:>12}{:>12}{:>8}", "TEST", "PROPORTION", "UNIFORMITY", "RESULT")];
+        for test in self.tests.iter() {
+            let name = match &test.variant {
+                None => test.test.clone(),
+                Some(variant) => format!("{} {}", test.test, variant),
+            };
+            let proportion = if test.p_values.is_empty() { "n/a".to_string() } else {
+                format!("{}/{}", test.p_values.iter().filter(|p| **p >= self.config.alpha).count(), test.p_values.len())
+            };
+            let uniformity = test.uniformity.map_or("-".to_string(), |p| format!("{:.6}", p));
+            lines.push(format!("{:<46}{:>12}{:>12}{:>8}", name, proportion, uniformity, if test.passed { "PASS" } else { "FAIL" }));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/*  The names of the tests and of their variants, in the order of the reports. */
+fn test_variants(templates: &[Vec<u8>]) -> Vec<(&'static str, Vec<Option<String>>)> {
+    let single = || vec![None];
+    let labels = |labels: Vec<String>| labels.into_iter().map(Some).collect::<Vec<Option<String>>>();
+
+    vec![
+        ("Frequency", single()),
+        ("BlockFrequency", single()),
+        ("CumulativeSums", labels(vec!["forward".to_string(), "backward".to_string()])),
+        ("Runs", single()),
+        ("LongestRun", single()),
+        ("Rank", single()),
+        ("FFT", single()),
+        ("NonOverlappingTemplate", labels(templates.iter()
+            .map(|template| template.iter().map(|bit| if *bit == 1 { '1' } else { '0' }).collect())
+            .collect())),
+        ("OverlappingTemplate", single()),
+        ("Universal", single()),
+        ("ApproximateEntropy", single()),
+        ("RandomExcursions", labels(excursions::EXCURSION_STATES.iter().map(|x| format!("x = {}", x)).collect())),
+        ("RandomExcursionsVariant", labels(excursions::VARIANT_STATES.iter().map(|x| format!("x = {}", x)).collect())),
+        ("Serial", labels(vec!["1".to_string(), "2".to_string()])),
+        ("LinearComplexity", single()),
+    ]
+}
+
+/*  Runs every test on a sequence, the results are in the order of test_variants. */
+fn test_sequence(bits: &[u8], config: &StatsConfig, templates: &[Vec<u8>]) -> Vec<Result<Vec<f64>, usize>> {
+    let min_cycles = excursions::min_cycles(bits.len());
+    let non_overlapping = templates.iter()
+        .map(|template| template::non_overlapping_template(bits, template, template::NON_OVERLAPPING_BLOCKS).map(|p| p[0]))
+        .collect::<Result<Vec<f64>, usize>>();
+
+    vec![
+        frequency::frequency(bits),
+        frequency::block_frequency(bits, config.block_frequency_len),
+        frequency::cumulative_sums(bits),
+        frequency::runs(bits),
+        frequency::longest_run(bits),
+        matrix::rank(bits, matrix::RANK_M, matrix::RANK_Q),
+        spectral::dft(bits),
+        non_overlapping,
+        template::overlapping_template(bits),
+        universal::universal(bits),
+        complexity::approximate_entropy(bits, config.approximate_entropy_len),
+        excursions::random_excursions(bits, min_cycles),
+        excursions::random_excursions_variant(bits, min_cycles),
+        complexity::serial(bits, config.serial_len),
+        complexity::linear_complexity(bits, config.linear_complexity_len),
+    ]
+}
+
+/*  Runs the test suite on the given sequences of bits (see bits_from_bytes). */
+pub fn run_sequences<I: IntoIterator<Item = Vec<u8>>>(sequences: I, config: &StatsConfig) -> StatsReport {
+    let templates = template::aperiodic_templates(config.template_len);
+    let variants = test_variants(&templates);
+    let mut p_values: Vec<Vec<Vec<f64>>> = variants.iter().map(|(_, labels)| vec![Vec::new(); labels.len()]).collect();
+
+    let mut count = 0;
+    for bits in sequences {
+        for (test, res) in test_sequence(&bits, config, &templates).into_iter().enumerate() {
+            if let Ok(values) = res {
+                for (variant, value) in values.into_iter().enumerate().take(p_values[test].len()) {
+                    p_values[test][variant].push(value);
+                }
+            }
+        }
+        count += 1;
+    }
+
+    let tests = variants.into_iter().zip(p_values)
+        .flat_map(|((test, labels), values)| labels.into_iter().zip(values).map(move |(variant, values)| (test, variant, values)))
+        .map(|(test, variant, values)| evaluate(test, variant, values, config.alpha))
+        .collect();
+
+    StatsReport { config: config.clone(), sequences: count, tests }
+}
+
+/*  Applies the proportion and uniformity checks to the p-values of a test. */
+fn evaluate(test: &str, variant: Option<String>, p_values: Vec<f64>, alpha: f64) -> StatTestReport {
+    let s = p_values.len();
+    if s == 0 {
+        return StatTestReport { test: test.to_string(), variant, p_values, proportion: 0.0, min_proportion: 0.0,
+                                uniformity: None, passed: true };
+    }
+
+    let p_hat = 1.0 - alpha;
+    let proportion = p_values.iter().filter(|p| **p >= alpha).count() as f64 / s as f64;
+    let min_proportion = p_hat - 3.0 * (p_hat * alpha / s as f64).sqrt();
+
+    let uniformity = if s >= MIN_UNIFORMITY_SEQUENCES { Some(uniformity(&p_values)) } else { None };
+    let passed = proportion >= min_proportion && uniformity.is_none_or(|p| p >= UNIFORMITY_THRESHOLD);
+
+    StatTestReport { test: test.to_string(), variant, p_values, proportion, min_proportion, uniformity, passed }
+}
+
+/*  Returns the p-value of the uniformity of the given p-values (section 4.2.2). */
+pub fn uniformity(p_values: &[f64]) -> f64 {
+    let mut bins = [0usize; 10];
+    for p in p_values {
+        bins[((p * 10.0) as usize).min(9)] += 1;
+    }
+
+    let expected = p_values.len() as f64 / 10.0;
+    let chi_squared: f64 = bins.iter().map(|count| (*count as f64 - expected).powi(2) / expected).sum();
+
+    special::igamc(4.5, chi_squared / 2.0)
+}
+
+/*  Runs the test suite on a byte string, which is split into config.sequences consecutive sequences of
+    config.sequence_bits bits (fewer if the string is too short).
+
+    Return values:
+        - Ok(report): SUCCESS
+        - Err(2): ERROR, the string is shorter than a sequence (or the sequence length is 0) */
+pub fn run_bytes(bytes: &[u8], config: &StatsConfig) -> Result<StatsReport, usize> {
+    let n = config.sequence_bits;
+    let count = (bytes.len() * 8).checked_div(n).unwrap_or(0).min(config.sequences);
+    if count == 0 {
+        return Err(2);
+    }
+
+    Ok(run_sequences((0..count).map(|idx| bit_range(bytes, idx * n, n)), config))
+}
+
+/*  Runs the test suite on the content of a file (see run_bytes).
+
+    Return values:
+        - Ok(report): SUCCESS
+        - Err(1): ERROR, the file could not be read
+        - Err(2): ERROR, the file is shorter than a sequence */
+pub fn run_file(path: &Path, config: &StatsConfig) -> Result<StatsReport, usize> {
+    let bytes = fs::read(path).map_err(|_| 1usize)?;
+
+    run_bytes(&bytes, config)
+}
+
+/*  Runs the test suite on config.sequences sequences generated by a DRBG instance, each sequence is taken from
+    consecutive generate calls of at most MAX_PRB bytes at the full strength of the instance.
+
+    Return values:
+        - Ok(report): SUCCESS
+        - Err(2): ERROR, the sequence length is 0
+        - Err(3): ERROR, the DRBG failed to generate the sequences */
+pub fn run_drbg<T: DRBG_Mechanism_Functions + 'static>(drbg: &mut DRBG<T>, config: &StatsConfig) -> Result<StatsReport, usize> {
+    if config.sequence_bits == 0 {
+        return Err(2);
+    }
+
+    let mut failed = false;
+    let sequences = (0..config.sequences).map_while(|_| {
+        let mut bytes = Vec::<u8>::new();
+        let mut chunk = Vec::<u8>::new();
+        while bytes.len() * 8 < config.sequence_bits {
+            let len = (config.sequence_bits.div_ceil(8) - bytes.len()).min(drbg.get_max_pbr());
+            if drbg.generate(&mut chunk, len, drbg.get_sec_str(), false, None) != 0 {
+                failed = true;
+                return None;
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        Some(bit_range(&bytes, 0, config.sequence_bits))
+    });
+    let report = run_sequences(sequences, config);
+
+    if failed { Err(3) } else { Ok(report) }
+}
@@ -0,0 +1,123 @@
+use super::special::erfc;
+
+/*  Discrete Fourier transform (spectral) test, section 2.6 of SP 800-22.
+
+    The sequence is mapped to +1/-1, its DFT is computed and the number of peaks of the first n/2 moduli that are below
+    the threshold T = sqrt(ln(1/0.05) n) is compared against the expected 95%. The DFT is computed with a radix-2 FFT,
+    sequences whose length is not a power of 2 are transformed with Bluestein's algorithm. */
+
+/*  A complex number (re, im). */
+type Complex = (f64, f64);
+
+/*  Discrete Fourier transform test.
+
+    Return values:
+        - Ok(p_values): SUCCESS
+        - Err(1): ERROR, the sequence is shorter than 2 bits */
+pub fn dft(bits: &[u8]) -> Result<Vec<f64>, usize> {
+    let n = bits.len();
+    if n < 2 {
+        return Err(1);
+    }
+
+    let x: Vec<Complex> = bits.iter().map(|bit| (2.0 * *bit as f64 - 1.0, 0.0)).collect();
+    let spectrum = fourier_transform(&x);
+
+    let threshold = ((1.0f64 / 0.05).ln() * n as f64).sqrt();
+    let n1 = spectrum[..n / 2].iter().filter(|(re, im)| (re * re + im * im).sqrt() < threshold).count() as f64;
+    let n0 = 0.95 * n as f64 / 2.0;
+    let d = (n1 - n0) / (n as f64 * 0.95 * 0.05 / 4.0).sqrt();
+
+    Ok(vec![erfc(d.abs() / std::f64::consts::SQRT_2)])
+}
+
+/*  Returns the DFT of x for any length. */
+pub fn fourier_transform(x: &[Complex]) -> Vec<Complex> {
+    if x.len().is_power_of_two() {
+        let mut data = x.to_vec();
+        fft(&mut data, false);
+        return data;
+    }
+
+    bluestein(x)
+}
+
+/*  In-place iterative radix-2 FFT, the length of data must be a power of 2. The inverse transform is not scaled. */
+fn fft(data: &mut [Complex], inverse: bool) {
+    let n = data.len();
+
+    // Bit reversal permutation.
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * 2.0 * std::f64::consts::PI / len as f64;
+        let twiddles: Vec<Complex> = (0..len / 2).map(|k| ((angle * k as f64).cos(), (angle * k as f64).sin())).collect();
+        for start in (0..n).step_by(len) {
+            for (k, w) in twiddles.iter().enumerate() {
+                let (a, b) = (data[start + k], data[start + k + len / 2]);
+                let t = mul(*w, b);
+                data[start + k] = (a.0 + t.0, a.1 + t.1);
+                data[start + k + len / 2] = (a.0 - t.0, a.1 - t.1);
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/*  DFT of any length with Bluestein's algorithm: X_k = w_k * sum_j (x_j w_j) conj(w_{k-j}), w_k = exp(-i pi k^2 / n). */
+fn bluestein(x: &[Complex]) -> Vec<Complex> {
+    let n = x.len();
+    let size = (2 * n - 1).next_power_of_two();
+
+    // k^2 is reduced modulo 2n to keep the angles accurate.
+    let chirp: Vec<Complex> = (0..n).map(|k| {
+        let angle = std::f64::consts::PI * ((k as u128 * k as u128) % (2 * n as u128)) as f64 / n as f64;
+        (angle.cos(), -angle.sin())
+    }).collect();
+
+    let mut a = vec![(0.0, 0.0); size];
+    for k in 0..n {
+        a[k] = mul(x[k], chirp[k]);
+    }
+    let mut b = vec![(0.0, 0.0); size];
+    b[0] = conj(chirp[0]);
+    for k in 1..n {
+        b[k] = conj(chirp[k]);
+        b[size - k] = conj(chirp[k]);
+    }
+
+    fft(&mut a, false);
+    fft(&mut b, false);
+    for k in 0..size {
+        a[k] = mul(a[k], b[k]);
+    }
+    fft(&mut a, true);
+
+    (0..n).map(|k| {
+        let scaled = (a[k].0 / size as f64, a[k].1 / size as f64);
+        mul(scaled, chirp[k])
+    }).collect()
+}
+
+/*  Complex multiplication. */
+fn mul(a: Complex, b: Complex) -> Complex {
+    (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+/*  Complex conjugate. */
+fn conj(a: Complex) -> Complex {
+    (a.0, -a.1)
+}
@@ -0,0 +1,98 @@
+use super::special::igamc;
+
+/*  Template matching tests of SP 800-22:
+
+        TEST                                    SECTION     P-VALUES
+        non-overlapping template matching       2.7         1 per template (148 aperiodic templates of 9 bits)
+        overlapping template matching           2.8         1 (template of 9 ones)
+
+    In the non-overlapping test the window slides by the length of the template after a match, in the overlapping test
+    it always slides by one bit. */
+
+/*  The default length of the templates and number of blocks of the non-overlapping test. */
+pub const TEMPLATE_LEN: usize = 9;
+pub const NON_OVERLAPPING_BLOCKS: usize = 8;
+
+/*  The parameters of the overlapping test: block length M and probabilities of 0, 1, 2, 3, 4 and >= 5 matches in a
+    block for a template of 9 ones (section 3.8 of SP 800-22 rev. 1a). */
+pub const OVERLAPPING_BLOCK_LEN: usize = 1032;
+const OVERLAPPING_PROBABILITIES: [f64; 6] = [0.364091, 0.185659, 0.139381, 0.100571, 0.0704323, 0.139865];
+
+/*  Returns the aperiodic templates of m bits (templates that cannot overlap with a shifted copy of themselves) in
+    lexicographic order, e.g. 148 templates for m = 9. */
+pub fn aperiodic_templates(m: usize) -> Vec<Vec<u8>> {
+    if m == 0 || m > 20 {
+        return Vec::new();
+    }
+
+    (0..1u32 << m)
+        .map(|value| (0..m).map(|bit| ((value >> (m - 1 - bit)) & 1) as u8).collect::<Vec<u8>>())
+        .filter(|template| (1..m).all(|shift| template[shift..] != template[..m - shift]))
+        .collect()
+}
+
+/*  Non-overlapping template matching test.
+
+    Parameters:
+        - template: the template B (bits)
+        - blocks: the number of blocks N
+
+    Return values:
+        - Ok(p_values): SUCCESS
+        - Err(1): ERROR, the blocks are not longer than the template
+        - Err(2): ERROR, empty template or no block */
+pub fn non_overlapping_template(bits: &[u8], template: &[u8], blocks: usize) -> Result<Vec<f64>, usize> {
+    let m = template.len();
+    if m == 0 || blocks == 0 {
+        return Err(2);
+    }
+    let block_len = bits.len() / blocks;
+    if block_len <= m {
+        return Err(1);
+    }
+
+    let mu = (block_len - m + 1) as f64 / 2f64.powi(m as i32);
+    let sigma_squared = block_len as f64 * (1.0 / 2f64.powi(m as i32) - (2 * m - 1) as f64 / 2f64.powi(2 * m as i32));
+
+    let chi_squared: f64 = bits.chunks_exact(block_len).take(blocks).map(|block| {
+        let (mut matches, mut pos) = (0usize, 0usize);
+        while pos + m <= block_len {
+            if &block[pos..pos + m] == template {
+                matches += 1;
+                pos += m;
+            }
+            else {
+                pos += 1;
+            }
+        }
+        (matches as f64 - mu).powi(2) / sigma_squared
+    }).sum();
+
+    Ok(vec![igamc(blocks as f64 / 2.0, chi_squared / 2.0)])
+}
+
+/*  Overlapping template matching test with a template of 9 ones and blocks of 1032 bits.
+
+    Return values:
+        - Ok(p_values): SUCCESS
+        - Err(1): ERROR, the sequence is shorter than a block */
+pub fn overlapping_template(bits: &[u8]) -> Result<Vec<f64>, usize> {
+    let blocks = bits.len() / OVERLAPPING_BLOCK_LEN;
+    if blocks == 0 {
+        return Err(1);
+    }
+
+    let classes = OVERLAPPING_PROBABILITIES.len();
+    let mut nu = [0usize; 6];
+    for block in bits.chunks_exact(OVERLAPPING_BLOCK_LEN) {
+        let matches = block.windows(TEMPLATE_LEN).filter(|window| window.iter().all(|bit| *bit == 1)).count();
+        nu[matches.min(classes - 1)] += 1;
+    }
+
+    let n = blocks as f64;
+    let chi_squared: f64 = nu.iter().zip(OVERLAPPING_PROBABILITIES.iter())
+        .map(|(nu, pi)| (*nu as f64 - n * pi).powi(2) / (n * pi))
+        .sum();
+
+    Ok(vec![igamc((classes - 1) as f64 / 2.0, chi_squared / 2.0)])
+}
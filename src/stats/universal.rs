@@ -0,0 +1,77 @@
+use super::special::erfc;
+
+/*  Maurer's "universal statistical" test, section 2.9 of SP 800-22.
+
+    The sequence is split into blocks of L bits: the first Q blocks initialize a table with the last position of every
+    L-bit pattern, then for each of the remaining K blocks the log2 of the distance from the previous occurrence of its
+    pattern is accumulated. The block length is selected by the length of the sequence (section 2.9.7) and Q = 10 * 2^L. */
+
+/*  The minimum length of the sequence for L = 6..16 (section 2.9.7). */
+const MIN_LENGTHS: [usize; 11] = [387_840, 904_960, 2_068_480, 4_654_080, 10_342_400, 22_753_280, 49_643_520,
+                                  107_560_960, 231_669_760, 496_435_200, 1_059_061_760];
+
+/*  The expected value and the variance of the test statistic for L = 1..16. */
+const EXPECTED_VALUE: [f64; 16] = [0.7326495, 1.5374383, 2.4016068, 3.3112247, 4.2534266, 5.2177052, 6.1962507,
+                                   7.1836656, 8.1764248, 9.1723243, 10.170032, 11.168765, 12.168070, 13.167693,
+                                   14.167488, 15.167379];
+const VARIANCE: [f64; 16] = [0.690, 1.338, 1.901, 2.358, 2.705, 2.954, 3.125, 3.238, 3.311, 3.356, 3.384, 3.401, 3.410,
+                             3.416, 3.419, 3.421];
+
+/*  Universal test with the parameters selected by the length of the sequence.
+
+    Return values:
+        - Ok(p_values): SUCCESS
+        - Err(1): ERROR, the sequence is shorter than 387840 bits */
+pub fn universal(bits: &[u8]) -> Result<Vec<f64>, usize> {
+    let l = match MIN_LENGTHS.iter().rposition(|min| bits.len() >= *min) {
+        None => return Err(1),
+        Some(idx) => idx + 6,
+    };
+
+    universal_with(bits, l, 10 << l)
+}
+
+/*  Universal test with the given parameters.
+
+    Parameters:
+        - l: the length of each block (1 <= L <= 16)
+        - q: the number of initialization blocks
+
+    Return values:
+        - Ok(p_values): SUCCESS
+        - Err(1): ERROR, the sequence has no test block after the initialization blocks
+        - Err(2): ERROR, invalid block length or no initialization block */
+pub fn universal_with(bits: &[u8], l: usize, q: usize) -> Result<Vec<f64>, usize> {
+    if l == 0 || l > 16 || q == 0 {
+        return Err(2);
+    }
+    let total = bits.len() / l;
+    if total <= q {
+        return Err(1);
+    }
+    let k = total - q;
+    let f_n = universal_statistic(bits, l, q);
+
+    let lf = l as f64;
+    let c = 0.7 - 0.8 / lf + (4.0 + 32.0 / lf) * (k as f64).powf(-3.0 / lf) / 15.0;
+    let sigma = c * (VARIANCE[l - 1] / k as f64).sqrt();
+
+    Ok(vec![erfc((f_n - EXPECTED_VALUE[l - 1]).abs() / (std::f64::consts::SQRT_2 * sigma))])
+}
+
+/*  Returns the test statistic f_n of the universal test, the average log2 distance between the occurrences of the
+    blocks of the test segment (L and Q must be valid, see universal_with). */
+pub fn universal_statistic(bits: &[u8], l: usize, q: usize) -> f64 {
+    let total = bits.len() / l;
+    let mut table = vec![0usize; 1 << l];
+    let mut sum = 0.0;
+    for (idx, block) in bits.chunks_exact(l).take(total).enumerate() {
+        let pattern = block.iter().fold(0usize, |acc, bit| (acc << 1) | *bit as usize);
+        if idx >= q {
+            sum += ((idx + 1 - table[pattern]) as f64).log2();
+        }
+        table[pattern] = idx + 1;
+    }
+
+    sum / (total - q) as f64
+}
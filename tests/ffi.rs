@@ -0,0 +1,93 @@
+#![cfg(unix)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use aes::{Aes128, Aes192, Aes256};
+use sha2::{Sha256, Sha512};
+use rust_nist_drbg::drbg::entropy::FixedEntropySource;
+use rust_nist_drbg::drbg::gen_drbg::{DRBG, DRBG_Functions};
+use rust_nist_drbg::mechs::ctr_mech::CtrDrbgMech;
+use rust_nist_drbg::mechs::ctr_mech_with_df::CtrDrbgMech_DF;
+use rust_nist_drbg::mechs::gen_mech::DRBG_Mechanism_Functions;
+use rust_nist_drbg::mechs::hash_mech::HashDrbgMech;
+use rust_nist_drbg::mechs::hmac_mech::HmacDrbgMech;
+
+/*  The entropy given to the instances of tests/ffi/ffi_test.c (with DRBG_TEST_ENTROPY): 5 chunks of 48 bytes counting from 0. */
+fn entropy() -> Vec<Vec<u8>> {
+    (0..240u32).map(|i| i as u8).collect::<Vec<u8>>().chunks(48).map(|chunk| chunk.to_vec()).collect()
+}
+
+/*  Returns the line printed by the C program for the mechanism T, computed with the Rust API. */
+fn expected_line<T: DRBG_Mechanism_Functions + 'static>(mech_id: u32, strength: usize) -> String {
+    let mut drbg = DRBG::<T>::new_with_entropy(strength, Some(b"ffi"), Box::new(FixedEntropySource::new(entropy())))
+        .expect("instantiation failed");
+    let (mut first, mut second) = (Vec::new(), Vec::new());
+    assert_eq!(drbg.generate(&mut first, 32, strength, false, None), 0);
+    assert_eq!(drbg.generate(&mut second, 32, strength, true, Some(b"add")), 0);
+
+    format!("{} {} {}", mech_id, hex::encode(first), hex::encode(second))
+}
+
+/*  The directory of the cdylib built for this test, i.e. the deps directory of the test executable (cargo test does not
+    copy the cdylib to the parent directory, where a stale one could be found). */
+fn library_dir() -> PathBuf {
+    let exe = std::env::current_exe().expect("no test executable");
+    exe.parent().expect("unexpected target layout").to_path_buf()
+}
+
+/*  Verifying that the committed header is the one generated from src/ffi.rs by build.rs. */
+#[test]
+fn header_up_to_date() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let committed = std::fs::read_to_string(root.join("include/drbg.h")).expect("include/drbg.h is missing");
+    let generated = std::fs::read_to_string(Path::new(env!("OUT_DIR")).join("drbg.h")).expect("drbg.h was not generated");
+
+    assert!(committed == generated, "include/drbg.h is out of date, regenerate it with DRBG_GEN_HEADER=1 cargo build");
+}
+
+#[test]
+fn c_interface() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let lib_dir = library_dir();
+    let program = lib_dir.join("ffi_test");
+
+    // The cdylib of the tests is built with the test-entropy feature (see the dev-dependencies in Cargo.toml).
+    let compiled = Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg("-DDRBG_TEST_ENTROPY")
+        .arg(root.join("tests/ffi/ffi_test.c"))
+        .arg("-I").arg(root.join("include"))
+        .arg("-L").arg(&lib_dir)
+        .arg("-lrust_nist_drbg")
+        .arg("-o").arg(&program)
+        .status();
+    match compiled {
+        Err(err) => {
+            eprintln!("skipping the C interface test, no C compiler: {}", err);
+            return;
+        }
+        Ok(status) => assert!(status.success(), "the C test program could not be compiled"),
+    }
+
+    let output = Command::new(&program)
+        .current_dir(root)
+        .env("LD_LIBRARY_PATH", &lib_dir)
+        .env("DYLD_LIBRARY_PATH", &lib_dir)
+        .output()
+        .expect("the C test program could not be run");
+    assert!(output.status.success(), "the C test program failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let expected = vec![
+        expected_line::<HashDrbgMech<Sha256>>(1, 32),
+        expected_line::<HashDrbgMech<Sha512>>(2, 32),
+        expected_line::<HmacDrbgMech<Sha256>>(3, 32),
+        expected_line::<HmacDrbgMech<Sha512>>(4, 32),
+        expected_line::<CtrDrbgMech<Aes128>>(5, 16),
+        expected_line::<CtrDrbgMech<Aes192>>(6, 24),
+        expected_line::<CtrDrbgMech<Aes256>>(7, 32),
+        expected_line::<CtrDrbgMech_DF<Aes128>>(8, 16),
+        expected_line::<CtrDrbgMech_DF<Aes192>>(9, 24),
+        expected_line::<CtrDrbgMech_DF<Aes256>>(10, 32),
+    ];
+    let lines: Vec<String> = String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect();
+    assert_eq!(lines, expected);
+}
@@ -0,0 +1,100 @@
+/*  Test program of the C interface (see src/ffi.rs and tests/ffi.rs).
+
+    The error codes of the interface are checked first: the program exits with 1 on the first unexpected code. When
+    DRBG_TEST_ENTROPY is defined (the cdylib is built with the test-entropy feature), an instance of each mechanism is
+    then created with a fixed entropy buffer and the output of two generate calls is printed as "<mech_id> <hex> <hex>",
+    so that it can be compared with the output of the Rust API. */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include "drbg.h"
+
+#define CHUNKS 5
+#define CHUNK_LEN 48
+#define OUT_LEN 32
+
+static void expect(const char *what, int res, int expected) {
+    if (res != expected) {
+        fprintf(stderr, "%s: got %d, expected %d\n", what, res, expected);
+        exit(1);
+    }
+}
+
+#if defined(DRBG_TEST_ENTROPY)
+static void print_hex(const uint8_t *bytes, size_t len) {
+    for (size_t i = 0; i < len; i++) {
+        printf("%02x", bytes[i]);
+    }
+}
+
+static size_t strength(uint32_t mech_id) {
+    switch (mech_id) {
+        case DRBG_MECH_CTR_AES128: case DRBG_MECH_CTR_DF_AES128: return 16;
+        case DRBG_MECH_CTR_AES192: case DRBG_MECH_CTR_DF_AES192: return 24;
+        default: return 32;
+    }
+}
+
+static void known_answers(void) {
+    uint8_t entropy[CHUNKS * CHUNK_LEN];
+    for (size_t i = 0; i < sizeof(entropy); i++) {
+        entropy[i] = (uint8_t)i;
+    }
+
+    for (uint32_t mech_id = DRBG_MECH_HASH_SHA256; mech_id <= DRBG_MECH_CTR_DF_AES256; mech_id++) {
+        drbg_t *drbg = NULL;
+        uint8_t first[OUT_LEN], second[OUT_LEN];
+
+        expect("drbg_new_with_entropy", drbg_new_with_entropy(mech_id, strength(mech_id), entropy, sizeof(entropy), CHUNK_LEN,
+                                                              (const uint8_t *)"ffi", 3, &drbg), DRBG_OK);
+        expect("drbg_generate", drbg_generate(drbg, first, OUT_LEN, strength(mech_id), 0, NULL, 0), DRBG_OK);
+        expect("drbg_generate (prediction resistance)", drbg_generate(drbg, second, OUT_LEN, strength(mech_id), 1,
+                                                                      (const uint8_t *)"add", 3), DRBG_OK);
+        drbg_free(drbg);
+
+        printf("%u ", mech_id);
+        print_hex(first, OUT_LEN);
+        printf(" ");
+        print_hex(second, OUT_LEN);
+        printf("\n");
+    }
+}
+#endif
+
+static void error_codes(void) {
+    drbg_t *drbg = (drbg_t *)&error_codes;
+    uint8_t pers[40] = {0}, out[OUT_LEN];
+
+    expect("unknown mechanism", drbg_new(99, 32, NULL, 0, &drbg), DRBG_ERR_INVALID_ARGUMENT);
+    expect("handle set to NULL", drbg == NULL, 1);
+    expect("NULL handle", drbg_new(DRBG_MECH_HMAC_SHA256, 32, NULL, 0, NULL), DRBG_ERR_INVALID_ARGUMENT);
+    expect("NULL personalization string", drbg_new(DRBG_MECH_HMAC_SHA256, 32, NULL, 3, &drbg), DRBG_ERR_INVALID_ARGUMENT);
+    expect("strength too high", drbg_new(DRBG_MECH_HMAC_SHA256, 64, NULL, 0, &drbg), DRBG_ERR_INSTANTIATE + 1);
+    expect("personalization string too long", drbg_new(DRBG_MECH_HMAC_SHA256, 32, pers, sizeof(pers), &drbg),
+           DRBG_ERR_INSTANTIATE + 2);
+
+    expect("drbg_new", drbg_new(DRBG_MECH_CTR_DF_AES128, 16, pers, 16, &drbg), DRBG_OK);
+    expect("strength not supported", drbg_generate(drbg, out, OUT_LEN, 32, 0, NULL, 0), DRBG_ERR_GENERATE + 3);
+    expect("NULL output", drbg_generate(drbg, NULL, OUT_LEN, 16, 0, NULL, 0), DRBG_ERR_INVALID_ARGUMENT);
+    expect("drbg_reseed", drbg_reseed(drbg, (const uint8_t *)"add", 3), DRBG_OK);
+    expect("drbg_generate", drbg_generate(drbg, out, OUT_LEN, 16, 0, NULL, 0), DRBG_OK);
+    expect("drbg_uninstantiate", drbg_uninstantiate(drbg), DRBG_OK);
+    expect("generate after uninstantiate", drbg_generate(drbg, out, OUT_LEN, 16, 0, NULL, 0), DRBG_ERR_GENERATE + 1);
+    expect("reseed after uninstantiate", drbg_reseed(drbg, NULL, 0), DRBG_ERR_RESEED + 1);
+    expect("uninstantiate twice", drbg_uninstantiate(drbg), DRBG_ERR_UNINSTANTIATE + 1);
+    drbg_free(drbg);
+    drbg_free(NULL);
+
+    expect("NULL handle (generate)", drbg_generate(NULL, out, OUT_LEN, 16, 0, NULL, 0), DRBG_ERR_INVALID_ARGUMENT);
+    expect("self-tests of an unknown mechanism", drbg_selftest(99), DRBG_ERR_INVALID_ARGUMENT);
+    expect("self-tests", drbg_selftest(DRBG_MECH_HMAC_SHA256), DRBG_OK);
+    expect("self-tests of every mechanism", drbg_selftest(DRBG_MECH_ALL), DRBG_OK);
+}
+
+int main(void) {
+    error_codes();
+#if defined(DRBG_TEST_ENTROPY)
+    known_answers();
+#endif
+    return 0;
+}
@@ -2,7 +2,9 @@ use rust_nist_drbg::self_tests;
 
 #[test]
 fn self_tests () {
-    let res = self_tests::run_tests::run_all();
+    let report = self_tests::run_tests::run_all();
+    let summary = report.summary();
 
-    assert_eq!(res, 0);
-}
\ No newline at end of file
+    assert_eq!(summary.failed, 0, "failed self-tests: {:#?}", summary.failed_tests);
+    assert!(summary.total > 0);
+}